@@ -0,0 +1,639 @@
+use std::{collections::HashMap, time::Instant};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use time::{Date, OffsetDateTime};
+
+use crate::{
+    App,
+    announcements::Announcement,
+    catchup::PlanEdit,
+    config::LinkFormat,
+    confirm::{self, Confirmation},
+    courses::{CourseGrade, CoursePage, CourseRoute},
+    dashboard::DashboardData,
+    detail::AssignmentDetail,
+    fetch::{Attempts, Calendar, CalendarEvent, Requirements},
+    grades::Gradebook,
+    grading::GradeRow,
+    inbox::{Conversation, Thread},
+    metrics,
+    modules::Module,
+    permissions::CoursePermissions,
+    posting::{Draft, PostKind},
+    sections::Section,
+    startup::{Loaded, Step},
+    todo::TodoItem,
+    triage::Decision,
+    update,
+    workload::Estimate,
+};
+
+/// Index into `App::sessions`
+pub type SessionId = usize;
+
+#[derive(Clone)]
+pub enum Action {
+    Tick,
+    Render,
+    Quit,
+    Fetch,
+    FetchComplete {
+        session: SessionId,
+        calendar: Calendar,
+    },
+    /// The planner of a session couldn't be fetched
+    FetchFailed {
+        session: SessionId,
+        error: String,
+    },
+    /// Planner data read from the cache file
+    FileFetchComplete {
+        session: SessionId,
+        calendar: Calendar,
+        /// When the cache was written, None if that couldn't be read
+        updated_at: Option<OffsetDateTime>,
+    },
+    OverlayFetchComplete {
+        name: String,
+        events: Vec<CalendarEvent>,
+    },
+    /// Course details or the user's profile from the initial load
+    StartupLoaded {
+        session: SessionId,
+        data: Loaded,
+    },
+    /// A step of the initial load finished, whether it succeeded or not
+    StartupStep {
+        session: SessionId,
+        step: Step,
+        failed: bool,
+    },
+    DetailsFetched {
+        session: SessionId,
+        html_url: String,
+        estimate: Estimate,
+        /// HTML description, kept for the reader view
+        description: String,
+        attempts: Option<Attempts>,
+        requirements: Requirements,
+    },
+    GradebookFetched {
+        session: SessionId,
+        course_id: u64,
+        gradebook: Gradebook,
+    },
+    PermissionsFetched {
+        session: SessionId,
+        course_id: u64,
+        permissions: CoursePermissions,
+    },
+    /// An item opened by url that wasn't in the planner yet
+    TargetFetched {
+        session: SessionId,
+        event: CalendarEvent,
+    },
+    DashboardFetched(DashboardData),
+    /// Group announcements by day or list them flat
+    ToggleDigest,
+    /// Collapse or expand the selected day of the announcement digest
+    ToggleFold,
+    /// Mark announcements from the top of the panel down to the selection read on Canvas
+    MarkRead,
+    AnnouncementsRead {
+        ids: Vec<u64>,
+    },
+    /// Mark the selected item done or not done in the Canvas planner
+    ToggleCompleted,
+    /// Copy the selected item as a link to paste into notes
+    YankLink(LinkFormat),
+    /// Open every item of the selected day still to hand in, in browser tabs
+    OpenDay,
+    /// Copy the urls of the selected day's items still to hand in, one per line
+    YankDay,
+    /// Create a reminder for the selected item in Reminders.app or on the CalDAV server
+    ExportReminder,
+    /// Fetch whether the selected item was submitted, without refetching the planner
+    RecheckSubmission,
+    SubmissionRechecked {
+        session: SessionId,
+        html_url: String,
+        submitted: bool,
+    },
+    /// Canvas accepted a submission made from the submit prompt
+    Submitted {
+        session: SessionId,
+        html_url: String,
+    },
+    /// Canvas saved the completed state of an item
+    CompletionSaved {
+        session: SessionId,
+        html_url: String,
+        completed: bool,
+        override_id: u64,
+    },
+    /// Canvas refused the completed state of an item, which goes back to `completed`
+    CompletionFailed {
+        session: SessionId,
+        html_url: String,
+        completed: bool,
+        error: String,
+    },
+    AnnouncementsFetched(Vec<Announcement>),
+    /// Read the body of the selected announcement in the reader
+    OpenAnnouncement,
+    TodoFetched(Vec<TodoItem>),
+    ConversationsFetched(Vec<Conversation>),
+    /// Read the selected conversation in the inbox
+    OpenConversation,
+    ConversationFetched(Thread),
+    /// Start writing a reply to the selected conversation
+    ComposeReply,
+    /// A key typed into the reply being written
+    ComposeKey(KeyEvent),
+    /// Canvas added the reply, with the thread fetched again
+    ReplySent(Thread),
+    /// Canvas refused the reply, which stays in the box to try again
+    ReplyFailed {
+        error: String,
+    },
+    ModulesFetched {
+        course_id: u64,
+        modules: Vec<Module>,
+    },
+    /// Open a page of the selected course in the courses view
+    OpenCourseRoute(CourseRoute),
+    /// Read the selected item's course syllabus from the part closest to its due date
+    OpenSyllabus,
+    CoursePageFetched {
+        course_id: u64,
+        route: CourseRoute,
+        page: CoursePage,
+    },
+    /// Open a link of the links view by its number, counting from 1
+    OpenLink(usize),
+    CopyLink,
+    /// Show or hide the required score solver on the grades page
+    ToggleSolver,
+    /// Change the solver's target grade by this many percentage points
+    AdjustTarget(f64),
+    /// Past due unsubmitted items for the catch-up plan, empty if they couldn't be fetched
+    MissingFetched {
+        session: SessionId,
+        events: Vec<CalendarEvent>,
+    },
+    EditPlan(PlanEdit),
+    /// A newly graded item with a preview of the grader's comment
+    GradeCommentFetched {
+        event: CalendarEvent,
+        comment: Option<String>,
+    },
+    /// Decide on the item at the front of the triage queue
+    Triage(Decision),
+    /// Show the full assignment behind the selected item in a popup
+    ShowDetail,
+    CloseDetail,
+    DetailFetched {
+        html_url: String,
+        detail: Result<AssignmentDetail, String>,
+    },
+    /// Enter grades for the selected assignment of a course page
+    OpenGrading,
+    GradingFetched {
+        assignment_id: u64,
+        rows: Result<Vec<GradeRow>, String>,
+        sections: Vec<Section>,
+    },
+    /// Filter grade entry to the next section of the course
+    CycleSection,
+    /// Current grades for the grades view by course id
+    CourseGradesFetched {
+        grades: Result<HashMap<u64, CourseGrade>, String>,
+    },
+    /// Send the grades and comments entered so far
+    SaveGrades,
+    /// The grade of one student was saved, with the grade and score Canvas now has
+    GradeSaved {
+        assignment_id: u64,
+        user_id: u64,
+        result: Result<(Option<String>, Option<f64>), String>,
+    },
+    /// Write an announcement or discussion topic for the selected course in the user's editor
+    ComposePost(PostKind),
+    /// Go back to the editor from the post preview
+    EditPost,
+    ConfirmPost,
+    /// A post finished, with its url or why it failed. The draft is kept in case of failure
+    Posted {
+        draft: Draft,
+        result: Result<String, String>,
+    },
+    Navigate(Navigation),
+    /// Move focus to the next dashboard panel
+    NextPanel,
+    /// Switch between the day table and the week and month grids
+    CycleLayout,
+    /// Show the day selected in the grid as a table
+    OpenGridDay,
+    /// Return to the position before the last navigation
+    Undo,
+    OpenPrompt(PromptKind),
+    PromptKey(KeyCode),
+    /// Edit the note of the current date in the user's editor
+    EditNote,
+    /// Replace the note of the date with the edited text, deleting it when blank
+    SaveNote(Date, String),
+    /// Open the files linked to the selected item in the user's editor
+    EditFiles,
+    /// Read the description of the selected or read item as Markdown in the user's pager
+    OpenInPager,
+    /// A key pressed while the idle lock is shown
+    LockKey(KeyCode),
+    /// Back out of whatever the current mode is
+    Escape,
+    /// Answer to the question asked before a held back action, carrying it out on yes
+    Confirm(bool),
+    OpenURL,
+    /// Bookmark the selected item, or remove the selected bookmark in the bookmarks view
+    ToggleBookmark,
+    FocusNext,
+    /// Replace the focused session with the next profile from the config file that isn't open
+    SwitchProfile,
+    ToggleView(View),
+    /// Hide or show again the course under the cursor in the course filter
+    ToggleCourseHidden,
+    /// Scope everything to the selected item's course, or back to every course
+    ToggleCourseFocus,
+    ToggleMetrics,
+    /// Mask grades, course names and titles for screen sharing
+    ToggleRedact,
+    /// Show or hide the list of keybindings
+    ToggleHelp,
+    Status(String),
+    /// Something failed, shown in a toast until dismissed
+    Error(String),
+    None,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Navigation {
+    NextEvent,
+    PrevEvent,
+    NextDate,
+    PrevDate,
+    /// First date with a shown item
+    ResetDate,
+    /// First date with a shown item from today on
+    Today,
+    LastDate,
+    /// Next match of the search, across dates
+    NextMatch,
+    PrevMatch,
+}
+
+/// What the text typed into the prompt is used for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PromptKind {
+    /// A Canvas url or id to open
+    Open,
+    /// Text to find in item titles, course names and date notes
+    Search,
+    /// Grade of the selected student in grade entry
+    Grade,
+    /// Comment for the selected student in grade entry
+    Comment,
+    /// Text or a file path handed in for the selected assignment
+    Submit,
+    /// Date to move to, e.g. 2025-03-10
+    Goto,
+    /// Path of a local file to link to the selected item, or unlink if it already is
+    Link,
+    /// How long before the official deadline to finish the selected item, e.g. 2d
+    SoftDeadline,
+}
+
+/// Screens shown instead of the sessions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum View {
+    Receipts,
+    /// What recent actions led to
+    Audit,
+    /// The selected item's course in module order
+    Modules,
+    Bookmarks,
+    /// Description of the selected item
+    Reader,
+    /// The courses with shortcuts to their pages
+    Courses,
+    /// The courses with the user's current grade in each
+    Grades,
+    /// Plan for overdue and soon due items
+    CatchUp,
+    /// Every profile's items in one timeline
+    Agenda,
+    /// Links in the description of the selected or read item
+    Links,
+    /// New and changed items, one at a time
+    Triage,
+    /// Checkboxes hiding courses from the planner
+    CourseFilter,
+    /// Unread announcements of every course
+    Announcements,
+    /// Conversations in the Canvas inbox
+    Inbox,
+    /// The Canvas To-Do list of work to submit or grade
+    Todo,
+}
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Tick => "tick",
+            Action::Render => "render",
+            Action::Quit => "quit",
+            Action::Fetch => "fetch",
+            Action::FetchComplete { .. } => "fetch_complete",
+            Action::FetchFailed { .. } => "fetch_failed",
+            Action::FileFetchComplete { .. } => "file_fetch_complete",
+            Action::OverlayFetchComplete { .. } => "overlay_fetch_complete",
+            Action::StartupLoaded { .. } => "startup_loaded",
+            Action::StartupStep { .. } => "startup_step",
+            Action::DetailsFetched { .. } => "details_fetched",
+            Action::GradebookFetched { .. } => "gradebook_fetched",
+            Action::PermissionsFetched { .. } => "permissions_fetched",
+            Action::TargetFetched { .. } => "target_fetched",
+            Action::DashboardFetched(_) => "dashboard_fetched",
+            Action::ToggleDigest => "toggle_digest",
+            Action::ToggleFold => "toggle_fold",
+            Action::MarkRead => "mark_read",
+            Action::AnnouncementsRead { .. } => "announcements_read",
+            Action::ToggleCompleted => "toggle_completed",
+            Action::YankLink(_) => "yank_link",
+            Action::OpenDay => "open_day",
+            Action::YankDay => "yank_day",
+            Action::ExportReminder => "export_reminder",
+            Action::RecheckSubmission => "recheck_submission",
+            Action::SubmissionRechecked { .. } => "submission_rechecked",
+            Action::Submitted { .. } => "submitted",
+            Action::CompletionSaved { .. } => "completion_saved",
+            Action::CompletionFailed { .. } => "completion_failed",
+            Action::AnnouncementsFetched(_) => "announcements_fetched",
+            Action::OpenAnnouncement => "open_announcement",
+            Action::TodoFetched(_) => "todo_fetched",
+            Action::ConversationsFetched(_) => "conversations_fetched",
+            Action::OpenConversation => "open_conversation",
+            Action::ConversationFetched(_) => "conversation_fetched",
+            Action::ComposeReply => "compose_reply",
+            Action::ComposeKey(_) => "compose_key",
+            Action::ReplySent(_) => "reply_sent",
+            Action::ReplyFailed { .. } => "reply_failed",
+            Action::ModulesFetched { .. } => "modules_fetched",
+            Action::OpenCourseRoute(_) => "open_course_route",
+            Action::OpenSyllabus => "open_syllabus",
+            Action::CoursePageFetched { .. } => "course_page_fetched",
+            Action::OpenLink(_) => "open_link",
+            Action::CopyLink => "copy_link",
+            Action::ToggleSolver => "toggle_solver",
+            Action::AdjustTarget(_) => "adjust_target",
+            Action::MissingFetched { .. } => "missing_fetched",
+            Action::EditPlan(_) => "edit_plan",
+            Action::GradeCommentFetched { .. } => "grade_comment_fetched",
+            Action::Triage(_) => "triage",
+            Action::ShowDetail => "show_detail",
+            Action::CloseDetail => "close_detail",
+            Action::DetailFetched { .. } => "detail_fetched",
+            Action::OpenGrading => "open_grading",
+            Action::GradingFetched { .. } => "grading_fetched",
+            Action::CourseGradesFetched { .. } => "course_grades_fetched",
+            Action::CycleSection => "cycle_section",
+            Action::SaveGrades => "save_grades",
+            Action::SaveNote(..) => "save_note",
+            Action::GradeSaved { .. } => "grade_saved",
+            Action::ComposePost(_) => "compose_post",
+            Action::EditPost => "edit_post",
+            Action::ConfirmPost => "confirm_post",
+            Action::Posted { .. } => "posted",
+            Action::Navigate(_) => "navigate",
+            Action::NextPanel => "next_panel",
+            Action::CycleLayout => "cycle_layout",
+            Action::OpenGridDay => "open_grid_day",
+            Action::Undo => "undo",
+            Action::OpenPrompt(PromptKind::Open) => "open_prompt",
+            Action::OpenPrompt(PromptKind::Search) => "search",
+            Action::OpenPrompt(PromptKind::Grade) => "edit_grade",
+            Action::OpenPrompt(PromptKind::Comment) => "edit_comment",
+            Action::OpenPrompt(PromptKind::Submit) => "submit",
+            Action::OpenPrompt(PromptKind::Goto) => "goto_date",
+            Action::OpenPrompt(PromptKind::Link) => "link_file",
+            Action::OpenPrompt(PromptKind::SoftDeadline) => "set_soft_deadline",
+            Action::EditNote => "edit_note",
+            Action::EditFiles => "edit_files",
+            Action::OpenInPager => "open_in_pager",
+            Action::PromptKey(_) => "prompt_key",
+            Action::LockKey(_) => "lock_key",
+            Action::Escape => "escape",
+            Action::Confirm(_) => "confirm",
+            Action::OpenURL => "open_url",
+            Action::ToggleBookmark => "toggle_bookmark",
+            Action::FocusNext => "focus_next",
+            Action::SwitchProfile => "switch_profile",
+            Action::ToggleView(_) => "toggle_view",
+            Action::ToggleCourseHidden => "toggle_course_hidden",
+            Action::ToggleCourseFocus => "focus_course",
+            Action::ToggleMetrics => "toggle_metrics",
+            Action::ToggleRedact => "toggle_redact",
+            Action::ToggleHelp => "toggle_help",
+            Action::Status(_) => "status",
+            Action::Error(_) => "error",
+            Action::None => "none",
+        }
+    }
+
+    /// Actions fired on every frame, left out of logs
+    fn is_periodic(&self) -> bool {
+        matches!(self, Action::Tick | Action::Render | Action::None)
+    }
+}
+
+/// Cross-cutting behavior run around every dispatched action
+pub trait Middleware {
+    fn before(&mut self, _app: &mut App, _action: &Action) {}
+    fn after(&mut self, _app: &mut App, _action_name: &'static str) {}
+}
+
+/// Runs actions through the middleware chain into `update`
+pub struct Dispatcher {
+    middleware: Vec<Box<dyn Middleware>>,
+}
+
+impl Dispatcher {
+    pub fn new() -> Self {
+        Self {
+            middleware: vec![
+                Box::new(Logging),
+                Box::new(Metrics::default()),
+                Box::new(UndoCapture),
+                Box::new(Audit::default()),
+            ],
+        }
+    }
+
+    /// Holds back actions whose class is set to ask first, and carries them out once confirmed
+    pub fn dispatch(&mut self, app: &mut App, action: Action) {
+        let action = match action {
+            Action::Confirm(confirmed) => match app.status_bar.confirmation.take() {
+                Some(confirmation) if confirmed => confirmation.action,
+                _ => return,
+            },
+            action => match confirm::question(app, &action) {
+                Some(question) => {
+                    app.status_bar.confirmation = Some(Confirmation { question, action });
+                    return;
+                }
+                None => action,
+            },
+        };
+        for middleware in &mut self.middleware {
+            middleware.before(app, &action);
+        }
+        let name = action.name();
+        update(app, action);
+        for middleware in self.middleware.iter_mut().rev() {
+            middleware.after(app, name);
+        }
+    }
+}
+
+struct Logging;
+
+impl Middleware for Logging {
+    fn before(&mut self, _app: &mut App, action: &Action) {
+        if !action.is_periodic() {
+            log::debug!("action {}", action.name());
+        }
+    }
+}
+
+/// Times how long each action takes to handle
+#[derive(Default)]
+struct Metrics {
+    started: Option<Instant>,
+}
+
+impl Middleware for Metrics {
+    fn before(&mut self, _app: &mut App, _action: &Action) {
+        self.started = Some(Instant::now());
+    }
+
+    fn after(&mut self, _app: &mut App, _action_name: &'static str) {
+        if let Some(started) = self.started.take() {
+            metrics::record_action(started.elapsed());
+        }
+    }
+}
+
+/// Records what each action led to for the audit trail: the error or status message it showed,
+/// or which profile a finished refresh was for
+#[derive(Default)]
+struct Audit {
+    /// Error and status shown before the action, to tell whether it showed new ones
+    error: Option<String>,
+    status: Option<String>,
+    /// What the action did, for actions that show no message of their own
+    described: Option<String>,
+}
+
+impl Middleware for Audit {
+    fn before(&mut self, app: &mut App, action: &Action) {
+        self.error = app
+            .popups
+            .error
+            .as_ref()
+            .map(|error| error.message().to_string());
+        self.status = app.status_bar.status.clone();
+        self.described = match action {
+            Action::FetchComplete { session, .. } => {
+                Some(format!("Refreshed {}", app.sessions[*session].profile.name))
+            }
+            _ => None,
+        };
+    }
+
+    fn after(&mut self, app: &mut App, action_name: &'static str) {
+        let error = app.popups.error.as_ref().map(|error| error.message());
+        let status = app.status_bar.status.as_deref();
+        if let Some(error) = error.filter(|&error| Some(error) != self.error.as_deref()) {
+            app.audit.record(action_name, error.to_string(), true);
+        } else if let Some(status) = status.filter(|&status| Some(status) != self.status.as_deref())
+        {
+            app.audit.record(action_name, status.to_string(), false);
+        } else if let Some(described) = self.described.take() {
+            app.audit.record(action_name, described, false);
+        }
+    }
+}
+
+/// Where the user was, restored by `Action::Undo`
+#[derive(Debug, Clone)]
+pub struct Position {
+    focus: usize,
+    /// Date index and selected row of each session
+    sessions: Vec<(usize, Option<usize>)>,
+}
+
+const UNDO_LIMIT: usize = 100;
+
+impl Position {
+    pub fn capture(app: &App) -> Self {
+        Self {
+            focus: app.focus,
+            sessions: app
+                .sessions
+                .iter()
+                .map(|session| {
+                    let selected = session
+                        .calendar
+                        .dates
+                        .get(session.current_date_index)
+                        .and_then(|date| date.table_state.selected());
+                    (session.current_date_index, selected)
+                })
+                .collect(),
+        }
+    }
+
+    pub fn restore(self, app: &mut App) {
+        app.focus = self.focus.min(app.sessions.len() - 1);
+        for (session, (date_index, selected)) in app.sessions.iter_mut().zip(self.sessions) {
+            let Some(date) = session.calendar.dates.get_mut(date_index) else {
+                continue;
+            };
+            session.current_date_index = date_index;
+            date.table_state
+                .select(selected.filter(|&row| row < date.events.len()));
+        }
+    }
+}
+
+/// Remembers the position before every navigation so it can be undone
+struct UndoCapture;
+
+impl Middleware for UndoCapture {
+    fn before(&mut self, app: &mut App, action: &Action) {
+        // A search moves while it is typed, so the position is kept from before it opened
+        let searching = matches!(app.status_bar.prompt, Some((PromptKind::Search, _)));
+        let moves = match action {
+            Action::Navigate(_)
+            | Action::FocusNext
+            | Action::TargetFetched { .. }
+            | Action::OpenPrompt(PromptKind::Search) => true,
+            Action::PromptKey(KeyCode::Enter) => !searching,
+            _ => false,
+        };
+        if moves {
+            app.history.push(Position::capture(app));
+            if app.history.len() > UNDO_LIMIT {
+                app.history.remove(0);
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Constraint,
+    prelude::{Buffer, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use time::{OffsetDateTime, format_description};
+
+use crate::{
+    action::{Action, Navigation, View},
+    component::{Component, Context},
+    fetch::{CalendarEvent, to_local},
+    locale, redact,
+    session::{Session, now_marker},
+    theme,
+};
+
+/// Badge colors of the profiles, in config order
+const PROFILE_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Yellow,
+    Color::LightMagenta,
+    Color::LightGreen,
+    Color::LightBlue,
+    Color::LightRed,
+];
+
+fn profile_color(id: usize) -> Color {
+    PROFILE_COLORS[id % PROFILE_COLORS.len()]
+}
+
+pub struct AgendaEntry {
+    pub session: usize,
+    pub profile: String,
+    pub course_color: Option<Color>,
+    pub event: CalendarEvent,
+}
+
+/// Every profile's planner items in due order, shared by the agenda and the printout
+pub fn entries(sessions: &[Session]) -> Vec<AgendaEntry> {
+    let mut entries: Vec<AgendaEntry> = sessions
+        .iter()
+        .enumerate()
+        .flat_map(|(id, session)| {
+            session
+                .calendar
+                .dates
+                .iter()
+                .flat_map(|date| &date.events)
+                // Every session carries the external calendars, keep them once
+                .filter(move |event| id == 0 || !event.is_external())
+                .filter(|event| !session.is_hidden(event))
+                .map(move |event| AgendaEntry {
+                    session: id,
+                    profile: session.profile.name.clone(),
+                    course_color: event
+                        .course_id
+                        .and_then(|course_id| session.colors.get(&course_id))
+                        .copied(),
+                    event: event.clone(),
+                })
+        })
+        .collect();
+    entries.sort_by_key(|entry| entry.event.due_at);
+    entries
+}
+
+/// Every profile's planner items merged into one timeline
+pub struct AgendaScreen {
+    entries: Vec<AgendaEntry>,
+    pub table_state: TableState,
+}
+
+impl AgendaScreen {
+    pub fn new(sessions: &[Session]) -> Self {
+        let mut screen = Self {
+            entries: vec![],
+            table_state: TableState::default(),
+        };
+        screen.refresh(sessions);
+        screen
+    }
+
+    /// Rebuilds the timeline from the sessions, keeping the selected item selected
+    pub fn refresh(&mut self, sessions: &[Session]) {
+        let selected = self
+            .selected()
+            .map(|(session, event)| (session, event.html_url.clone()));
+        self.entries = entries(sessions);
+        let index = selected
+            .and_then(|(session, html_url)| {
+                self.entries
+                    .iter()
+                    .position(|entry| entry.session == session && entry.event.html_url == html_url)
+            })
+            .or((!self.entries.is_empty()).then_some(0));
+        self.table_state.select(index);
+    }
+
+    /// The selected item with the session it comes from
+    pub fn selected(&self) -> Option<(usize, &CalendarEvent)> {
+        let entry = self.entries.get(self.table_state.selected()?)?;
+        Some((entry.session, &entry.event))
+    }
+}
+
+impl Component for AgendaScreen {
+    fn handle_event(&mut self, _key: KeyEvent, bound: Action) -> Action {
+        match bound {
+            Action::Quit
+            | Action::Fetch
+            | Action::OpenURL
+            | Action::Navigate(Navigation::PrevEvent | Navigation::NextEvent)
+            | Action::ToggleView(View::Agenda) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, _context: &Context) -> Option<Action> {
+        match action {
+            Action::Navigate(Navigation::PrevEvent) => self.table_state.select_previous(),
+            Action::Navigate(Navigation::NextEvent) => self.table_state.select_next(),
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, _context: &Context) {
+        if self.entries.is_empty() {
+            Paragraph::new("Waiting for data...").render(area, buf);
+            return;
+        }
+        let header = ["Date", "Due", "Profile", "Course", "Assignment"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(Style::default().fg(theme::current().heading));
+        let locale = locale::current();
+        let time_format = format_description::parse("[hour]:[minute]").unwrap();
+        let now = to_local(OffsetDateTime::now_utc());
+        let mut previous_date = None;
+        let mut rows: Vec<Row> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let event = &entry.event;
+                let date = event.due_at.date();
+                let date_cell = match previous_date.replace(date) == Some(date) {
+                    true => String::new(),
+                    false => locale.format(date, "%a %b %-d"),
+                };
+                let badge = match event.is_external() {
+                    true => Cell::from(""),
+                    false => Cell::from(format!("[{}]", entry.profile))
+                        .style(Style::default().fg(profile_color(entry.session))),
+                };
+                Row::new([
+                    Cell::from(date_cell).style(Style::default().fg(theme::current().accent)),
+                    Cell::from(event.due_at.format(&time_format).unwrap()),
+                    badge,
+                    Cell::from(redact::mask(&event.course_name)).style(match entry.course_color {
+                        Some(color) => Style::default().fg(color),
+                        None => Style::default(),
+                    }),
+                    Cell::from(match event.is_overdue(now) {
+                        true => Line::from(vec![
+                            Span::raw(redact::mask(&event.title)),
+                            Span::styled(" overdue", Style::default().bold()),
+                        ]),
+                        false => Line::raw(redact::mask(&event.title)),
+                    }),
+                ])
+                .style(Style::default().fg(
+                    match (event.is_external(), event.is_done(), event.is_overdue(now)) {
+                        (true, _, _) => theme::current().external,
+                        (false, true, _) => theme::current().done,
+                        (false, false, true) => theme::current().error,
+                        (false, false, false) => theme::current().text,
+                    },
+                ))
+            })
+            .collect();
+        // The marker sits before the first item still ahead, and the selection moves down past it
+        let marker = self
+            .entries
+            .partition_point(|entry| entry.event.due_at <= now);
+        rows.insert(marker, now_marker(now.time(), 5));
+        let mut table_state = self.table_state.clone();
+        table_state.select(
+            self.table_state
+                .selected()
+                .map(|index| index + usize::from(index >= marker)),
+        );
+        let profile_width = self
+            .entries
+            .iter()
+            .map(|entry| entry.profile.chars().count() as u16 + 2)
+            .max()
+            .unwrap_or_default()
+            .max("Profile".len() as u16);
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(12),
+                Constraint::Length(6),
+                Constraint::Length(profile_width + 1),
+                Constraint::Length(18),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::default().bg(theme::current().selection))
+        .style(Style::default().fg(theme::current().text));
+        StatefulWidget::render(table, area, buf, &mut table_state);
+        *self.table_state.offset_mut() = table_state.offset();
+    }
+}
@@ -0,0 +1,254 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Constraint,
+    prelude::{Buffer, Rect},
+    style::Style,
+    text::{Line, Text},
+    widgets::{Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use serde::Deserialize;
+use time::{Date, Duration, OffsetDateTime};
+
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::{Action, Navigation, View},
+    component::{Component, Context},
+    config::Profile,
+    fetch::{get_json, to_local},
+    locale,
+    lookup::{Course, fetch_courses},
+    redact, theme,
+    workload::html_to_text,
+};
+
+/// How far back announcements are looked for
+const ANNOUNCEMENT_DAYS: i64 = 14;
+/// Characters of the message shown under each title
+const PREVIEW_CHARS: usize = 120;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Announcement {
+    pub id: u64,
+    pub title: String,
+    pub html_url: String,
+    pub context_code: String,
+    pub read_state: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "time::serde::iso8601::option::deserialize"
+    )]
+    pub posted_at: Option<OffsetDateTime>,
+    /// HTML body
+    pub message: Option<String>,
+    /// Filled in from the course list after fetching
+    #[serde(skip)]
+    pub course_name: String,
+}
+
+impl Announcement {
+    /// Local date the announcement was posted on
+    pub fn posted_on(&self) -> Option<Date> {
+        self.posted_at.map(|posted_at| to_local(posted_at).date())
+    }
+
+    /// Endpoint marking the announcement read for the user
+    pub fn read_endpoint(&self) -> Option<String> {
+        let course_id = self.context_code.strip_prefix("course_")?;
+        Some(format!(
+            "/api/v1/courses/{course_id}/discussion_topics/{}/read",
+            self.id
+        ))
+    }
+
+    /// Start of the message as plain text on one line
+    fn preview(&self) -> String {
+        let text = html_to_text(self.message.as_deref().unwrap_or_default());
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let text = words.join(" ");
+        match text.char_indices().nth(PREVIEW_CHARS) {
+            Some((end, _)) => format!("{}…", text[..end].trim_end()),
+            None => text,
+        }
+    }
+}
+
+/// Fetches the announcements of the last two weeks the user hasn't read in `courses`
+pub async fn fetch_unread(profile: &Profile, courses: &[Course]) -> Result<Vec<Announcement>> {
+    let context_codes: Vec<String> = courses
+        .iter()
+        .map(|course| format!("course_{}", course.id))
+        .collect();
+    let start_date = (OffsetDateTime::now_utc() - Duration::days(ANNOUNCEMENT_DAYS))
+        .date()
+        .to_string();
+    let mut query = vec![("start_date", start_date.as_str()), ("per_page", "50")];
+    query.extend(
+        context_codes
+            .iter()
+            .map(|code| ("context_codes[]", code.as_str())),
+    );
+    let announcements: Vec<Announcement> =
+        get_json(profile, "/api/v1/announcements", &query).await?;
+    Ok(announcements
+        .into_iter()
+        .filter(|announcement| announcement.read_state.as_deref() != Some("read"))
+        .map(|mut announcement| {
+            announcement.course_name = courses
+                .iter()
+                .find(|course| format!("course_{}", course.id) == announcement.context_code)
+                .map(|course| course.name.clone())
+                .unwrap_or_default();
+            announcement
+        })
+        .collect())
+}
+
+/// Unread announcements of every course that isn't excluded
+pub async fn fetch_all_unread(profile: &Profile) -> Result<Vec<Announcement>> {
+    let courses: Vec<Course> = fetch_courses(profile)
+        .await?
+        .into_iter()
+        .filter(|course| {
+            !profile
+                .exclude_courses
+                .excludes(course.id, &[&course.name, &course.course_code])
+        })
+        .collect();
+    fetch_unread(profile, &courses).await
+}
+
+/// Unread announcements grouped by course, with a preview of each
+pub struct AnnouncementsScreen {
+    /// None while they are being fetched
+    announcements: Option<Vec<Announcement>>,
+    pub table_state: TableState,
+}
+
+impl AnnouncementsScreen {
+    pub fn loading() -> Self {
+        Self {
+            announcements: None,
+            table_state: TableState::default(),
+        }
+    }
+
+    /// Sorts the announcements by course, newest first within each
+    fn set_announcements(&mut self, mut announcements: Vec<Announcement>) {
+        announcements.sort_by(|a, b| {
+            a.course_name
+                .cmp(&b.course_name)
+                .then(b.posted_at.cmp(&a.posted_at))
+        });
+        self.table_state
+            .select((!announcements.is_empty()).then_some(0));
+        self.announcements = Some(announcements);
+    }
+
+    pub fn selected(&self) -> Option<&Announcement> {
+        self.announcements
+            .as_ref()?
+            .get(self.table_state.selected()?)
+    }
+}
+
+impl Component for AnnouncementsScreen {
+    fn init(&mut self, profile: &Profile, action_tx: &UnboundedSender<Action>) -> Result<()> {
+        let profile = profile.clone();
+        let action_tx = action_tx.clone();
+        tokio::spawn(async move {
+            let action = match fetch_all_unread(&profile).await {
+                Ok(announcements) => Action::AnnouncementsFetched(announcements),
+                Err(e) => Action::Error(format!("Loading announcements failed: {e}")),
+            };
+            let _ = action_tx.send(action);
+        });
+        Ok(())
+    }
+
+    fn handle_event(&mut self, key: KeyEvent, bound: Action) -> Action {
+        if key.code == KeyCode::Enter {
+            return Action::OpenAnnouncement;
+        }
+        match bound {
+            Action::Quit
+            | Action::OpenURL
+            | Action::Navigate(Navigation::PrevEvent | Navigation::NextEvent)
+            | Action::ToggleView(View::Announcements) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, context: &Context) -> Option<Action> {
+        match action {
+            Action::Navigate(Navigation::PrevEvent) => self.table_state.select_previous(),
+            Action::Navigate(Navigation::NextEvent) => self.table_state.select_next(),
+            Action::AnnouncementsFetched(announcements) => {
+                let mut announcements = announcements.clone();
+                if let Some(course) = &context.session.focused_course {
+                    let context_code = format!("course_{}", course.id);
+                    announcements.retain(|announcement| announcement.context_code == context_code);
+                }
+                self.set_announcements(announcements);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, _context: &Context) {
+        let Some(announcements) = &self.announcements else {
+            Paragraph::new("Loading announcements...").render(area, buf);
+            return;
+        };
+        if announcements.is_empty() {
+            Paragraph::new("No unread announcements").render(area, buf);
+            return;
+        }
+        let header = ["Course", "Posted", "Announcement"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(Style::default().fg(theme::current().heading));
+        let mut previous_course = None;
+        let rows: Vec<Row> = announcements
+            .iter()
+            .map(|announcement| {
+                let course = match previous_course.replace(&announcement.course_name)
+                    == Some(&announcement.course_name)
+                {
+                    true => String::new(),
+                    false => redact::mask(&announcement.course_name),
+                };
+                let posted = announcement
+                    .posted_on()
+                    .map(|date| locale::current().format(date, "%a %b %-d"))
+                    .unwrap_or_default();
+                let mut text = Text::from(redact::mask(&announcement.title));
+                text.push_line(Line::styled(
+                    redact::mask(&announcement.preview()),
+                    Style::default().fg(theme::current().secondary),
+                ));
+                Row::new([
+                    Cell::from(course).style(Style::default().fg(theme::current().accent)),
+                    Cell::from(posted),
+                    Cell::from(text),
+                ])
+                .height(2)
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(25),
+                Constraint::Length(12),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::default().bg(theme::current().selection))
+        .style(Style::default().fg(theme::current().text));
+        StatefulWidget::render(table, area, buf, &mut self.table_state);
+    }
+}
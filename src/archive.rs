@@ -0,0 +1,189 @@
+use std::{fmt::Write, path::Path};
+
+use color_eyre::eyre::Result;
+use serde::Deserialize;
+
+use crate::{
+    config::Config,
+    fetch::get_json,
+    lookup::{Course, find_course},
+    redact,
+    workload::html_to_text,
+};
+
+#[derive(Debug, Deserialize)]
+struct Assignment {
+    name: String,
+    description: Option<String>,
+    due_at: Option<String>,
+    points_possible: Option<f64>,
+    html_url: String,
+    submission: Option<Submission>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Submission {
+    score: Option<f64>,
+    grade: Option<String>,
+    submitted_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Announcement {
+    title: String,
+    message: Option<String>,
+    posted_at: Option<String>,
+    user_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct File {
+    display_name: String,
+    url: String,
+}
+
+/// Exports a course's assignments, grades, announcements and optionally files as markdown
+pub async fn archive(course: &str, dir: &Path, include_files: bool) -> Result<()> {
+    redact::check_export()?;
+    let profile = Config::load()?.default_profile()?;
+    let course = find_course(&profile, course).await?;
+    let course_endpoint = format!("/api/v1/courses/{}", course.id);
+    println!("Archiving {} into {}", course.name, dir.display());
+
+    let assignments: Vec<Assignment> = get_json(
+        &profile,
+        &format!("{course_endpoint}/assignments"),
+        &[("include[]", "submission"), ("per_page", "100")],
+    )
+    .await?;
+    std::fs::create_dir_all(dir.join("assignments"))?;
+    for assignment in &assignments {
+        let path = dir
+            .join("assignments")
+            .join(format!("{}.md", slug(&assignment.name)));
+        std::fs::write(path, assignment_markdown(assignment)?)?;
+    }
+    std::fs::write(
+        dir.join("grades.md"),
+        grades_markdown(&course, &assignments)?,
+    )?;
+    println!("  {} assignments", assignments.len());
+
+    let context_code = format!("course_{}", course.id);
+    let announcements: Vec<Announcement> = get_json(
+        &profile,
+        "/api/v1/announcements",
+        &[
+            ("context_codes[]", &context_code),
+            ("start_date", "2000-01-01"),
+            ("per_page", "100"),
+        ],
+    )
+    .await?;
+    std::fs::create_dir_all(dir.join("announcements"))?;
+    for announcement in &announcements {
+        let date = announcement
+            .posted_at
+            .as_deref()
+            .and_then(|posted_at| posted_at.get(..10))
+            .unwrap_or("undated");
+        let path = dir
+            .join("announcements")
+            .join(format!("{date}-{}.md", slug(&announcement.title)));
+        std::fs::write(path, announcement_markdown(announcement)?)?;
+    }
+    println!("  {} announcements", announcements.len());
+
+    if include_files {
+        let files: Vec<File> = get_json(
+            &profile,
+            &format!("{course_endpoint}/files"),
+            &[("per_page", "100")],
+        )
+        .await?;
+        std::fs::create_dir_all(dir.join("files"))?;
+        for file in &files {
+            let bytes = reqwest::get(&file.url)
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?;
+            let name = file.display_name.replace(['/', '\\'], "_");
+            std::fs::write(dir.join("files").join(name), bytes)?;
+        }
+        println!("  {} files", files.len());
+    }
+
+    Ok(())
+}
+
+fn slug(title: &str) -> String {
+    let slug = title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase();
+    match slug.is_empty() {
+        true => "untitled".to_string(),
+        false => slug,
+    }
+}
+
+fn assignment_markdown(assignment: &Assignment) -> Result<String> {
+    let mut markdown = String::new();
+    writeln!(markdown, "# {}\n", assignment.name)?;
+    writeln!(markdown, "- Url: {}", assignment.html_url)?;
+    if let Some(due_at) = &assignment.due_at {
+        writeln!(markdown, "- Due: {due_at}")?;
+    }
+    if let Some(points) = assignment.points_possible {
+        writeln!(markdown, "- Points: {points}")?;
+    }
+    if let Some(submission) = &assignment.submission {
+        if let Some(submitted_at) = &submission.submitted_at {
+            writeln!(markdown, "- Submitted: {submitted_at}")?;
+        }
+        if let Some(grade) = &submission.grade {
+            writeln!(markdown, "- Grade: {grade}")?;
+        }
+    }
+    if let Some(description) = &assignment.description {
+        writeln!(markdown, "\n{}", html_to_text(description).trim())?;
+    }
+    Ok(markdown)
+}
+
+fn grades_markdown(course: &Course, assignments: &[Assignment]) -> Result<String> {
+    let mut markdown = String::new();
+    writeln!(markdown, "# Grades for {}\n", course.name)?;
+    writeln!(markdown, "| Assignment | Score | Points |")?;
+    writeln!(markdown, "| --- | --- | --- |")?;
+    for assignment in assignments {
+        let score = assignment
+            .submission
+            .as_ref()
+            .and_then(|submission| submission.score)
+            .map_or("-".to_string(), |score| score.to_string());
+        let points = assignment
+            .points_possible
+            .map_or("-".to_string(), |points| points.to_string());
+        writeln!(markdown, "| {} | {score} | {points} |", assignment.name)?;
+    }
+    Ok(markdown)
+}
+
+fn announcement_markdown(announcement: &Announcement) -> Result<String> {
+    let mut markdown = String::new();
+    writeln!(markdown, "# {}\n", announcement.title)?;
+    if let Some(user_name) = &announcement.user_name {
+        writeln!(markdown, "- Author: {user_name}")?;
+    }
+    if let Some(posted_at) = &announcement.posted_at {
+        writeln!(markdown, "- Posted: {posted_at}")?;
+    }
+    if let Some(message) = &announcement.message {
+        writeln!(markdown, "\n{}", html_to_text(message).trim())?;
+    }
+    Ok(markdown)
+}
@@ -0,0 +1,133 @@
+use std::collections::VecDeque;
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Constraint,
+    prelude::{Buffer, Rect},
+    style::Style,
+    widgets::{Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use time::{OffsetDateTime, format_description};
+
+use crate::{
+    action::{Action, Navigation, View},
+    component::{Component, Context},
+    fetch::now_local,
+    redact, theme,
+};
+
+/// Entries kept for the audit trail, the oldest dropped first
+const AUDIT_LIMIT: usize = 500;
+
+/// An action with what came of it, as shown to the user at the time
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub at: OffsetDateTime,
+    pub action: &'static str,
+    pub outcome: String,
+    pub failed: bool,
+}
+
+/// What recent actions led to, filled in by the action middleware
+#[derive(Default)]
+pub struct AuditLog {
+    entries: VecDeque<AuditEntry>,
+}
+
+impl AuditLog {
+    pub fn record(&mut self, action: &'static str, outcome: String, failed: bool) {
+        self.entries.push_front(AuditEntry {
+            at: now_local(),
+            action,
+            outcome,
+            failed,
+        });
+        self.entries.truncate(AUDIT_LIMIT);
+    }
+
+    /// Newest first
+    pub fn entries(&self) -> impl Iterator<Item = &AuditEntry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Screen listing the audit trail of this run
+pub struct AuditScreen {
+    pub table_state: TableState,
+}
+
+impl AuditScreen {
+    pub fn new() -> Self {
+        Self {
+            table_state: TableState::default().with_selected(0),
+        }
+    }
+}
+
+impl Component for AuditScreen {
+    fn handle_event(&mut self, _key: KeyEvent, bound: Action) -> Action {
+        match bound {
+            Action::Quit
+            | Action::Navigate(Navigation::PrevEvent | Navigation::NextEvent)
+            | Action::ToggleView(View::Audit) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, _context: &Context) -> Option<Action> {
+        match action {
+            Action::Navigate(Navigation::PrevEvent) => self.table_state.select_previous(),
+            Action::Navigate(Navigation::NextEvent) => self.table_state.select_next(),
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, context: &Context) {
+        if context.audit.is_empty() {
+            Paragraph::new("Nothing has happened yet").render(area, buf);
+            return;
+        }
+
+        let time_format = format_description::parse("[hour]:[minute]:[second]").unwrap();
+        let header = ["Time", "Action", "Outcome"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(Style::default().fg(theme::current().heading));
+        let rows = context.audit.entries().map(|entry| {
+            Row::new([
+                entry.at.format(&time_format).unwrap(),
+                entry.action.replace('_', " "),
+                redact::mask(&entry.outcome),
+            ])
+            .style(Style::default().fg(match entry.failed {
+                true => theme::current().error,
+                false => theme::current().text,
+            }))
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(8),
+                Constraint::Length(20),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::default().bg(theme::current().selection))
+        .style(Style::default().fg(theme::current().text));
+        let selected = self.table_state.selected().unwrap_or_default();
+        self.table_state
+            .select(Some(selected.min(context.audit.len() - 1)));
+        StatefulWidget::render(table, area, buf, &mut self.table_state);
+    }
+}
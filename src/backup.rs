@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use color_eyre::eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+
+use crate::{bookmarks::Bookmarks, receipts::Receipt, redact, store::Annotations};
+
+/// Version of the export format, bumped whenever its layout changes. Older exports are migrated
+/// on import by [`migrate`]
+const EXPORT_VERSION: u64 = 2;
+
+/// Every piece of local-only data, in a form that can be moved to another machine
+#[derive(Debug, Serialize, Deserialize)]
+struct Export {
+    version: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    exported_at: OffsetDateTime,
+    annotations: Annotations,
+    receipts: Vec<Receipt>,
+    bookmarks: Bookmarks,
+}
+
+pub fn export(out: &Path) -> Result<()> {
+    redact::check_export()?;
+    let export = Export {
+        version: EXPORT_VERSION,
+        exported_at: OffsetDateTime::now_utc(),
+        annotations: Annotations::load()?,
+        receipts: Receipt::load_all()?,
+        bookmarks: Bookmarks::load()?,
+    };
+    std::fs::write(out, serde_json::to_vec_pretty(&export)?)?;
+    println!(
+        "Exported {} completed item(s), {} receipt(s) and {} bookmark(s) to {}",
+        export.annotations.completed.len(),
+        export.receipts.len(),
+        export.bookmarks.items.len(),
+        out.display()
+    );
+    Ok(())
+}
+
+/// Merges an export into the local data. Annotations are merged like synced ones, and receipts
+/// and bookmarks already present are skipped, so importing the same file twice is harmless
+pub fn import(input: &Path) -> Result<()> {
+    let export = migrate(serde_json::from_slice(&std::fs::read(input)?)?)?;
+
+    let mut annotations = Annotations::load()?;
+    annotations.merge(&export.annotations);
+    annotations.save()?;
+
+    let mut bookmarks = Bookmarks::load()?;
+    bookmarks.merge(&export.bookmarks);
+    bookmarks.save()?;
+
+    let existing = Receipt::load_all()?;
+    let mut new_receipts: Vec<&Receipt> = export
+        .receipts
+        .iter()
+        .filter(|receipt| {
+            !existing.iter().any(|existing| {
+                existing.timestamp == receipt.timestamp && existing.html_url == receipt.html_url
+            })
+        })
+        .collect();
+    new_receipts.sort_by_key(|receipt| receipt.timestamp);
+    for receipt in &new_receipts {
+        receipt.record()?;
+    }
+
+    println!(
+        "Imported annotations and {} new receipt(s) from {}",
+        new_receipts.len(),
+        input.display()
+    );
+    Ok(())
+}
+
+/// Brings an export of any known version up to the current layout
+fn migrate(mut value: Value) -> Result<Export> {
+    let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version > EXPORT_VERSION {
+        return Err(eyre!(
+            "The export is version {version}, this version of canvastui only reads up to {EXPORT_VERSION}"
+        ));
+    }
+    if version == 0 {
+        // Version 0 is a bare annotations.json copied from another machine
+        value = serde_json::json!({
+            "version": 1,
+            "exported_at": OffsetDateTime::now_utc()
+                .format(&time::format_description::well_known::Rfc3339)?,
+            "annotations": value,
+            "receipts": [],
+        });
+    }
+    if version <= 1 {
+        // Version 1 predates bookmarks
+        value["version"] = 2.into();
+        value["bookmarks"] = serde_json::json!([]);
+    }
+    Ok(serde_json::from_value(value)?)
+}
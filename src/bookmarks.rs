@@ -0,0 +1,164 @@
+use std::path::PathBuf;
+
+use color_eyre::eyre::Result;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Constraint,
+    prelude::{Buffer, Rect},
+    style::Style,
+    widgets::{Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    action::{Action, Navigation, View},
+    component::{Component, Context},
+    redact,
+    store::data_dir,
+    theme,
+};
+
+const BOOKMARKS_FILE: &str = "canvastui/bookmarks.json";
+
+/// An item kept on the user's shortlist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// Absolute url, so bookmarks from every profile open the right Canvas instance
+    pub url: String,
+    pub title: String,
+    pub course_name: String,
+    /// Planner type of the item, e.g. assignment, wiki_page or discussion_topic
+    pub kind: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub added_at: OffsetDateTime,
+}
+
+/// Bookmarked items, most recently added first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Bookmarks {
+    pub items: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    fn path() -> Option<PathBuf> {
+        Some(data_dir()?.join(BOOKMARKS_FILE))
+    }
+
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match std::fs::read(&path) {
+            Ok(contents) => Ok(serde_json::from_slice(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Adds the bookmark, or removes it if its url is already bookmarked. Returns whether it was
+    /// added
+    pub fn toggle(&mut self, bookmark: Bookmark) -> bool {
+        match self.items.iter().position(|item| item.url == bookmark.url) {
+            Some(index) => {
+                self.items.remove(index);
+                false
+            }
+            None => {
+                self.items.insert(0, bookmark);
+                true
+            }
+        }
+    }
+
+    /// Adds bookmarks that aren't here yet, keeping the newest first
+    pub fn merge(&mut self, other: &Bookmarks) {
+        for bookmark in &other.items {
+            if !self.items.iter().any(|item| item.url == bookmark.url) {
+                self.items.push(bookmark.clone());
+            }
+        }
+        self.items
+            .sort_by_key(|bookmark| std::cmp::Reverse(bookmark.added_at));
+    }
+}
+
+/// Screen listing the bookmarks
+pub struct BookmarksScreen {
+    pub table_state: TableState,
+}
+
+impl BookmarksScreen {
+    pub fn new() -> Self {
+        Self {
+            table_state: TableState::default().with_selected(0),
+        }
+    }
+}
+
+impl Component for BookmarksScreen {
+    fn handle_event(&mut self, _key: KeyEvent, bound: Action) -> Action {
+        match bound {
+            Action::Quit
+            | Action::OpenURL
+            | Action::ToggleBookmark
+            | Action::Navigate(Navigation::PrevEvent | Navigation::NextEvent)
+            | Action::ToggleView(View::Bookmarks) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, _context: &Context) -> Option<Action> {
+        match action {
+            Action::Navigate(Navigation::PrevEvent) => self.table_state.select_previous(),
+            Action::Navigate(Navigation::NextEvent) => self.table_state.select_next(),
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, context: &Context) {
+        let bookmarks = context.bookmarks;
+        if bookmarks.items.is_empty() {
+            Paragraph::new("No bookmarks yet, press b on an item to add it").render(area, buf);
+            return;
+        }
+
+        let header = ["Course", "Item", "Type"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(Style::default().fg(theme::current().heading));
+        let rows = bookmarks.items.iter().map(|bookmark| {
+            Row::new([
+                redact::mask(&bookmark.course_name),
+                redact::mask(&bookmark.title),
+                bookmark.kind.replace('_', " "),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(16),
+                Constraint::Fill(1),
+                Constraint::Length(16),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::default().bg(theme::current().selection))
+        .style(Style::default().fg(theme::current().text));
+        StatefulWidget::render(table, area, buf, &mut self.table_state);
+    }
+}
@@ -0,0 +1,230 @@
+use color_eyre::eyre::{Result, eyre};
+use reqwest::{StatusCode, Url};
+use time::{OffsetDateTime, UtcOffset, format_description};
+
+use crate::{
+    config::{CalDavConfig, Config, ConflictPreference},
+    fetch::{CalendarEvent, fetch, load_cache, set_planner_completed},
+    ics,
+    store::Annotations,
+};
+
+/// Pushes every Canvas deadline to the CalDAV server as a VTODO and pulls back completion
+/// status changed on the server. Each side's status is compared with the one both had at the
+/// last sync, so only the side that changed since wins
+pub async fn sync(config: &Config) -> Result<()> {
+    let caldav = config
+        .caldav
+        .as_ref()
+        .ok_or(eyre!("No [caldav] section in the config file"))?;
+    let profile = config.default_profile()?;
+    // Items marked done in Canvas since the last fetch have to reach the server too
+    let calendar = match fetch(&profile).await {
+        Ok(calendar) => calendar,
+        Err(e) => {
+            eprintln!("Using cached planner data, Canvas could not be reached: {e}");
+            load_cache(&profile).map_err(|e| eyre!("Could not read cached planner data: {e}"))?
+        }
+    };
+    let mut annotations = Annotations::load()?;
+    let client = CalDavClient::new(caldav, &profile.url)?;
+
+    let (mut pushed, mut pulled) = (0, 0);
+    for event in calendar
+        .dates
+        .iter()
+        .flat_map(|date| &date.events)
+        .filter(|event| !event.is_external())
+    {
+        let local_done = event.is_done() || annotations.completed.contains(&event.html_url);
+        let remote = client.remote(event).await?;
+        let trigger = remote.as_ref().and_then(|remote| remote.trigger.clone());
+        let done = match remote {
+            Some(remote) => resolve(
+                annotations.caldav_synced.get(&event.html_url).copied(),
+                local_done,
+                remote.done,
+                caldav.prefer,
+            ),
+            None => local_done,
+        };
+        // Submissions can't be taken back, so the server is told they are done again
+        let done = done || event.submitted;
+
+        if done != local_done {
+            if event.completed
+                && let Err(e) = set_planner_completed(&profile, event, false).await
+            {
+                // The next sync tries again, as the last synced status stays as it was
+                eprintln!("Could not mark {} not done in Canvas: {e}", event.title);
+                continue;
+            }
+            annotations.set_completed(&event.html_url, done);
+            pulled += 1;
+        }
+        client.push(event, done, trigger.as_deref()).await?;
+        annotations
+            .caldav_synced
+            .insert(event.html_url.clone(), done);
+        pushed += 1;
+    }
+
+    annotations.save()?;
+    println!("Pushed {pushed} item(s), pulled {pulled} completion change(s)");
+    Ok(())
+}
+
+/// Settles whether an item is done from each side's status and the one both had at the last
+/// sync. `prefer` decides only when the sides disagree without a last sync to tell which changed
+fn resolve(base: Option<bool>, local: bool, remote: bool, prefer: ConflictPreference) -> bool {
+    match base {
+        _ if local == remote => local,
+        Some(base) if local == base => remote,
+        Some(_) => local,
+        None => match prefer {
+            ConflictPreference::Remote => remote,
+            ConflictPreference::Local => local,
+        },
+    }
+}
+
+/// Pushes one item with an alarm `minutes_before` its deadline, which later syncs keep
+pub async fn push_reminder(
+    config: &CalDavConfig,
+    canvas_url: &str,
+    event: &CalendarEvent,
+    minutes_before: u64,
+) -> Result<()> {
+    let client = CalDavClient::new(config, canvas_url)?;
+    let trigger = format!("-PT{minutes_before}M");
+    client.push(event, event.is_done(), Some(&trigger)).await
+}
+
+/// What the server has for an item
+struct RemoteTodo {
+    done: bool,
+    /// When its alarm goes off relative to the deadline, e.g. -PT60M
+    trigger: Option<String>,
+}
+
+struct CalDavClient<'a> {
+    config: &'a CalDavConfig,
+    collection: Url,
+    canvas_url: Option<Url>,
+    http: reqwest::Client,
+}
+
+impl<'a> CalDavClient<'a> {
+    fn new(config: &'a CalDavConfig, canvas_url: &str) -> Result<Self> {
+        let mut collection = config.url.clone();
+        if !collection.ends_with('/') {
+            collection.push('/');
+        }
+        Ok(Self {
+            config,
+            collection: collection.parse()?,
+            canvas_url: canvas_url.parse().ok(),
+            http: reqwest::Client::new(),
+        })
+    }
+
+    fn resource_url(&self, event: &CalendarEvent) -> Result<Url> {
+        Ok(self.collection.join(&format!("{}.ics", ics::uid(event)))?)
+    }
+
+    /// Returns whether the server has the item marked completed and its alarm, or None if it
+    /// was never pushed
+    async fn remote(&self, event: &CalendarEvent) -> Result<Option<RemoteTodo>> {
+        let response = self
+            .http
+            .get(self.resource_url(event)?)
+            .basic_auth(&self.config.username, Some(&self.config.password))
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let body = response.error_for_status()?.text().await?;
+        Ok(Some(RemoteTodo {
+            done: ics::property(&body, "STATUS").is_some_and(|status| status == "COMPLETED"),
+            trigger: ics::property(&body, "TRIGGER"),
+        }))
+    }
+
+    async fn push(&self, event: &CalendarEvent, done: bool, trigger: Option<&str>) -> Result<()> {
+        let utc_format = format_description::parse("[year][month][day]T[hour][minute][second]Z")?;
+        // The task is due when Canvas has it due, whatever soft deadline it was moved to
+        let due = event
+            .official_due_at
+            .unwrap_or(event.due_at)
+            .assume_offset(UtcOffset::current_local_offset()?)
+            .to_offset(UtcOffset::UTC)
+            .format(&utc_format)?;
+        let url = match &self.canvas_url {
+            Some(base) => base.join(&event.html_url)?.to_string(),
+            None => event.html_url.clone(),
+        };
+        let summary = ics::escape(&format!("{}: {}", event.course_name, event.title));
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//CanvasTUI//EN".to_string(),
+            "BEGIN:VTODO".to_string(),
+            format!("UID:{}", ics::uid(event)),
+            format!("DTSTAMP:{}", OffsetDateTime::now_utc().format(&utc_format)?),
+            format!("DUE:{due}"),
+            format!("SUMMARY:{summary}"),
+            format!("URL:{url}"),
+            format!("STATUS:{}", if done { "COMPLETED" } else { "NEEDS-ACTION" }),
+        ];
+        if let Some(trigger) = trigger {
+            lines.extend([
+                "BEGIN:VALARM".to_string(),
+                "ACTION:DISPLAY".to_string(),
+                format!("DESCRIPTION:{summary}"),
+                format!("TRIGGER:{trigger}"),
+                "END:VALARM".to_string(),
+            ]);
+        }
+        lines.extend(["END:VTODO".to_string(), "END:VCALENDAR".to_string()]);
+        let body = lines.join("\r\n");
+
+        self.http
+            .put(self.resource_url(event)?)
+            .basic_auth(&self.config.username, Some(&self.config.password))
+            .header("Content-Type", "text/calendar; charset=utf-8")
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_side_that_changed_wins() {
+        for prefer in [ConflictPreference::Remote, ConflictPreference::Local] {
+            // Done in Canvas since the last sync
+            assert!(resolve(Some(false), true, false, prefer));
+            // Done on the server since the last sync
+            assert!(resolve(Some(false), false, true, prefer));
+            // Taken back on the server, e.g. ticked by mistake on a phone
+            assert!(!resolve(Some(true), true, false, prefer));
+            // Taken back in Canvas
+            assert!(!resolve(Some(true), false, true, prefer));
+            assert!(resolve(Some(false), true, true, prefer));
+        }
+    }
+
+    #[test]
+    fn prefer_decides_without_a_last_sync() {
+        assert!(resolve(None, false, true, ConflictPreference::Remote));
+        assert!(!resolve(None, false, true, ConflictPreference::Local));
+        assert!(!resolve(None, true, false, ConflictPreference::Remote));
+        assert!(resolve(None, true, false, ConflictPreference::Local));
+    }
+}
@@ -0,0 +1,351 @@
+//! Client for the Canvas REST and GraphQL APIs. Every request to a Canvas instance goes through
+//! [`Client`], which authenticates with a bearer token or a browser session cookie, retries
+//! requests that failed on the way or were throttled, and records the rate limit budget and
+//! request timings.
+
+use std::{
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use color_eyre::eyre::{Result, eyre};
+use reqwest::{
+    Method, RequestBuilder, Response, StatusCode, Url,
+    header::{COOKIE, HeaderMap},
+};
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+
+use crate::{config::Profile, metrics, ratelimit, sso};
+
+/// Retries after the first attempt before a request counts as failed
+const MAX_RETRIES: u32 = 3;
+/// Wait before the first retry, doubled for each one after it
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+/// Longest wait a `Retry-After` header is followed for
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const PLANNER_ENDPOINT: &str = "/api/v1/planner/items";
+/// Planner items asked for per page, the most Canvas hands out at once
+const PLANNER_PER_PAGE: &str = "100";
+/// Stops following `Link` headers after this many pages in case the server keeps sending them
+const MAX_PAGES: usize = 50;
+/// Prefix Canvas puts before JSON it sends to cookie sessions, against JSON hijacking
+const JSON_GUARD: &[u8] = b"while(1);";
+
+/// Connection pool shared by every client
+static HTTP: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// A Canvas instance and the token to use on it
+#[derive(Clone)]
+pub struct Client {
+    base: Url,
+    auth: Auth,
+    http: reqwest::Client,
+}
+
+/// How requests prove who they are made for
+#[derive(Clone)]
+enum Auth {
+    Token(String),
+    /// A browser session cookie, with the CSRF token writes have to repeat
+    Cookie {
+        cookie: String,
+        csrf: Option<String>,
+    },
+}
+
+impl Auth {
+    fn apply(&self, request: RequestBuilder, method: &Method) -> RequestBuilder {
+        match self {
+            Auth::Token(token) => request.bearer_auth(token),
+            Auth::Cookie { cookie, csrf } => {
+                let request = request.header(COOKIE, cookie);
+                match csrf {
+                    Some(csrf) if method != Method::GET => request.header("X-CSRF-Token", csrf),
+                    _ => request,
+                }
+            }
+        }
+    }
+
+    /// Why Canvas answered 401
+    fn rejected(&self) -> &'static str {
+        match self {
+            Auth::Token(_) => {
+                "Canvas rejected the access token, it may have expired or been revoked"
+            }
+            Auth::Cookie { .. } => {
+                "The Canvas session has ended, sign in again with canvastui cookie-login"
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct GraphqlResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphqlError>,
+}
+
+#[derive(Deserialize)]
+struct GraphqlError {
+    message: String,
+}
+
+impl Client {
+    pub fn new(profile: &Profile) -> Result<Self> {
+        let auth = match &profile.cookie {
+            Some(cookie) => Auth::Cookie {
+                cookie: cookie.clone(),
+                csrf: sso::csrf_token(cookie),
+            },
+            None if profile.token.is_empty() => {
+                return Err(eyre!("profile {} has no access token", profile.name));
+            }
+            None => Auth::Token(profile.token.clone()),
+        };
+        Ok(Self {
+            base: profile.url.parse()?,
+            auth,
+            http: HTTP.get_or_init(reqwest::Client::new).clone(),
+        })
+    }
+
+    /// Url of an endpoint on the instance, e.g. `/api/v1/courses`
+    pub fn url(&self, endpoint: &str) -> Result<Url> {
+        Ok(self.base.join(endpoint)?)
+    }
+
+    /// Whether the token may be sent to `url`, which only holds for the Canvas instance itself
+    pub fn owns(&self, url: &Url) -> bool {
+        url.origin() == self.base.origin()
+    }
+
+    /// Sends a request, retrying when Canvas throttled it and, for requests that are safe to
+    /// repeat, when it failed on the way or the server had a temporary error
+    async fn send(&self, method: Method, url: Url, body: Option<&Value>) -> Result<Response> {
+        let started = Instant::now();
+        let path = url.path().to_string();
+        let repeatable = matches!(method, Method::GET | Method::PUT);
+        let mut attempt = 0;
+        let response = loop {
+            let mut request = self
+                .auth
+                .apply(self.http.request(method.clone(), url.clone()), &method);
+            request = match body {
+                Some(body) => request.json(body),
+                None if method != Method::GET => request.header(reqwest::header::CONTENT_LENGTH, 0),
+                None => request,
+            };
+            let retry = match request.send().await {
+                Ok(response) => {
+                    ratelimit::record(response.headers());
+                    let status = response.status();
+                    let throttled = status == StatusCode::TOO_MANY_REQUESTS
+                        || status == StatusCode::FORBIDDEN
+                            && ratelimit::remaining_in(response.headers())
+                                .is_some_and(|remaining| remaining <= 0.0);
+                    match throttled || repeatable && status.is_server_error() {
+                        true if attempt < MAX_RETRIES => retry_after(response.headers()),
+                        _ if status == StatusCode::UNAUTHORIZED => {
+                            break Err(eyre!(self.auth.rejected()));
+                        }
+                        _ => break response.error_for_status().map_err(Into::into),
+                    }
+                }
+                Err(e) if repeatable && (e.is_connect() || e.is_timeout()) => {
+                    if attempt == MAX_RETRIES {
+                        break Err(e.into());
+                    }
+                    None
+                }
+                Err(e) => break Err(e.into()),
+            };
+            tokio::time::sleep(retry.unwrap_or(BASE_BACKOFF * 2u32.pow(attempt))).await;
+            attempt += 1;
+        };
+        metrics::record_fetch(&path, started.elapsed());
+        response
+    }
+
+    /// Fetches a url on the instance, returning the body and the `rel="next"` page if any
+    async fn get_page(&self, url: Url) -> Result<(Bytes, Option<Url>)> {
+        let response = self.send(Method::GET, url, None).await?;
+        let next = next_link(response.headers()).filter(|next| self.owns(next));
+        let body = strip_json_guard(response.bytes().await?);
+        #[cfg(feature = "netsim")]
+        let body = crate::netsim::simulate(body).await?;
+        Ok((body, next))
+    }
+
+    pub async fn get_bytes(&self, endpoint: &str, query: &[(&str, &str)]) -> Result<Bytes> {
+        let mut url = self.url(endpoint)?;
+        url.query_pairs_mut().extend_pairs(query);
+        Ok(self.get_page(url).await?.0)
+    }
+
+    /// Fetches an endpoint and deserializes its JSON body
+    pub async fn get_json<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T> {
+        Ok(serde_json::from_slice(
+            &self.get_bytes(endpoint, query).await?,
+        )?)
+    }
+
+    /// Fetches every page of a list endpoint, following the `Link` headers
+    pub async fn get_all(&self, endpoint: &str, query: &[(&str, &str)]) -> Result<Vec<Value>> {
+        let mut url = self.url(endpoint)?;
+        url.query_pairs_mut().extend_pairs(query);
+        let mut items: Vec<Value> = vec![];
+        let mut next = Some(url);
+        for _ in 0..MAX_PAGES {
+            let Some(url) = next.take() else {
+                break;
+            };
+            let (body, next_url) = self.get_page(url).await?;
+            items.extend(serde_json::from_slice::<Vec<Value>>(&body)?);
+            next = next_url;
+        }
+        if next.is_some() {
+            log::warn!("Stopped fetching {endpoint} after {MAX_PAGES} pages");
+        }
+        Ok(items)
+    }
+
+    /// Sends a PUT request without a body, as used by endpoints that set a flag
+    pub async fn put(&self, endpoint: &str) -> Result<()> {
+        self.send(Method::PUT, self.url(endpoint)?, None).await?;
+        Ok(())
+    }
+
+    /// Sends a PUT request with a JSON body and deserializes the JSON response
+    pub async fn put_json<T: DeserializeOwned>(&self, endpoint: &str, body: &Value) -> Result<T> {
+        let response = self
+            .send(Method::PUT, self.url(endpoint)?, Some(body))
+            .await?;
+        Ok(serde_json::from_slice(&strip_json_guard(
+            response.bytes().await?,
+        ))?)
+    }
+
+    /// Sends a POST request with a JSON body and deserializes the JSON response
+    pub async fn post_json<T: DeserializeOwned>(&self, endpoint: &str, body: &Value) -> Result<T> {
+        let response = self
+            .send(Method::POST, self.url(endpoint)?, Some(body))
+            .await?;
+        Ok(serde_json::from_slice(&strip_json_guard(
+            response.bytes().await?,
+        ))?)
+    }
+
+    /// Runs a query against the GraphQL API, for settings the REST API doesn't expose
+    pub async fn graphql<T: DeserializeOwned>(&self, query: &str) -> Result<T> {
+        let response: GraphqlResponse<T> = self
+            .post_json("/api/graphql", &json!({ "query": query }))
+            .await?;
+        match (response.data, response.errors.first()) {
+            (Some(data), None) => Ok(data),
+            (_, Some(error)) => Err(eyre!("{}", error.message)),
+            (None, None) => Err(eyre!("the response has no data")),
+        }
+    }
+
+    /// Planner items from `start_date` on, up to `end_date` if given, limited to `context_codes`
+    /// when there are any
+    pub async fn planner_items(
+        &self,
+        start_date: &str,
+        end_date: Option<&str>,
+        context_codes: &[String],
+    ) -> Result<Vec<Value>> {
+        let mut query = vec![("start_date", start_date), ("per_page", PLANNER_PER_PAGE)];
+        query.extend(end_date.map(|end_date| ("end_date", end_date)));
+        query.extend(
+            context_codes
+                .iter()
+                .map(|code| ("context_codes[]", code.as_str())),
+        );
+        self.get_all(PLANNER_ENDPOINT, &query).await
+    }
+
+    /// A single assignment with the user's submission
+    pub async fn assignment<T: DeserializeOwned>(
+        &self,
+        course_id: u64,
+        assignment_id: u64,
+    ) -> Result<T> {
+        self.get_json(
+            &format!("/api/v1/courses/{course_id}/assignments/{assignment_id}"),
+            &[("include[]", "submission")],
+        )
+        .await
+    }
+}
+
+fn strip_json_guard(body: Bytes) -> Bytes {
+    match body.starts_with(JSON_GUARD) {
+        true => body.slice(JSON_GUARD.len()..),
+        false => body,
+    }
+}
+
+/// How long a throttled response asks to wait with `Retry-After`, if it does
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds: f64 = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()?;
+    // Capped before the conversion, which panics on huge values; NaN is ignored
+    Duration::try_from_secs_f64(seconds.clamp(0.0, MAX_BACKOFF.as_secs_f64())).ok()
+}
+
+/// The `rel="next"` url of a `Link` header such as
+/// `<https://canvas.example.edu/api/v1/planner/items?page=2>; rel="next", <...>; rel="last"`
+fn next_link(headers: &HeaderMap) -> Option<Url> {
+    headers
+        .get_all(reqwest::header::LINK)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .find_map(|link| {
+            let (url, params) = link.trim().split_once(';')?;
+            params
+                .split(';')
+                .any(|param| param.trim().replace('"', "") == "rel=next")
+                .then(|| {
+                    url.trim()
+                        .trim_start_matches('<')
+                        .trim_end_matches('>')
+                        .parse()
+                        .ok()
+                })?
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn waits(value: &str) -> Option<Duration> {
+        let mut headers = HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, value.parse().unwrap());
+        retry_after(&headers)
+    }
+
+    #[test]
+    fn caps_retry_after_without_panicking() {
+        assert_eq!(waits("1.5"), Some(Duration::from_millis(1500)));
+        assert_eq!(waits("-3"), Some(Duration::ZERO));
+        assert_eq!(waits("1e30"), Some(MAX_BACKOFF));
+        assert_eq!(waits("inf"), Some(MAX_BACKOFF));
+        assert_eq!(waits("NaN"), None);
+        assert_eq!(waits("soon"), None);
+    }
+}
@@ -0,0 +1,268 @@
+use std::{env, sync::OnceLock};
+
+use ratatui::{buffer::Buffer, style::Color, style::Modifier};
+use serde::Deserialize;
+
+use crate::config::TerminalConfig;
+
+static CURRENT: OnceLock<Capabilities> = OnceLock::new();
+
+/// Colors the terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+pub enum ColorSupport {
+    /// The 16 ANSI colors, e.g. the Linux console
+    #[serde(rename = "16")]
+    Basic,
+    #[serde(rename = "256")]
+    Indexed,
+    #[serde(rename = "truecolor")]
+    TrueColor,
+}
+
+/// Characters the terminal's font can draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Glyphs {
+    Ascii,
+    /// Common Unicode symbols and light box drawing, but no Nerd Font icons
+    Unicode,
+    NerdFont,
+}
+
+/// What the terminal supports, detected from the environment at startup and overridable in the
+/// config file
+#[derive(Debug, Clone, Copy)]
+pub struct Capabilities {
+    pub color: ColorSupport,
+    pub glyphs: Glyphs,
+    pub italic: bool,
+    pub bold: bool,
+}
+
+/// Detects the terminal's capabilities, with the configured settings taking precedence
+pub fn init(config: &TerminalConfig) {
+    let detected = Capabilities::detect();
+    let _ = CURRENT.set(Capabilities {
+        color: config.color.unwrap_or(detected.color),
+        glyphs: config.glyphs.unwrap_or(detected.glyphs),
+        italic: config.italic.unwrap_or(detected.italic),
+        bold: config.bold.unwrap_or(detected.bold),
+    });
+}
+
+pub fn current() -> &'static Capabilities {
+    CURRENT.get_or_init(Capabilities::detect)
+}
+
+impl Capabilities {
+    /// Guesses from $TERM, $COLORTERM, $TERM_PROGRAM and the locale. Terminals can't be asked
+    /// about their font, so Nerd Font icons are assumed anywhere Unicode is
+    fn detect() -> Self {
+        let term = env::var("TERM").unwrap_or_default();
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let program = env::var("TERM_PROGRAM").unwrap_or_default();
+        let console = term == "linux";
+        let legacy = matches!(
+            term.as_str(),
+            "dumb" | "ansi" | "vt52" | "vt100" | "vt102" | "vt220" | "cons25"
+        );
+
+        let color = if colorterm == "truecolor"
+            || colorterm == "24bit"
+            || term.ends_with("-direct")
+            || matches!(
+                program.as_str(),
+                "iTerm.app" | "WezTerm" | "vscode" | "ghostty"
+            ) {
+            ColorSupport::TrueColor
+        } else if term.contains("256color") {
+            ColorSupport::Indexed
+        } else {
+            ColorSupport::Basic
+        };
+
+        let glyphs = if legacy || !utf8_locale() {
+            Glyphs::Ascii
+        } else if console {
+            Glyphs::Unicode
+        } else {
+            Glyphs::NerdFont
+        };
+
+        Self {
+            color,
+            glyphs,
+            // The console and old screen versions show italics as inverse or colored text
+            italic: !(console || legacy || term.starts_with("screen")),
+            bold: term != "dumb",
+        }
+    }
+
+    pub fn nerd_fonts(&self) -> bool {
+        self.glyphs == Glyphs::NerdFont
+    }
+
+    /// Whether the frame needs touching up before it is drawn
+    fn is_limited(&self) -> bool {
+        self.color < ColorSupport::TrueColor
+            || self.glyphs < Glyphs::NerdFont
+            || !self.italic
+            || !self.bold
+    }
+
+    /// Rewrites a rendered frame into what the terminal can show: colors are mapped to the
+    /// nearest supported one, unsupported modifiers are dropped and symbols are replaced by
+    /// simpler ones
+    pub fn degrade(&self, buf: &mut Buffer) {
+        if !self.is_limited() {
+            return;
+        }
+        for cell in buf.content.iter_mut() {
+            cell.fg = self.color(cell.fg);
+            cell.bg = self.color(cell.bg);
+            if !self.italic {
+                cell.modifier.remove(Modifier::ITALIC);
+            }
+            if !self.bold {
+                cell.modifier.remove(Modifier::BOLD);
+            }
+            if let Some(replacement) = self.fallback(cell.symbol()) {
+                cell.set_symbol(replacement);
+            }
+        }
+    }
+
+    pub fn color(&self, color: Color) -> Color {
+        match (self.color, color) {
+            (ColorSupport::TrueColor, _) => color,
+            (ColorSupport::Indexed, Color::Rgb(r, g, b)) => Color::Indexed(rgb_to_indexed(r, g, b)),
+            (ColorSupport::Basic, Color::Rgb(r, g, b)) => nearest_basic((r, g, b)),
+            (ColorSupport::Basic, Color::Indexed(index)) => nearest_basic(indexed_to_rgb(index)),
+            _ => color,
+        }
+    }
+
+    /// Simpler symbol for one the font may not have
+    fn fallback(&self, symbol: &str) -> Option<&'static str> {
+        let mut chars = symbol.chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            return None;
+        };
+        if c.is_ascii() {
+            return None;
+        }
+        // Heavy and rounded box drawing, which console fonts often lack
+        let light = match c {
+            '━' => Some("─"),
+            '┃' => Some("│"),
+            '┏' | '╭' => Some("┌"),
+            '┓' | '╮' => Some("┐"),
+            '┗' | '╰' => Some("└"),
+            '┛' | '╯' => Some("┘"),
+            _ => None,
+        };
+        match self.glyphs {
+            Glyphs::NerdFont => None,
+            Glyphs::Unicode => light,
+            Glyphs::Ascii => Some(match c {
+                '─' | '━' => "-",
+                '│' | '┃' => "|",
+                '┌' | '┐' | '└' | '┘' | '├' | '┤' | '┬' | '┴' | '┼' | '┏' | '┓' | '┗' | '┛'
+                | '╭' | '╮' | '╰' | '╯' => "+",
+                '·' => "-",
+                '•' => "*",
+                '█' => "_",
+                '▸' => ">",
+                '▾' => "v",
+                '…' => ".",
+                _ => return None,
+            }),
+        }
+    }
+}
+
+/// Whether the locale's character set is UTF-8. An unset locale is assumed to be, since that is
+/// what terminals default to
+fn utf8_locale() -> bool {
+    let Some(locale) = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+    else {
+        return true;
+    };
+    let locale = locale.to_ascii_lowercase();
+    locale.contains("utf-8") || locale.contains("utf8")
+}
+
+/// xterm's default values of the 16 ANSI colors
+const BASIC_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Levels of the 6x6x6 color cube in the 256 color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+fn nearest_basic(rgb: (u8, u8, u8)) -> Color {
+    BASIC_COLORS
+        .iter()
+        .min_by_key(|(_, basic)| distance(rgb, *basic))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..16 => BASIC_COLORS[index as usize].1,
+        16..232 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+/// Closest color of the cube or the gray ramp in the 256 color palette
+fn rgb_to_indexed(r: u8, g: u8, b: u8) -> u8 {
+    let cube_index = |value: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, level)| (**level as i32 - value as i32).abs())
+            .map_or(0, |(index, _)| index as u8)
+    };
+    let cube = 16 + 36 * cube_index(r) + 6 * cube_index(g) + cube_index(b);
+    let average = ((r as u32 + g as u32 + b as u32) / 3) as u8;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23);
+    match distance((r, g, b), indexed_to_rgb(gray)) < distance((r, g, b), indexed_to_rgb(cube)) {
+        true => gray,
+        false => cube,
+    }
+}
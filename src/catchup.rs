@@ -0,0 +1,445 @@
+use std::collections::{HashMap, HashSet};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout},
+    prelude::{Buffer, Rect},
+    style::Style,
+    widgets::{Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime, Time};
+
+use crate::{
+    action::{Action, Navigation, View},
+    component::{Component, Context},
+    config::CatchUpConfig,
+    fetch::{CalendarEvent, local_offset, now_local},
+    locale, redact,
+    store::Reminder,
+    theme,
+    workload::Estimate,
+};
+
+/// Unfinished items due within this many days are planned along with overdue ones
+const DUE_SOON_DAYS: i64 = 7;
+/// Step of the time adjustment of an item, in hours
+const HOURS_STEP: f64 = 0.5;
+
+/// Changes made to the plan from the catch-up screen
+#[derive(Debug, Clone, Copy)]
+pub enum PlanEdit {
+    MoreTime,
+    LessTime,
+    /// Create reminders for the plan
+    Accept,
+}
+
+struct PlannedItem {
+    event: CalendarEvent,
+    hours: f64,
+    day: Date,
+}
+
+/// Hours an item is expected to take before it is adjusted, from its type and reading time
+fn default_hours(event: &CalendarEvent, estimate: Option<Estimate>) -> f64 {
+    let base = match event.plannable_type.as_str() {
+        "assignment" => 2.0,
+        _ => 1.0,
+    };
+    let reading = estimate.map_or(0.0, |estimate| estimate.minutes as f64 / 60.0);
+    ((base + reading) / HOURS_STEP).ceil() * HOURS_STEP
+}
+
+/// Screen proposing a day-by-day plan for overdue and soon due items within the time available
+/// each day
+pub struct CatchUpScreen {
+    config: CatchUpConfig,
+    today: Date,
+    /// None while overdue items are being fetched
+    items: Option<Vec<PlannedItem>>,
+    pub table_state: TableState,
+}
+
+impl CatchUpScreen {
+    pub fn loading(config: CatchUpConfig, today: Date) -> Self {
+        Self {
+            config,
+            today,
+            items: None,
+            table_state: TableState::default(),
+        }
+    }
+
+    /// Plans the items, overdue and earliest due first, filling each day up to the hours
+    /// available. An item longer than a whole day gets a day to itself
+    pub fn set_items(
+        &mut self,
+        mut events: Vec<CalendarEvent>,
+        estimates: &HashMap<String, Option<Estimate>>,
+    ) {
+        let mut seen = HashSet::new();
+        events.retain(|event| seen.insert(event.html_url.clone()));
+        events.sort_by_key(|event| event.due_at);
+        let mut day = self.today;
+        let mut used = 0.0;
+        let items: Vec<PlannedItem> = events
+            .into_iter()
+            .map(|event| {
+                let estimate = estimates.get(&event.html_url).copied().flatten();
+                let hours = default_hours(&event, estimate);
+                if used > 0.0 && used + hours > self.config.hours_per_day {
+                    day = day.next_day().unwrap();
+                    used = 0.0;
+                }
+                used += hours;
+                PlannedItem { event, hours, day }
+            })
+            .collect();
+        self.table_state = TableState::default().with_selected((!items.is_empty()).then_some(0));
+        self.items = Some(items);
+    }
+
+    fn selected_item(&mut self) -> Option<&mut PlannedItem> {
+        let index = self.table_state.selected()?;
+        self.items.as_mut()?.get_mut(index)
+    }
+
+    pub fn selected_event(&self) -> Option<&CalendarEvent> {
+        let index = self.table_state.selected()?;
+        Some(&self.items.as_ref()?.get(index)?.event)
+    }
+
+    /// Moves the selected item to another day, never before today, keeping it selected
+    pub fn move_selected(&mut self, days: i64) {
+        let today = self.today;
+        let Some(item) = self.selected_item() else {
+            return;
+        };
+        item.day = (item.day + Duration::days(days)).max(today);
+        let html_url = item.event.html_url.clone();
+        let Some(items) = &mut self.items else {
+            return;
+        };
+        items.sort_by_key(|item| (item.day, item.event.due_at));
+        let index = items
+            .iter()
+            .position(|item| item.event.html_url == html_url);
+        self.table_state.select(index);
+    }
+
+    /// Plans the unfinished items due soon along with the missing ones, which are named after
+    /// their course like the planner's
+    fn set_missing(&mut self, missing: &[CalendarEvent], context: &Context) {
+        let session = context.session;
+        let now = now_local();
+        let horizon =
+            PrimitiveDateTime::new(now.date(), now.time()) + Duration::days(DUE_SOON_DAYS);
+        let events = session
+            .calendar
+            .dates
+            .iter()
+            .flat_map(|date| &date.events)
+            .filter(|event| !event.is_done() && !event.is_external() && event.due_at <= horizon)
+            .cloned()
+            .chain(
+                missing
+                    .iter()
+                    .filter(|event| !context.annotations.completed.contains(&event.html_url))
+                    .filter(|event| {
+                        event
+                            .course_id
+                            .is_none_or(|course_id| !session.excluded_courses.contains(&course_id))
+                    })
+                    .map(|event| {
+                        let mut event = event.clone();
+                        // Missing submissions only come with a course id
+                        let name = event.course_id.and_then(|course_id| {
+                            session
+                                .nicknames
+                                .get(&course_id)
+                                .or(session.courses.get(&course_id))
+                        });
+                        if let Some(name) = name {
+                            event.course_name = name.clone();
+                        }
+                        event
+                    }),
+            )
+            .collect();
+        self.set_items(events, &session.estimates);
+    }
+
+    fn adjust_selected(&mut self, edit: PlanEdit) {
+        let Some(item) = self.selected_item() else {
+            return;
+        };
+        match edit {
+            PlanEdit::MoreTime => item.hours += HOURS_STEP,
+            PlanEdit::LessTime => item.hours = (item.hours - HOURS_STEP).max(HOURS_STEP),
+            PlanEdit::Accept => {}
+        }
+    }
+
+    /// Reminders for each item at the configured time of its planned day, keyed by html_url
+    pub fn reminders(&self) -> Vec<(String, Reminder)> {
+        let time = self
+            .config
+            .reminder_time
+            .unwrap_or(Time::from_hms(9, 0, 0).unwrap());
+        let offset = local_offset();
+        let now = OffsetDateTime::now_utc();
+        self.items
+            .iter()
+            .flatten()
+            .map(|item| {
+                let at = PrimitiveDateTime::new(item.day, time).assume_offset(offset);
+                let reminder = Reminder {
+                    at: at.max(now),
+                    title: item.event.title.clone(),
+                    course_name: item.event.course_name.clone(),
+                };
+                (item.event.html_url.clone(), reminder)
+            })
+            .collect()
+    }
+
+    fn hours_on(&self, day: Date) -> f64 {
+        self.items
+            .iter()
+            .flatten()
+            .filter(|item| item.day == day)
+            .map(|item| item.hours)
+            .sum()
+    }
+}
+
+impl Component for CatchUpScreen {
+    fn handle_event(&mut self, key: KeyEvent, bound: Action) -> Action {
+        match key.code {
+            KeyCode::Char('+' | '=') => return Action::EditPlan(PlanEdit::MoreTime),
+            KeyCode::Char('-') => return Action::EditPlan(PlanEdit::LessTime),
+            KeyCode::Enter => return Action::EditPlan(PlanEdit::Accept),
+            _ => {}
+        }
+        match bound {
+            Action::Quit
+            | Action::OpenURL
+            | Action::Navigate(
+                Navigation::PrevEvent
+                | Navigation::NextEvent
+                | Navigation::PrevDate
+                | Navigation::NextDate,
+            )
+            | Action::ToggleView(View::CatchUp) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, context: &Context) -> Option<Action> {
+        match action {
+            Action::MissingFetched { session, events } if *session == context.focus => {
+                self.set_missing(events, context);
+            }
+            Action::EditPlan(edit) => self.adjust_selected(*edit),
+            Action::Navigate(Navigation::PrevEvent) => self.table_state.select_previous(),
+            Action::Navigate(Navigation::NextEvent) => self.table_state.select_next(),
+            Action::Navigate(Navigation::PrevDate) => self.move_selected(-1),
+            Action::Navigate(Navigation::NextDate) => self.move_selected(1),
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, _context: &Context) {
+        let Some(items) = &self.items else {
+            Paragraph::new("Looking for overdue items...").render(area, buf);
+            return;
+        };
+        if items.is_empty() {
+            Paragraph::new(format!(
+                "Nothing overdue or due in the next {DUE_SOON_DAYS} days"
+            ))
+            .render(area, buf);
+            return;
+        }
+
+        let [help_area, table_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(area);
+        Paragraph::new(format!(
+            "Catch-up plan with {}h a day · h/l move to another day · +/- time · Enter create reminders",
+            self.config.hours_per_day
+        ))
+        .style(Style::default().fg(theme::current().heading))
+        .render(help_area, buf);
+
+        let header = ["Day", "Course", "Item", "Hours", "Due"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(Style::default().fg(theme::current().heading));
+        let locale = locale::current();
+        let mut previous_day = None;
+        let rows: Vec<Row> = items
+            .iter()
+            .map(|item| {
+                let day = match previous_day.replace(item.day) == Some(item.day) {
+                    true => Cell::from(""),
+                    false => {
+                        let hours = self.hours_on(item.day);
+                        Cell::from(format!("{} {hours}h", locale.format(item.day, "%a %b %-d")))
+                            .style(
+                                Style::default().fg(match hours > self.config.hours_per_day {
+                                    true => theme::current().error,
+                                    false => theme::current().accent,
+                                }),
+                            )
+                    }
+                };
+                let due_date = item.event.due_at.date();
+                let (state, state_color) = match (due_date < self.today, item.day > due_date) {
+                    (true, _) => ("overdue", theme::current().error),
+                    (false, true) => ("late", theme::current().warning),
+                    (false, false) => ("", theme::current().text),
+                };
+                Row::new([
+                    day,
+                    Cell::from(redact::mask(&item.event.course_name)),
+                    Cell::from(redact::mask(&item.event.title)),
+                    Cell::from(format!("{}", item.hours)),
+                    Cell::from(format!("{} {state}", locale.format(due_date, "%a %b %-d")))
+                        .style(Style::default().fg(state_color)),
+                ])
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(16),
+                Constraint::Length(16),
+                Constraint::Fill(1),
+                Constraint::Length(6),
+                Constraint::Length(20),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::default().bg(theme::current().selection))
+        .style(Style::default().fg(theme::current().text));
+        StatefulWidget::render(table, table_area, buf, &mut self.table_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Month;
+
+    use super::*;
+
+    fn today() -> Date {
+        Date::from_calendar_date(2026, Month::October, 17).unwrap()
+    }
+
+    fn event(html_url: &str, plannable_type: &str, days: i64) -> CalendarEvent {
+        CalendarEvent {
+            course_name: "PHYS 1".to_string(),
+            due_at: (today() + Duration::days(days)).midnight(),
+            official_due_at: None,
+            title: html_url.to_string(),
+            html_url: html_url.to_string(),
+            plannable_type: plannable_type.to_string(),
+            course_id: Some(1),
+            plannable_id: 0,
+            submitted: false,
+            graded: false,
+            completed: false,
+            override_id: None,
+            unlock_at: None,
+            lock_at: None,
+            attempts: None,
+        }
+    }
+
+    fn screen(hours_per_day: f64) -> CatchUpScreen {
+        let config = CatchUpConfig {
+            hours_per_day,
+            reminder_time: None,
+        };
+        CatchUpScreen::loading(config, today())
+    }
+
+    /// Planned items as url, hours and days after today, in plan order
+    fn plan(screen: &CatchUpScreen) -> Vec<(&str, f64, i64)> {
+        screen
+            .items
+            .iter()
+            .flatten()
+            .map(|item| {
+                let days = (item.day - today()).whole_days();
+                (item.event.html_url.as_str(), item.hours, days)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fills_each_day_up_to_the_hours_available() {
+        let mut screen = screen(3.0);
+        screen.set_items(
+            vec![
+                event("/essay", "assignment", -1),
+                event("/lab", "assignment", -3),
+                event("/quiz", "quiz", -2),
+                // Overdue and due soon lists can both hold an item
+                event("/lab", "assignment", -3),
+            ],
+            &HashMap::new(),
+        );
+        assert_eq!(
+            plan(&screen),
+            [("/lab", 2.0, 0), ("/quiz", 1.0, 0), ("/essay", 2.0, 1)]
+        );
+        assert_eq!(screen.hours_on(today()), 3.0);
+    }
+
+    #[test]
+    fn items_longer_than_a_day_get_one_to_themselves() {
+        let mut screen = screen(1.5);
+        let estimates = HashMap::from([(
+            "/thesis".to_string(),
+            Some(Estimate {
+                words: 24000,
+                minutes: 120,
+            }),
+        )]);
+        screen.set_items(
+            vec![
+                event("/thesis", "assignment", -2),
+                event("/quiz", "quiz", -1),
+                event("/reading", "discussion_topic", 0),
+            ],
+            &estimates,
+        );
+        assert_eq!(
+            plan(&screen),
+            [("/thesis", 4.0, 0), ("/quiz", 1.0, 1), ("/reading", 1.0, 2)]
+        );
+    }
+
+    #[test]
+    fn moves_items_without_going_before_today() {
+        let mut screen = screen(3.0);
+        screen.set_items(
+            vec![event("/lab", "assignment", -3), event("/quiz", "quiz", -2)],
+            &HashMap::new(),
+        );
+        screen.move_selected(-5);
+        assert_eq!(plan(&screen), [("/lab", 2.0, 0), ("/quiz", 1.0, 0)]);
+
+        screen.move_selected(2);
+        assert_eq!(plan(&screen), [("/quiz", 1.0, 0), ("/lab", 2.0, 2)]);
+        assert_eq!(screen.selected_event().unwrap().html_url, "/lab");
+
+        screen.adjust_selected(PlanEdit::LessTime);
+        screen.adjust_selected(PlanEdit::LessTime);
+        screen.adjust_selected(PlanEdit::LessTime);
+        assert_eq!(plan(&screen)[1], ("/lab", HOURS_STEP, 2));
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use color_eyre::eyre::Result;
+
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about = "View your upcoming Canvas assignments from the terminal"
+)]
+pub struct Cli {
+    /// Use this profile from the config file in place of the default one, `default` for the
+    /// default one itself
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+    /// Show this profile from the config file side by side with the default one
+    #[arg(long, global = true)]
+    pub split: Option<String>,
+    /// Load every profile from the config file and start on the agenda merging them
+    #[arg(long, global = true)]
+    pub agenda: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Generate an Atom feed of a course's assignments and announcements from the cache
+    Feed {
+        /// Course code to generate the feed for, e.g. CS101
+        #[arg(long)]
+        course: String,
+        /// File to write the feed to
+        #[arg(long, default_value = "feed.xml")]
+        out: PathBuf,
+    },
+    /// Sync local annotations through the sync directory and deadlines with the CalDAV server
+    Sync,
+    /// Export a course's assignments, grades and announcements as markdown before access is lost
+    Archive {
+        /// Course id, code or name
+        course: String,
+        /// Directory to write the archive to
+        dir: PathBuf,
+        /// Also download the course's files
+        #[arg(long)]
+        files: bool,
+    },
+    /// Simulate a course grade with different assignment group weights, without changing Canvas
+    WhatIf {
+        /// Course id, code or name
+        course: String,
+        /// Group weight override such as "Final Exam=40", can be repeated
+        #[arg(long = "weight", value_name = "GROUP=PERCENT")]
+        weights: Vec<String>,
+    },
+    /// Export all local-only data (annotations and submission receipts) to a JSON file, or the
+    /// cached planner items as an iCalendar file
+    Export {
+        /// File to write the export to [default: canvastui-export.json, or canvastui.ics with
+        /// --ics]
+        out: Option<PathBuf>,
+        /// Write every profile's cached items as iCalendar events at their due time instead
+        #[arg(long)]
+        ics: bool,
+    },
+    /// Merge local-only data from a file written by the export command
+    Import {
+        /// File to import
+        file: PathBuf,
+    },
+    /// Print the number of items due today for embedding in a shell prompt, from the cache only
+    Prompt {
+        /// Print without color codes
+        #[arg(long)]
+        plain: bool,
+    },
+    /// Preview notification rules taken from your Canvas notification preferences
+    ImportNotifications {
+        /// Save the previewed rules instead of only showing them
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Print a one-page overview of a day's schedule and deadlines from the cache, for paper
+    Print {
+        /// Day to print: today, tomorrow, yesterday or a date like 2025-03-14
+        #[arg(long, default_value = "today")]
+        date: String,
+        /// Write a PDF to this file instead of printing plain text
+        #[arg(long, value_name = "FILE")]
+        pdf: Option<PathBuf>,
+    },
+    /// List items due soon, exiting with 1 if there are any, e.g. to check before shutting down
+    Due {
+        /// How far ahead to look, such as 30m, 48h, 2d or 1w
+        #[arg(long, default_value = "24h")]
+        within: String,
+        /// Only items of courses whose name contains this, e.g. CS101
+        #[arg(long)]
+        course: Option<String>,
+        /// Only items that haven't been handed in or marked done
+        #[arg(long)]
+        unsubmitted: bool,
+    },
+    /// Print the items due soon without starting the TUI, for scripts, status bars and cron jobs
+    List {
+        /// How far ahead to look, such as 30m, 48h, 2d or 1w
+        #[arg(long, default_value = "7d")]
+        within: String,
+        /// Print a JSON array with each item's course, title, kind, due time, url and status
+        #[arg(long, conflicts_with = "plain")]
+        json: bool,
+        /// Print one tab-separated line per item: due time, course, title and done or todo
+        #[arg(long)]
+        plain: bool,
+    },
+    /// Sign in with an access token, which is checked with Canvas and stored in the OS keyring
+    Login,
+    /// Sign in with a session cookie copied from the browser, for schools that don't allow access
+    /// tokens
+    CookieLogin,
+    /// Open the TUI at the item a pasted Canvas url or assignment id points to
+    Open {
+        /// Canvas url or assignment id
+        target: String,
+    },
+    /// Print a shell completion script, e.g. `canvastui completions zsh > ~/.zfunc/_canvastui`
+    Completions { shell: Shell },
+    /// Print the man page, e.g. `canvastui man > canvastui.1`
+    Man {
+        /// Write a page for every subcommand into this directory instead
+        #[arg(long)]
+        dir: Option<PathBuf>,
+    },
+}
+
+/// Writes a completion script for `shell` covering every subcommand and flag to stdout
+pub fn print_completions(shell: Shell) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+}
+
+/// Writes the man page to stdout as roff, or a page per subcommand into `dir`
+pub fn print_man(dir: Option<&Path>) -> Result<()> {
+    match dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)?;
+            clap_mangen::generate_to(Cli::command(), dir)?;
+            println!("Wrote man pages to {}", dir.display());
+        }
+        None => clap_mangen::Man::new(Cli::command()).render(&mut io::stdout())?,
+    }
+    Ok(())
+}
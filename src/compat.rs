@@ -0,0 +1,211 @@
+//! Shims for self-hosted Canvas instances that lack newer endpoints. Open-source deployments
+//! often predate the planner API, so when it answers 404 the items are built from the upcoming
+//! events and each course's assignments instead, in the shape the planner would have sent them.
+
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+};
+
+use color_eyre::eyre::{Report, Result};
+use futures::future::try_join_all;
+use reqwest::{StatusCode, Url};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use time::{Date, OffsetDateTime, format_description::well_known::Rfc3339};
+
+use crate::{canvas::Client, config::Profile, fetch::to_local, lookup::Course};
+
+/// Whether each instance, by url, has the planner API, once a fetch found out
+static PLANNER: LazyLock<Mutex<HashMap<String, bool>>> = LazyLock::new(Default::default);
+
+#[derive(Debug, Deserialize)]
+struct Assignment {
+    id: u64,
+    name: String,
+    html_url: String,
+    #[serde(
+        default,
+        deserialize_with = "time::serde::iso8601::option::deserialize"
+    )]
+    due_at: Option<OffsetDateTime>,
+    unlock_at: Option<String>,
+    lock_at: Option<String>,
+    submission: Option<Submission>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Submission {
+    submitted_at: Option<String>,
+    #[serde(default)]
+    workflow_state: String,
+}
+
+/// An entry of `/users/self/upcoming_events`, which lists assignments as well as events
+#[derive(Debug, Deserialize)]
+struct UpcomingEvent {
+    id: u64,
+    title: String,
+    html_url: String,
+    #[serde(rename = "type")]
+    kind: String,
+    context_code: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "time::serde::iso8601::option::deserialize"
+    )]
+    start_at: Option<OffsetDateTime>,
+}
+
+/// Whether the instance is known to lack the planner API
+pub fn lacks_planner(profile: &Profile) -> bool {
+    PLANNER.lock().unwrap().get(&profile.url) == Some(&false)
+}
+
+/// Planner items from `start` on, up to `end` if given, built from other endpoints on instances
+/// without the planner. `contexts` limits the planner to them when there are any, like
+/// [`Client::planner_items`]
+pub async fn planner_items(
+    profile: &Profile,
+    client: &Client,
+    start: Date,
+    end: Option<Date>,
+    contexts: &[String],
+) -> Result<Vec<Value>> {
+    if !lacks_planner(profile) {
+        let end_date = end.map(|end| end.to_string());
+        match client
+            .planner_items(&start.to_string(), end_date.as_deref(), contexts)
+            .await
+        {
+            Err(e) if is_not_found(&e) => {
+                log::info!(
+                    "{} has no planner API, building items from assignments",
+                    profile.url
+                );
+            }
+            result => {
+                PLANNER.lock().unwrap().insert(profile.url.clone(), true);
+                return result;
+            }
+        }
+        PLANNER.lock().unwrap().insert(profile.url.clone(), false);
+    }
+    let in_range = |date: Date| date >= start && end.is_none_or(|end| date <= end);
+    fallback_items(profile, client, in_range).await
+}
+
+fn is_not_found(e: &Report) -> bool {
+    e.downcast_ref::<reqwest::Error>()
+        .and_then(reqwest::Error::status)
+        == Some(StatusCode::NOT_FOUND)
+}
+
+/// Assignments of the active courses that aren't excluded and calendar events, due on dates
+/// `in_range` accepts
+async fn fallback_items(
+    profile: &Profile,
+    client: &Client,
+    in_range: impl Fn(Date) -> bool,
+) -> Result<Vec<Value>> {
+    let courses: Vec<Course> = client
+        .get_json(
+            "/api/v1/courses",
+            &[("enrollment_state", "active"), ("per_page", "100")],
+        )
+        .await?;
+    let courses: Vec<Course> = courses
+        .into_iter()
+        .filter(|course| {
+            !profile
+                .exclude_courses
+                .excludes(course.id, &[&course.name, &course.course_code])
+        })
+        .collect();
+
+    let assignments = try_join_all(courses.iter().map(|course| async move {
+        client
+            .get_all(
+                &format!("/api/v1/courses/{}/assignments", course.id),
+                &[
+                    ("include[]", "submission"),
+                    ("order_by", "due_at"),
+                    ("per_page", "100"),
+                ],
+            )
+            .await
+    }))
+    .await?;
+    let mut items = vec![];
+    for (course, assignments) in courses.iter().zip(assignments) {
+        for assignment in assignments {
+            let assignment: Assignment = serde_json::from_value(assignment)?;
+            let Some(due_at) = assignment
+                .due_at
+                .filter(|due_at| in_range(to_local(*due_at).date()))
+            else {
+                continue;
+            };
+            let (submitted, graded) = assignment.submission.map_or((false, false), |submission| {
+                (
+                    submission.submitted_at.is_some(),
+                    submission.workflow_state == "graded",
+                )
+            });
+            items.push(json!({
+                "context_name": course.name,
+                "course_id": course.id,
+                "plannable_id": assignment.id,
+                "html_url": path_of(assignment.html_url),
+                "plannable_type": "assignment",
+                "submissions": { "submitted": submitted, "graded": graded },
+                "plannable": {
+                    "title": assignment.name,
+                    "unlock_at": assignment.unlock_at,
+                    "lock_at": assignment.lock_at,
+                },
+                "plannable_date": due_at.format(&Rfc3339)?,
+            }));
+        }
+    }
+
+    // Assignments are already in from the course lists, so only the events are taken
+    let events: Vec<UpcomingEvent> = client
+        .get_json("/api/v1/users/self/upcoming_events", &[])
+        .await?;
+    for event in events.into_iter().filter(|event| event.kind == "event") {
+        let course = event
+            .context_code
+            .as_deref()
+            .and_then(|code| code.strip_prefix("course_"))
+            .and_then(|id| id.parse::<u64>().ok());
+        let context_name = match course {
+            Some(id) => match courses.iter().find(|course| course.id == id) {
+                Some(course) => course.name.clone(),
+                None => continue,
+            },
+            None => "Calendar".to_string(),
+        };
+        let Some(start_at) = event.start_at.filter(|at| in_range(to_local(*at).date())) else {
+            continue;
+        };
+        items.push(json!({
+            "context_name": context_name,
+            "course_id": course,
+            "plannable_id": event.id,
+            "html_url": path_of(event.html_url),
+            "plannable_type": "calendar_event",
+            "submissions": false,
+            "plannable": { "title": event.title },
+            "plannable_date": start_at.format(&Rfc3339)?,
+        }));
+    }
+    Ok(items)
+}
+
+/// Path of an absolute url, as the planner gives them
+fn path_of(url: String) -> String {
+    url.parse::<Url>()
+        .map(|parsed| parsed.path().to_string())
+        .unwrap_or(url)
+}
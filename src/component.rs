@@ -0,0 +1,50 @@
+use color_eyre::eyre::Result;
+use crossterm::event::KeyEvent;
+use ratatui::prelude::{Buffer, Rect};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::{Action, SessionId},
+    audit::AuditLog,
+    bookmarks::Bookmarks,
+    config::Profile,
+    session::Session,
+    store::Annotations,
+    triage::Change,
+};
+
+/// App state that components show without owning it
+pub struct Context<'a> {
+    /// Session of the focused profile
+    pub session: &'a Session,
+    /// Which session `session` is, to tell apart fetches made for other profiles
+    pub focus: SessionId,
+    pub bookmarks: &'a Bookmarks,
+    /// New and changed items waiting to be triaged, oldest first
+    pub triage_queue: &'a [Change],
+    pub audit: &'a AuditLog,
+    /// Local-only data about items, such as the files linked to them
+    pub annotations: &'a Annotations,
+}
+
+/// A part of the interface with its own state, which takes the keys while it is in front
+pub trait Component {
+    /// Starts loading what the component shows once it is opened for a profile
+    fn init(&mut self, _profile: &Profile, _action_tx: &UnboundedSender<Action>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Turns a key into an action. `bound` is what the keybindings made of the key, which is
+    /// passed on when it means something here
+    fn handle_event(&mut self, _key: KeyEvent, bound: Action) -> Action {
+        bound
+    }
+
+    /// Keeps the component's own state in step with an action, such as moving its selection or
+    /// taking in what was fetched for it. Returns what the app is to do next, e.g. show a status
+    fn update(&mut self, _action: &Action, _context: &Context) -> Option<Action> {
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, context: &Context);
+}
@@ -0,0 +1,882 @@
+use std::{collections::BTreeMap, env, path::PathBuf, process::Command, sync::OnceLock};
+
+use color_eyre::eyre::{Result, eyre};
+use serde::{Deserialize, Deserializer};
+use time::{Date, Duration, PrimitiveDateTime, Time, format_description};
+
+use crate::{
+    CACHE_FILE,
+    capabilities::{ColorSupport, Glyphs},
+    courses::CourseContext,
+    login, sso,
+    theme::Palette,
+};
+
+const CONFIG_FILE: &str = "canvastui/config.toml";
+const DEFAULT_PROFILE: &str = "default";
+
+/// Profile picked with `--profile`, standing in for the default one everywhere for this run
+static SELECTED_PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Makes `default_profile` return the named profile from now on, `default` keeping the usual one
+pub fn select_profile(name: String) {
+    let _ = SELECTED_PROFILE.set(name);
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Canvas instance of the default profile, e.g. https://canvas.example.edu
+    pub canvas_url: Option<String>,
+    pub access_token: Option<String>,
+    /// Shell command printing the access token, e.g. from a password manager
+    pub token_command: Option<String>,
+    /// How the default profile signs in to Canvas
+    pub auth: AuthMode,
+    /// Browser session cookie for `auth = "cookie"`, by default the one `canvastui cookie-login`
+    /// saved
+    pub cookie_file: Option<PathBuf>,
+    /// Name of an instance in `environments` the default profile uses instead of `canvas_url`
+    pub environment: Option<String>,
+    /// Beta or test instances of the institution's Canvas by name
+    pub environments: BTreeMap<String, Environment>,
+    /// Minutes between automatic refetches of the planner, never when unset
+    pub refresh_minutes: Option<u64>,
+    /// Screen shown on startup
+    pub default_view: DefaultView,
+    /// Resolves each course's primary instructor for the courses view and the detail line
+    pub show_instructors: bool,
+    /// Groups each day's items under headings for the part of the day they are due in
+    pub time_buckets: bool,
+    /// Courses of the default profile left out of every view and never fetched
+    pub exclude_courses: CourseExclusions,
+    /// Days around today the default profile's planner is fetched for
+    #[serde(flatten)]
+    pub horizon: Horizon,
+    pub notifications: NotificationConfig,
+    /// External ICS calendars overlaid onto the Canvas planner
+    pub calendars: Vec<ExternalCalendar>,
+    pub caldav: Option<CalDavConfig>,
+    pub profiles: Vec<Profile>,
+    pub sync: Option<SyncConfig>,
+    /// Key sequences bound to each action, replacing its default bindings
+    #[serde(deserialize_with = "key_sequences")]
+    pub keys: BTreeMap<String, Vec<String>>,
+    pub locale: LocaleConfig,
+    pub lock: Option<LockConfig>,
+    pub terminal: TerminalConfig,
+    pub theme: ThemeConfig,
+    pub catch_up: CatchUpConfig,
+    pub grades: GradesConfig,
+    pub yank: YankConfig,
+    pub reminders: RemindersConfig,
+    pub confirm: ConfirmConfig,
+}
+
+/// A Canvas account on some instance
+#[derive(Debug, Clone, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub url: String,
+    #[serde(default, alias = "access_token")]
+    pub token: String,
+    /// Shell command printing the token, run when the profile is opened if `token` is unset
+    #[serde(default)]
+    pub token_command: Option<String>,
+    /// Browser session cookie sent instead of the token, for `auth = "cookie"`
+    #[serde(skip)]
+    pub cookie: Option<String>,
+    #[serde(default)]
+    pub exclude_courses: CourseExclusions,
+    /// Marks the instance as non-production, e.g. beta or test
+    #[serde(default)]
+    pub environment: Option<String>,
+    #[serde(flatten)]
+    pub horizon: Horizon,
+}
+
+/// How far back and ahead of today the planner is fetched
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct Horizon {
+    /// Days before today, so overdue work shows up
+    pub days_back: u32,
+    /// Days after today, everything ahead when unset
+    pub days_forward: Option<u32>,
+}
+
+impl Horizon {
+    pub fn start(&self, today: Date) -> Date {
+        today - Duration::days(self.days_back.into())
+    }
+
+    pub fn end(&self, today: Date) -> Option<Date> {
+        Some(today + Duration::days(self.days_forward?.into()))
+    }
+}
+
+/// How requests to Canvas are authenticated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthMode {
+    /// An access token generated in Canvas under Account > Settings
+    #[default]
+    Token,
+    /// A session cookie copied from a browser signed in through SSO. Best-effort, for
+    /// institutions that don't let students generate tokens
+    Cookie,
+}
+
+/// A non-production Canvas instance, e.g. the beta instance refreshed from production weekly
+#[derive(Debug, Clone, Deserialize)]
+pub struct Environment {
+    pub url: String,
+    /// The production token or token command is used when neither is set
+    pub access_token: Option<String>,
+    pub token_command: Option<String>,
+}
+
+impl Profile {
+    /// The default profile without credentials, enough to read its cache
+    pub fn offline() -> Self {
+        Self {
+            name: DEFAULT_PROFILE.to_string(),
+            url: String::new(),
+            token: String::new(),
+            token_command: None,
+            cookie: None,
+            exclude_courses: CourseExclusions::default(),
+            environment: None,
+            horizon: Horizon::default(),
+        }
+    }
+
+    /// Badge for a non-production instance, from the profile's `environment` or a url like
+    /// school.beta.instructure.com
+    pub fn environment_badge(&self) -> Option<String> {
+        let environment = self.environment.clone().or_else(|| {
+            let url: reqwest::Url = self.url.parse().ok()?;
+            let host = url.host_str()?;
+            ["beta", "test"]
+                .into_iter()
+                .find(|label| host.split('.').any(|part| part == *label))
+                .map(str::to_string)
+        })?;
+        Some(environment.to_uppercase())
+    }
+
+    /// The profile with its token, running `token_command` when no token is given
+    pub fn with_token(mut self) -> Result<Self> {
+        if let Some(command) = &self.token_command
+            && self.token.is_empty()
+            && self.cookie.is_none()
+        {
+            self.token = run_token_command(command)?;
+        }
+        Ok(self)
+    }
+
+    /// Where the planner is cached. Names are checked by `Config::load` to stay within the file
+    /// name
+    pub fn cache_file(&self) -> PathBuf {
+        match self.name.as_str() {
+            DEFAULT_PROFILE => PathBuf::from(CACHE_FILE),
+            name => PathBuf::from(format!("/tmp/canvastui-{name}.json")),
+        }
+    }
+}
+
+/// Courses to leave out
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct CourseExclusions(Vec<CourseExclusion>);
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum CourseExclusion {
+    Id(u64),
+    /// Matched against the course name or code, ignoring case, where `*` stands for any text,
+    /// e.g. "*orientation*"
+    Pattern(String),
+}
+
+impl CourseExclusions {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether the course with `id`, known by `names`, is excluded
+    pub fn excludes(&self, id: u64, names: &[&str]) -> bool {
+        self.0.iter().any(|exclusion| match exclusion {
+            CourseExclusion::Id(excluded_id) => *excluded_id == id,
+            CourseExclusion::Pattern(pattern) => {
+                names.iter().any(|name| matches_pattern(pattern, name))
+            }
+        })
+    }
+}
+
+/// Matches text against a pattern where `*` stands for any text, ignoring case
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let text = text.to_lowercase();
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(start) => rest = &rest[start + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Overrides for terminal capabilities that are detected wrongly, e.g. over mosh or with a font
+/// without Nerd Font icons
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TerminalConfig {
+    pub color: Option<ColorSupport>,
+    pub glyphs: Option<Glyphs>,
+    pub italic: Option<bool>,
+    pub bold: Option<bool>,
+}
+
+/// Colors of the interface: a built-in palette, with any of its colors replaced by name, e.g.
+/// `heading = "#ff79c6"`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub palette: Palette,
+    #[serde(flatten)]
+    pub colors: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CalDavConfig {
+    /// Url of the calendar collection deadlines are stored in
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    #[serde(default)]
+    pub prefer: ConflictPreference,
+}
+
+/// Which side wins when Canvas and the CalDAV server disagree on whether an item is done with no
+/// earlier sync to tell which side changed. Submissions on Canvas always count as done
+/// regardless of this setting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPreference {
+    #[default]
+    Remote,
+    Local,
+}
+
+/// Shared directory local annotations are synced through, e.g. a Dropbox or Syncthing folder
+/// or a git checkout
+#[derive(Debug, Clone, Deserialize)]
+pub struct SyncConfig {
+    pub dir: PathBuf,
+    /// Name this machine's annotations are stored under, defaults to the hostname
+    pub device: Option<String>,
+}
+
+/// Hides the planner on shared machines when nobody is using it
+#[derive(Debug, Clone, Deserialize)]
+pub struct LockConfig {
+    pub idle_minutes: u64,
+    /// Required to unlock when set, otherwise any key unlocks
+    pub passphrase: Option<String>,
+}
+
+/// Language of dates shown in the app
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LocaleConfig {
+    /// Locale such as "de_DE", defaults to $LC_ALL, $LC_TIME or $LANG
+    pub name: Option<String>,
+    /// strftime-style format of the date header
+    pub header_format: Option<String>,
+    /// Whether to prefix headers of nearby dates with Today, Tomorrow or Yesterday
+    pub relative_dates: bool,
+    pub today: Option<String>,
+    pub tomorrow: Option<String>,
+    pub yesterday: Option<String>,
+}
+
+impl Default for LocaleConfig {
+    fn default() -> Self {
+        Self {
+            name: None,
+            header_format: None,
+            relative_dates: true,
+            today: None,
+            tomorrow: None,
+            yesterday: None,
+        }
+    }
+}
+
+/// Screen the app starts on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DefaultView {
+    #[default]
+    Planner,
+    /// Every profile's items in one timeline, like `--agenda`
+    Agenda,
+    Triage,
+}
+
+/// Time available for the catch-up planner
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CatchUpConfig {
+    pub hours_per_day: f64,
+    /// Local time reminders from an accepted plan go off on each planned day
+    #[serde(deserialize_with = "clock_time")]
+    pub reminder_time: Option<Time>,
+}
+
+impl Default for CatchUpConfig {
+    fn default() -> Self {
+        Self {
+            hours_per_day: 3.0,
+            reminder_time: None,
+        }
+    }
+}
+
+/// Grades the required score solver aims for
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GradesConfig {
+    /// Percentage for courses without a target of their own
+    pub target: f64,
+    /// Targets keyed by course id or by the course name shown in the courses view
+    pub targets: BTreeMap<String, f64>,
+}
+
+impl Default for GradesConfig {
+    fn default() -> Self {
+        Self {
+            target: 90.0,
+            targets: BTreeMap::new(),
+        }
+    }
+}
+
+impl GradesConfig {
+    pub fn target(&self, course: &CourseContext) -> f64 {
+        self.targets
+            .get(&course.id.to_string())
+            .or(self.targets.get(&course.name))
+            .copied()
+            .unwrap_or(self.target)
+    }
+}
+
+/// Kinds of link the selected item can be copied as
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinkFormat {
+    Markdown,
+    Org,
+}
+
+impl LinkFormat {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LinkFormat::Markdown => "Markdown",
+            LinkFormat::Org => "Org",
+        }
+    }
+}
+
+/// Templates items are copied in, with {course}, {title}, {url}, {due} (e.g. Fri 17:00) and
+/// {deadline} (e.g. 2025-03-14 Fri 17:00, as in an Org timestamp) filled in
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct YankConfig {
+    pub markdown: String,
+    pub org: String,
+}
+
+impl Default for YankConfig {
+    fn default() -> Self {
+        Self {
+            markdown: "[{course} {title}]({url}) (due {due})".to_string(),
+            org: "[[{url}][{course} {title}]] DEADLINE: <{deadline}>".to_string(),
+        }
+    }
+}
+
+impl YankConfig {
+    pub fn template(&self, format: LinkFormat) -> &str {
+        match format {
+            LinkFormat::Markdown => &self.markdown,
+            LinkFormat::Org => &self.org,
+        }
+    }
+}
+
+/// When an action asks before it is carried out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmPolicy {
+    Never,
+    /// Only when it can't be taken back, e.g. a submission using up one of a few attempts
+    #[default]
+    Destructive,
+    Always,
+}
+
+/// Confirmation policy of each class of actions that change something on Canvas, open a lot or
+/// throw away what the user kept locally
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ConfirmConfig {
+    pub mark_done: ConfirmPolicy,
+    pub mark_read: ConfirmPolicy,
+    pub submit: ConfirmPolicy,
+    pub grades: ConfirmPolicy,
+    /// Opening every item of a day in the browser
+    pub open_day: ConfirmPolicy,
+    /// Emptying the note of a date
+    pub delete_note: ConfirmPolicy,
+    /// Unlinking a local file from an item
+    pub unlink_file: ConfirmPolicy,
+}
+
+impl Default for ConfirmConfig {
+    fn default() -> Self {
+        Self {
+            mark_done: ConfirmPolicy::default(),
+            mark_read: ConfirmPolicy::default(),
+            submit: ConfirmPolicy::default(),
+            grades: ConfirmPolicy::default(),
+            // Nothing is lost, but a day can hold more tabs than expected
+            open_day: ConfirmPolicy::Always,
+            delete_note: ConfirmPolicy::default(),
+            unlink_file: ConfirmPolicy::default(),
+        }
+    }
+}
+
+/// Where reminders for items are created and how early they go off
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RemindersConfig {
+    pub backend: ReminderBackend,
+    pub minutes_before: u64,
+    /// Reminders list on macOS, the default list when unset
+    pub list: Option<String>,
+}
+
+impl Default for RemindersConfig {
+    fn default() -> Self {
+        Self {
+            backend: ReminderBackend::default(),
+            minutes_before: 60,
+            list: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReminderBackend {
+    /// Reminders.app through AppleScript
+    Apple,
+    /// A task with an alarm on the server in the [caldav] section
+    CalDav,
+}
+
+impl Default for ReminderBackend {
+    fn default() -> Self {
+        match cfg!(target_os = "macos") {
+            true => ReminderBackend::Apple,
+            false => ReminderBackend::CalDav,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExternalCalendar {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    /// How many hours before a deadline an unsubmitted item triggers a notification
+    pub deadline_hours: i64,
+    /// Webhooks from before sinks existed, treated like webhook sinks
+    pub webhooks: Vec<Webhook>,
+    pub sinks: Vec<SinkConfig>,
+    pub quiet_hours: Option<QuietHours>,
+    /// Whether deadlines and new grades are announced. Unset ones follow the rules imported
+    /// from Canvas with `canvastui import-notifications`, or are on
+    pub deadlines: Option<bool>,
+    pub grades: Option<bool>,
+    /// Ids of courses never notified about, on top of the ones muted in Canvas
+    pub muted_courses: Vec<u64>,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            deadline_hours: 24,
+            webhooks: vec![],
+            sinks: vec![],
+            quiet_hours: None,
+            deadlines: None,
+            grades: None,
+            muted_courses: vec![],
+        }
+    }
+}
+
+/// When notifications are held back, to be sent as a digest once the quiet period ends
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct QuietHours {
+    /// Local time quiet hours start, e.g. "22:00". May be later than `end` to span midnight
+    #[serde(deserialize_with = "clock_time")]
+    pub start: Option<Time>,
+    #[serde(deserialize_with = "clock_time")]
+    pub end: Option<Time>,
+    /// Days that are quiet all day, e.g. ["saturday", "sunday"]
+    pub days: Vec<String>,
+}
+
+impl QuietHours {
+    pub fn contains(&self, now: PrimitiveDateTime) -> bool {
+        let weekday = now.weekday().to_string().to_lowercase();
+        if self
+            .days
+            .iter()
+            .any(|day| day.len() >= 3 && weekday.starts_with(&day.to_lowercase()))
+        {
+            return true;
+        }
+        match (self.start, self.end) {
+            (Some(start), Some(end)) if start <= end => start <= now.time() && now.time() < end,
+            (Some(start), Some(end)) => now.time() >= start || now.time() < end,
+            _ => false,
+        }
+    }
+}
+
+fn clock_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Time>, D::Error> {
+    let format = format_description::parse("[hour]:[minute]").unwrap();
+    Time::parse(&String::deserialize(deserializer)?, &format)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+/// Reads `[keys]`, where an action takes a single key sequence or a list of alternatives
+fn key_sequences<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, Vec<String>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Sequences {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(BTreeMap::<String, Sequences>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, sequences)| {
+            let sequences = match sequences {
+                Sequences::One(sequence) => vec![sequence],
+                Sequences::Many(sequences) => sequences,
+            };
+            (name, sequences)
+        })
+        .collect())
+}
+
+/// Where notifications are sent, see `sinks::NotificationSink`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SinkConfig {
+    Webhook(Webhook),
+    Desktop,
+    Bell,
+    Osc,
+    /// Runs a program with the message in $CANVASTUI_MESSAGE
+    Command {
+        command: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Webhook {
+    pub url: String,
+    #[serde(default)]
+    pub kind: WebhookKind,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookKind {
+    #[default]
+    Discord,
+    Slack,
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        let config_dir = match env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+        };
+        Some(config_dir.join(CONFIG_FILE))
+    }
+
+    /// The profile picked with `--profile`, otherwise the one from the top of the config file
+    pub fn default_profile(&self) -> Result<Profile> {
+        match SELECTED_PROFILE.get() {
+            Some(name) if name != DEFAULT_PROFILE => self.profile(name),
+            _ => self.main_profile(),
+        }
+    }
+
+    /// The profile from `canvas_url` and `access_token` or `token_command`, falling back to the
+    /// CANVAS_URL and CANVAS_ACCESS_TOKEN environment variables. When `environment` is set, the
+    /// instance it names replaces the url, and its token replaces the token if it has one
+    pub fn main_profile(&self) -> Result<Profile> {
+        let Some(name) = &self.environment else {
+            return self.production_profile();
+        };
+        let environment = self.environments.get(name).ok_or(eyre!(
+            "environment = {name:?} but there is no [environments.{name}] in {}",
+            self.describe_path()
+        ))?;
+        let token = match (&environment.access_token, &environment.token_command) {
+            (Some(token), _) => token.clone(),
+            (None, Some(command)) => run_token_command(command)?,
+            (None, None) => self.production_profile()?.token,
+        };
+        Ok(Profile {
+            // Kept apart from production, whose cache and course ids don't apply
+            name: name.clone(),
+            url: environment.url.clone(),
+            token,
+            token_command: None,
+            cookie: None,
+            exclude_courses: self.exclude_courses.clone(),
+            environment: Some(name.clone()),
+            horizon: self.horizon,
+        })
+    }
+
+    /// Name of the main profile, known without running its token command
+    pub fn main_profile_name(&self) -> &str {
+        self.environment.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// Url of the production instance from `canvas_url`, CANVAS_URL or the last login
+    pub fn production_url(&self) -> Option<String> {
+        self.canvas_url
+            .clone()
+            .or_else(|| env::var("CANVAS_URL").ok())
+            .filter(|url| !url.is_empty())
+            .or_else(login::saved_url)
+    }
+
+    /// Whether the default profile has no url or token yet, so the login form asks for them
+    pub fn needs_login(&self) -> bool {
+        if SELECTED_PROFILE
+            .get()
+            .is_some_and(|name| name != DEFAULT_PROFILE)
+            || self.environment.is_some()
+            || self.auth == AuthMode::Cookie
+        {
+            return false;
+        }
+        let Some(url) = self.production_url() else {
+            return true;
+        };
+        self.access_token.is_none()
+            && self.token_command.is_none()
+            && !env::var("CANVAS_ACCESS_TOKEN").is_ok_and(|token| !token.is_empty())
+            && login::keyring_token(&url).is_none()
+    }
+
+    fn production_profile(&self) -> Result<Profile> {
+        let url = self.production_url().ok_or(eyre!(
+            "No Canvas url, run canvastui login or set canvas_url in {} or CANVAS_URL",
+            self.describe_path()
+        ))?;
+        if self.auth == AuthMode::Cookie {
+            let path = self
+                .cookie_path()
+                .ok_or(eyre!("No home directory for the cookie file"))?;
+            return Ok(Profile {
+                name: DEFAULT_PROFILE.to_string(),
+                url,
+                token: String::new(),
+                token_command: None,
+                cookie: Some(sso::read_cookie(&path)?),
+                exclude_courses: self.exclude_courses.clone(),
+                environment: None,
+                horizon: self.horizon,
+            });
+        }
+        let token = match (&self.access_token, &self.token_command) {
+            (Some(token), _) => token.clone(),
+            (None, Some(command)) => run_token_command(command)?,
+            (None, None) => env::var("CANVAS_ACCESS_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty())
+                .or_else(|| login::keyring_token(&url))
+                .ok_or(eyre!(
+                    "No access token, run canvastui login or set access_token or token_command in {} or CANVAS_ACCESS_TOKEN",
+                    self.describe_path()
+                ))?,
+        };
+        Ok(Profile {
+            name: DEFAULT_PROFILE.to_string(),
+            url,
+            token,
+            token_command: None,
+            cookie: None,
+            exclude_courses: self.exclude_courses.clone(),
+            environment: None,
+            horizon: self.horizon,
+        })
+    }
+
+    /// Where the session cookie for `auth = "cookie"` is read from
+    pub fn cookie_path(&self) -> Option<PathBuf> {
+        self.cookie_file.clone().or_else(sso::default_cookie_path)
+    }
+
+    fn describe_path(&self) -> String {
+        Self::path().map_or("the config file".to_string(), |path| {
+            path.display().to_string()
+        })
+    }
+
+    pub fn profile(&self, name: &str) -> Result<Profile> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name == name)
+            .cloned()
+            .ok_or(eyre!("No profile named {name:?} in the config file"))?
+            .with_token()
+    }
+
+    /// Reads the config file, using defaults when it doesn't exist
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        let config: Self = match std::fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        config.check_names()?;
+        Ok(config)
+    }
+
+    /// Rejects profile and environment names that can't be part of the cache file's path
+    fn check_names(&self) -> Result<()> {
+        let names = self
+            .profiles
+            .iter()
+            .map(|profile| ("profile", &profile.name))
+            .chain(self.environments.keys().map(|name| ("environment", name)));
+        for (kind, name) in names {
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(eyre!(
+                    "The {kind} name {name:?} may only hold letters, digits, - and _"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs the token command through the shell and returns what it printed
+fn run_token_command(command: &str) -> Result<String> {
+    let output = Command::new("sh").arg("-c").arg(command).output()?;
+    if !output.status.success() {
+        return Err(eyre!(
+            "token_command failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let token = String::from_utf8(output.stdout)?.trim().to_string();
+    match token.is_empty() {
+        true => Err(eyre!("token_command printed nothing")),
+        false => Ok(token),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_token_of_a_profile_from_either_key_or_a_command() {
+        let config: Config = toml::from_str(
+            r#"
+            [[profiles]]
+            name = "college"
+            url = "https://canvas.example.edu"
+            access_token = "key-here"
+
+            [[profiles]]
+            name = "work"
+            url = "https://canvas.example.com"
+            token_command = "echo from-the-command"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.profile("college").unwrap().token, "key-here");
+        assert_eq!(config.profile("work").unwrap().token, "from-the-command");
+    }
+
+    #[test]
+    fn rejects_names_that_leave_the_cache_file() {
+        for name in ["../../home/me/.bashrc", "a/b", "", "dot.ted"] {
+            let config = Config {
+                profiles: vec![Profile {
+                    name: name.to_string(),
+                    ..Profile::offline()
+                }],
+                ..Config::default()
+            };
+            assert!(config.check_names().is_err(), "{name:?}");
+        }
+        let config: Config = toml::from_str(
+            r#"
+            [[profiles]]
+            name = "community-college_2"
+            url = "https://canvas.example.edu"
+
+            [environments."../beta"]
+            url = "https://canvas.beta.example.edu"
+            "#,
+        )
+        .unwrap();
+        let error = config.check_names().unwrap_err().to_string();
+        assert!(error.starts_with("The environment name"), "{error}");
+    }
+}
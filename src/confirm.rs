@@ -0,0 +1,108 @@
+use crossterm::event::KeyCode;
+
+use crate::{
+    App,
+    action::{Action, PromptKind},
+    config::ConfirmPolicy,
+    dashboard::Panel,
+    redact,
+};
+
+/// An action held back until the user answers the question about it
+pub struct Confirmation {
+    pub question: String,
+    pub action: Action,
+}
+
+/// What to ask before the action, None if its class isn't set to ask for it this time
+pub fn question(app: &App, action: &Action) -> Option<String> {
+    let config = &app.confirm_config;
+    let session = &app.sessions[app.focus];
+    let (policy, destructive, question) = match action {
+        Action::ToggleCompleted => {
+            let event = session
+                .selected_event()
+                .filter(|event| !event.is_external())?;
+            let done = match event.completed {
+                true => "not done",
+                false => "done",
+            };
+            // Marking is undone with the same key
+            let question = format!("Mark {} {done}?", redact::mask(&event.title));
+            (config.mark_done, false, question)
+        }
+        Action::MarkRead if app.dashboard.focus == Panel::Announcements => {
+            let count = app.dashboard.up_to_selection().len();
+            if count == 0 {
+                return None;
+            }
+            // Canvas has no way to mark them unread again
+            let question = format!("Mark {count} announcement(s) read?");
+            (config.mark_read, true, question)
+        }
+        Action::PromptKey(KeyCode::Enter) => {
+            let (kind, input) = app.status_bar.prompt.as_ref()?;
+            let event = session.selected_event()?;
+            if *kind == PromptKind::Link {
+                // Linking the path again unlinks it
+                let path = crate::link_path(input.trim()).ok()?;
+                if !app.annotations.files(&event.html_url).contains(&path) {
+                    return None;
+                }
+                let question = format!(
+                    "Unlink {} from {}?",
+                    path.display(),
+                    redact::mask(&event.title)
+                );
+                return ask(config.unlink_file, true, question);
+            }
+            if *kind != PromptKind::Submit {
+                return None;
+            }
+            let limited = session
+                .requirements
+                .get(&event.html_url)
+                .is_some_and(|requirements| requirements.allowed_attempts.is_some());
+            let question = format!(
+                "Submit {} for {}?",
+                input.trim(),
+                redact::mask(&event.title)
+            );
+            (config.submit, limited, question)
+        }
+        Action::OpenDay => {
+            let count = session.unfinished_on_current_date().len();
+            if count == 0 {
+                return None;
+            }
+            let question = format!("Open {count} item(s) in the browser?");
+            (config.open_day, false, question)
+        }
+        Action::SaveNote(date, text) => {
+            let note = session.notes.get(*date).unwrap_or_default();
+            if !text.trim().is_empty() || note.trim().is_empty() {
+                return None;
+            }
+            let question = format!("Delete the note of {date}?");
+            (config.delete_note, true, question)
+        }
+        Action::SaveGrades => {
+            let unsaved = app.screens.grading.as_ref()?.unsaved();
+            if unsaved == 0 {
+                return None;
+            }
+            // Students see them right away
+            let question = format!("Save {unsaved} grade(s)?");
+            (config.grades, true, question)
+        }
+        _ => return None,
+    };
+    ask(policy, destructive, question)
+}
+
+fn ask(policy: ConfirmPolicy, destructive: bool, question: String) -> Option<String> {
+    match (policy, destructive) {
+        (ConfirmPolicy::Always, _) | (ConfirmPolicy::Destructive, true) => Some(question),
+        _ => None,
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    path::PathBuf,
+};
+
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::Style,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, StatefulWidget, Widget},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    action::{Action, Navigation, View},
+    component::{Component, Context},
+    redact,
+    session::Session,
+    store::data_dir,
+    theme,
+};
+
+const HIDDEN_COURSES_FILE: &str = "canvastui/hidden_courses.json";
+
+/// Courses unchecked in the course filter, kept across restarts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct HiddenCourses {
+    /// Course ids by profile name, since ids only mean something on one Canvas instance
+    profiles: BTreeMap<String, BTreeSet<u64>>,
+}
+
+impl HiddenCourses {
+    fn path() -> Option<PathBuf> {
+        Some(data_dir()?.join(HIDDEN_COURSES_FILE))
+    }
+
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match std::fs::read(&path) {
+            Ok(contents) => Ok(serde_json::from_slice(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, profile: &str) -> HashSet<u64> {
+        self.profiles
+            .get(profile)
+            .map(|courses| courses.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Hides the course, or shows it again if it is hidden. Returns whether it is now hidden
+    pub fn toggle(&mut self, profile: &str, course_id: u64) -> bool {
+        let courses = self.profiles.entry(profile.to_string()).or_default();
+        let hidden = !courses.remove(&course_id);
+        if hidden {
+            courses.insert(course_id);
+        }
+        if courses.is_empty() {
+            self.profiles.remove(profile);
+        }
+        hidden
+    }
+}
+
+/// Sidebar with a checkbox for every course in the focused profile's planner
+pub struct CourseFilterSidebar {
+    /// Course ids with their names, by name
+    courses: Vec<(u64, String)>,
+    pub list_state: ListState,
+}
+
+impl CourseFilterSidebar {
+    pub fn new(session: &Session) -> Self {
+        let mut courses: Vec<(u64, String)> = session
+            .calendar
+            .dates
+            .iter()
+            .flat_map(|date| &date.events)
+            .filter_map(|event| Some((event.course_id?, event.course_name.clone())))
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .collect();
+        courses.sort_by(|a, b| a.1.cmp(&b.1));
+        Self {
+            list_state: ListState::default().with_selected((!courses.is_empty()).then_some(0)),
+            courses,
+        }
+    }
+
+    /// Id and name of the course under the cursor
+    pub fn selected(&self) -> Option<&(u64, String)> {
+        self.courses.get(self.list_state.selected()?)
+    }
+}
+
+impl Component for CourseFilterSidebar {
+    fn handle_event(&mut self, key: KeyEvent, bound: Action) -> Action {
+        if matches!(key.code, KeyCode::Char(' ') | KeyCode::Enter) {
+            return Action::ToggleCourseHidden;
+        }
+        match bound {
+            Action::Quit
+            | Action::Navigate(Navigation::PrevEvent | Navigation::NextEvent)
+            | Action::ToggleView(View::CourseFilter) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, _context: &Context) -> Option<Action> {
+        match action {
+            Action::Navigate(Navigation::PrevEvent) => self.list_state.select_previous(),
+            Action::Navigate(Navigation::NextEvent) => self.list_state.select_next(),
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, context: &Context) {
+        let hidden = &context.session.hidden_courses;
+        let block = Block::default()
+            .title(" Courses ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme::current().heading));
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if self.courses.is_empty() {
+            Paragraph::new("No courses in the planner").render(inner, buf);
+            return;
+        }
+        let items: Vec<ListItem> = self
+            .courses
+            .iter()
+            .map(|(id, name)| {
+                let shown = !hidden.contains(id);
+                ListItem::new(format!(
+                    "[{}] {}",
+                    match shown {
+                        true => "x",
+                        false => " ",
+                    },
+                    redact::mask(name)
+                ))
+                .style(Style::default().fg(match shown {
+                    true => theme::current().text,
+                    false => theme::current().muted,
+                }))
+            })
+            .collect();
+        let list =
+            List::new(items).highlight_style(Style::default().bg(theme::current().selection));
+        StatefulWidget::render(list, inner, buf, &mut self.list_state);
+    }
+}
@@ -0,0 +1,597 @@
+use std::collections::HashMap;
+
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout},
+    prelude::{Buffer, Rect},
+    style::Style,
+    widgets::{Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use serde::Deserialize;
+use time::{OffsetDateTime, format_description};
+
+use crate::{
+    action::{Action, Navigation, View},
+    component::{Component, Context},
+    config::Profile,
+    fetch::{get_json, to_local},
+    grades::Gradebook,
+    posting::PostKind,
+    redact,
+    session::Session,
+    theme,
+};
+
+/// Pages of a course reachable with a single key from the courses view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CourseRoute {
+    Assignments,
+    Announcements,
+    Grades,
+    Files,
+    /// Shown in the reader instead of a list
+    Syllabus,
+}
+
+impl CourseRoute {
+    pub fn from_key(key: char) -> Option<Self> {
+        match key {
+            'a' => Some(CourseRoute::Assignments),
+            'n' => Some(CourseRoute::Announcements),
+            'g' => Some(CourseRoute::Grades),
+            'f' => Some(CourseRoute::Files),
+            's' => Some(CourseRoute::Syllabus),
+            _ => None,
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            CourseRoute::Assignments => "assignments",
+            CourseRoute::Announcements => "announcements",
+            CourseRoute::Grades => "grades",
+            CourseRoute::Files => "files",
+            CourseRoute::Syllabus => "syllabus",
+        }
+    }
+}
+
+/// The course a page belongs to, handed to every route
+#[derive(Debug, Clone)]
+pub struct CourseContext {
+    pub id: u64,
+    pub name: String,
+}
+
+/// Where a course's syllabus lives on Canvas, also used to match the fetched syllabus to the
+/// reader
+pub fn syllabus_url(course_id: u64) -> String {
+    format!("/courses/{course_id}/assignments/syllabus")
+}
+
+impl CourseContext {
+    pub fn page_title(&self, route: CourseRoute) -> String {
+        format!("{} {}", self.name, route.title())
+    }
+}
+
+/// A row of a course page
+#[derive(Debug, Clone)]
+pub struct CourseEntry {
+    pub title: String,
+    detail: String,
+    pub url: Option<String>,
+    /// Set on entries that are assignments, for grade entry
+    pub assignment_id: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub enum CoursePage {
+    Entries(Vec<CourseEntry>),
+    /// Grades with the course's grading setup for the required score solver
+    Grades(Vec<CourseEntry>, Gradebook),
+    /// HTML body, e.g. the syllabus
+    Html(String),
+}
+
+#[derive(Deserialize)]
+struct CanvasAssignment {
+    id: u64,
+    name: String,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    due_at: Option<OffsetDateTime>,
+    points_possible: Option<f64>,
+    html_url: String,
+    submission: Option<CanvasSubmission>,
+}
+
+#[derive(Deserialize)]
+struct CanvasSubmission {
+    score: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct CanvasAnnouncement {
+    title: String,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    posted_at: Option<OffsetDateTime>,
+    html_url: String,
+}
+
+#[derive(Deserialize)]
+struct CanvasFile {
+    display_name: String,
+    size: u64,
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct CanvasGradedCourse {
+    id: u64,
+    #[serde(default)]
+    enrollments: Vec<CanvasEnrollment>,
+}
+
+#[derive(Deserialize)]
+struct CanvasEnrollment {
+    #[serde(rename = "type")]
+    kind: String,
+    computed_current_score: Option<f64>,
+    computed_current_grade: Option<String>,
+}
+
+/// The user's current grade in a course, counting only graded work
+#[derive(Debug, Clone)]
+pub struct CourseGrade {
+    /// Percentage
+    pub score: Option<f64>,
+    /// Letter grade, for courses with a grading scheme
+    pub grade: Option<String>,
+}
+
+impl CourseGrade {
+    fn describe(&self) -> String {
+        match (self.score, &self.grade) {
+            (Some(score), Some(grade)) => format!("{score:.1}% ({grade})"),
+            (Some(score), None) => format!("{score:.1}%"),
+            (None, Some(grade)) => grade.clone(),
+            (None, None) => "-".to_string(),
+        }
+    }
+}
+
+/// Current grades of the user's courses by course id, from their student enrollments
+pub async fn fetch_course_grades(profile: &Profile) -> Result<HashMap<u64, CourseGrade>> {
+    let courses: Vec<CanvasGradedCourse> = get_json(
+        profile,
+        "/api/v1/courses",
+        &[("include[]", "total_scores"), ("per_page", "100")],
+    )
+    .await?;
+    Ok(courses
+        .into_iter()
+        .filter_map(|course| {
+            let enrollment = course
+                .enrollments
+                .into_iter()
+                .find(|enrollment| enrollment.kind == "student")?;
+            Some((
+                course.id,
+                CourseGrade {
+                    score: enrollment.computed_current_score,
+                    grade: enrollment.computed_current_grade,
+                },
+            ))
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct CanvasSyllabus {
+    syllabus_body: Option<String>,
+}
+
+fn format_date(date_time: Option<OffsetDateTime>, missing: &str) -> String {
+    let format = format_description::parse(
+        "[weekday repr:short] [month repr:short] [day padding:none] [hour]:[minute]",
+    )
+    .unwrap();
+    match date_time {
+        Some(date_time) => to_local(date_time).format(&format).unwrap(),
+        None => missing.to_string(),
+    }
+}
+
+/// Assignments of the course, with their due date or with their score for the grades page
+async fn fetch_assignments(
+    profile: &Profile,
+    course_endpoint: &str,
+    route: CourseRoute,
+) -> Result<Vec<CourseEntry>> {
+    let mut assignments: Vec<CanvasAssignment> = get_json(
+        profile,
+        &format!("{course_endpoint}/assignments"),
+        &[
+            ("include[]", "submission"),
+            ("order_by", "due_at"),
+            ("per_page", "100"),
+        ],
+    )
+    .await?;
+    if route == CourseRoute::Grades {
+        assignments.retain(|assignment| assignment.points_possible.is_some());
+    }
+    Ok(assignments
+        .into_iter()
+        .map(|assignment| CourseEntry {
+            detail: match route {
+                CourseRoute::Grades => {
+                    let points = assignment.points_possible.unwrap_or_default();
+                    match assignment
+                        .submission
+                        .and_then(|submission| submission.score)
+                    {
+                        Some(score) => format!("{score} / {points}"),
+                        None => format!("- / {points}"),
+                    }
+                }
+                _ => format_date(assignment.due_at, "no due date"),
+            },
+            title: assignment.name,
+            url: Some(assignment.html_url),
+            assignment_id: Some(assignment.id),
+        })
+        .collect())
+}
+
+/// Fetches the page a route leads to within the course
+pub async fn fetch_route(
+    profile: &Profile,
+    context: &CourseContext,
+    route: CourseRoute,
+) -> Result<CoursePage> {
+    let course_endpoint = format!("/api/v1/courses/{}", context.id);
+    let entries = match route {
+        CourseRoute::Assignments => fetch_assignments(profile, &course_endpoint, route).await?,
+        CourseRoute::Grades => {
+            let (entries, gradebook) = futures::try_join!(
+                fetch_assignments(profile, &course_endpoint, route),
+                Gradebook::fetch(profile, context.id)
+            )?;
+            return Ok(CoursePage::Grades(entries, gradebook));
+        }
+        CourseRoute::Announcements => {
+            let context_code = format!("course_{}", context.id);
+            let announcements: Vec<CanvasAnnouncement> = get_json(
+                profile,
+                "/api/v1/announcements",
+                &[("context_codes[]", &context_code), ("per_page", "50")],
+            )
+            .await?;
+            announcements
+                .into_iter()
+                .map(|announcement| CourseEntry {
+                    title: announcement.title,
+                    detail: format_date(announcement.posted_at, "not posted"),
+                    url: Some(announcement.html_url),
+                    assignment_id: None,
+                })
+                .collect()
+        }
+        CourseRoute::Files => {
+            let files: Vec<CanvasFile> = get_json(
+                profile,
+                &format!("{course_endpoint}/files"),
+                &[("per_page", "100")],
+            )
+            .await?;
+            files
+                .into_iter()
+                .map(|file| CourseEntry {
+                    title: file.display_name,
+                    detail: format!("{} KB", file.size.div_ceil(1024)),
+                    url: Some(file.url),
+                    assignment_id: None,
+                })
+                .collect()
+        }
+        CourseRoute::Syllabus => {
+            let course: CanvasSyllabus =
+                get_json(profile, &course_endpoint, &[("include[]", "syllabus_body")]).await?;
+            return Ok(CoursePage::Html(course.syllabus_body.unwrap_or_default()));
+        }
+    };
+    Ok(CoursePage::Entries(entries))
+}
+
+/// A list page opened from the courses view
+pub struct CoursePageView {
+    pub context: CourseContext,
+    pub route: CourseRoute,
+    /// None while the page is being fetched
+    entries: Option<Vec<CourseEntry>>,
+    pub table_state: TableState,
+    /// Grading setup of the course, on the grades page
+    pub gradebook: Option<Gradebook>,
+    /// Grade the solver works towards, while it is shown
+    pub target: Option<f64>,
+}
+
+impl CoursePageView {
+    fn set_entries(&mut self, entries: Vec<CourseEntry>) {
+        self.table_state = TableState::default().with_selected((!entries.is_empty()).then_some(0));
+        self.entries = Some(entries);
+    }
+
+    pub fn selected_entry(&self) -> Option<&CourseEntry> {
+        self.entries.as_ref()?.get(self.table_state.selected()?)
+    }
+}
+
+/// Screen listing the session's courses, leading to each course's pages
+pub struct CoursesScreen {
+    courses: Vec<CourseContext>,
+    /// Primary instructor by course id, empty unless `show_instructors` is set
+    instructors: HashMap<u64, String>,
+    /// Whether the list was opened as the grades view, with a column of current grades
+    pub show_grades: bool,
+    /// Current grades by course id, None while they are being fetched
+    grades: Option<HashMap<u64, CourseGrade>>,
+    pub table_state: TableState,
+    pub page: Option<CoursePageView>,
+}
+
+impl CoursesScreen {
+    /// Lists the courses known from the initial load and the planner, by nickname where set. Only
+    /// the focused course is listed in focused course mode
+    pub fn new(session: &Session) -> Self {
+        let mut names: HashMap<u64, String> = session.courses.clone();
+        for event in session.calendar.dates.iter().flat_map(|date| &date.events) {
+            if let Some(course_id) = event.course_id.filter(|_| !event.is_external()) {
+                names.insert(course_id, event.course_name.clone());
+            }
+        }
+        names.extend(session.nicknames.clone());
+        names.retain(|id, _| {
+            !session.excluded_courses.contains(id)
+                && session
+                    .focused_course
+                    .as_ref()
+                    .is_none_or(|course| course.id == *id)
+        });
+        let mut courses: Vec<CourseContext> = names
+            .into_iter()
+            .map(|(id, name)| CourseContext { id, name })
+            .collect();
+        courses.sort_by(|a, b| a.name.cmp(&b.name));
+        Self {
+            table_state: TableState::default().with_selected((!courses.is_empty()).then_some(0)),
+            courses,
+            instructors: session.instructors.clone(),
+            show_grades: false,
+            grades: None,
+            page: None,
+        }
+    }
+
+    /// The same list with each course's current grade next to it
+    pub fn with_grades(session: &Session) -> Self {
+        Self {
+            show_grades: true,
+            ..Self::new(session)
+        }
+    }
+
+    fn set_grades(&mut self, grades: HashMap<u64, CourseGrade>) {
+        self.grades = Some(grades);
+    }
+
+    /// The course of the open page, or else the selected course
+    pub fn context(&self) -> Option<&CourseContext> {
+        match &self.page {
+            Some(page) => Some(&page.context),
+            None => self.courses.get(self.table_state.selected()?),
+        }
+    }
+
+    pub fn open_page(&mut self, context: CourseContext, route: CourseRoute) {
+        self.page = Some(CoursePageView {
+            context,
+            route,
+            entries: None,
+            table_state: TableState::default(),
+            gradebook: None,
+            target: None,
+        });
+    }
+}
+
+impl Component for CoursesScreen {
+    fn handle_event(&mut self, key: KeyEvent, bound: Action) -> Action {
+        let route = self.page.as_ref().map(|page| page.route);
+        match (route, key.code) {
+            (Some(CourseRoute::Grades), KeyCode::Char('r')) => return Action::ToggleSolver,
+            (Some(CourseRoute::Grades), KeyCode::Char('+' | '=')) => {
+                return Action::AdjustTarget(1.0);
+            }
+            (Some(CourseRoute::Grades), KeyCode::Char('-')) => return Action::AdjustTarget(-1.0),
+            (Some(CourseRoute::Assignments), KeyCode::Char('e')) => return Action::OpenGrading,
+            (None, KeyCode::Enter) if self.show_grades => {
+                return Action::OpenCourseRoute(CourseRoute::Grades);
+            }
+            _ => {}
+        }
+        // Page shortcuts take precedence over bindings such as the start of gg
+        if let KeyCode::Char(c) = key.code {
+            if let Some(route) = CourseRoute::from_key(c) {
+                return Action::OpenCourseRoute(route);
+            }
+            if let Some(kind) = PostKind::from_key(c) {
+                return Action::ComposePost(kind);
+            }
+        }
+        match bound {
+            Action::Quit
+            | Action::OpenURL
+            | Action::Navigate(Navigation::PrevEvent | Navigation::NextEvent)
+            | Action::ToggleView(View::Courses | View::Grades) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, _context: &Context) -> Option<Action> {
+        match action {
+            Action::Navigate(navigation) => {
+                let table_state = match &mut self.page {
+                    Some(page) => &mut page.table_state,
+                    None => &mut self.table_state,
+                };
+                match navigation {
+                    Navigation::PrevEvent => table_state.select_previous(),
+                    Navigation::NextEvent => table_state.select_next(),
+                    _ => {}
+                }
+            }
+            Action::AdjustTarget(change) => {
+                if let Some(target) = self.page.as_mut().and_then(|page| page.target.as_mut()) {
+                    *target = (*target + change).max(0.0);
+                }
+            }
+            Action::CoursePageFetched {
+                course_id,
+                route,
+                page,
+            } => {
+                let shown = self
+                    .page
+                    .as_mut()
+                    .filter(|shown| shown.context.id == *course_id && shown.route == *route)?;
+                match page {
+                    CoursePage::Entries(entries) => shown.set_entries(entries.clone()),
+                    CoursePage::Grades(entries, gradebook) => {
+                        shown.set_entries(entries.clone());
+                        shown.gradebook = Some(gradebook.clone());
+                    }
+                    // The syllabus is read in the reader
+                    CoursePage::Html(_) => {}
+                }
+            }
+            Action::CourseGradesFetched { grades } if self.show_grades => match grades {
+                Ok(grades) => self.set_grades(grades.clone()),
+                Err(e) => {
+                    self.set_grades(HashMap::new());
+                    return Some(Action::Status(format!(
+                        "Could not load the course grades: {e}"
+                    )));
+                }
+            },
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, _context: &Context) {
+        let header_style = Style::default().fg(theme::current().heading);
+        let Some(page) = &mut self.page else {
+            if self.courses.is_empty() {
+                Paragraph::new("No courses loaded yet").render(area, buf);
+                return;
+            }
+            let title = match self.show_grades {
+                true => "Course (g or Enter assignment grades)",
+                false => "Course (a assignments, n announcements, g grades, f files, s syllabus)",
+            };
+            let header = Row::new([
+                Cell::from(title),
+                Cell::from(match self.show_grades {
+                    true => "Current grade",
+                    false => "",
+                }),
+                Cell::from(match self.instructors.is_empty() {
+                    true => "",
+                    false => "Instructor",
+                }),
+            ])
+            .style(header_style);
+            let rows = self.courses.iter().map(|course| {
+                let grade = match &self.grades {
+                    _ if !self.show_grades => String::new(),
+                    None => "loading...".to_string(),
+                    Some(grades) => grades
+                        .get(&course.id)
+                        .map(|grade| redact::mask(&grade.describe()))
+                        .unwrap_or_default(),
+                };
+                Row::new([
+                    redact::mask(&course.name),
+                    grade,
+                    self.instructors
+                        .get(&course.id)
+                        .map(|instructor| redact::mask(instructor))
+                        .unwrap_or_default(),
+                ])
+            });
+            let instructor_width = self
+                .instructors
+                .values()
+                .map(|instructor| instructor.chars().count() as u16)
+                .max()
+                .unwrap_or_default();
+            let grade_width = match self.show_grades {
+                true => 14,
+                false => 0,
+            };
+            let widths = [
+                Constraint::Fill(1),
+                Constraint::Length(grade_width),
+                Constraint::Length(instructor_width),
+            ];
+            let table = Table::new(rows, widths)
+                .header(header)
+                .row_highlight_style(Style::default().bg(theme::current().selection))
+                .style(Style::default().fg(theme::current().text));
+            StatefulWidget::render(table, area, buf, &mut self.table_state);
+            return;
+        };
+
+        let title = redact::mask(&page.context.page_title(page.route));
+        let Some(entries) = &page.entries else {
+            Paragraph::new(format!("Loading {title}...")).render(area, buf);
+            return;
+        };
+        if entries.is_empty() {
+            Paragraph::new(format!("No {title}")).render(area, buf);
+            return;
+        }
+        let solver = page.target.zip(page.gradebook.as_ref());
+        let [table_area, solver_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(solver.is_some() as u16),
+        ])
+        .areas(area);
+        if let Some((target, gradebook)) = solver {
+            Paragraph::new(format!(
+                "{} · +/- change target",
+                gradebook.required(target).describe(target)
+            ))
+            .style(header_style)
+            .render(solver_area, buf);
+        }
+        let header = Row::new([title, String::new()]).style(header_style);
+        let rows = entries.iter().map(|entry| {
+            Row::new([
+                redact::mask(&entry.title),
+                match page.route {
+                    CourseRoute::Grades => redact::mask(&entry.detail),
+                    _ => entry.detail.clone(),
+                },
+            ])
+        });
+        let table = Table::new(rows, [Constraint::Fill(1), Constraint::Length(18)])
+            .header(header)
+            .row_highlight_style(Style::default().bg(theme::current().selection))
+            .style(Style::default().fg(theme::current().text));
+        StatefulWidget::render(table, table_area, buf, &mut page.table_state);
+    }
+}
@@ -0,0 +1,446 @@
+use std::{cmp::Reverse, collections::BTreeSet};
+
+use color_eyre::eyre::Result;
+use ratatui::{
+    layout::{Constraint, Layout},
+    prelude::{Buffer, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Text},
+    widgets::{Block, Borders, Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use serde::Deserialize;
+use time::{Date, Duration, OffsetDateTime};
+
+use crate::{
+    announcements::{Announcement, fetch_unread},
+    config::Profile,
+    fetch::{SubmissionComment, comment_preview, get_json, to_local},
+    locale,
+    lookup::fetch_courses,
+    redact,
+    session::Session,
+    theme,
+};
+
+/// Terminals at least this wide show the dashboard instead of the plain planner
+pub const DASHBOARD_MIN_WIDTH: u16 = 160;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Panel {
+    Agenda,
+    Week,
+    Grades,
+    Announcements,
+}
+
+impl Panel {
+    pub fn next(self) -> Self {
+        match self {
+            Panel::Agenda => Panel::Week,
+            Panel::Week => Panel::Grades,
+            Panel::Grades => Panel::Announcements,
+            Panel::Announcements => Panel::Agenda,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecentGrade {
+    pub grade: Option<String>,
+    pub score: Option<f64>,
+    pub assignment: GradedAssignment,
+    pub user_id: Option<u64>,
+    #[serde(default)]
+    pub submission_comments: Vec<SubmissionComment>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GradedAssignment {
+    pub course_id: Option<u64>,
+    pub name: String,
+    pub html_url: String,
+    pub points_possible: Option<f64>,
+}
+
+/// Row of the announcements panel
+enum AnnouncementRow {
+    /// Heading of the announcements posted on a day in digest mode, None for undated ones
+    Day(Option<Date>),
+    /// Index into `DashboardData::announcements`
+    Announcement(usize),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DashboardData {
+    pub grades: Vec<RecentGrade>,
+    pub announcements: Vec<Announcement>,
+}
+
+/// Fetches the most recently graded submissions and unread announcements from every course
+pub async fn fetch_dashboard(profile: &Profile) -> Result<DashboardData> {
+    let grades: Vec<RecentGrade> = get_json(
+        profile,
+        "/api/v1/users/self/graded_submissions",
+        &[
+            ("include[]", "assignment"),
+            ("include[]", "submission_comments"),
+            ("only_current_enrollments", "true"),
+            ("per_page", "20"),
+        ],
+    )
+    .await?;
+
+    let (courses, excluded): (Vec<_>, Vec<_>) = fetch_courses(profile)
+        .await?
+        .into_iter()
+        .partition(|course| {
+            !profile
+                .exclude_courses
+                .excludes(course.id, &[&course.name, &course.course_code])
+        });
+    let grades = grades
+        .into_iter()
+        .filter(|grade| {
+            !excluded
+                .iter()
+                .any(|course| grade.assignment.course_id == Some(course.id))
+        })
+        .collect();
+    let announcements = fetch_unread(profile, &courses).await?;
+
+    Ok(DashboardData {
+        grades,
+        announcements,
+    })
+}
+
+/// Grid of panels shown on wide terminals around the focused session's planner
+pub struct Dashboard {
+    pub focus: Panel,
+    /// None until the first fetch completes
+    pub data: Option<DashboardData>,
+    pub grades_state: TableState,
+    pub announcements_state: TableState,
+    /// Whether announcements are grouped by the day they were posted
+    pub digest: bool,
+    /// Days whose announcements are hidden in digest mode
+    collapsed: BTreeSet<Option<Date>>,
+    /// Whether the last frame was wide enough to show the dashboard
+    pub active: bool,
+    /// Whether data was requested since the planner was last fetched
+    pub requested: bool,
+}
+
+impl Dashboard {
+    pub fn new() -> Self {
+        Self {
+            focus: Panel::Agenda,
+            data: None,
+            grades_state: TableState::default().with_selected(0),
+            announcements_state: TableState::default().with_selected(0),
+            digest: false,
+            collapsed: BTreeSet::new(),
+            active: false,
+            requested: false,
+        }
+    }
+
+    /// Table state of the focused panel while the dashboard is shown and it is a list
+    pub fn list_state(&mut self) -> Option<&mut TableState> {
+        if !self.active {
+            return None;
+        }
+        match self.focus {
+            Panel::Grades => Some(&mut self.grades_state),
+            Panel::Announcements => Some(&mut self.announcements_state),
+            Panel::Agenda | Panel::Week => None,
+        }
+    }
+
+    /// Url of the item selected in the focused list panel while the dashboard is shown
+    pub fn selected_url(&self) -> Option<&str> {
+        if !self.active {
+            return None;
+        }
+        let data = self.data.as_ref()?;
+        match self.focus {
+            Panel::Grades => data
+                .grades
+                .get(self.grades_state.selected()?)
+                .map(|grade| grade.assignment.html_url.as_str()),
+            Panel::Announcements => {
+                match self
+                    .announcement_rows()
+                    .get(self.announcements_state.selected()?)?
+                {
+                    AnnouncementRow::Announcement(index) => {
+                        Some(data.announcements[*index].html_url.as_str())
+                    }
+                    AnnouncementRow::Day(_) => None,
+                }
+            }
+            Panel::Agenda | Panel::Week => None,
+        }
+    }
+
+    /// Rows of the announcements panel. Digest mode groups them under the day they were posted,
+    /// newest first, leaving out the announcements of collapsed days
+    fn announcement_rows(&self) -> Vec<AnnouncementRow> {
+        let Some(data) = &self.data else {
+            return vec![];
+        };
+        let mut order: Vec<usize> = (0..data.announcements.len()).collect();
+        if !self.digest {
+            return order
+                .into_iter()
+                .map(AnnouncementRow::Announcement)
+                .collect();
+        }
+        order.sort_by_key(|&index| Reverse(data.announcements[index].posted_at));
+        let mut rows = vec![];
+        let mut last_day = None;
+        for index in order {
+            let day = data.announcements[index].posted_on();
+            if last_day != Some(day) {
+                rows.push(AnnouncementRow::Day(day));
+                last_day = Some(day);
+            }
+            if !self.collapsed.contains(&day) {
+                rows.push(AnnouncementRow::Announcement(index));
+            }
+        }
+        rows
+    }
+
+    pub fn toggle_digest(&mut self) {
+        self.digest = !self.digest;
+        self.announcements_state.select(Some(0));
+    }
+
+    /// Collapses or expands the selected day in digest mode
+    pub fn toggle_fold(&mut self) {
+        let rows = self.announcement_rows();
+        let Some(selected) = self.announcements_state.selected() else {
+            return;
+        };
+        let day = match rows.get(selected) {
+            Some(AnnouncementRow::Day(day)) => *day,
+            Some(AnnouncementRow::Announcement(index)) => {
+                let day = self.data.as_ref().unwrap().announcements[*index].posted_on();
+                // Collapsing from inside a day moves the selection to its heading
+                let heading = rows[..selected]
+                    .iter()
+                    .rposition(|row| matches!(row, AnnouncementRow::Day(_)));
+                self.announcements_state.select(heading);
+                day
+            }
+            None => return,
+        };
+        if !self.collapsed.remove(&day) {
+            self.collapsed.insert(day);
+        }
+    }
+
+    /// Announcements from the top of the panel down to the selected row, including the contents
+    /// of collapsed days
+    pub fn up_to_selection(&self) -> Vec<Announcement> {
+        let (Some(data), Some(selected)) = (&self.data, self.announcements_state.selected()) else {
+            return vec![];
+        };
+        let rows = self.announcement_rows();
+        let end = (selected + 1).min(rows.len());
+        rows[..end]
+            .iter()
+            .flat_map(|row| match row {
+                AnnouncementRow::Announcement(index) => vec![data.announcements[*index].clone()],
+                AnnouncementRow::Day(day) if self.collapsed.contains(day) => data
+                    .announcements
+                    .iter()
+                    .filter(|announcement| announcement.posted_on() == *day)
+                    .cloned()
+                    .collect(),
+                AnnouncementRow::Day(_) => vec![],
+            })
+            .collect()
+    }
+
+    /// Drops announcements that were marked read
+    pub fn remove_read(&mut self, ids: &[u64]) {
+        if let Some(data) = &mut self.data {
+            data.announcements
+                .retain(|announcement| !ids.contains(&announcement.id));
+        }
+        let last = self.announcement_rows().len().saturating_sub(1);
+        if let Some(selected) = self.announcements_state.selected() {
+            self.announcements_state.select(Some(selected.min(last)));
+        }
+    }
+
+    fn block(&self, panel: Panel, title: &str) -> Block<'static> {
+        Block::default()
+            .title(format!(" {title} "))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(match self.focus == panel {
+                true => theme::current().heading,
+                false => theme::current().muted,
+            }))
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, session: &mut Session) {
+        let [agenda_area, side_area] =
+            Layout::horizontal([Constraint::Fill(3), Constraint::Fill(2)]).areas(area);
+        let [week_area, grades_area, announcements_area] = Layout::vertical([
+            Constraint::Length(5),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ])
+        .areas(side_area);
+
+        let block = self.block(Panel::Agenda, "Agenda");
+        session.render(block.inner(agenda_area), buf);
+        block.render(agenda_area, buf);
+
+        let block = self.block(Panel::Week, "Week");
+        render_week(session, block.inner(week_area), buf);
+        block.render(week_area, buf);
+
+        let grades_block = self.block(Panel::Grades, "Recent grades");
+        let announcements_block = self.block(
+            Panel::Announcements,
+            match self.digest {
+                true => "Unread announcements by day",
+                false => "Unread announcements",
+            },
+        );
+        let Some(data) = &self.data else {
+            Paragraph::new("Loading...")
+                .block(grades_block)
+                .render(grades_area, buf);
+            Paragraph::new("Loading...")
+                .block(announcements_block)
+                .render(announcements_area, buf);
+            return;
+        };
+
+        let grade_rows = data.grades.iter().map(|grade| {
+            let score = match (&grade.grade, grade.score, grade.assignment.points_possible) {
+                (_, Some(score), Some(points)) => format!("{score}/{points}"),
+                (Some(grade), _, _) => grade.clone(),
+                _ => "-".to_string(),
+            };
+            let mut name = Text::from(redact::mask(&grade.assignment.name));
+            if let Some(comment) = comment_preview(&grade.submission_comments, grade.user_id) {
+                name.push_line(Line::styled(
+                    format!("  “{}”", redact::mask(&comment)),
+                    Style::default().fg(theme::current().secondary),
+                ));
+            }
+            let height = name.height() as u16;
+            Row::new([Cell::from(name), Cell::from(redact::mask(&score))]).height(height)
+        });
+        StatefulWidget::render(
+            Table::new(grade_rows, [Constraint::Fill(1), Constraint::Length(12)])
+                .block(grades_block)
+                .row_highlight_style(Style::default().bg(theme::current().selection))
+                .style(Style::default().fg(theme::current().text)),
+            grades_area,
+            buf,
+            &mut self.grades_state,
+        );
+
+        let announcement_rows = self.announcement_rows().into_iter().map(|row| match row {
+            AnnouncementRow::Day(day) => {
+                let count = data
+                    .announcements
+                    .iter()
+                    .filter(|announcement| announcement.posted_on() == day)
+                    .count();
+                let marker = match self.collapsed.contains(&day) {
+                    true => '▸',
+                    false => '▾',
+                };
+                let day = match day {
+                    Some(day) => locale::current().format(day, "%a %b %-d"),
+                    None => "Undated".to_string(),
+                };
+                Row::new([
+                    Cell::from(format!("{marker} {day} ({count})")),
+                    Cell::from(""),
+                ])
+                .style(Style::default().fg(theme::current().accent).bold())
+            }
+            AnnouncementRow::Announcement(index) => {
+                let announcement = &data.announcements[index];
+                let indent = match self.digest {
+                    true => "  ",
+                    false => "",
+                };
+                Row::new([
+                    Cell::from(format!(
+                        "{indent}{}",
+                        redact::mask(&announcement.course_name)
+                    )),
+                    Cell::from(redact::mask(&announcement.title)),
+                ])
+            }
+        });
+        StatefulWidget::render(
+            Table::new(
+                announcement_rows,
+                [Constraint::Percentage(35), Constraint::Fill(1)],
+            )
+            .block(announcements_block)
+            .row_highlight_style(Style::default().bg(theme::current().selection))
+            .style(Style::default().fg(theme::current().text)),
+            announcements_area,
+            buf,
+            &mut self.announcements_state,
+        );
+    }
+}
+
+/// Heatmap of how many unfinished items are due on each of the next seven days
+/// Color for a day with `count` items left to do
+pub fn load_color(count: usize) -> Color {
+    match count {
+        0 => theme::current().muted,
+        1 => theme::current().done,
+        2..=3 => theme::current().warning,
+        _ => theme::current().error,
+    }
+}
+
+fn render_week(session: &Session, area: Rect, buf: &mut Buffer) {
+    let today = to_local(OffsetDateTime::now_utc()).date();
+    let selected = session
+        .calendar
+        .dates
+        .get(session.current_date_index)
+        .and_then(|date| date.events.first())
+        .map(|event| event.due_at.date());
+
+    let day_areas = Layout::horizontal([Constraint::Fill(1); 7]).split(area);
+    for (offset, day_area) in day_areas.iter().enumerate() {
+        let date = today + Duration::days(offset as i64);
+        let count = session
+            .calendar
+            .dates
+            .iter()
+            .flat_map(|calendar_date| &calendar_date.events)
+            .filter(|event| event.due_at.date() == date && !event.is_done())
+            .count();
+        let mut style = Style::default()
+            .fg(theme::current().inverse)
+            .bg(load_color(count));
+        if selected == Some(date) {
+            style = style.bold().underlined();
+        }
+        Paragraph::new(format!(
+            "{}\n{count}",
+            locale::current().abbreviated_weekday(date)
+        ))
+        .centered()
+        .style(style)
+        .render(*day_area, buf);
+    }
+}
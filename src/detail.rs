@@ -0,0 +1,278 @@
+use color_eyre::eyre::Result;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::{Constraint, Layout},
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+use serde::Deserialize;
+use time::{Duration, OffsetDateTime, PrimitiveDateTime};
+
+use crate::{
+    action::{Action, Navigation, PromptKind, View},
+    component::{Component, Context},
+    config::Profile,
+    fetch::{CalendarEvent, Requirements, get_json, to_local},
+    locale,
+    reader::Document,
+    redact, theme,
+};
+
+/// Share of the screen the popup covers, in percent
+const POPUP_SIZE: u16 = 80;
+
+#[derive(Deserialize)]
+struct CanvasAssignmentDetail {
+    points_possible: Option<f64>,
+    #[serde(default)]
+    submission_types: Vec<String>,
+    #[serde(default)]
+    allowed_extensions: Vec<String>,
+    /// -1 or missing when attempts are unlimited
+    allowed_attempts: Option<i64>,
+    group_category_id: Option<u64>,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    due_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    unlock_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::iso8601::option")]
+    lock_at: Option<OffsetDateTime>,
+    description: Option<String>,
+}
+
+/// Everything about an assignment the popup shows
+#[derive(Debug, Clone)]
+pub struct AssignmentDetail {
+    pub points_possible: Option<f64>,
+    pub requirements: Requirements,
+    pub due_at: Option<PrimitiveDateTime>,
+    pub unlock_at: Option<PrimitiveDateTime>,
+    pub lock_at: Option<PrimitiveDateTime>,
+    /// HTML description
+    pub description: String,
+}
+
+/// Fetches the full assignment behind a planner item
+pub async fn fetch_detail(profile: &Profile, event: &CalendarEvent) -> Result<AssignmentDetail> {
+    let course_id = event
+        .course_id
+        .filter(|_| event.plannable_type == "assignment")
+        .ok_or(color_eyre::eyre::eyre!(
+            "details are only shown for assignments"
+        ))?;
+    let assignment: CanvasAssignmentDetail = get_json(
+        profile,
+        &format!(
+            "/api/v1/courses/{course_id}/assignments/{}",
+            event.plannable_id
+        ),
+        &[],
+    )
+    .await?;
+    Ok(AssignmentDetail {
+        points_possible: assignment.points_possible,
+        requirements: Requirements {
+            submission_types: assignment.submission_types,
+            allowed_extensions: assignment.allowed_extensions,
+            allowed_attempts: assignment
+                .allowed_attempts
+                .filter(|&allowed| allowed > 0)
+                .map(|allowed| allowed as u32),
+            group: assignment.group_category_id.is_some(),
+        },
+        due_at: assignment.due_at.map(to_local),
+        unlock_at: assignment.unlock_at.map(to_local),
+        lock_at: assignment.lock_at.map(to_local),
+        description: assignment.description.unwrap_or_default(),
+    })
+}
+
+fn format_time(date_time: PrimitiveDateTime) -> String {
+    format!(
+        "{} {:02}:{:02}",
+        locale::current().format(date_time.date(), "%a %b %-d"),
+        date_time.hour(),
+        date_time.minute()
+    )
+}
+
+/// Popup over the planner with the details of the selected assignment
+pub struct DetailPopup {
+    pub html_url: String,
+    title: String,
+    /// Deadline on Canvas of an item moved to its soft deadline, for when the assignment has none
+    official_due_at: Option<PrimitiveDateTime>,
+    /// None while the assignment is being fetched
+    detail: Option<Result<AssignmentDetail, String>>,
+    document: Option<Document>,
+    /// Index of the first visible line of the description
+    scroll: usize,
+}
+
+impl DetailPopup {
+    pub fn new(event: &CalendarEvent) -> Self {
+        Self {
+            html_url: event.html_url.clone(),
+            title: event.title.clone(),
+            official_due_at: event.official_due_at,
+            detail: None,
+            document: None,
+            scroll: 0,
+        }
+    }
+
+    fn set_detail(&mut self, detail: Result<AssignmentDetail, String>) {
+        if let Ok(detail) = &detail {
+            self.document = Some(Document::new(detail.description.clone()));
+        }
+        self.detail = Some(detail);
+    }
+
+    pub fn scroll_by(&mut self, lines: isize) {
+        self.scroll = self.scroll.saturating_add_signed(lines);
+    }
+}
+
+impl Component for DetailPopup {
+    fn handle_event(&mut self, _key: KeyEvent, bound: Action) -> Action {
+        match bound {
+            Action::ShowDetail => Action::CloseDetail,
+            Action::Quit
+            | Action::OpenURL
+            | Action::ToggleView(View::Modules)
+            | Action::OpenSyllabus
+            | Action::OpenPrompt(PromptKind::Link)
+            | Action::EditFiles
+            | Action::Navigate(Navigation::PrevEvent | Navigation::NextEvent) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, _context: &Context) -> Option<Action> {
+        match action {
+            Action::Navigate(Navigation::PrevEvent) => self.scroll_by(-1),
+            Action::Navigate(Navigation::NextEvent) => self.scroll_by(1),
+            Action::DetailFetched { html_url, detail } if *html_url == self.html_url => {
+                self.set_detail(detail.clone());
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, context: &Context) {
+        let [_, popup, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Percentage(POPUP_SIZE),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+        let [_, popup, _] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Percentage(POPUP_SIZE),
+            Constraint::Fill(1),
+        ])
+        .areas(popup);
+        Clear.render(popup, buf);
+        let block = Block::default()
+            .title(format!(" {} ", redact::mask(&self.title)))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme::current().heading));
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+
+        let detail = match &self.detail {
+            None => {
+                Paragraph::new("Loading assignment...").render(inner, buf);
+                return;
+            }
+            Some(Err(e)) => {
+                Paragraph::new(format!("Could not load the assignment: {e}"))
+                    .style(Style::default().fg(theme::current().error))
+                    .render(inner, buf);
+                return;
+            }
+            Some(Ok(detail)) => detail,
+        };
+        let field = |name: &str, value: String| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{name:<10}"),
+                    Style::default().fg(theme::current().secondary),
+                ),
+                Span::raw(value),
+            ])
+        };
+        let mut facts = vec![field(
+            "Points",
+            match detail.points_possible {
+                Some(points) => redact::mask(&format!("{points}")),
+                None => "ungraded".to_string(),
+            },
+        )];
+        facts.push(field("Submit", detail.requirements.summary()));
+        if let Some(unlock_at) = detail.unlock_at {
+            facts.push(field("Opens", format_time(unlock_at)));
+        }
+        let due_at = detail.due_at.or(self.official_due_at);
+        if let Some(due_at) = due_at {
+            facts.push(field("Due", format_time(due_at)));
+        }
+        if let Some((lead, due_at)) = context
+            .annotations
+            .soft_deadlines
+            .get(&self.html_url)
+            .zip(due_at)
+        {
+            let finish_by = due_at - Duration::minutes(lead.minutes_early);
+            facts.push(field(
+                "Finish by",
+                format!("{} ({} early)", format_time(finish_by), lead.describe()),
+            ));
+        }
+        if let Some(lock_at) = detail.lock_at {
+            facts.push(field("Closes", format_time(lock_at)));
+        }
+        for (i, path) in context.annotations.files(&self.html_url).iter().enumerate() {
+            let name = if i == 0 { "Files" } else { "" };
+            facts.push(field(name, redact::mask(&path.display().to_string())));
+        }
+        let [facts_area, help_area, description_area] = Layout::vertical([
+            Constraint::Length(facts.len() as u16),
+            Constraint::Length(2),
+            Constraint::Fill(1),
+        ])
+        .areas(inner);
+        Paragraph::new(facts)
+            .style(Style::default().fg(theme::current().text))
+            .render(facts_area, buf);
+        Paragraph::new(
+            "j/k scroll · o open in browser · m module · g s syllabus · F link file · e edit files",
+        )
+        .style(Style::default().fg(theme::current().muted).italic())
+        .render(help_area, buf);
+
+        let Some(document) = &mut self.document else {
+            return;
+        };
+        let lines: Vec<String> = document
+            .page(
+                &mut self.scroll,
+                description_area.height as usize,
+                description_area.width as usize,
+            )
+            .iter()
+            .map(|line| redact::mask(line))
+            .collect();
+        match lines.is_empty() && self.scroll == 0 {
+            true => Paragraph::new("No description")
+                .style(Style::default().fg(theme::current().secondary)),
+            false => {
+                Paragraph::new(lines.join("\n")).style(Style::default().fg(theme::current().text))
+            }
+        }
+        .render(description_area, buf);
+    }
+}
@@ -0,0 +1,74 @@
+use color_eyre::eyre::{Result, eyre};
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+    config::{Config, Profile},
+    fetch::{Calendar, fetch, load_cache, to_local},
+    locale, redact,
+    store::Annotations,
+    workload::find_ignore_case,
+};
+
+/// Prints the default profile's items due between now and `within` from now, such as `48h`,
+/// limited to a course and to items not handed in yet if asked. Reads the cache when Canvas
+/// can't be reached. Returns whether anything matched, which the command exits with 1 for so
+/// scripts can check it
+pub async fn print(within: &str, course: Option<&str>, unsubmitted: bool) -> Result<bool> {
+    let config = Config::load()?;
+    locale::init(&config.locale)?;
+    let within = parse_window(within)?;
+    let calendar = fetch_or_cache(&config.default_profile()?).await?;
+
+    let now = to_local(OffsetDateTime::now_utc());
+    let due: Vec<_> = calendar
+        .dates
+        .iter()
+        .flat_map(|date| &date.events)
+        .filter(|event| !event.is_external() && event.due_at >= now && event.due_at <= now + within)
+        .filter(|event| {
+            course.is_none_or(|course| find_ignore_case(&event.course_name, course).is_some())
+        })
+        .filter(|event| !unsubmitted || !event.is_done())
+        .collect();
+    let locale = locale::current();
+    for event in &due {
+        println!(
+            "{} {:02}:{:02}  {}  {}",
+            locale.format(event.due_at.date(), "%a %b %-d"),
+            event.due_at.hour(),
+            event.due_at.minute(),
+            redact::mask(&event.course_name),
+            redact::mask(&event.title)
+        );
+    }
+    Ok(!due.is_empty())
+}
+
+/// The profile's planner with local annotations, from the cache when Canvas can't be reached
+pub async fn fetch_or_cache(profile: &Profile) -> Result<Calendar> {
+    let mut calendar = match fetch(profile).await {
+        Ok(calendar) => calendar,
+        Err(e) => {
+            eprintln!("Couldn't reach Canvas, using the cache: {e}");
+            load_cache(profile)?
+        }
+    };
+    calendar.apply_annotations(&Annotations::load().unwrap_or_default());
+    Ok(calendar)
+}
+
+/// Reads a window like `30m`, `48h`, `2d` or `1w`
+pub fn parse_window(window: &str) -> Result<Duration> {
+    let invalid = || eyre!("{window:?} is not a window, use a number with m, h, d or w like 48h");
+    let unit = window.chars().last().ok_or_else(invalid)?;
+    let amount: i64 = window[..window.len() - unit.len_utf8()]
+        .parse()
+        .map_err(|_| invalid())?;
+    match unit {
+        'm' => Ok(Duration::minutes(amount)),
+        'h' => Ok(Duration::hours(amount)),
+        'd' => Ok(Duration::days(amount)),
+        'w' => Ok(Duration::weeks(amount)),
+        _ => Err(invalid()),
+    }
+}
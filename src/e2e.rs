@@ -0,0 +1,1369 @@
+//! End-to-end harness running the whole app against a local mock of the Canvas API. Keys are fed
+//! in the way the terminal would send them and the screen is drawn to a `TestBackend` buffer, so
+//! tests can script a user flow and assert on what ends up on screen.
+
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{Terminal, backend::TestBackend};
+use serde_json::{Value, json};
+use time::{OffsetDateTime, UtcOffset, format_description::well_known::Rfc3339};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+    sync::{
+        Mutex, MutexGuard,
+        mpsc::{self, UnboundedReceiver},
+    },
+};
+
+use crate::{
+    App,
+    action::Action,
+    action::Dispatcher,
+    config::Config,
+    config::{
+        CalDavConfig, ConfirmPolicy, ConflictPreference, CourseExclusions, Horizon, Profile,
+        ReminderBackend,
+    },
+    course_filter::HiddenCourses,
+    get_action,
+    links::Clipboard,
+    session::FetchState,
+    startup,
+    store::Annotations,
+    tui, ui,
+};
+
+/// How long the action queue has to stay empty before the app counts as settled
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+/// How long to wait for requests to come back before failing the test
+const WAIT_LIMIT: Duration = Duration::from_secs(20);
+const WIDTH: u16 = 120;
+const HEIGHT: u16 = 30;
+
+/// Tests point the data directory of the whole process at their own, so they run one at a time
+static SERIAL: Mutex<()> = Mutex::const_new(());
+
+/// Session cookie the mock accepts in place of the token, with its CSRF token URL-encoded
+const SESSION_COOKIE: &str = "canvas_session=session; _csrf_token=csrf%2Btoken";
+
+/// Serves canned JSON bodies by path, ignoring the query string. Unknown paths get a 404, and
+/// API requests without the profile's token as a bearer token or the session cookie a 401.
+/// Like Canvas, writes with the cookie need the CSRF token and JSON sent to it is guarded
+async fn serve(routes: HashMap<String, Value>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let routes = routes.clone();
+            tokio::spawn(async move {
+                let mut request = vec![];
+                let mut chunk = [0; 4096];
+                while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                    match stream.read(&mut chunk).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(read) => request.extend_from_slice(&chunk[..read]),
+                    }
+                }
+                let request = String::from_utf8_lossy(&request);
+                let target = request.split_whitespace().nth(1).unwrap_or_default();
+                let path = target.split('?').next().unwrap_or_default();
+                let has_header = |header: &str| {
+                    request
+                        .lines()
+                        .any(|line| line.eq_ignore_ascii_case(header))
+                };
+                let session = has_header(&format!("cookie: {SESSION_COOKIE}"))
+                    && (request.starts_with("GET ") || has_header("x-csrf-token: csrf+token"));
+                let authorized = !path.starts_with("/api/")
+                    || has_header("authorization: bearer token")
+                    || session;
+                let response = match routes.get(path) {
+                    _ if !authorized => "HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\n\
+                                         Connection: close\r\n\r\n"
+                        .to_string(),
+                    Some(body) => {
+                        let body = match session {
+                            true => format!("while(1);{body}"),
+                            false => body.to_string(),
+                        };
+                        format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                             Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                            body.len()
+                        )
+                    }
+                    None => {
+                        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                            .to_string()
+                    }
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    url
+}
+
+/// The app wired to a mock server and a headless terminal
+pub struct Harness {
+    app: App,
+    action_rx: UnboundedReceiver<Action>,
+    dispatcher: Dispatcher,
+    terminal: Terminal<TestBackend>,
+    cache_file: PathBuf,
+    data_dir: PathBuf,
+    _serial: MutexGuard<'static, ()>,
+}
+
+impl Harness {
+    /// Starts the app against a mock serving `routes` and waits for the startup requests
+    pub async fn start(routes: HashMap<String, Value>) -> Self {
+        Self::launch(routes, None, None).await
+    }
+
+    /// Like `start`, with `planner` as the planner items cached by an earlier run
+    pub async fn start_with_cache(routes: HashMap<String, Value>, planner: Value) -> Self {
+        Self::launch(routes, Some(planner), None).await
+    }
+
+    /// Like `start`, signed in with the browser session cookie instead of a token
+    pub async fn start_with_cookie(routes: HashMap<String, Value>) -> Self {
+        Self::launch(routes, None, Some(SESSION_COOKIE.to_string())).await
+    }
+
+    async fn launch(
+        routes: HashMap<String, Value>,
+        cache: Option<Value>,
+        cookie: Option<String>,
+    ) -> Self {
+        let serial = SERIAL.lock().await;
+        let url = serve(routes).await;
+        let name = format!("e2e-{}", url.rsplit(':').next().unwrap_or_default());
+        let data_dir = std::env::temp_dir().join(format!("canvastui-{name}"));
+        std::fs::create_dir_all(&data_dir).unwrap();
+        // SAFETY: only these tests read the environment, and they hold SERIAL
+        unsafe { std::env::set_var("XDG_DATA_HOME", &data_dir) };
+        let profile = Profile {
+            name,
+            url,
+            token: match cookie {
+                Some(_) => String::new(),
+                None => "token".to_string(),
+            },
+            token_command: None,
+            cookie,
+            exclude_courses: CourseExclusions::default(),
+            environment: None,
+            horizon: Horizon::default(),
+        };
+        let cache_file = profile.cache_file();
+        if let Some(planner) = cache {
+            std::fs::write(&cache_file, planner.to_string()).unwrap();
+        }
+        let (action_tx, action_rx) = mpsc::unbounded_channel();
+        let mut app = App::new(
+            Config::default(),
+            vec![profile.clone()],
+            false,
+            Annotations::default(),
+            action_tx.clone(),
+        )
+        .unwrap();
+        // Escapes written to the real terminal would end up in the test output
+        app.clipboard = Clipboard::Captured(vec![]);
+        startup::spawn_cached(0, &profile, &action_tx);
+        startup::spawn(0, &profile, false, &action_tx);
+        let mut harness = Self {
+            app,
+            action_rx,
+            dispatcher: Dispatcher::new(),
+            terminal: Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap(),
+            cache_file,
+            data_dir,
+            _serial: serial,
+        };
+        harness
+            .until(|app| {
+                let session = &app.sessions[0];
+                session.progress.is_done() && !matches!(session.fetch_state, FetchState::Loading)
+            })
+            .await;
+        harness.settle().await;
+        harness
+    }
+
+    /// Handles actions as they arrive until `done` holds for the app
+    pub async fn until(&mut self, done: impl Fn(&App) -> bool) {
+        let deadline = tokio::time::Instant::now() + WAIT_LIMIT;
+        while !done(&self.app) {
+            match tokio::time::timeout_at(deadline, self.action_rx.recv()).await {
+                Ok(Some(action)) => self.dispatcher.dispatch(&mut self.app, action),
+                _ => panic!("timed out waiting for the app:\n{}", self.screen()),
+            }
+        }
+    }
+
+    /// Handles actions as they arrive until the screen shows `text`, and returns the screen
+    pub async fn wait_for(&mut self, text: &str) -> String {
+        let deadline = tokio::time::Instant::now() + WAIT_LIMIT;
+        loop {
+            let screen = self.screen();
+            if screen.contains(text) {
+                return screen;
+            }
+            match tokio::time::timeout_at(deadline, self.action_rx.recv()).await {
+                Ok(Some(action)) => self.dispatcher.dispatch(&mut self.app, action),
+                _ => panic!("timed out waiting for {text:?} on screen:\n{screen}"),
+            }
+        }
+    }
+
+    /// Handles actions until none have arrived for a while
+    pub async fn settle(&mut self) {
+        while let Ok(Some(action)) = tokio::time::timeout(SETTLE_TIME, self.action_rx.recv()).await
+        {
+            self.dispatcher.dispatch(&mut self.app, action);
+        }
+    }
+
+    /// Presses each key in turn: characters as typed, and `<Enter>`, `<Esc>`, `<Tab>`, `<BS>` or
+    /// `<C-s>` by name
+    pub async fn press(&mut self, keys: &str) {
+        let mut rest = keys;
+        while let Some(c) = rest.chars().next() {
+            let named = [
+                ("<Enter>", KeyCode::Enter, KeyModifiers::NONE),
+                ("<Esc>", KeyCode::Esc, KeyModifiers::NONE),
+                ("<Tab>", KeyCode::Tab, KeyModifiers::NONE),
+                ("<BS>", KeyCode::Backspace, KeyModifiers::NONE),
+                ("<C-s>", KeyCode::Char('s'), KeyModifiers::CONTROL),
+            ]
+            .into_iter()
+            .find(|(name, _, _)| rest.starts_with(name));
+            let (code, modifiers) = match named {
+                Some((name, code, modifiers)) => {
+                    rest = &rest[name.len()..];
+                    (code, modifiers)
+                }
+                None => {
+                    rest = &rest[c.len_utf8()..];
+                    (KeyCode::Char(c), KeyModifiers::NONE)
+                }
+            };
+            let event = tui::Event::Key(KeyEvent::new(code, modifiers));
+            let action = get_action(&mut self.app, event);
+            self.dispatcher.dispatch(&mut self.app, action);
+            self.settle().await;
+        }
+    }
+
+    /// Text copied to the clipboard so far, oldest first
+    pub fn copied(&self) -> &[String] {
+        match &self.app.clipboard {
+            Clipboard::Captured(copied) => copied,
+            Clipboard::Terminal => &[],
+        }
+    }
+
+    /// Draws the app and returns the screen as lines of text
+    pub fn screen(&mut self) -> String {
+        self.terminal
+            .draw(|frame| ui(frame, &mut self.app))
+            .unwrap();
+        let buffer = self.terminal.backend().buffer();
+        buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.cache_file);
+        let _ = std::fs::remove_dir_all(&self.data_dir);
+    }
+}
+
+/// Canvas timestamp at noon some days from now
+fn days_from_now(days: i64) -> String {
+    let date = OffsetDateTime::now_utc().date() + time::Duration::days(days);
+    format!("{date}T12:00:00Z")
+}
+
+/// Canvas timestamp at a local time some days from now
+fn local_time_from_now(days: i64, hour: u8, minute: u8) -> String {
+    let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+    let date = OffsetDateTime::now_utc().to_offset(offset).date() + time::Duration::days(days);
+    date.with_hms(hour, minute, 0)
+        .unwrap()
+        .assume_offset(offset)
+        .format(&Rfc3339)
+        .unwrap()
+}
+
+fn planner_item(id: u64, title: &str, days: i64) -> Value {
+    json!({
+        "context_name": "PHYS 1 Mechanics",
+        "course_id": 1,
+        "plannable_id": id,
+        "html_url": format!("/courses/1/assignments/{id}"),
+        "plannable_type": "assignment",
+        "submissions": { "submitted": false },
+        "plannable": { "title": title },
+        "plannable_date": days_from_now(days),
+    })
+}
+
+fn planner() -> Value {
+    json!([
+        planner_item(5, "Problem set 4", 1),
+        planner_item(7, "Pendulum lab report", 3),
+    ])
+}
+
+/// A course with two assignments due on different days
+fn routes() -> HashMap<String, Value> {
+    HashMap::from([
+        ("/api/v1/planner/items".to_string(), planner()),
+        (
+            "/api/v1/courses".to_string(),
+            json!([{ "id": 1, "course_code": "PHYS 1", "name": "Mechanics" }]),
+        ),
+        (
+            "/api/v1/users/self/colors".to_string(),
+            json!({ "custom_colors": {} }),
+        ),
+        ("/api/v1/users/self/course_nicknames".to_string(), json!([])),
+        (
+            "/api/v1/users/self/profile".to_string(),
+            json!({ "name": "Test Student" }),
+        ),
+        (
+            "/api/v1/courses/1/assignments/7".to_string(),
+            json!({
+                "description": "<p>Measure the period of the pendulum at three lengths.</p>",
+                "points_possible": 20,
+                "submission_types": ["online_upload"],
+                "due_at": days_from_now(3),
+            }),
+        ),
+    ])
+}
+
+#[tokio::test]
+async fn shows_the_planner_after_startup() {
+    let mut harness = Harness::start(routes()).await;
+    let screen = harness.screen();
+    assert!(screen.contains("Test Student"), "{screen}");
+    assert!(screen.contains("Problem set 4"), "{screen}");
+    assert!(!screen.contains("Pendulum lab report"), "{screen}");
+}
+
+#[tokio::test]
+async fn refresh_navigate_and_open_detail() {
+    let mut harness = Harness::start(routes()).await;
+    harness.press("u").await;
+    harness
+        .until(|app| matches!(app.sessions[0].fetch_state, FetchState::Loaded))
+        .await;
+    harness.press("l").await;
+    let screen = harness.screen();
+    assert!(screen.contains("Pendulum lab report"), "{screen}");
+    assert!(!screen.contains("Problem set 4"), "{screen}");
+
+    harness.press("i").await;
+    let screen = harness.wait_for("Measure the period of the pendulum").await;
+    assert!(screen.contains("DETAIL"), "{screen}");
+
+    harness.press("<Esc>").await;
+    assert!(!harness.screen().contains("DETAIL"));
+}
+
+#[tokio::test]
+async fn week_grid_counts_items_per_day() {
+    let mut harness = Harness::start(routes()).await;
+    harness.press("V").await;
+    let screen = harness.screen();
+    assert!(screen.contains("WEEK"), "{screen}");
+    assert!(screen.contains("1 due, 1 left"), "{screen}");
+}
+
+#[tokio::test]
+async fn keeps_cached_items_when_offline() {
+    let mut routes = routes();
+    routes.remove("/api/v1/planner/items");
+    let mut harness = Harness::start_with_cache(routes, planner()).await;
+    let screen = harness.screen();
+    assert!(screen.contains("Problem set 4"), "{screen}");
+    assert!(
+        screen.contains("stale (last updated 0 min ago)"),
+        "{screen}"
+    );
+}
+
+#[tokio::test]
+async fn marks_items_done_on_canvas() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/planner/overrides".to_string(),
+        json!({ "id": 9, "marked_complete": true }),
+    );
+    let mut harness = Harness::start(routes).await;
+    harness.press("x").await;
+    harness.wait_for("Marked Problem set 4 done").await;
+    let event = harness.app.sessions[0].selected_event().unwrap();
+    assert!(event.completed);
+    assert_eq!(event.override_id, Some(9));
+}
+
+#[tokio::test]
+async fn asks_before_marking_done_when_set_to() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/planner/overrides".to_string(),
+        json!({ "id": 9, "marked_complete": true }),
+    );
+    let mut harness = Harness::start(routes).await;
+    harness.app.confirm_config.mark_done = ConfirmPolicy::Always;
+    harness.press("x").await;
+    let screen = harness.screen();
+    assert!(
+        screen.contains("Mark Problem set 4 done? (y/n)"),
+        "{screen}"
+    );
+    assert!(screen.contains("CONFIRM"), "{screen}");
+
+    harness.press("n").await;
+    assert!(!harness.app.sessions[0].selected_event().unwrap().completed);
+    assert!(!harness.screen().contains("(y/n)"));
+
+    harness.press("xy").await;
+    harness.wait_for("Marked Problem set 4 done").await;
+    assert!(harness.app.sessions[0].selected_event().unwrap().completed);
+}
+
+#[tokio::test]
+async fn rolls_back_when_marking_done_fails() {
+    let mut harness = Harness::start(routes()).await;
+    harness.press("x").await;
+    harness.wait_for("Could not update Problem set 4").await;
+    assert!(!harness.app.sessions[0].selected_event().unwrap().completed);
+}
+
+#[tokio::test]
+async fn rechecks_a_single_submission() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/courses/1/assignments/5/submissions/self".to_string(),
+        json!({ "submitted_at": days_from_now(0), "workflow_state": "submitted" }),
+    );
+    let mut harness = Harness::start(routes).await;
+    harness.press("R").await;
+    harness.wait_for("Problem set 4 is submitted").await;
+    assert!(harness.app.sessions[0].selected_event().unwrap().submitted);
+}
+
+#[tokio::test]
+async fn submits_a_text_entry() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/courses/1/assignments/5/submissions".to_string(),
+        json!({ "id": 3, "workflow_state": "submitted" }),
+    );
+    let mut harness = Harness::start(routes).await;
+    harness.press("sMy answer<Enter>").await;
+    harness.wait_for("Submitted Problem set 4").await;
+    assert!(harness.app.sessions[0].selected_event().unwrap().submitted);
+}
+
+#[tokio::test]
+async fn filters_items_while_searching() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/planner/items".to_string(),
+        json!([
+            planner_item(5, "Problem set 4", 1),
+            planner_item(6, "Reading quiz", 1),
+            planner_item(7, "Pendulum lab report", 3),
+        ]),
+    );
+    let mut harness = Harness::start(routes).await;
+    harness.press("/re").await;
+    let screen = harness.screen();
+    assert!(screen.contains("Reading quiz"), "{screen}");
+    assert!(!screen.contains("Problem set 4"), "{screen}");
+
+    harness.press("<Enter>n").await;
+    let title = |harness: &Harness| {
+        harness.app.sessions[0]
+            .selected_event()
+            .unwrap()
+            .title
+            .clone()
+    };
+    assert_eq!(title(&harness), "Pendulum lab report");
+    harness.press("n").await;
+    assert_eq!(title(&harness), "Reading quiz");
+    harness.press("N").await;
+    assert_eq!(title(&harness), "Pendulum lab report");
+
+    harness.press("<Esc>h").await;
+    harness.wait_for("Problem set 4").await;
+}
+
+#[tokio::test]
+async fn hides_unchecked_courses() {
+    let mut harness = Harness::start(routes()).await;
+    harness.press("f<Enter>").await;
+    let screen = harness.screen();
+    assert!(screen.contains("COURSE FILTER"), "{screen}");
+    assert!(!screen.contains("Problem set 4"), "{screen}");
+    let profile = harness.app.sessions[0].profile.name.clone();
+    assert!(HiddenCourses::load().unwrap().get(&profile).contains(&1));
+
+    harness.press("<Enter><Esc>").await;
+    harness.wait_for("Problem set 4").await;
+}
+
+#[tokio::test]
+async fn reads_unread_announcements() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/announcements".to_string(),
+        json!([{
+            "id": 3,
+            "title": "Midterm moved",
+            "html_url": "/courses/1/discussion_topics/3",
+            "context_code": "course_1",
+            "read_state": "unread",
+            "posted_at": days_from_now(0),
+            "message": "<p>The midterm is now on Friday.</p>",
+        }]),
+    );
+    let mut harness = Harness::start(routes).await;
+    harness.press("n").await;
+    harness.wait_for("Midterm moved").await;
+    let screen = harness.screen();
+    assert!(screen.contains("ANNOUNCEMENTS"), "{screen}");
+    assert!(screen.contains("Mechanics"), "{screen}");
+
+    harness.press("<Enter>").await;
+    harness.wait_for("midterm is now on Friday").await;
+}
+
+#[tokio::test]
+async fn yanks_the_selected_item_as_a_link() {
+    let mut harness = Harness::start(routes()).await;
+    harness.press("ym").await;
+    harness.wait_for("Copied Problem set 4 as Markdown").await;
+    harness.press("yo").await;
+    harness.wait_for("Copied Problem set 4 as Org").await;
+
+    let session = &harness.app.sessions[0];
+    let url = format!("{}/courses/1/assignments/5", session.profile.url);
+    let due_at = session.selected_event().unwrap().due_at;
+    let time = format!("{:02}:{:02}", due_at.hour(), due_at.minute());
+    let [markdown, org] = harness.copied() else {
+        panic!("{:?}", harness.copied());
+    };
+    assert!(
+        markdown.starts_with(&format!("[PHYS 1 Problem set 4]({url}) (due ")),
+        "{markdown}"
+    );
+    assert!(markdown.ends_with(&format!(" {time})")), "{markdown}");
+    assert_eq!(
+        *org,
+        format!(
+            "[[{url}][PHYS 1 Problem set 4]] DEADLINE: <{} {} {time}>",
+            due_at.date(),
+            &due_at.weekday().to_string()[..3]
+        )
+    );
+}
+
+#[tokio::test]
+async fn bookmarks_the_selected_item() {
+    let mut harness = Harness::start(routes()).await;
+    harness.press("b").await;
+    harness.wait_for("Bookmarked Problem set 4").await;
+    let url = format!(
+        "{}/courses/1/assignments/5",
+        harness.app.sessions[0].profile.url
+    );
+    assert_eq!(harness.app.bookmarks.items[0].url, url);
+    harness.press("b").await;
+    harness.wait_for("Removed bookmark Problem set 4").await;
+
+    // A url without a scheme is reported instead of taking the app down
+    harness.app.sessions[0].profile.url = "canvas.example.edu".to_string();
+    harness.press("b").await;
+    harness.wait_for("Could not bookmark Problem set 4").await;
+    assert!(harness.app.bookmarks.items.is_empty());
+}
+
+#[tokio::test]
+async fn opens_or_copies_everything_left_on_a_day() {
+    let mut harness = Harness::start(routes()).await;
+    harness.press("go").await;
+    let screen = harness.screen();
+    assert!(
+        screen.contains("Open 1 item(s) in the browser? (y/n)"),
+        "{screen}"
+    );
+    harness.press("n").await;
+    assert!(!harness.screen().contains("(y/n)"));
+
+    harness.press("yd").await;
+    harness.wait_for("Copied 1 url(s)").await;
+    let url = format!(
+        "{}/courses/1/assignments/5",
+        harness.app.sessions[0].profile.url
+    );
+    assert_eq!(harness.copied(), [url]);
+}
+
+#[tokio::test]
+async fn exports_a_reminder_over_caldav() {
+    let mut routes = routes();
+    routes.insert(
+        "/dav/canvastui-courses-1-assignments-5.ics".to_string(),
+        json!({}),
+    );
+    let mut harness = Harness::start(routes).await;
+    let url = harness.app.sessions[0].profile.url.clone();
+    harness.app.reminders_config.backend = ReminderBackend::CalDav;
+    harness.app.caldav_config = Some(CalDavConfig {
+        url: format!("{url}/dav/"),
+        username: "me".to_string(),
+        password: "password".to_string(),
+        prefer: ConflictPreference::default(),
+    });
+    harness.press("E").await;
+    harness
+        .wait_for("Reminder for Problem set 4 goes off 60 minutes before it is due")
+        .await;
+}
+
+#[tokio::test]
+async fn replies_to_a_conversation() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/conversations".to_string(),
+        json!([{
+            "id": 9,
+            "subject": "Lab groups",
+            "workflow_state": "unread",
+            "last_message_at": days_from_now(0),
+            "message_count": 1,
+            "context_name": "PHYS 1 Mechanics",
+        }]),
+    );
+    routes.insert(
+        "/api/v1/conversations/9".to_string(),
+        json!({
+            "id": 9,
+            "participants": [{ "id": 2, "name": "Professor Oak" }],
+            "messages": [{
+                "author_id": 2,
+                "created_at": days_from_now(0),
+                "body": "Please pick a lab partner by Friday.",
+            }],
+        }),
+    );
+    routes.insert(
+        "/api/v1/conversations/9/add_message".to_string(),
+        json!({ "id": 9 }),
+    );
+    let mut harness = Harness::start(routes).await;
+    harness.press("I").await;
+    harness.wait_for("1 unread").await;
+    harness.press("<Enter>").await;
+    harness
+        .wait_for("Please pick a lab partner by Friday.")
+        .await;
+    assert!(harness.screen().contains("Professor Oak"));
+
+    harness.press("rI will work with Sam<Enter>Thanks").await;
+    let screen = harness.screen();
+    assert!(screen.contains("COMPOSE"), "{screen}");
+    assert!(screen.contains("I will work with Sam"), "{screen}");
+    harness.press("<C-s>").await;
+    harness.wait_for("Reply sent").await;
+    assert!(!harness.screen().contains("COMPOSE"));
+}
+
+#[tokio::test]
+async fn badges_a_beta_instance() {
+    let mut harness = Harness::start(routes()).await;
+    assert!(!harness.screen().contains("BETA"));
+    harness.app.sessions[0].profile.environment = Some("beta".to_string());
+    let screen = harness.screen();
+    assert!(screen.contains(" BETA "), "{screen}");
+}
+
+#[tokio::test]
+async fn lists_the_todo_items_by_due_date() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/users/self/todo".to_string(),
+        json!([
+            {
+                "type": "grading",
+                "context_name": "PHYS 1 Mechanics",
+                "assignment": {
+                    "name": "Pendulum lab report",
+                    "html_url": "/courses/1/assignments/7",
+                    "due_at": days_from_now(3),
+                    "needs_grading_count": 4,
+                },
+            },
+            {
+                "type": "submitting",
+                "context_name": "PHYS 1 Mechanics",
+                "quiz": {
+                    "title": "Kinematics quiz",
+                    "html_url": "/courses/1/quizzes/2",
+                    "due_at": days_from_now(1),
+                },
+            },
+        ]),
+    );
+    let mut harness = Harness::start(routes).await;
+    harness.press("t").await;
+    harness.wait_for("Kinematics quiz").await;
+    let screen = harness.screen();
+    assert!(screen.contains("needs submitting"), "{screen}");
+    assert!(screen.contains("needs grading (4)"), "{screen}");
+    assert!(
+        screen.find("Kinematics quiz") < screen.find("Pendulum lab report"),
+        "{screen}"
+    );
+}
+
+#[tokio::test]
+async fn builds_the_planner_without_the_planner_api() {
+    let mut routes = routes();
+    routes.remove("/api/v1/planner/items");
+    let assignment = |id: u64, name: &str, days: i64| {
+        json!({
+            "id": id,
+            "name": name,
+            "html_url": format!("http://canvas.example.edu/courses/1/assignments/{id}"),
+            "due_at": days_from_now(days),
+        })
+    };
+    routes.insert(
+        "/api/v1/courses/1/assignments".to_string(),
+        json!([
+            assignment(3, "Problem set 3", -2),
+            assignment(5, "Problem set 4", 1),
+            assignment(7, "Pendulum lab report", 3),
+        ]),
+    );
+    routes.insert(
+        "/api/v1/users/self/upcoming_events".to_string(),
+        json!([
+            {
+                "id": 11,
+                "type": "event",
+                "title": "Review session",
+                "html_url": "http://canvas.example.edu/calendar?event_id=11",
+                "context_code": "course_1",
+                "start_at": days_from_now(1),
+            },
+            {
+                "id": 5,
+                "type": "assignment",
+                "title": "Problem set 4",
+                "html_url": "http://canvas.example.edu/courses/1/assignments/5",
+                "context_code": "course_1",
+                "start_at": days_from_now(1),
+            },
+        ]),
+    );
+    let mut harness = Harness::start(routes).await;
+    let screen = harness.screen();
+    assert!(screen.contains("Problem set 4"), "{screen}");
+    assert!(screen.contains("Review session"), "{screen}");
+    assert!(!screen.contains("Problem set 3"), "{screen}");
+    assert_eq!(screen.matches("Problem set 4").count(), 1, "{screen}");
+
+    harness.press("x").await;
+    harness.wait_for("no planner to mark items done in").await;
+}
+
+#[tokio::test]
+async fn shows_failures_in_a_toast() {
+    let mut harness = Harness::start(routes()).await;
+    harness.press("t").await;
+    let screen = harness.wait_for("Loading the To-Do list failed").await;
+    assert!(screen.contains("Error · Esc dismiss"), "{screen}");
+
+    harness.press("<Esc>").await;
+    let screen = harness.screen();
+    assert!(
+        !screen.contains("Loading the To-Do list failed"),
+        "{screen}"
+    );
+    assert!(harness.app.screens.todo.is_some());
+}
+
+#[tokio::test]
+async fn signs_in_with_a_session_cookie() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/planner/overrides".to_string(),
+        json!({ "id": 9, "marked_complete": true }),
+    );
+    let mut harness = Harness::start_with_cookie(routes).await;
+    let screen = harness.screen();
+    assert!(screen.contains("Test Student"), "{screen}");
+    assert!(screen.contains("Problem set 4"), "{screen}");
+
+    harness.press("x").await;
+    harness.wait_for("Marked Problem set 4 done").await;
+}
+
+#[tokio::test]
+async fn groups_a_day_under_time_headings() {
+    let mut routes = routes();
+    let item = |id: u64, title: &str, hour: u8, minute: u8| {
+        let mut item = planner_item(id, title, 1);
+        item["plannable_date"] = json!(local_time_from_now(1, hour, minute));
+        item
+    };
+    routes.insert(
+        "/api/v1/planner/items".to_string(),
+        json!([
+            item(5, "Problem set 4", 13, 0),
+            item(6, "Reading quiz", 9, 30),
+            item(8, "Lab writeup", 23, 59),
+        ]),
+    );
+    let mut harness = Harness::start(routes).await;
+    assert!(!harness.screen().contains("Morning"));
+
+    harness.app.sessions[0].time_buckets = true;
+    let screen = harness.screen();
+    let positions: Vec<Option<usize>> = [
+        "Morning",
+        "Reading quiz",
+        "Afternoon",
+        "Problem set 4",
+        "11:59pm",
+        "Lab writeup",
+    ]
+    .iter()
+    .map(|text| screen.find(text))
+    .collect();
+    assert!(positions.iter().all(Option::is_some), "{screen}");
+    assert!(positions.is_sorted(), "{screen}");
+    assert!(!screen.contains("Evening"), "{screen}");
+
+    harness.press("jj").await;
+    let event = harness.app.sessions[0].selected_event().unwrap();
+    assert_eq!(event.title, "Lab writeup");
+}
+
+#[tokio::test]
+async fn jumps_between_dates() {
+    let mut routes = routes();
+    let item = |id: u64, title: &str, days: i64| {
+        let mut item = planner_item(id, title, days);
+        item["plannable_date"] = json!(local_time_from_now(days, 12, 0));
+        item
+    };
+    routes.insert(
+        "/api/v1/planner/items".to_string(),
+        json!([
+            item(5, "Problem set 4", 1),
+            item(6, "Reading quiz", 1),
+            item(7, "Pendulum lab report", 3),
+            item(8, "Final project", 5),
+        ]),
+    );
+    let mut harness = Harness::start(routes).await;
+    harness.press("j").await;
+    harness.press("ge").await;
+    assert_eq!(harness.app.sessions[0].current_date_index, 2);
+    assert!(harness.screen().contains("Final project"));
+
+    // Coming back keeps the item selected before
+    harness.press("gg").await;
+    let event = harness.app.sessions[0].selected_event().unwrap();
+    assert_eq!(event.title, "Reading quiz");
+
+    let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+    let date = OffsetDateTime::now_utc().to_offset(offset).date();
+    harness
+        .press(&format!(":{}<Enter>", date + time::Duration::days(3)))
+        .await;
+    assert!(harness.screen().contains("Pendulum lab report"));
+    harness
+        .press(&format!(":{}<Enter>", date + time::Duration::days(4)))
+        .await;
+    let screen = harness.wait_for("Nothing due on").await;
+    assert!(screen.contains("Final project"), "{screen}");
+
+    harness.press("gt").await;
+    assert_eq!(harness.app.sessions[0].current_date_index, 0);
+}
+
+#[tokio::test]
+async fn marks_the_current_time_between_todays_items() {
+    let mut routes = routes();
+    let item = |id: u64, title: &str, hour: u8, minute: u8| {
+        let mut item = planner_item(id, title, 0);
+        item["plannable_date"] = json!(local_time_from_now(0, hour, minute));
+        item
+    };
+    routes.insert(
+        "/api/v1/planner/items".to_string(),
+        json!([
+            item(5, "Problem set 4", 0, 0),
+            item(6, "Reading quiz", 23, 59),
+        ]),
+    );
+    let mut harness = Harness::start(routes).await;
+    harness.press("j").await;
+    let screen = harness.screen();
+    let positions: Vec<Option<usize>> = ["Problem set 4", "── now", "Reading quiz"]
+        .iter()
+        .map(|text| screen.find(text))
+        .collect();
+    assert!(positions.iter().all(Option::is_some), "{screen}");
+    assert!(positions.is_sorted(), "{screen}");
+    let event = harness.app.sessions[0].selected_event().unwrap();
+    assert_eq!(event.title, "Reading quiz");
+
+    harness.press("A").await;
+    let screen = harness.screen();
+    assert!(screen.contains("── now"), "{screen}");
+}
+
+#[tokio::test]
+async fn flags_overdue_items() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/planner/items".to_string(),
+        json!([
+            planner_item(5, "Problem set 4", -2),
+            planner_item(7, "Pendulum lab report", 3),
+        ]),
+    );
+    let mut harness = Harness::start(routes).await;
+    let screen = harness.screen();
+    assert!(screen.contains("Problem set 4 overdue"), "{screen}");
+
+    harness.press("l").await;
+    let screen = harness.screen();
+    assert!(screen.contains("Pendulum lab report"), "{screen}");
+    assert!(!screen.contains("overdue"), "{screen}");
+}
+
+#[tokio::test]
+async fn moves_on_to_the_next_day_at_midnight() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/planner/items".to_string(),
+        json!([
+            planner_item(5, "Problem set 4", -1),
+            planner_item(6, "Reading quiz", 0),
+            planner_item(7, "Pendulum lab report", 3),
+        ]),
+    );
+    let mut harness = Harness::start(routes).await;
+    // As if the app was started yesterday and left on that day
+    harness.app.today -= time::Duration::days(1);
+    assert_eq!(
+        harness.app.sessions[0].selected_event().unwrap().title,
+        "Problem set 4"
+    );
+
+    harness.dispatcher.dispatch(&mut harness.app, Action::Tick);
+    harness
+        .until(|app| matches!(app.sessions[0].fetch_state, FetchState::Loaded))
+        .await;
+    harness.settle().await;
+    let event = harness.app.sessions[0].selected_event().unwrap();
+    assert_eq!(event.title, "Reading quiz");
+}
+
+#[tokio::test]
+async fn shows_what_failed_to_load_next_to_the_rest() {
+    let mut routes = routes();
+    routes.remove("/api/v1/users/self/colors");
+    routes.remove("/api/v1/users/self/profile");
+    let mut harness = Harness::start(routes).await;
+    let screen = harness.screen();
+    assert!(screen.contains("Problem set 4"), "{screen}");
+    assert!(
+        screen.contains("Partial data, couldn't load course colors, profile · u retry"),
+        "{screen}"
+    );
+
+    harness.press("u").await;
+    harness
+        .until(|app| app.sessions[0].progress.is_done())
+        .await;
+    assert_eq!(harness.app.sessions[0].progress.failed.len(), 2);
+    assert!(harness.screen().contains("Partial data"));
+}
+
+#[tokio::test]
+async fn lists_the_keybindings_in_a_help_overlay() {
+    let mut harness = Harness::start(routes()).await;
+    harness.press("?").await;
+    let screen = harness.screen();
+    assert!(screen.contains("HELP"), "{screen}");
+    assert!(screen.contains("next date"), "{screen}");
+    assert!(screen.contains("l, Right"), "{screen}");
+
+    // Other keys are held back while it is open
+    harness.press("l").await;
+    assert_eq!(harness.app.sessions[0].current_date_index, 0);
+    harness.press("<Esc>").await;
+    assert!(!harness.screen().contains("HELP"));
+}
+
+#[tokio::test]
+async fn opens_the_syllabus_at_the_week_of_an_assignment() {
+    let mut routes = routes();
+    let today = OffsetDateTime::now_utc().date();
+    let schedule: String = (0..60)
+        .map(|week| {
+            let date = today + time::Duration::days(week - 30);
+            format!("<p>Topic {week}: {date}</p>")
+        })
+        .collect();
+    routes.insert(
+        "/api/v1/courses/1".to_string(),
+        json!({ "syllabus_body": format!("<h2>Schedule</h2>{schedule}") }),
+    );
+    routes.insert(
+        "/api/v1/courses/1/assignments/5".to_string(),
+        json!({ "description": "<p>Chapter 4</p>", "due_at": days_from_now(1) }),
+    );
+    let mut harness = Harness::start(routes).await;
+    harness.press("i").await;
+    harness.wait_for("Chapter 4").await;
+    harness.press("gs").await;
+    let screen = harness.wait_for("Topic 31").await;
+    assert!(screen.contains("PHYS 1 syllabus"), "{screen}");
+    assert!(!screen.contains("Topic 0:"), "{screen}");
+    assert!(harness.app.popups.detail.is_none());
+}
+
+#[tokio::test]
+async fn opens_a_bare_id_as_an_assignment() {
+    let mut routes = routes();
+    let mut quiz = planner_item(42, "Quiz 3", 1);
+    quiz["plannable_type"] = json!("quiz");
+    quiz["html_url"] = json!("/courses/1/quizzes/42");
+    routes.insert(
+        "/api/v1/planner/items".to_string(),
+        json!([planner_item(5, "Problem set 4", 1), quiz]),
+    );
+    routes.insert(
+        "/api/v1/courses/1/assignments/42".to_string(),
+        json!({
+            "id": 42,
+            "name": "Lab safety form",
+            "html_url": "https://canvas.example.edu/courses/1/assignments/42",
+            "due_at": days_from_now(9),
+        }),
+    );
+    let mut harness = Harness::start(routes).await;
+    harness.press("O42<Enter>").await;
+    // The quiz shares the id but isn't an assignment, so the assignment is looked up
+    harness.wait_for("Lab safety form").await;
+    let event = harness.app.sessions[0].selected_event().unwrap();
+    assert_eq!(event.html_url, "/courses/1/assignments/42");
+    assert_eq!(event.course_id, Some(1));
+}
+
+#[tokio::test]
+async fn keeps_an_audit_trail_of_outcomes() {
+    let mut harness = Harness::start(routes()).await;
+    harness.press("x").await;
+    harness.wait_for("Could not update Problem set 4").await;
+    harness.press("<Esc>").await;
+    harness.press("H").await;
+    let screen = harness.screen();
+    assert!(screen.contains("AUDIT"), "{screen}");
+    assert!(screen.contains("Marking Problem set 4 done..."), "{screen}");
+    assert!(screen.contains("completion failed"), "{screen}");
+    assert!(screen.contains("Refreshed"), "{screen}");
+}
+
+#[tokio::test]
+async fn switches_the_session_to_another_profile() {
+    let mut harness = Harness::start(routes()).await;
+    harness.press("p").await;
+    harness.wait_for("No other profile to switch to").await;
+
+    let first = harness.app.sessions[0].profile.clone();
+    let second = Profile {
+        name: format!("{}-college", first.name),
+        ..first.clone()
+    };
+    harness.app.profiles.push(second.clone());
+    harness.press("p").await;
+    harness
+        .until(|app| {
+            let session = &app.sessions[0];
+            session.profile.name == second.name
+                && session.progress.is_done()
+                && !matches!(session.fetch_state, FetchState::Loading)
+        })
+        .await;
+    let screen = harness.wait_for("Problem set 4").await;
+    assert!(
+        screen.contains(&format!("Switched to {}", second.name)),
+        "{screen}"
+    );
+    assert!(second.cache_file().exists());
+
+    harness.press("p").await;
+    assert_eq!(harness.app.sessions[0].profile.name, first.name);
+    let _ = std::fs::remove_file(second.cache_file());
+}
+
+#[tokio::test]
+async fn links_local_files_to_an_item() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/courses/1/assignments/5".to_string(),
+        json!({ "description": "<p>Chapter 4</p>", "due_at": days_from_now(1) }),
+    );
+    let mut harness = Harness::start(routes).await;
+    harness.press("e").await;
+    harness.wait_for("No files linked to Problem set 4").await;
+
+    harness.press("F/notes/pset4.md<Enter>").await;
+    harness.wait_for("Linked /notes/pset4.md").await;
+    let html_url = harness.app.sessions[0]
+        .selected_event()
+        .unwrap()
+        .html_url
+        .clone();
+    assert_eq!(
+        harness.app.annotations.files(&html_url),
+        [PathBuf::from("/notes/pset4.md")]
+    );
+    harness.press("i").await;
+    let screen = harness.wait_for("Chapter 4").await;
+    assert!(screen.contains("Files     /notes/pset4.md"), "{screen}");
+
+    // The same path again unlinks it, also from the popup, once confirmed
+    harness.press("F/notes/pset4.md<Enter>").await;
+    harness
+        .wait_for("Unlink /notes/pset4.md from Problem set 4? (y/n)")
+        .await;
+    // Declining leaves the prompt open as it was
+    harness.press("n").await;
+    assert_eq!(harness.app.annotations.files(&html_url).len(), 1);
+    harness.press("<Enter>y").await;
+    harness.wait_for("Unlinked /notes/pset4.md").await;
+    assert!(harness.app.annotations.files(&html_url).is_empty());
+
+    // Unlinking has a class of its own, apart from deleting notes
+    harness.app.confirm_config.unlink_file = ConfirmPolicy::Never;
+    harness.press("F/notes/pset4.md<Enter>").await;
+    harness.wait_for("Linked /notes/pset4.md").await;
+    harness.press("F/notes/pset4.md<Enter>").await;
+    harness.wait_for("Unlinked /notes/pset4.md").await;
+    assert!(harness.app.annotations.files(&html_url).is_empty());
+}
+
+#[tokio::test]
+async fn asks_before_deleting_a_note() {
+    let mut harness = Harness::start(routes()).await;
+    harness.wait_for("Problem set 4").await;
+    let date = harness.app.sessions[0].current_date().unwrap();
+
+    let note = Action::SaveNote(date, "Bring a calculator".to_string());
+    harness.dispatcher.dispatch(&mut harness.app, note);
+    harness.wait_for("Saved the note").await;
+    // Saved empty, the note would be gone for good
+    let empty = Action::SaveNote(date, "\n".to_string());
+    harness.dispatcher.dispatch(&mut harness.app, empty);
+    harness
+        .wait_for(&format!("Delete the note of {date}? (y/n)"))
+        .await;
+    harness.press("n").await;
+    assert_eq!(
+        harness.app.sessions[0].notes.get(date),
+        Some("Bring a calculator")
+    );
+
+    let empty = Action::SaveNote(date, String::new());
+    harness.dispatcher.dispatch(&mut harness.app, empty);
+    harness.press("y").await;
+    harness.wait_for("Saved the note").await;
+    assert_eq!(harness.app.sessions[0].notes.get(date), None);
+}
+
+#[tokio::test]
+async fn names_courses_by_their_course_code() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/courses".to_string(),
+        json!([{ "id": 1, "course_code": "PHYS-101A", "name": "Mechanics" }]),
+    );
+    let mut harness = Harness::start(routes).await;
+    let screen = harness.wait_for("PHYS-101A").await;
+    assert!(!screen.contains("PHYS-1 "), "{screen}");
+    assert!(
+        course_column(&screen, "Problem set 4").starts_with("PHYS-101A "),
+        "{screen}"
+    );
+}
+
+#[tokio::test]
+async fn names_courses_without_a_code_by_their_name() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/courses".to_string(),
+        json!([{ "id": 1, "name": "Mechanics" }]),
+    );
+    let mut harness = Harness::start(routes).await;
+    harness
+        .until(|app| app.sessions[0].progress.is_done())
+        .await;
+    let screen = harness.screen();
+    assert!(
+        course_column(&screen, "Problem set 4").starts_with("Mechanics "),
+        "{screen}"
+    );
+}
+
+/// The text of the row showing the item, from the course column on
+fn course_column<'a>(screen: &'a str, title: &str) -> &'a str {
+    let row = screen
+        .lines()
+        .find(|line| line.contains(title))
+        .unwrap_or_else(|| panic!("no row for {title} in {screen}"));
+    row.trim_start_matches('┃').trim_start()
+}
+
+#[tokio::test]
+async fn focuses_everything_on_one_course() {
+    let mut routes = routes();
+    let mut chemistry = planner_item(9, "Titration worksheet", 1);
+    chemistry["context_name"] = json!("CHEM 2 Organic");
+    chemistry["course_id"] = json!(2);
+    chemistry["html_url"] = json!("/courses/2/assignments/9");
+    routes.insert(
+        "/api/v1/planner/items".to_string(),
+        json!([planner_item(5, "Problem set 4", 1), chemistry]),
+    );
+    routes.insert(
+        "/api/v1/courses".to_string(),
+        json!([
+            { "id": 1, "course_code": "PHYS 1", "name": "Mechanics" },
+            { "id": 2, "course_code": "CHEM 2", "name": "Organic" },
+        ]),
+    );
+    let mut harness = Harness::start(routes).await;
+    harness.wait_for("Titration worksheet").await;
+    while harness.app.sessions[0].selected_event().unwrap().course_id != Some(1) {
+        harness.press("j").await;
+    }
+
+    harness.press("z").await;
+    let screen = harness.screen();
+    assert!(screen.contains("FOCUS PHYS 1"), "{screen}");
+    assert!(screen.contains("Problem set 4"), "{screen}");
+    assert!(!screen.contains("Titration worksheet"), "{screen}");
+    harness.press("c").await;
+    let screen = harness.screen();
+    assert!(screen.contains("COURSES"), "{screen}");
+    assert!(!screen.contains("CHEM 2"), "{screen}");
+
+    harness.press("<Esc>z").await;
+    let screen = harness.wait_for("Titration worksheet").await;
+    assert!(!screen.contains("FOCUS"), "{screen}");
+}
+
+#[tokio::test]
+async fn moves_an_item_to_its_soft_deadline() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/courses/1/assignments/5".to_string(),
+        json!({ "description": "<p>Chapter 4</p>", "due_at": days_from_now(3) }),
+    );
+    routes.insert(
+        "/api/v1/planner/items".to_string(),
+        json!([planner_item(5, "Problem set 4", 3)]),
+    );
+    let mut harness = Harness::start(routes).await;
+    let official = harness.app.sessions[0].selected_event().unwrap().due_at;
+
+    harness.press("d2d<Enter>").await;
+    let screen = harness.wait_for("Finishing Problem set 4 by").await;
+    assert!(screen.contains("soft"), "{screen}");
+    let event = harness.app.sessions[0].selected_event().unwrap();
+    assert_eq!(event.official_due_at, Some(official));
+    assert_eq!(event.due_at, official - time::Duration::days(2));
+
+    harness.press("i").await;
+    let screen = harness.wait_for("Chapter 4").await;
+    assert!(screen.contains("Finish by"), "{screen}");
+    assert!(screen.contains("(2d early)"), "{screen}");
+
+    // Clearing the prefilled lead time goes back to the official deadline
+    harness.press("<Esc>d").await;
+    assert!(harness.screen().contains("2d█"));
+    harness.press("<BS><BS><Enter>").await;
+    harness.wait_for("Back to the official deadline").await;
+    let event = harness.app.sessions[0].selected_event().unwrap();
+    assert_eq!((event.due_at, event.official_due_at), (official, None));
+}
+
+#[tokio::test]
+async fn shows_the_official_deadline_when_the_assignment_has_none() {
+    let mut routes = routes();
+    routes.insert(
+        "/api/v1/courses/1/assignments/5".to_string(),
+        json!({ "description": "<p>Chapter 4</p>" }),
+    );
+    routes.insert(
+        "/api/v1/planner/items".to_string(),
+        json!([planner_item(5, "Problem set 4", 3)]),
+    );
+    let mut harness = Harness::start(routes).await;
+    let official = harness.app.sessions[0].selected_event().unwrap().due_at;
+    harness.press("d2d<Enter>").await;
+    harness.wait_for("Finishing Problem set 4 by").await;
+
+    harness.press("i").await;
+    let screen = harness.wait_for("Chapter 4").await;
+    let due = format!(
+        "{} {:02}:{:02}",
+        official.day(),
+        official.hour(),
+        official.minute()
+    );
+    let finish_by = official - time::Duration::days(2);
+    let finish_by = format!(
+        "{} {:02}:{:02} (2d early)",
+        finish_by.day(),
+        finish_by.hour(),
+        finish_by.minute()
+    );
+    let line = |name: &str| {
+        screen
+            .lines()
+            .find(|line| line.contains(&format!("│{name}")))
+            .unwrap_or_else(|| panic!("no {name} in {screen}"))
+            .to_string()
+    };
+    assert!(line("Due ").contains(&due), "{screen}");
+    assert!(line("Finish by").contains(&finish_by), "{screen}");
+}
@@ -0,0 +1,40 @@
+use std::{env, path::PathBuf, process::Command};
+
+use color_eyre::eyre::{Result, eyre};
+
+/// `$VISUAL` or `$EDITOR` with its arguments, falling back to vi, and the program's name
+fn command() -> Result<(Command, String)> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or(eyre!("$EDITOR is empty"))?;
+    let mut command = Command::new(program);
+    command.args(words);
+    Ok((command, program.to_string()))
+}
+
+/// Lets the user edit text in `$VISUAL` or `$EDITOR`, falling back to vi. `name` tells the
+/// temporary files apart. The terminal must be out of raw mode while the editor runs
+pub fn edit(name: &str, text: &str) -> Result<String> {
+    let (mut command, program) = command()?;
+    let path = env::temp_dir().join(format!("canvastui-{name}.txt"));
+    std::fs::write(&path, text)?;
+    let status = command.arg(&path).status();
+    let edited = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+    if !status?.success() {
+        return Err(eyre!("{program} exited with an error"));
+    }
+    Ok(edited?)
+}
+
+/// Opens the files in the editor all at once, which creates the missing ones when they are
+/// saved. The terminal must be out of raw mode while the editor runs
+pub fn open(paths: &[PathBuf]) -> Result<()> {
+    let (mut command, program) = command()?;
+    if !command.args(paths).status()?.success() {
+        return Err(eyre!("{program} exited with an error"));
+    }
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use std::{fmt::Write, path::Path};
+
+use color_eyre::eyre::{Result, eyre};
+use reqwest::Url;
+use time::{OffsetDateTime, PrimitiveDateTime, UtcOffset, format_description::well_known::Rfc3339};
+
+use crate::{
+    config::{Config, Profile},
+    fetch::{Calendar, CalendarEvent, load_cache},
+    lookup::normalize_course,
+    redact,
+};
+
+/// Writes an Atom feed of the cached assignments and announcements for a course
+pub fn write_feed(course: &str, out: &Path) -> Result<()> {
+    redact::check_export()?;
+    let profile = Config::load()?.default_profile()?;
+    let calendar =
+        load_cache(&profile).map_err(|e| eyre!("Could not read cached planner data: {e}"))?;
+    let feed = build_feed(&calendar, &profile, course)?;
+    std::fs::write(out, feed)?;
+    Ok(())
+}
+
+fn build_feed(calendar: &Calendar, profile: &Profile, course: &str) -> Result<String> {
+    let course = normalize_course(course);
+    let base_url = profile.url.parse::<Url>().ok();
+    let offset = UtcOffset::current_local_offset()?;
+    let to_rfc3339 = |dt: PrimitiveDateTime| dt.assume_offset(offset).format(&Rfc3339);
+
+    let events: Vec<&CalendarEvent> = calendar
+        .dates
+        .iter()
+        .flat_map(|date| &date.events)
+        .filter(|event| normalize_course(&event.course_name).contains(&course))
+        .filter(|event| matches!(event.plannable_type.as_str(), "assignment" | "announcement"))
+        .collect();
+
+    let updated = match events.iter().map(|event| event.due_at).max() {
+        Some(latest) => to_rfc3339(latest)?,
+        None => OffsetDateTime::now_utc().format(&Rfc3339)?,
+    };
+
+    let mut feed = String::new();
+    writeln!(feed, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+    writeln!(feed, r#"<feed xmlns="http://www.w3.org/2005/Atom">"#)?;
+    writeln!(
+        feed,
+        "  <title>{} - CanvasTUI</title>",
+        escape(&course.to_uppercase())
+    )?;
+    writeln!(feed, "  <id>urn:canvastui:feed:{}</id>", escape(&course))?;
+    writeln!(feed, "  <updated>{updated}</updated>")?;
+    for event in events {
+        let url = match &base_url {
+            Some(base) => base.join(&event.html_url)?.to_string(),
+            None => event.html_url.clone(),
+        };
+        writeln!(feed, "  <entry>")?;
+        writeln!(feed, "    <title>{}</title>", escape(&event.title))?;
+        writeln!(feed, "    <id>{}</id>", escape(&url))?;
+        writeln!(feed, r#"    <link href="{}"/>"#, escape(&url))?;
+        writeln!(feed, "    <updated>{}</updated>", to_rfc3339(event.due_at)?)?;
+        writeln!(
+            feed,
+            "    <category term=\"{}\"/>",
+            escape(&event.plannable_type)
+        )?;
+        writeln!(
+            feed,
+            "    <author><name>{}</name></author>",
+            escape(&event.course_name)
+        )?;
+        writeln!(feed, "  </entry>")?;
+    }
+    writeln!(feed, "</feed>")?;
+    Ok(feed)
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,15 +1,18 @@
-use std::{collections::BTreeMap, env};
+use std::collections::{BTreeMap, HashMap};
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{Result, eyre};
 use ratatui::widgets::TableState;
 use reqwest::Url;
-use serde::{Deserialize, de::Visitor};
-use time::{Date, OffsetDateTime, PrimitiveDateTime, UtcOffset, format_description};
-use tokio::sync::mpsc::UnboundedSender;
+use serde::{Deserialize, de::DeserializeOwned, de::Visitor};
+use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime, UtcOffset, format_description};
 
-use crate::{Action, CACHE_FILE};
+use crate::{
+    canvas::Client, capabilities, compat, config::Profile, lookup::fetch_courses, metrics,
+    store::Annotations,
+};
 
-const ENDPOINT: &str = "/api/v1/planner/items";
+/// Marks events imported from an external ICS calendar rather than Canvas
+pub const EXTERNAL_PLANNABLE_TYPE: &str = "external";
 
 #[derive(Debug, Clone)]
 pub struct Calendar {
@@ -25,18 +28,274 @@ pub struct CalendarDate {
 #[derive(Debug, Clone)]
 pub struct CalendarEvent {
     pub course_name: String,
+    /// When the item is due, moved earlier by a soft deadline if the user set one
     pub due_at: PrimitiveDateTime,
+    /// Due date set in Canvas while a soft deadline replaces it in `due_at`
+    pub official_due_at: Option<PrimitiveDateTime>,
     pub title: String,
     pub html_url: String,
+    pub plannable_type: String,
+    pub course_id: Option<u64>,
+    pub plannable_id: u64,
     pub submitted: bool,
+    pub graded: bool,
+    /// Marked done without a submission, either in the Canvas planner or locally
+    pub completed: bool,
+    /// Id of the Canvas planner override holding the completed state, once one exists
+    pub override_id: Option<u64>,
+    pub unlock_at: Option<PrimitiveDateTime>,
+    pub lock_at: Option<PrimitiveDateTime>,
+    /// Attempt limit and attempts used, once fetched. None when attempts are unlimited
+    pub attempts: Option<Attempts>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attempts {
+    pub allowed: u32,
+    pub used: u32,
+}
+
+impl Attempts {
+    pub fn remaining(&self) -> u32 {
+        self.allowed.saturating_sub(self.used)
+    }
+
+    pub fn describe(&self) -> String {
+        match self.remaining() {
+            0 => "no attempts left".to_string(),
+            1 => "1 attempt left".to_string(),
+            remaining => format!("{remaining} attempts left"),
+        }
+    }
+}
+
+/// How an item is handed in, from its submission settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct Requirements {
+    /// Canvas submission types, e.g. online_upload, online_text_entry or on_paper
+    pub submission_types: Vec<String>,
+    /// File extensions accepted by uploads, empty when any file is accepted
+    pub allowed_extensions: Vec<String>,
+    /// None when attempts are unlimited
+    pub allowed_attempts: Option<u32>,
+    /// Submitted once for a whole group
+    pub group: bool,
+}
+
+impl Requirements {
+    /// Nerd Font icons for the submission types, or letters on terminals without them
+    pub fn icons(&self) -> String {
+        let nerd_fonts = capabilities::current().nerd_fonts();
+        let icon = |nerd_font, letter| match nerd_fonts {
+            true => nerd_font,
+            false => letter,
+        };
+        let mut icons: String = self
+            .submission_types
+            .iter()
+            .filter_map(|submission_type| match submission_type.as_str() {
+                "online_upload" => Some(icon('\u{f093}', 'F')),
+                "online_text_entry" => Some(icon('\u{f040}', 'T')),
+                "online_url" => Some(icon('\u{f0c1}', 'U')),
+                "media_recording" => Some(icon('\u{f130}', 'M')),
+                "on_paper" => Some(icon('\u{f0f6}', 'P')),
+                "external_tool" => Some(icon('\u{f12e}', 'X')),
+                "online_quiz" => Some(icon('\u{f059}', 'Q')),
+                "discussion_topic" => Some(icon('\u{f086}', 'D')),
+                _ => None,
+            })
+            .collect();
+        if self.group {
+            icons.push(icon('\u{f0c0}', 'G'));
+        }
+        icons
+    }
+
+    /// One line describing how to submit, e.g. "Submit as file upload (pdf, docx) · 3 attempts"
+    pub fn summary(&self) -> String {
+        let mut types: Vec<String> = self
+            .submission_types
+            .iter()
+            .map(|submission_type| match submission_type.as_str() {
+                "online_upload" if !self.allowed_extensions.is_empty() => {
+                    format!("file upload ({})", self.allowed_extensions.join(", "))
+                }
+                "online_upload" => "file upload".to_string(),
+                "online_text_entry" => "text entry".to_string(),
+                "online_url" => "website url".to_string(),
+                "media_recording" => "media recording".to_string(),
+                "on_paper" => "on paper".to_string(),
+                "external_tool" => "external tool".to_string(),
+                "online_quiz" => "quiz".to_string(),
+                "discussion_topic" => "discussion reply".to_string(),
+                "none" | "not_graded" => "nothing to submit".to_string(),
+                other => other.replace('_', " "),
+            })
+            .collect();
+        if types.is_empty() {
+            types.push("nothing to submit".to_string());
+        }
+        let mut summary = match types.len() {
+            1 => format!("Submit as {}", types[0]),
+            _ => {
+                let last = types.pop().unwrap();
+                format!("Submit as {} or {last}", types.join(", "))
+            }
+        };
+        match self.allowed_attempts {
+            Some(1) => summary.push_str(" · 1 attempt"),
+            Some(allowed) => summary.push_str(&format!(" · {allowed} attempts")),
+            None => summary.push_str(" · unlimited attempts"),
+        }
+        if self.group {
+            summary.push_str(" · group submission");
+        }
+        summary
+    }
+}
+
+impl Calendar {
+    fn from_events(events: impl IntoIterator<Item = CalendarEvent>) -> Self {
+        let mut dates: BTreeMap<Date, Vec<CalendarEvent>> = BTreeMap::new();
+        for event in events {
+            dates.entry(event.due_at.date()).or_default().push(event);
+        }
+        let dates = dates
+            .into_values()
+            .map(|mut events| {
+                events.sort_by_key(|event| event.due_at);
+                CalendarDate {
+                    events,
+                    table_state: TableState::default().with_selected(0),
+                }
+            })
+            .collect();
+        Calendar { dates }
+    }
+
+    /// Returns the calendar with read-only events from external calendars mixed in
+    pub fn with_overlay(self, overlay: &[CalendarEvent]) -> Self {
+        let events = self
+            .dates
+            .into_iter()
+            .flat_map(|date| date.events)
+            .filter(|event| !event.is_external())
+            .chain(overlay.iter().cloned());
+        Self::from_events(events)
+    }
+
+    pub fn insert(self, event: CalendarEvent) -> Self {
+        let events = self.dates.into_iter().flat_map(|date| date.events);
+        Self::from_events(events.chain([event]))
+    }
+
+    /// Marks items completed locally and moves items with a soft deadline to it, or back to
+    /// their official due date once it is cleared
+    pub fn apply_annotations(&mut self, annotations: &Annotations) {
+        let mut moved = false;
+        for event in self.dates.iter_mut().flat_map(|date| &mut date.events) {
+            if annotations.completed.contains(&event.html_url) {
+                event.completed = true;
+            }
+            let soft_deadline = annotations.soft_deadlines.get(&event.html_url);
+            let official = event.official_due_at.unwrap_or(event.due_at);
+            let due_at = soft_deadline.map_or(official, |soft_deadline| {
+                official - Duration::minutes(soft_deadline.minutes_early)
+            });
+            event.official_due_at = soft_deadline.map(|_| official);
+            if due_at != event.due_at {
+                event.due_at = due_at;
+                moved = true;
+            }
+        }
+        // Items may have moved to another date
+        if moved {
+            let events = std::mem::take(&mut self.dates)
+                .into_iter()
+                .flat_map(|date| date.events);
+            *self = Self::from_events(events);
+        }
+    }
+
+    /// Shows course nicknames, or else course codes, instead of the course names from the planner
+    /// and the placeholders of items fetched on their own that only know their course id
+    pub fn apply_course_names(
+        &mut self,
+        courses: &HashMap<u64, String>,
+        nicknames: &HashMap<u64, String>,
+    ) {
+        for event in self.dates.iter_mut().flat_map(|date| &mut date.events) {
+            let Some(course_id) = event.course_id else {
+                continue;
+            };
+            if let Some(nickname) = nicknames.get(&course_id) {
+                event.course_name = nickname.clone();
+            } else if let Some(course_code) = courses.get(&course_id) {
+                event.course_name = course_code.clone();
+            }
+        }
+    }
+
+    pub fn event_mut(&mut self, html_url: &str) -> Option<&mut CalendarEvent> {
+        self.dates
+            .iter_mut()
+            .flat_map(|date| &mut date.events)
+            .find(|event| event.html_url == html_url)
+    }
+
+    /// Sets attempt counts fetched earlier, keyed by html_url
+    pub fn apply_attempts(&mut self, attempts: &HashMap<String, Attempts>) {
+        for event in self.dates.iter_mut().flat_map(|date| &mut date.events) {
+            if let Some(attempts) = attempts.get(&event.html_url) {
+                event.attempts = Some(*attempts);
+            }
+        }
+    }
+}
+
+impl CalendarEvent {
+    pub fn is_external(&self) -> bool {
+        self.plannable_type == EXTERNAL_PLANNABLE_TYPE
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.submitted || self.completed
+    }
+
+    /// Whether the item was due before `now` and still isn't done
+    pub fn is_overdue(&self, now: PrimitiveDateTime) -> bool {
+        !self.is_external() && !self.is_done() && self.due_at < now
+    }
+
+    /// Explains why the item can't be worked on right now, if it is locked
+    pub fn lock_reason(&self, now: PrimitiveDateTime) -> Option<String> {
+        let format =
+            format_description::parse("[month repr:short] [day padding:none] [hour]:[minute]")
+                .unwrap();
+        match (self.unlock_at, self.lock_at) {
+            (Some(unlock_at), _) if now < unlock_at => Some(format!(
+                "locked until {}",
+                unlock_at.format(&format).unwrap()
+            )),
+            (_, Some(lock_at)) if now >= lock_at => {
+                Some(format!("locked since {}", lock_at.format(&format).unwrap()))
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct CanvasPlannerNote {
     context_name: String,
     html_url: String,
+    plannable_type: String,
+    course_id: Option<u64>,
+    #[serde(default)]
+    plannable_id: u64,
     submissions: SubmissionStatus,
     plannable: CanvasPlannable,
+    planner_override: Option<PlannerOverride>,
     #[serde(deserialize_with = "time::serde::iso8601::deserialize")]
     plannable_date: OffsetDateTime,
 }
@@ -45,12 +304,32 @@ struct CanvasPlannerNote {
 #[serde(untagged)]
 enum SubmissionStatus {
     Bool(bool),
-    Object { submitted: bool },
+    Object {
+        submitted: bool,
+        #[serde(default)]
+        graded: bool,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct PlannerOverride {
+    id: u64,
+    marked_complete: bool,
 }
 
 #[derive(Debug, Deserialize)]
 struct CanvasPlannable {
     title: String,
+    #[serde(
+        default,
+        deserialize_with = "time::serde::iso8601::option::deserialize"
+    )]
+    unlock_at: Option<OffsetDateTime>,
+    #[serde(
+        default,
+        deserialize_with = "time::serde::iso8601::option::deserialize"
+    )]
+    lock_at: Option<OffsetDateTime>,
 }
 
 struct CalendarVisitor {}
@@ -65,45 +344,45 @@ impl<'de> Visitor<'de> for CalendarVisitor {
     where
         A: serde::de::SeqAccess<'de>,
     {
-        let mut events: BTreeMap<Date, Vec<CalendarEvent>> = BTreeMap::new();
+        let mut events = vec![];
 
         while let Some(item) = seq.next_element::<CanvasPlannerNote>()? {
-            let local_due_at = item.plannable_date;
-            let submission_status = match item.submissions {
-                SubmissionStatus::Bool(submitted) => submitted,
-                SubmissionStatus::Object { submitted } => submitted,
+            let (submission_status, graded) = match item.submissions {
+                SubmissionStatus::Bool(submitted) => (submitted, false),
+                SubmissionStatus::Object { submitted, graded } => (submitted, graded),
             };
-            events
-                .entry(local_due_at.date())
-                .or_default()
-                .push(CalendarEvent {
-                    course_name: item
-                        .context_name
-                        .split_whitespace()
-                        .take(2)
-                        .collect::<Vec<&str>>()
-                        .join("-"),
-                    due_at: {
-                        /* Remove timezone info */
-                        let local_offset = UtcOffset::current_local_offset().unwrap();
-                        let local_odt = item.plannable_date.to_offset(local_offset);
-                        PrimitiveDateTime::new(local_odt.date(), local_odt.time())
-                    },
-                    title: item.plannable.title,
-                    html_url: item.html_url,
-                    submitted: submission_status,
-                });
-        }
-
-        let dates: Vec<_> = events
-            .into_values()
-            .map(|events| CalendarDate {
-                events,
-                table_state: TableState::default().with_selected(0),
-            })
-            .collect();
+            events.push(CalendarEvent {
+                // Stands in until the course codes arrive from the courses request
+                course_name: item
+                    .context_name
+                    .split_whitespace()
+                    .take(2)
+                    .collect::<Vec<&str>>()
+                    .join("-"),
+                /* Remove timezone info */
+                due_at: to_local(item.plannable_date),
+                official_due_at: None,
+                title: item.plannable.title,
+                unlock_at: item.plannable.unlock_at.map(to_local),
+                lock_at: item.plannable.lock_at.map(to_local),
+                attempts: None,
+                html_url: item.html_url,
+                plannable_type: item.plannable_type,
+                course_id: item.course_id,
+                plannable_id: item.plannable_id,
+                submitted: submission_status,
+                graded,
+                completed: item
+                    .planner_override
+                    .as_ref()
+                    .is_some_and(|planner_override| planner_override.marked_complete),
+                override_id: item
+                    .planner_override
+                    .map(|planner_override| planner_override.id),
+            });
+        }
 
-        Ok(Calendar { dates })
+        Ok(Calendar::from_events(events))
     }
 }
 
@@ -116,32 +395,488 @@ impl<'de> Deserialize<'de> for Calendar {
     }
 }
 
-pub async fn fetch(action_tx: &mut UnboundedSender<Action>) -> Result<()> {
-    let access_token = env::var("CANVAS_ACCESS_TOKEN")?;
+/// Offset of the local time zone, UTC when it can't be determined
+pub fn local_offset() -> UtcOffset {
+    UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC)
+}
+
+/// The current time in the local time zone, or in UTC when it can't be determined
+pub fn now_local() -> OffsetDateTime {
+    OffsetDateTime::now_utc().to_offset(local_offset())
+}
 
-    let mut url = env::var("CANVAS_URL")
-        .unwrap()
-        .parse::<Url>()
-        .unwrap()
-        .join(ENDPOINT)?;
+/// Converts a timestamp to local time without offset, which is how events store dates
+pub fn to_local(date_time: OffsetDateTime) -> PrimitiveDateTime {
+    let local_odt = date_time.to_offset(local_offset());
+    PrimitiveDateTime::new(local_odt.date(), local_odt.time())
+}
+
+/// Reads the planner items stored by the last successful fetch
+pub fn load_cache(profile: &Profile) -> Result<Calendar> {
+    let cached_body_bytes = std::fs::read(profile.cache_file());
+    metrics::record_cache(cached_body_bytes.is_ok());
+    let cached_body_bytes = cached_body_bytes?;
+    Ok(serde_json::from_slice(&cached_body_bytes)?)
+}
+
+/// When the last successful fetch wrote the cache, None without a cache
+pub fn cache_updated_at(profile: &Profile) -> Option<OffsetDateTime> {
+    let modified = std::fs::metadata(profile.cache_file())
+        .ok()?
+        .modified()
+        .ok()?;
+    Some(OffsetDateTime::from(modified))
+}
+
+/// Sends a PUT request without a body to the Canvas API, as used by endpoints that set a flag
+pub async fn put(profile: &Profile, endpoint: &str) -> Result<()> {
+    Client::new(profile)?.put(endpoint).await
+}
+
+/// Marks an item done or not done in the Canvas planner, creating its planner override the first
+/// time. Returns the id of the override
+pub async fn set_planner_completed(
+    profile: &Profile,
+    event: &CalendarEvent,
+    completed: bool,
+) -> Result<u64> {
+    if compat::lacks_planner(profile) {
+        return Err(eyre!(
+            "this Canvas instance has no planner to mark items done in"
+        ));
+    }
+    let planner_override: PlannerOverride = match event.override_id {
+        Some(id) => {
+            let endpoint = format!("/api/v1/planner/overrides/{id}");
+            put_json(
+                profile,
+                &endpoint,
+                &serde_json::json!({ "marked_complete": completed }),
+            )
+            .await?
+        }
+        None => {
+            let body = serde_json::json!({
+                "plannable_type": event.plannable_type,
+                "plannable_id": event.plannable_id,
+                "marked_complete": completed,
+            });
+            post_json(profile, "/api/v1/planner/overrides", &body).await?
+        }
+    };
+    Ok(planner_override.id)
+}
+
+/// Sends a PUT request with a JSON body to the Canvas API and deserializes the JSON response
+pub async fn put_json<T: DeserializeOwned>(
+    profile: &Profile,
+    endpoint: &str,
+    body: &serde_json::Value,
+) -> Result<T> {
+    Client::new(profile)?.put_json(endpoint, body).await
+}
+
+/// Sends a POST request with a JSON body to the Canvas API and deserializes the JSON response
+pub async fn post_json<T: DeserializeOwned>(
+    profile: &Profile,
+    endpoint: &str,
+    body: &serde_json::Value,
+) -> Result<T> {
+    Client::new(profile)?.post_json(endpoint, body).await
+}
+
+/// Runs a query against the Canvas GraphQL API, for settings the REST API doesn't expose
+pub async fn graphql<T: DeserializeOwned>(profile: &Profile, query: &str) -> Result<T> {
+    Client::new(profile)?.graphql(query).await
+}
+
+/// Fetches an API endpoint and deserializes its JSON body
+pub async fn get_json<T: DeserializeOwned>(
+    profile: &Profile,
+    endpoint: &str,
+    query: &[(&str, &str)],
+) -> Result<T> {
+    Client::new(profile)?.get_json(endpoint, query).await
+}
+
+#[derive(Debug, Deserialize)]
+struct CanvasDescription {
+    #[serde(alias = "message")]
+    description: Option<String>,
+    /// -1 or missing when attempts are unlimited
+    allowed_attempts: Option<i64>,
+    submission: Option<CanvasAttempt>,
+    submission_types: Option<Vec<String>>,
+    #[serde(default)]
+    allowed_extensions: Vec<String>,
+    group_category_id: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CanvasAttempt {
+    attempt: Option<u32>,
+    #[serde(default)]
+    workflow_state: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CanvasQuizSubmissions {
+    quiz_submissions: Vec<CanvasAttempt>,
+}
+
+/// Details of an item that the planner doesn't include
+pub struct ItemDetails {
+    /// HTML description of the assignment, quiz or discussion
+    pub description: String,
+    pub attempts: Option<Attempts>,
+    pub requirements: Requirements,
+}
+
+/// Fetches the description, attempt counts and submission requirements of an assignment, quiz
+/// or discussion
+pub async fn fetch_details(profile: &Profile, event: &CalendarEvent) -> Result<ItemDetails> {
+    let course_id = event
+        .course_id
+        .ok_or(eyre!("Item does not belong to a course"))?;
+    let collection = match event.plannable_type.as_str() {
+        "assignment" => "assignments",
+        "quiz" => "quizzes",
+        "discussion_topic" | "announcement" => "discussion_topics",
+        other => return Err(eyre!("{other} items have no description")),
+    };
+    let endpoint = format!(
+        "/api/v1/courses/{course_id}/{collection}/{}",
+        event.plannable_id
+    );
+    let details: CanvasDescription =
+        get_json(profile, &endpoint, &[("include[]", "submission")]).await?;
+
+    let allowed = details
+        .allowed_attempts
+        .filter(|&allowed| allowed > 0)
+        .map(|allowed| allowed as u32);
+    let attempts = match (allowed, collection) {
+        (None, _) => None,
+        (Some(allowed), "quizzes") => {
+            // Quizzes keep attempts in quiz submissions, where an untaken one is still in progress
+            let submissions: CanvasQuizSubmissions =
+                get_json(profile, &format!("{endpoint}/submissions"), &[]).await?;
+            let used = submissions
+                .quiz_submissions
+                .iter()
+                .map(|submission| {
+                    let attempt = submission.attempt.unwrap_or_default();
+                    match submission.workflow_state.as_str() {
+                        "untaken" => attempt.saturating_sub(1),
+                        _ => attempt,
+                    }
+                })
+                .max()
+                .unwrap_or_default();
+            Some(Attempts { allowed, used })
+        }
+        (Some(allowed), _) => Some(Attempts {
+            allowed,
+            used: details
+                .submission
+                .and_then(|submission| submission.attempt)
+                .unwrap_or_default(),
+        }),
+    };
+
+    // Quizzes and discussions don't list submission types of their own
+    let submission_types = match (details.submission_types, collection) {
+        (Some(submission_types), _) => submission_types,
+        (None, "quizzes") => vec!["online_quiz".to_string()],
+        (None, _) => vec!["discussion_topic".to_string()],
+    };
+    let requirements = Requirements {
+        submission_types,
+        allowed_extensions: details.allowed_extensions,
+        allowed_attempts: allowed,
+        group: details.group_category_id.is_some(),
+    };
+
+    Ok(ItemDetails {
+        description: details.description.unwrap_or_default(),
+        attempts,
+        requirements,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct CanvasAssignment {
+    id: u64,
+    name: String,
+    html_url: String,
+    #[serde(
+        default,
+        deserialize_with = "time::serde::iso8601::option::deserialize"
+    )]
+    due_at: Option<OffsetDateTime>,
+    #[serde(
+        default,
+        deserialize_with = "time::serde::iso8601::option::deserialize"
+    )]
+    unlock_at: Option<OffsetDateTime>,
+    #[serde(
+        default,
+        deserialize_with = "time::serde::iso8601::option::deserialize"
+    )]
+    lock_at: Option<OffsetDateTime>,
+    submission: Option<CanvasSubmission>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CanvasSubmission {
+    submitted_at: Option<String>,
+    #[serde(default)]
+    workflow_state: String,
+}
+
+/// Course name of items whose course is only known by id
+fn placeholder_course_name(course_id: u64) -> String {
+    format!("course-{course_id}")
+}
+
+impl CanvasAssignment {
+    fn into_event(self, course_id: u64) -> Result<CalendarEvent> {
+        let due_at = self
+            .due_at
+            .ok_or(eyre!("{} has no due date", self.name))?
+            .to_offset(UtcOffset::current_local_offset()?);
+        Ok(CalendarEvent {
+            course_name: placeholder_course_name(course_id),
+            due_at: PrimitiveDateTime::new(due_at.date(), due_at.time()),
+            official_due_at: None,
+            title: self.name,
+            html_url: self
+                .html_url
+                .parse::<Url>()
+                .map(|url| url.path().to_string())
+                .unwrap_or(self.html_url),
+            plannable_type: "assignment".to_string(),
+            course_id: Some(course_id),
+            plannable_id: self.id,
+            submitted: self
+                .submission
+                .as_ref()
+                .is_some_and(|submission| submission.submitted_at.is_some()),
+            graded: self
+                .submission
+                .is_some_and(|submission| submission.workflow_state == "graded"),
+            completed: false,
+            override_id: None,
+            unlock_at: self.unlock_at.map(to_local),
+            lock_at: self.lock_at.map(to_local),
+            attempts: None,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CanvasUser {
+    id: u64,
+}
+
+/// Context codes of the user and every course that isn't excluded, for asking the planner about
+/// those alone
+async fn included_contexts(profile: &Profile) -> Result<Vec<String>> {
+    let user: CanvasUser = get_json(profile, "/api/v1/users/self", &[]).await?;
+    let mut codes = vec![format!("user_{}", user.id)];
+    codes.extend(
+        fetch_courses(profile)
+            .await?
+            .into_iter()
+            .filter(|course| {
+                !profile
+                    .exclude_courses
+                    .excludes(course.id, &[&course.name, &course.course_code])
+            })
+            .map(|course| format!("course_{}", course.id)),
+    );
+    Ok(codes)
+}
+
+/// Fetches a single assignment that isn't part of the loaded planner items. Without the course
+/// it belongs to, every enrolled course is asked for it in turn
+pub async fn fetch_assignment(
+    profile: &Profile,
+    course_id: Option<u64>,
+    assignment_id: u64,
+) -> Result<CalendarEvent> {
+    let client = Client::new(profile)?;
+    if let Some(course_id) = course_id {
+        let assignment: CanvasAssignment = client.assignment(course_id, assignment_id).await?;
+        return assignment.into_event(course_id);
+    }
+    for course in fetch_courses(profile).await? {
+        if let Ok(assignment) = client
+            .assignment::<CanvasAssignment>(course.id, assignment_id)
+            .await
+        {
+            return assignment.into_event(course.id);
+        }
+    }
+    Err(eyre!(
+        "None of your courses has an assignment {assignment_id}"
+    ))
+}
+
+/// Characters of a grading comment shown in notifications and the recent grades panel
+const COMMENT_PREVIEW_LENGTH: usize = 120;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubmissionComment {
+    pub author_id: Option<u64>,
+    pub comment: String,
+}
+
+/// First lines of the latest comment left by someone other than the student, cut short
+pub fn comment_preview(comments: &[SubmissionComment], student_id: Option<u64>) -> Option<String> {
+    let comment = comments
+        .iter()
+        .rev()
+        .find(|comment| student_id.is_none() || comment.author_id != student_id)?;
+    let preview = comment
+        .comment
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .take(2)
+        .collect::<Vec<_>>()
+        .join(" / ");
+    match preview.chars().count() > COMMENT_PREVIEW_LENGTH {
+        true => Some(format!(
+            "{}…",
+            preview
+                .chars()
+                .take(COMMENT_PREVIEW_LENGTH)
+                .collect::<String>()
+        )),
+        false => (!preview.is_empty()).then_some(preview),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CanvasCommentedSubmission {
+    user_id: Option<u64>,
+    #[serde(default)]
+    submission_comments: Vec<SubmissionComment>,
+}
+
+/// Preview of the grader's comment on the user's submission of a planner item, None for items
+/// other than assignments or without comments
+pub async fn fetch_grade_comment(
+    profile: &Profile,
+    event: &CalendarEvent,
+) -> Result<Option<String>> {
+    let Some(course_id) = event
+        .course_id
+        .filter(|_| event.plannable_type == "assignment")
+    else {
+        return Ok(None);
+    };
+    let submission: CanvasCommentedSubmission = get_json(
+        profile,
+        &format!(
+            "/api/v1/courses/{course_id}/assignments/{}/submissions/self",
+            event.plannable_id
+        ),
+        &[("include[]", "submission_comments")],
+    )
+    .await?;
+    Ok(comment_preview(
+        &submission.submission_comments,
+        submission.user_id,
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct CanvasGradedItem {
+    /// Only set for graded quizzes and discussions
+    assignment_id: Option<u64>,
+}
+
+/// Asks Canvas whether the user has submitted a single planner item, without refetching the
+/// planner
+pub async fn fetch_submitted(profile: &Profile, event: &CalendarEvent) -> Result<bool> {
+    let course_id = event
+        .course_id
+        .ok_or(eyre!("Item does not belong to a course"))?;
+    // Submissions of quizzes and discussions belong to the assignment grading them
+    let assignment_id = match event.plannable_type.as_str() {
+        "assignment" => event.plannable_id,
+        plannable_type @ ("quiz" | "discussion_topic") => {
+            let collection = match plannable_type {
+                "quiz" => "quizzes",
+                _ => "discussion_topics",
+            };
+            let item: CanvasGradedItem = get_json(
+                profile,
+                &format!(
+                    "/api/v1/courses/{course_id}/{collection}/{}",
+                    event.plannable_id
+                ),
+                &[],
+            )
+            .await?;
+            item.assignment_id
+                .ok_or(eyre!("Ungraded items have nothing to submit"))?
+        }
+        other => {
+            return Err(eyre!("{other} items have nothing to submit"));
+        }
+    };
+    let submission: CanvasSubmission = get_json(
+        profile,
+        &format!("/api/v1/courses/{course_id}/assignments/{assignment_id}/submissions/self"),
+        &[],
+    )
+    .await?;
+    Ok(submission.submitted_at.is_some())
+}
+
+#[derive(Debug, Deserialize)]
+struct CanvasMissingSubmission {
+    course_id: u64,
+    #[serde(flatten)]
+    assignment: CanvasAssignment,
+}
+
+/// Fetches past due assignments that haven't been submitted, which the planner leaves out unless
+/// `days_back` reaches them
+pub async fn fetch_missing(profile: &Profile) -> Result<Vec<CalendarEvent>> {
+    let missing: Vec<CanvasMissingSubmission> = get_json(
+        profile,
+        "/api/v1/users/self/missing_submissions",
+        &[("filter[]", "submittable"), ("per_page", "100")],
+    )
+    .await?;
+    Ok(missing
+        .into_iter()
+        .filter_map(|missing| missing.assignment.into_event(missing.course_id).ok())
+        .collect())
+}
 
-    let current_date = OffsetDateTime::now_local()
-        .expect("Could not get current date")
-        .date();
-    url.query_pairs_mut()
-        .append_pair("access_token", &access_token)
-        .append_pair(
-            "start_date",
-            &current_date
-                .format(&format_description::parse("[year]-[month]-[day]").unwrap())
-                .expect("Could not format date")
-                .to_string(),
-        );
+pub async fn fetch(profile: &Profile) -> Result<Calendar> {
+    let today = OffsetDateTime::now_local()?.date();
+    let contexts = match profile.exclude_courses.is_empty() {
+        true => vec![],
+        false => included_contexts(profile).await?,
+    };
 
-    let response = reqwest::get(url).await?;
-    let body_bytes = response.bytes().await?;
+    // Pages are merged before parsing so the cache holds every item in the same form
+    let items = compat::planner_items(
+        profile,
+        &Client::new(profile)?,
+        profile.horizon.start(today),
+        profile.horizon.end(today),
+        &contexts,
+    )
+    .await?;
+    let body_bytes = serde_json::to_vec(&items)?;
     let calendar: Calendar = serde_json::from_slice(&body_bytes)?;
-    action_tx.send(Action::FetchComplete(calendar))?;
-    tokio::fs::write(CACHE_FILE, &body_bytes).await?;
-    Ok(())
+    tokio::fs::write(profile.cache_file(), &body_bytes).await?;
+    Ok(calendar)
 }
@@ -0,0 +1,545 @@
+use std::collections::HashMap;
+
+use color_eyre::eyre::{Result, eyre};
+use serde::Deserialize;
+
+use crate::{
+    config::{Config, Profile},
+    fetch::get_json,
+    lookup::find_course,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssignmentGroup {
+    pub name: String,
+    /// Percentage of the course grade, only used when the course weights its groups
+    #[serde(default)]
+    pub group_weight: f64,
+    #[serde(default)]
+    pub assignments: Vec<GradedAssignment>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GradedAssignment {
+    pub id: u64,
+    /// Set for quizzes, whose planner items carry the quiz id instead of the assignment id
+    pub quiz_id: Option<u64>,
+    pub points_possible: Option<f64>,
+    pub submission: Option<GradedSubmission>,
+    #[serde(default)]
+    pub omit_from_final_grade: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GradedSubmission {
+    pub score: Option<f64>,
+    #[serde(default)]
+    pub excused: bool,
+}
+
+impl GradedAssignment {
+    pub fn score(&self) -> Option<f64> {
+        self.submission.as_ref()?.score
+    }
+
+    /// Points possible of an item that will count towards the final grade but isn't graded yet
+    fn remaining_points(&self) -> Option<f64> {
+        let excused = self
+            .submission
+            .as_ref()
+            .is_some_and(|submission| submission.excused);
+        if excused || self.omit_from_final_grade || self.score().is_some() {
+            return None;
+        }
+        self.points_possible.filter(|points| *points > 0.0)
+    }
+}
+
+impl AssignmentGroup {
+    /// Points earned and possible over the group's graded assignments
+    pub fn points(&self) -> (f64, f64) {
+        self.assignments
+            .iter()
+            .filter_map(|assignment| Some((assignment.score()?, assignment.points_possible?)))
+            .fold((0.0, 0.0), |(earned, possible), (score, points)| {
+                (earned + score, possible + points)
+            })
+    }
+
+    pub fn percentage(&self) -> Option<f64> {
+        let (earned, possible) = self.points();
+        (possible > 0.0).then(|| earned / possible * 100.0)
+    }
+
+    /// Points possible over the graded and the remaining items
+    fn possible(&self) -> f64 {
+        let (_, graded) = self.points();
+        let (remaining, _) = self.remaining();
+        graded + remaining
+    }
+
+    /// Points possible and count of the items still to be graded
+    fn remaining(&self) -> (f64, usize) {
+        self.assignments
+            .iter()
+            .filter_map(GradedAssignment::remaining_points)
+            .fold((0.0, 0), |(possible, items), points| {
+                (possible + points, items + 1)
+            })
+    }
+}
+
+/// What it takes on the ungraded items to reach a target grade
+#[derive(Debug, Clone, Copy)]
+pub enum Requirement {
+    /// The target holds even with nothing on the remaining items
+    Secured {
+        worst: f64,
+    },
+    /// Score needed on every remaining item, as a percentage and in total points
+    Needed {
+        percent: f64,
+        points: f64,
+        possible: f64,
+        items: usize,
+    },
+    /// Full marks on the remaining items fall short
+    OutOfReach {
+        best: f64,
+    },
+    NothingLeft,
+}
+
+impl Requirement {
+    pub fn describe(&self, target: f64) -> String {
+        match *self {
+            Requirement::Secured { worst } => {
+                format!("{target}% is secured: even 0 on the rest leaves {worst:.1}%")
+            }
+            Requirement::Needed {
+                percent,
+                points,
+                possible,
+                items,
+            } => format!(
+                "{target}% needs {percent:.1}% on the {items} remaining item(s), {points:.1} of {possible} points"
+            ),
+            Requirement::OutOfReach { best } => {
+                format!("{target}% is out of reach: full marks on the rest give {best:.1}%")
+            }
+            Requirement::NothingLeft => format!("Nothing left to grade towards {target}%"),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CourseSettings {
+    #[serde(default)]
+    apply_assignment_group_weights: bool,
+}
+
+/// A course's grading setup, enough to recompute its grade locally
+#[derive(Debug, Clone)]
+pub struct Gradebook {
+    pub weighted: bool,
+    pub groups: Vec<AssignmentGroup>,
+}
+
+impl Gradebook {
+    pub async fn fetch(profile: &Profile, course_id: u64) -> Result<Self> {
+        let course_endpoint = format!("/api/v1/courses/{course_id}");
+        let settings: CourseSettings = get_json(profile, &course_endpoint, &[]).await?;
+        let groups = get_json(
+            profile,
+            &format!("{course_endpoint}/assignment_groups"),
+            &[
+                ("include[]", "assignments"),
+                ("include[]", "submission"),
+                ("per_page", "100"),
+            ],
+        )
+        .await?;
+        Ok(Self {
+            weighted: settings.apply_assignment_group_weights,
+            groups,
+        })
+    }
+
+    /// Current grade in percent, with group weights optionally overridden by name.
+    /// Groups without graded work are left out and the remaining weights rescaled, like Canvas does
+    pub fn grade(&self, weight_overrides: &HashMap<String, f64>) -> Option<f64> {
+        if !self.weighted {
+            let (earned, possible) = self
+                .groups
+                .iter()
+                .map(AssignmentGroup::points)
+                .fold((0.0, 0.0), |(e, p), (earned, possible)| {
+                    (e + earned, p + possible)
+                });
+            return (possible > 0.0).then(|| earned / possible * 100.0);
+        }
+
+        let (weighted_sum, total_weight) = self
+            .groups
+            .iter()
+            .filter_map(|group| {
+                let weight = self.weight(group, weight_overrides);
+                Some((group.percentage()? * weight, weight))
+            })
+            .fold((0.0, 0.0), |(s, w), (sum, weight)| (s + sum, w + weight));
+        (total_weight > 0.0).then(|| weighted_sum / total_weight)
+    }
+
+    /// Final grade as a fraction, written as `base + slope * p` where `p` is the fraction scored
+    /// on every remaining item. Unlike the current grade, ungraded groups count with their full
+    /// weight
+    fn projection(&self) -> (f64, f64) {
+        let totals = self.groups.iter().map(|group| {
+            let (earned, graded) = group.points();
+            let (remaining, _) = group.remaining();
+            (group, earned, graded + remaining, remaining)
+        });
+        if !self.weighted {
+            let (earned, possible, remaining) = totals.fold(
+                (0.0, 0.0, 0.0),
+                |(e, p, r), (_, earned, possible, remaining)| {
+                    (e + earned, p + possible, r + remaining)
+                },
+            );
+            return match possible > 0.0 {
+                true => (earned / possible, remaining / possible),
+                false => (0.0, 0.0),
+            };
+        }
+
+        let counted: Vec<_> = totals
+            .filter(|(group, _, possible, _)| group.group_weight > 0.0 && *possible > 0.0)
+            .collect();
+        let total_weight: f64 = counted.iter().map(|(group, ..)| group.group_weight).sum();
+        counted.iter().fold(
+            (0.0, 0.0),
+            |(base, slope), (group, earned, possible, remaining)| {
+                let share = group.group_weight / total_weight / possible;
+                (base + share * earned, slope + share * remaining)
+            },
+        )
+    }
+
+    /// Smallest score needed on every remaining item to finish with `target` percent
+    pub fn required(&self, target: f64) -> Requirement {
+        let (base, slope) = self.projection();
+        if slope <= 0.0 {
+            return Requirement::NothingLeft;
+        }
+        let needed = (target / 100.0 - base) / slope;
+        if needed <= 0.0 {
+            return Requirement::Secured {
+                worst: base * 100.0,
+            };
+        }
+        if needed > 1.0 {
+            return Requirement::OutOfReach {
+                best: (base + slope) * 100.0,
+            };
+        }
+        // Items in groups without weight don't move the grade, so nothing is needed on them
+        let (possible, items) = self
+            .groups
+            .iter()
+            .filter(|group| !self.weighted || group.group_weight > 0.0)
+            .map(AssignmentGroup::remaining)
+            .fold((0.0, 0), |(p, i), (possible, items)| {
+                (p + possible, i + items)
+            });
+        Requirement::Needed {
+            percent: needed * 100.0,
+            points: needed * possible,
+            possible,
+            items,
+        }
+    }
+
+    /// Percentage points of the final grade a planner item is worth: its share of its group's
+    /// points times the group's weight, or its share of all points when groups aren't weighted
+    pub fn impact(&self, plannable_type: &str, plannable_id: u64) -> Option<f64> {
+        let (group, assignment) = self.groups.iter().find_map(|group| {
+            let assignment = group
+                .assignments
+                .iter()
+                .find(|assignment| match plannable_type {
+                    "assignment" => assignment.id == plannable_id,
+                    "quiz" => assignment.quiz_id == Some(plannable_id),
+                    _ => false,
+                })?;
+            Some((group, assignment))
+        })?;
+        if assignment.omit_from_final_grade {
+            return Some(0.0);
+        }
+        let points = assignment.points_possible.filter(|points| *points > 0.0)?;
+        if !self.weighted {
+            let total: f64 = self.groups.iter().map(AssignmentGroup::possible).sum();
+            return (total > 0.0).then(|| points / total * 100.0);
+        }
+        let total_weight: f64 = self
+            .groups
+            .iter()
+            .filter(|group| group.group_weight > 0.0 && group.possible() > 0.0)
+            .map(|group| group.group_weight)
+            .sum();
+        let group_possible = group.possible();
+        (total_weight > 0.0 && group_possible > 0.0)
+            .then(|| group.group_weight / total_weight * points / group_possible * 100.0)
+    }
+
+    pub fn weight(&self, group: &AssignmentGroup, weight_overrides: &HashMap<String, f64>) -> f64 {
+        weight_overrides
+            .get(&group.name.to_lowercase())
+            .copied()
+            .unwrap_or(group.group_weight)
+    }
+}
+
+/// Parses `GROUP=PERCENT` overrides, keyed by lowercase group name
+pub fn parse_weight_overrides(overrides: &[String]) -> Result<HashMap<String, f64>> {
+    overrides
+        .iter()
+        .map(|weight_override| {
+            let (name, weight) = weight_override
+                .rsplit_once('=')
+                .ok_or(eyre!("Expected GROUP=PERCENT, got {weight_override:?}"))?;
+            let weight = weight.trim().trim_end_matches('%').parse::<f64>()?;
+            Ok((name.trim().to_lowercase(), weight))
+        })
+        .collect()
+}
+
+/// Prints a course's grade next to what it would be with different group weights
+pub async fn what_if(course: &str, weights: &[String]) -> Result<()> {
+    let profile = Config::load()?.default_profile()?;
+    let course = find_course(&profile, course).await?;
+    let gradebook = Gradebook::fetch(&profile, course.id).await?;
+    let overrides = parse_weight_overrides(weights)?;
+
+    if let Some(unknown) = overrides.keys().find(|name| {
+        !gradebook
+            .groups
+            .iter()
+            .any(|group| group.name.to_lowercase() == **name)
+    }) {
+        return Err(eyre!(
+            "{} has no assignment group named {unknown:?}",
+            course.name
+        ));
+    }
+    if !gradebook.weighted && !overrides.is_empty() {
+        println!(
+            "Note: {} doesn't weight assignment groups, simulating as if it did\n",
+            course.name
+        );
+    }
+    let simulated = Gradebook {
+        weighted: gradebook.weighted || !overrides.is_empty(),
+        ..gradebook.clone()
+    };
+
+    println!("{}", course.name);
+    for group in &gradebook.groups {
+        let weight = simulated.weight(group, &overrides);
+        let marker = match overrides.contains_key(&group.name.to_lowercase()) {
+            true => format!(" (was {}%)", group.group_weight),
+            false => String::new(),
+        };
+        let score = group
+            .percentage()
+            .map_or("-".to_string(), |percentage| format!("{percentage:.1}%"));
+        println!("  {:<30} {weight:>5}%{marker:<12} {score:>7}", group.name);
+    }
+
+    let format_grade =
+        |grade: Option<f64>| grade.map_or("-".to_string(), |grade| format!("{grade:.2}%"));
+    println!(
+        "\nCurrent grade: {}",
+        format_grade(gradebook.grade(&HashMap::new()))
+    );
+    if !overrides.is_empty() {
+        println!(
+            "What-if grade: {} (local simulation, nothing is changed on Canvas)",
+            format_grade(simulated.grade(&overrides))
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assignment(score: Option<f64>, points: f64) -> GradedAssignment {
+        GradedAssignment {
+            id: 0,
+            quiz_id: None,
+            points_possible: Some(points),
+            submission: Some(GradedSubmission {
+                score,
+                excused: false,
+            }),
+            omit_from_final_grade: false,
+        }
+    }
+
+    fn group(name: &str, weight: f64, assignments: Vec<GradedAssignment>) -> AssignmentGroup {
+        AssignmentGroup {
+            name: name.to_string(),
+            group_weight: weight,
+            assignments,
+        }
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("a grade");
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn what_if_overrides_group_weights_by_name() {
+        let gradebook = Gradebook {
+            weighted: true,
+            groups: vec![
+                group("Homework", 40.0, vec![assignment(Some(90.0), 100.0)]),
+                group("Exams", 60.0, vec![assignment(Some(70.0), 100.0)]),
+            ],
+        };
+        assert_close(gradebook.grade(&HashMap::new()), 78.0);
+
+        let overrides =
+            parse_weight_overrides(&["Exams=40%".to_string(), "homework = 60".to_string()])
+                .unwrap();
+        assert_close(gradebook.grade(&overrides), 82.0);
+        assert!(parse_weight_overrides(&["Exams 40".to_string()]).is_err());
+    }
+
+    #[test]
+    fn what_if_leaves_out_groups_without_graded_points() {
+        let gradebook = Gradebook {
+            weighted: true,
+            groups: vec![
+                group("Homework", 40.0, vec![assignment(Some(45.0), 50.0)]),
+                group("Final", 50.0, vec![assignment(None, 100.0)]),
+                // Zero-point items earn nothing to take a percentage of
+                group("Participation", 10.0, vec![assignment(Some(0.0), 0.0)]),
+            ],
+        };
+        assert_close(gradebook.grade(&HashMap::new()), 90.0);
+        let overrides = HashMap::from([("final".to_string(), 0.0)]);
+        assert_close(gradebook.grade(&overrides), 90.0);
+
+        let ungraded = Gradebook {
+            weighted: true,
+            groups: vec![group("Final", 100.0, vec![assignment(None, 100.0)])],
+        };
+        assert_eq!(ungraded.grade(&HashMap::new()), None);
+    }
+
+    #[test]
+    fn unweighted_courses_total_points() {
+        let gradebook = Gradebook {
+            weighted: false,
+            groups: vec![
+                group("Homework", 0.0, vec![assignment(Some(10.0), 10.0)]),
+                group("Exams", 0.0, vec![assignment(Some(20.0), 40.0)]),
+            ],
+        };
+        assert_close(gradebook.grade(&HashMap::new()), 60.0);
+    }
+
+    fn assert_needed(requirement: Requirement, expected_percent: f64, expected_items: usize) {
+        let Requirement::Needed { percent, items, .. } = requirement else {
+            panic!("{requirement:?}");
+        };
+        assert!((percent - expected_percent).abs() < 1e-9, "{percent}");
+        assert_eq!(items, expected_items);
+    }
+
+    #[test]
+    fn solves_for_the_score_needed_on_the_rest() {
+        let mut excused = assignment(None, 100.0);
+        excused.submission.as_mut().unwrap().excused = true;
+        let mut omitted = assignment(None, 100.0);
+        omitted.omit_from_final_grade = true;
+        let gradebook = Gradebook {
+            weighted: false,
+            groups: vec![group(
+                "Assignments",
+                0.0,
+                vec![
+                    assignment(Some(40.0), 50.0),
+                    assignment(None, 50.0),
+                    // None of these are left to score points on
+                    assignment(None, 0.0),
+                    excused,
+                    omitted,
+                ],
+            )],
+        };
+
+        assert_needed(gradebook.required(80.0), 80.0, 1);
+        let Requirement::Needed {
+            points, possible, ..
+        } = gradebook.required(80.0)
+        else {
+            unreachable!();
+        };
+        assert!((points - 40.0).abs() < 1e-9 && possible == 50.0);
+        assert!(matches!(
+            gradebook.required(30.0),
+            Requirement::Secured { worst } if (worst - 40.0).abs() < 1e-9
+        ));
+    }
+
+    #[test]
+    fn reports_unreachable_targets() {
+        let gradebook = Gradebook {
+            weighted: false,
+            groups: vec![group(
+                "Assignments",
+                0.0,
+                vec![assignment(Some(40.0), 50.0), assignment(None, 50.0)],
+            )],
+        };
+        assert!(matches!(
+            gradebook.required(95.0),
+            Requirement::OutOfReach { best } if (best - 90.0).abs() < 1e-9
+        ));
+    }
+
+    #[test]
+    fn weighs_ungraded_groups_fully() {
+        let gradebook = Gradebook {
+            weighted: true,
+            groups: vec![
+                group("Homework", 50.0, vec![assignment(Some(90.0), 100.0)]),
+                group("Final", 50.0, vec![assignment(None, 100.0)]),
+                // Zero-weight groups don't move the final grade
+                group("Practice", 0.0, vec![assignment(None, 100.0)]),
+            ],
+        };
+        assert_needed(gradebook.required(90.0), 90.0, 1);
+        assert!(matches!(
+            gradebook.required(100.0),
+            Requirement::OutOfReach { best } if (best - 95.0).abs() < 1e-9
+        ));
+    }
+
+    #[test]
+    fn nothing_left_once_everything_is_graded() {
+        let gradebook = Gradebook {
+            weighted: true,
+            groups: vec![
+                group("Homework", 50.0, vec![assignment(Some(90.0), 100.0)]),
+                group("Participation", 50.0, vec![assignment(None, 0.0)]),
+            ],
+        };
+        assert!(matches!(gradebook.required(50.0), Requirement::NothingLeft));
+    }
+}
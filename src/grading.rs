@@ -0,0 +1,451 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout},
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    widgets::{Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use serde::Deserialize;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::{Action, Navigation, PromptKind},
+    component::{Component, Context},
+    config::Profile,
+    courses::CourseContext,
+    fetch::{get_json, put_json},
+    redact,
+    sections::Section,
+    theme,
+};
+
+#[derive(Deserialize)]
+struct CanvasGradingSubmission {
+    user_id: u64,
+    score: Option<f64>,
+    grade: Option<String>,
+    user: Option<CanvasStudent>,
+}
+
+#[derive(Deserialize)]
+struct CanvasStudent {
+    sortable_name: String,
+}
+
+/// Where a row stands with respect to Canvas
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowState {
+    Unchanged,
+    Edited,
+    Saving,
+    Saved,
+    Failed(String),
+}
+
+/// A student's submission with the grade and comment being entered for it
+#[derive(Debug, Clone)]
+pub struct GradeRow {
+    pub user_id: u64,
+    /// Last name first, which is how gradebooks sort
+    name: String,
+    /// Grade currently in Canvas, as the teacher entered it
+    grade: Option<String>,
+    score: Option<f64>,
+    /// Grade to post, as a score, percentage or letter like in the Canvas gradebook
+    pub new_grade: Option<String>,
+    pub comment: String,
+    pub state: RowState,
+}
+
+impl GradeRow {
+    fn is_edited(&self) -> bool {
+        self.new_grade.is_some() || !self.comment.is_empty()
+    }
+}
+
+/// A grade and comment sent for one student
+#[derive(Debug, Clone)]
+pub struct GradeEdit {
+    pub user_id: u64,
+    pub new_grade: Option<String>,
+    pub comment: String,
+}
+
+/// Fetches every student's submission of an assignment
+pub async fn fetch_rows(
+    profile: &Profile,
+    course_id: u64,
+    assignment_id: u64,
+) -> Result<Vec<GradeRow>> {
+    let submissions: Vec<CanvasGradingSubmission> = get_json(
+        profile,
+        &format!("/api/v1/courses/{course_id}/assignments/{assignment_id}/submissions"),
+        &[("include[]", "user"), ("per_page", "100")],
+    )
+    .await?;
+    let mut rows: Vec<GradeRow> = submissions
+        .into_iter()
+        .map(|submission| GradeRow {
+            user_id: submission.user_id,
+            name: submission
+                .user
+                .map(|user| user.sortable_name)
+                .unwrap_or_else(|| format!("user {}", submission.user_id)),
+            grade: submission.grade,
+            score: submission.score,
+            new_grade: None,
+            comment: String::new(),
+            state: RowState::Unchanged,
+        })
+        .collect();
+    rows.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(rows)
+}
+
+#[derive(Deserialize)]
+struct CanvasGradedSubmission {
+    score: Option<f64>,
+    grade: Option<String>,
+}
+
+/// Posts the grades and comments one student at a time, reporting each result as it arrives so
+/// a failure only affects its own row
+pub fn save(
+    profile: Profile,
+    course_id: u64,
+    assignment_id: u64,
+    edits: Vec<GradeEdit>,
+    action_tx: UnboundedSender<Action>,
+) {
+    tokio::spawn(async move {
+        for edit in edits {
+            let mut body = serde_json::json!({});
+            if let Some(grade) = &edit.new_grade {
+                body["submission"] = serde_json::json!({ "posted_grade": grade });
+            }
+            if !edit.comment.is_empty() {
+                body["comment"] = serde_json::json!({ "text_comment": edit.comment });
+            }
+            let result: Result<CanvasGradedSubmission> = put_json(
+                &profile,
+                &format!(
+                    "/api/v1/courses/{course_id}/assignments/{assignment_id}/submissions/{}",
+                    edit.user_id
+                ),
+                &body,
+            )
+            .await;
+            let _ = action_tx.send(Action::GradeSaved {
+                assignment_id,
+                user_id: edit.user_id,
+                result: result
+                    .map(|submission| (submission.grade, submission.score))
+                    .map_err(|e| e.to_string()),
+            });
+        }
+    });
+}
+
+/// Grade entry for one assignment, a row per student
+pub struct GradingScreen {
+    pub session: usize,
+    pub course: CourseContext,
+    pub assignment_id: u64,
+    title: String,
+    /// None while the submissions are being fetched
+    rows: Option<Vec<GradeRow>>,
+    sections: Vec<Section>,
+    /// Section the rows are filtered to, all of them when None
+    pub section: Option<u64>,
+    pub table_state: TableState,
+}
+
+/// Whether a row belongs to the section the screen is filtered to
+fn in_section(section: Option<&Section>, row: &GradeRow) -> bool {
+    section.is_none_or(|section| section.student_ids.contains(&row.user_id))
+}
+
+impl GradingScreen {
+    pub fn new(session: usize, course: CourseContext, assignment_id: u64, title: String) -> Self {
+        Self {
+            session,
+            course,
+            assignment_id,
+            title,
+            rows: None,
+            sections: vec![],
+            section: None,
+            table_state: TableState::default(),
+        }
+    }
+
+    /// Sets the submissions and the course's sections, filtered to `section` if it is one of them
+    pub fn set_rows(&mut self, rows: Vec<GradeRow>, sections: Vec<Section>, section: Option<u64>) {
+        self.section = section.filter(|id| sections.iter().any(|section| section.id == *id));
+        self.sections = sections;
+        self.rows = Some(rows);
+        self.select_first();
+    }
+
+    fn select_first(&mut self) {
+        let any = self.visible_rows().next().is_some();
+        self.table_state = TableState::default().with_selected(any.then_some(0));
+    }
+
+    fn current_section(&self) -> Option<&Section> {
+        self.sections
+            .iter()
+            .find(|section| Some(section.id) == self.section)
+    }
+
+    fn visible_rows(&self) -> impl Iterator<Item = &GradeRow> {
+        let section = self.current_section();
+        self.rows
+            .iter()
+            .flatten()
+            .filter(move |row| in_section(section, row))
+    }
+
+    /// Moves the filter to the next section, going back to every section after the last one.
+    /// Returns the section now shown
+    pub fn cycle_section(&mut self) -> Option<u64> {
+        let next = match self.section {
+            None => 0,
+            Some(id) => self
+                .sections
+                .iter()
+                .position(|section| section.id == id)
+                .map_or(0, |index| index + 1),
+        };
+        self.section = self.sections.get(next).map(|section| section.id);
+        self.select_first();
+        self.section
+    }
+
+    /// Name of the section shown, for the status message
+    pub fn section_name(&self) -> String {
+        match self.current_section() {
+            Some(section) => section.name.clone(),
+            None => "all sections".to_string(),
+        }
+    }
+
+    pub fn selected(&mut self) -> Option<&mut GradeRow> {
+        let index = self.table_state.selected()?;
+        let section = self
+            .sections
+            .iter()
+            .find(|section| Some(section.id) == self.section);
+        self.rows
+            .as_mut()?
+            .iter_mut()
+            .filter(|row| in_section(section, row))
+            .nth(index)
+    }
+
+    fn row(&mut self, user_id: u64) -> Option<&mut GradeRow> {
+        self.rows
+            .as_mut()?
+            .iter_mut()
+            .find(|row| row.user_id == user_id)
+    }
+
+    /// Sets the grade to post for the selected student, clearing it when blank
+    pub fn set_grade(&mut self, grade: &str) {
+        if let Some(row) = self.selected() {
+            let grade = grade.trim();
+            row.new_grade = (!grade.is_empty()).then(|| grade.to_string());
+            row.state = match row.is_edited() {
+                true => RowState::Edited,
+                false => RowState::Unchanged,
+            };
+        }
+    }
+
+    pub fn set_comment(&mut self, comment: &str) {
+        if let Some(row) = self.selected() {
+            row.comment = comment.trim().to_string();
+            row.state = match row.is_edited() {
+                true => RowState::Edited,
+                false => RowState::Unchanged,
+            };
+        }
+    }
+
+    /// Edits waiting to be sent, including ones that failed before. Their rows are marked as
+    /// being saved
+    pub fn take_edits(&mut self) -> Vec<GradeEdit> {
+        let Some(rows) = &mut self.rows else {
+            return vec![];
+        };
+        rows.iter_mut()
+            .filter(|row| matches!(row.state, RowState::Edited | RowState::Failed(_)))
+            .map(|row| {
+                row.state = RowState::Saving;
+                GradeEdit {
+                    user_id: row.user_id,
+                    new_grade: row.new_grade.clone(),
+                    comment: row.comment.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Records the result of saving one student's grade
+    fn saved(&mut self, user_id: u64, result: Result<(Option<String>, Option<f64>), String>) {
+        let Some(row) = self.row(user_id) else {
+            return;
+        };
+        match result {
+            Ok((grade, score)) => {
+                row.grade = grade;
+                row.score = score;
+                row.new_grade = None;
+                row.comment.clear();
+                row.state = RowState::Saved;
+            }
+            Err(e) => row.state = RowState::Failed(e),
+        }
+    }
+
+    /// Rows edited but not saved yet
+    pub fn unsaved(&self) -> usize {
+        self.rows
+            .iter()
+            .flatten()
+            .filter(|row| row.is_edited())
+            .count()
+    }
+
+    /// Counts of rows saved and rows that failed, once nothing is being saved anymore
+    fn outcome(&self) -> Option<(usize, usize)> {
+        let rows = self.rows.as_ref()?;
+        if rows.iter().any(|row| row.state == RowState::Saving) {
+            return None;
+        }
+        let count =
+            |matches: fn(&RowState) -> bool| rows.iter().filter(|row| matches(&row.state)).count();
+        Some((
+            count(|state| *state == RowState::Saved),
+            count(|state| matches!(state, RowState::Failed(_))),
+        ))
+    }
+}
+
+impl Component for GradingScreen {
+    fn handle_event(&mut self, key: KeyEvent, bound: Action) -> Action {
+        let action = match key.code {
+            KeyCode::Char('s') | KeyCode::Enter => Action::OpenPrompt(PromptKind::Grade),
+            KeyCode::Char('c') => Action::OpenPrompt(PromptKind::Comment),
+            KeyCode::Char('S') => Action::SaveGrades,
+            KeyCode::Char('f') => Action::CycleSection,
+            _ => bound,
+        };
+        match action {
+            Action::Quit
+            | Action::OpenPrompt(PromptKind::Grade | PromptKind::Comment)
+            | Action::SaveGrades
+            | Action::CycleSection
+            | Action::Navigate(Navigation::PrevEvent | Navigation::NextEvent) => action,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, _context: &Context) -> Option<Action> {
+        match action {
+            Action::Navigate(Navigation::PrevEvent) => self.table_state.select_previous(),
+            Action::Navigate(Navigation::NextEvent) => self.table_state.select_next(),
+            Action::GradeSaved {
+                assignment_id,
+                user_id,
+                result,
+            } if *assignment_id == self.assignment_id => {
+                self.saved(*user_id, result.clone());
+                let (saved, failed) = self.outcome()?;
+                return Some(Action::Status(match failed {
+                    0 => format!("Saved {saved} grade(s)"),
+                    failed => format!("Saved {saved} grade(s), {failed} failed"),
+                }));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, _context: &Context) {
+        let [title_area, table_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(area);
+        let filter = match self.sections.len() {
+            0 | 1 => String::new(),
+            _ => format!(" · f section: {}", redact::mask(&self.section_name())),
+        };
+        Paragraph::new(format!(
+            "Grades for {} · s grade · c comment · S save {} edited{filter}",
+            redact::mask(&self.title),
+            self.unsaved()
+        ))
+        .style(Style::default().fg(theme::current().heading).bold())
+        .render(title_area, buf);
+        let Some(rows) = &self.rows else {
+            Paragraph::new("Loading submissions...").render(table_area, buf);
+            return;
+        };
+        if rows.is_empty() {
+            Paragraph::new("No students have this assignment").render(table_area, buf);
+            return;
+        }
+        // Borrowing the fields rather than self leaves the table state free to render with
+        let section = self
+            .sections
+            .iter()
+            .find(|section| Some(section.id) == self.section);
+        let rows: Vec<&GradeRow> = rows.iter().filter(|row| in_section(section, row)).collect();
+        if rows.is_empty() {
+            Paragraph::new("No students in this section have this assignment")
+                .render(table_area, buf);
+            return;
+        }
+
+        let header = Row::new(["Student", "Current", "New", "Comment", "Status"])
+            .style(Style::default().fg(theme::current().heading));
+        let table_rows = rows.iter().map(|row| {
+            let current = match (&row.grade, row.score) {
+                (Some(grade), Some(score)) if *grade != score.to_string() => {
+                    format!("{grade} ({score})")
+                }
+                (Some(grade), _) => grade.clone(),
+                (None, _) => "-".to_string(),
+            };
+            let (status, color) = match &row.state {
+                RowState::Unchanged => (String::new(), theme::current().text),
+                RowState::Edited => ("unsaved".to_string(), theme::current().warning),
+                RowState::Saving => ("saving...".to_string(), theme::current().secondary),
+                RowState::Saved => ("saved".to_string(), theme::current().done),
+                RowState::Failed(e) => (e.clone(), theme::current().error),
+            };
+            Row::new([
+                Cell::from(redact::mask(&row.name)),
+                Cell::from(redact::mask(&current)),
+                Cell::from(redact::mask(row.new_grade.as_deref().unwrap_or_default()))
+                    .style(Style::default().fg(theme::current().warning)),
+                Cell::from(redact::mask(&row.comment)),
+                Cell::from(status).style(Style::default().fg(color)),
+            ])
+        });
+        let table = Table::new(
+            table_rows,
+            [
+                Constraint::Length(28),
+                Constraint::Length(14),
+                Constraint::Length(8),
+                Constraint::Fill(2),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::default().bg(theme::current().selection))
+        .style(Style::default().fg(theme::current().text));
+        StatefulWidget::render(table, table_area, buf, &mut self.table_state);
+    }
+}
@@ -0,0 +1,211 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout},
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime};
+
+use crate::{
+    action::{Action, Navigation, PromptKind},
+    component::{Component, Context},
+    dashboard::load_color,
+    fetch::{now_local, to_local},
+    locale, redact,
+    session::Session,
+    theme,
+};
+
+/// How many days the grid shows at once
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GridLayout {
+    Week,
+    Month,
+}
+
+/// Week or month of the planner as a grid of days, instead of one date's table
+pub struct GridScreen {
+    pub layout: GridLayout,
+    /// Selected day
+    pub cursor: Date,
+}
+
+/// First day of the week `date` is in. Weeks start on Sunday like the month calendar below the
+/// day table
+fn week_start(date: Date) -> Date {
+    date - Duration::days(date.weekday().number_days_from_sunday() as i64)
+}
+
+impl GridScreen {
+    pub fn new(layout: GridLayout, cursor: Date) -> Self {
+        Self { layout, cursor }
+    }
+
+    pub fn move_by(&mut self, days: i64) {
+        self.cursor += Duration::days(days);
+    }
+
+    /// Days shown, a row per week
+    fn weeks(&self) -> Vec<[Date; 7]> {
+        let (first, last) = match self.layout {
+            GridLayout::Week => (self.cursor, self.cursor),
+            GridLayout::Month => (
+                self.cursor.replace_day(1).unwrap(),
+                self.cursor
+                    .replace_day(self.cursor.month().length(self.cursor.year()))
+                    .unwrap(),
+            ),
+        };
+        let mut weeks = vec![];
+        let mut start = week_start(first);
+        while start <= last {
+            weeks.push(std::array::from_fn(|offset| {
+                start + Duration::days(offset as i64)
+            }));
+            start += Duration::days(7);
+        }
+        weeks
+    }
+
+    fn render_day(
+        &self,
+        day: Date,
+        now: PrimitiveDateTime,
+        area: Rect,
+        buf: &mut Buffer,
+        session: &Session,
+    ) {
+        let today = now.date();
+        let events: Vec<_> = session
+            .calendar
+            .dates
+            .iter()
+            .flat_map(|calendar_date| &calendar_date.events)
+            .filter(|event| event.due_at.date() == day && !session.is_hidden(event))
+            .collect();
+        let left = events.iter().filter(|event| !event.is_done()).count();
+        let in_month = self.layout == GridLayout::Week || day.month() == self.cursor.month();
+
+        let mut border_style = Style::default().fg(theme::current().muted);
+        if day == self.cursor {
+            border_style = Style::default().fg(theme::current().warning).bold();
+        }
+        let mut title_style = Style::default().fg(match in_month {
+            true => theme::current().text,
+            false => theme::current().muted,
+        });
+        if day == today {
+            title_style = title_style.fg(theme::current().heading).bold();
+        }
+        let block = Block::default()
+            .title(Line::styled(format!(" {} ", day.day()), title_style))
+            .borders(Borders::ALL)
+            .border_style(border_style);
+
+        let mut lines = vec![];
+        if !events.is_empty() {
+            lines.push(Line::styled(
+                format!("{} due, {left} left", events.len()),
+                Style::default()
+                    .fg(theme::current().inverse)
+                    .bg(load_color(left)),
+            ));
+        }
+        lines.extend(events.iter().map(|event| {
+            Line::styled(
+                redact::mask(&event.title),
+                Style::default().fg(match (event.is_done(), event.is_overdue(now)) {
+                    (true, _) => theme::current().done,
+                    (false, true) => theme::current().error,
+                    (false, false) => theme::current().text,
+                }),
+            )
+        }));
+        // A rule at the current time between today's items
+        if day == today {
+            let ahead = events.iter().filter(|event| event.due_at > now).count();
+            let rule = format!(
+                "── {:02}:{:02} {}",
+                now.hour(),
+                now.minute(),
+                "─".repeat(40)
+            );
+            lines.insert(
+                lines.len() - ahead,
+                Line::styled(rule, Style::default().fg(theme::current().error)),
+            );
+        }
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
+
+impl Component for GridScreen {
+    fn handle_event(&mut self, key: KeyEvent, bound: Action) -> Action {
+        if key.code == KeyCode::Enter {
+            return Action::OpenGridDay;
+        }
+        match bound {
+            Action::Quit
+            | Action::Fetch
+            | Action::CycleLayout
+            | Action::Navigate(_)
+            | Action::OpenPrompt(PromptKind::Goto) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, _context: &Context) -> Option<Action> {
+        let Action::Navigate(navigation) = action else {
+            return None;
+        };
+        match navigation {
+            Navigation::PrevEvent => self.move_by(-7),
+            Navigation::NextEvent => self.move_by(7),
+            Navigation::PrevDate => self.move_by(-1),
+            Navigation::NextDate => self.move_by(1),
+            Navigation::ResetDate | Navigation::Today => {
+                self.cursor = now_local().date();
+            }
+            Navigation::LastDate | Navigation::NextMatch | Navigation::PrevMatch => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, context: &Context) {
+        let session = context.session;
+        let now = to_local(OffsetDateTime::now_utc());
+        let weeks = self.weeks();
+        let title = match self.layout {
+            GridLayout::Week => format!(
+                "Week of {}",
+                locale::current().format(weeks[0][0], "%a %b %-d %Y")
+            ),
+            GridLayout::Month => locale::current().format(self.cursor, "%B %Y"),
+        };
+        let [title_area, header_area, grid_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+        Paragraph::new(format!("{title} · Enter open day · V next layout"))
+            .style(Style::default().fg(theme::current().heading).bold())
+            .render(title_area, buf);
+        let columns = Layout::horizontal([Constraint::Fill(1); 7]);
+        for (day, day_area) in weeks[0].iter().zip(columns.split(header_area).iter()) {
+            Paragraph::new(locale::current().abbreviated_weekday(*day))
+                .centered()
+                .style(Style::default().fg(theme::current().heading))
+                .render(*day_area, buf);
+        }
+
+        let rows = Layout::vertical(weeks.iter().map(|_| Constraint::Fill(1))).split(grid_area);
+        for (week, row_area) in weeks.iter().zip(rows.iter()) {
+            for (day, day_area) in week.iter().zip(columns.split(*row_area).iter()) {
+                self.render_day(*day, now, *day_area, buf, session);
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use ratatui::{
+    layout::Alignment,
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Padding, Widget},
+};
+
+use crate::{
+    component::{Component, Context},
+    redact, theme,
+};
+
+/// The frame around the app, titled with the signed in user of the focused profile, how fresh
+/// its data is and the course it is focused on
+pub struct Header;
+
+impl Header {
+    fn block() -> Block<'static> {
+        Block::default()
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Thick)
+            .style(Style::default().fg(theme::current().accent))
+            .padding(Padding::horizontal(1))
+    }
+
+    /// Space left inside the frame
+    pub fn inner(area: Rect) -> Rect {
+        Self::block().inner(area)
+    }
+}
+
+impl Component for Header {
+    fn render(&mut self, area: Rect, buf: &mut Buffer, context: &Context) {
+        let session = context.session;
+        let mut title = match &session.user_name {
+            Some(name) => format!(" CanvasTUI · {} ", redact::mask(name)),
+            None => " CanvasTUI ".to_string(),
+        };
+        if let Some(staleness) = session.staleness() {
+            title.push_str(&format!("· {staleness} "));
+        }
+        let mut title = Line::raw(title);
+        // Hard to miss, so a narrowed view isn't taken for everything that is due
+        if let Some(course) = &session.focused_course {
+            title.push_span(Span::styled(
+                format!(" FOCUS {} ", redact::mask(&course.name)),
+                Style::default()
+                    .fg(theme::current().inverse)
+                    .bg(session
+                        .colors
+                        .get(&course.id)
+                        .copied()
+                        .unwrap_or(theme::current().accent))
+                    .bold(),
+            ));
+            title.push_span(" ");
+        }
+        // Non-production data is easy to mistake for the real thing without it
+        if let Some(badge) = session.profile.environment_badge() {
+            title.push_span(Span::styled(
+                format!(" {badge} "),
+                Style::default()
+                    .fg(theme::current().inverse)
+                    .bg(theme::current().warning)
+                    .bold(),
+            ));
+            title.push_span(" ");
+        }
+        Self::block().title(title).render(area, buf);
+    }
+}
@@ -0,0 +1,103 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+
+use crate::{
+    action::{Action, Navigation},
+    component::{Component, Context},
+    keymap::Keymap,
+    theme,
+};
+
+const HELP_WIDTH: u16 = 64;
+
+/// Every bindable action with the keys it is bound to, shown over whatever is on screen
+pub struct HelpOverlay {
+    /// Action names with their key sequences, in the order of the default bindings
+    entries: Vec<(String, String)>,
+    scroll: u16,
+}
+
+impl HelpOverlay {
+    pub fn new(keymap: &Keymap) -> Self {
+        let entries = keymap
+            .listing()
+            .into_iter()
+            .map(|(name, sequences)| {
+                let keys = match sequences.is_empty() {
+                    true => "unbound".to_string(),
+                    false => sequences.join(", "),
+                };
+                (name.replace('_', " "), keys)
+            })
+            .collect();
+        Self { entries, scroll: 0 }
+    }
+
+    pub fn scroll_by(&mut self, lines: i32) {
+        let last = self.entries.len().saturating_sub(1) as i32;
+        self.scroll = (self.scroll as i32 + lines).clamp(0, last) as u16;
+    }
+}
+
+impl Component for HelpOverlay {
+    fn handle_event(&mut self, _key: KeyEvent, bound: Action) -> Action {
+        match bound {
+            Action::Quit
+            | Action::ToggleHelp
+            | Action::Navigate(Navigation::PrevEvent | Navigation::NextEvent) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, _context: &Context) -> Option<Action> {
+        match action {
+            Action::Navigate(Navigation::PrevEvent) => self.scroll_by(-1),
+            Action::Navigate(_) => self.scroll_by(1),
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, _context: &Context) {
+        let width = HELP_WIDTH.min(area.width);
+        let height = (self.entries.len() as u16 + 2).min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        let name_width = self
+            .entries
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or_default();
+        let lines: Vec<Line> = self
+            .entries
+            .iter()
+            .map(|(name, keys)| {
+                Line::from(vec![
+                    Span::raw(format!("{name:<name_width$}  ")),
+                    Span::styled(keys.clone(), Style::default().fg(theme::current().accent)),
+                ])
+            })
+            .collect();
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .scroll((self.scroll, 0))
+            .block(
+                Block::default()
+                    .title(" Keys · j/k scroll · Esc close ".bold())
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme::current().heading)),
+            )
+            .style(Style::default().fg(theme::current().text))
+            .render(popup, buf);
+    }
+}
@@ -0,0 +1,562 @@
+use std::{ops::RangeInclusive, path::Path};
+
+use color_eyre::eyre::Result;
+use reqwest::Url;
+use time::{
+    Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset, Weekday,
+    format_description,
+};
+
+use crate::{
+    config::{Config, ExternalCalendar, Horizon, Profile},
+    fetch::{CalendarEvent, EXTERNAL_PLANNABLE_TYPE, load_cache},
+    redact,
+    store::Annotations,
+};
+
+/// How far ahead recurring events are expanded when the horizon has no end
+const RECURRENCE_DAYS: i64 = 365;
+/// Stands in for the url of external events that have none, keeping their annotations apart
+pub const UNLINKED: &str = "canvastui-ics:";
+
+/// Downloads an external ICS feed and converts its events within the horizon into read-only
+/// calendar events, one for each occurrence of recurring ones
+pub async fn fetch_external(
+    calendar: &ExternalCalendar,
+    horizon: Horizon,
+) -> Result<Vec<CalendarEvent>> {
+    let body = reqwest::get(&calendar.url)
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let today = OffsetDateTime::now_local()?.date();
+    let end = horizon
+        .end(today)
+        .unwrap_or(today + Duration::days(RECURRENCE_DAYS));
+    Ok(parse(&body, &calendar.name, horizon.start(today)..=end))
+}
+
+/// The time zone a date-time property is given in
+#[derive(Clone)]
+enum Zone {
+    /// Floating times, dates, and TZIDs the system doesn't know
+    Local,
+    Utc,
+    Named(tz::TimeZone),
+}
+
+impl Zone {
+    fn from_params(params: &str) -> Zone {
+        let Some(tzid) = params
+            .split(';')
+            .find_map(|param| param.strip_prefix("TZID="))
+        else {
+            return Zone::Local;
+        };
+        match tz::TimeZone::from_posix_tz(tzid.trim_matches('"')) {
+            Ok(zone) => Zone::Named(zone),
+            Err(e) => {
+                log::warn!("Treating unknown time zone {tzid} as local time: {e}");
+                Zone::Local
+            }
+        }
+    }
+
+    /// Converts a wall-clock time in the zone to local time
+    fn to_local(&self, wall: PrimitiveDateTime) -> Option<PrimitiveDateTime> {
+        let utc = match self {
+            Zone::Local => return Some(wall),
+            Zone::Utc => wall.assume_utc(),
+            Zone::Named(zone) => {
+                // The offset at the wall-clock time read as UTC is off by at most one
+                // transition, which the offset at the corrected guess settles
+                let guess = wall.assume_utc().unix_timestamp();
+                let offset = zone.find_local_time_type(guess).ok()?.ut_offset();
+                let offset = zone
+                    .find_local_time_type(guess - i64::from(offset))
+                    .ok()?
+                    .ut_offset();
+                OffsetDateTime::from_unix_timestamp(guess - i64::from(offset)).ok()?
+            }
+        };
+        let local = utc.to_offset(UtcOffset::local_offset_at(utc).unwrap_or(UtcOffset::UTC));
+        Some(PrimitiveDateTime::new(local.date(), local.time()))
+    }
+}
+
+/// Properties of a VEVENT gathered while parsing it
+#[derive(Default)]
+struct VEvent {
+    start: Option<(PrimitiveDateTime, Zone)>,
+    summary: String,
+    url: String,
+    uid: String,
+    rrule: Option<String>,
+    /// Occurrences left out of the recurrence, in local time
+    exdates: Vec<PrimitiveDateTime>,
+    /// The occurrence of a recurring event this one replaces, in local time
+    recurrence_id: Option<PrimitiveDateTime>,
+}
+
+impl VEvent {
+    fn occurrence(&self, calendar_name: &str, start: PrimitiveDateTime) -> CalendarEvent {
+        let html_url = match self.url.is_empty() {
+            false => self.url.clone(),
+            true => format!(
+                "{UNLINKED}{calendar_name}/{}/{}",
+                if self.uid.is_empty() {
+                    &self.summary
+                } else {
+                    &self.uid
+                },
+                start.assume_utc().unix_timestamp()
+            ),
+        };
+        CalendarEvent {
+            course_name: calendar_name.to_string(),
+            due_at: start,
+            official_due_at: None,
+            title: self.summary.clone(),
+            html_url,
+            plannable_type: EXTERNAL_PLANNABLE_TYPE.to_string(),
+            course_id: None,
+            plannable_id: 0,
+            submitted: false,
+            graded: false,
+            completed: false,
+            override_id: None,
+            unlock_at: None,
+            lock_at: None,
+            attempts: None,
+        }
+    }
+}
+
+/// Parses the events of an ICS feed that fall within `window`, expanding recurring ones
+fn parse(body: &str, calendar_name: &str, window: RangeInclusive<Date>) -> Vec<CalendarEvent> {
+    let mut vevents = vec![];
+    let mut current: Option<VEvent> = None;
+
+    for line in unfold(body) {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (property, params) = name.split_once(';').unwrap_or((name, ""));
+        let to_local = |value: &str| {
+            let (wall, zone) = parse_date_time(value, params)?;
+            zone.to_local(wall)
+        };
+        match (property, current.as_mut()) {
+            ("BEGIN", None) if value == "VEVENT" => current = Some(VEvent::default()),
+            ("END", Some(_)) if value == "VEVENT" => vevents.extend(current.take()),
+            ("DTSTART", Some(event)) => event.start = parse_date_time(value, params),
+            ("SUMMARY", Some(event)) => event.summary = unescape(value),
+            ("URL", Some(event)) => event.url = value.to_string(),
+            ("UID", Some(event)) => event.uid = value.to_string(),
+            ("RRULE", Some(event)) => event.rrule = Some(value.to_string()),
+            ("EXDATE", Some(event)) => event.exdates.extend(value.split(',').filter_map(to_local)),
+            ("RECURRENCE-ID", Some(event)) => event.recurrence_id = to_local(value),
+            _ => {}
+        }
+    }
+
+    // Occurrences moved or changed by an event of their own
+    let overridden: Vec<(&str, PrimitiveDateTime)> = vevents
+        .iter()
+        .filter_map(|event| Some((event.uid.as_str(), event.recurrence_id?)))
+        .collect();
+    let last = *window.end() + Duration::days(1);
+    vevents
+        .iter()
+        .flat_map(|event| {
+            let Some((start, zone)) = &event.start else {
+                return vec![];
+            };
+            let recurring = event
+                .rrule
+                .as_ref()
+                .filter(|_| event.recurrence_id.is_none());
+            let starts = match recurring {
+                Some(rule) => occurrences(*start, rule, last),
+                None => vec![*start],
+            };
+            starts
+                .into_iter()
+                .filter_map(|wall| zone.to_local(wall))
+                .filter(|start| window.contains(&start.date()))
+                .filter(|start| !event.exdates.contains(start))
+                .filter(|start| {
+                    recurring.is_none() || !overridden.contains(&(event.uid.as_str(), *start))
+                })
+                .map(|start| event.occurrence(calendar_name, start))
+                .collect()
+        })
+        .collect()
+}
+
+/// Start times of a recurring event's occurrences until `last`, following the FREQ, INTERVAL,
+/// COUNT, UNTIL and weekly BYDAY parts of its RRULE. Other rules only keep the first occurrence
+fn occurrences(start: PrimitiveDateTime, rule: &str, last: Date) -> Vec<PrimitiveDateTime> {
+    let parts: Vec<(&str, &str)> = rule
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .collect();
+    let part = |name: &str| {
+        parts
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+    };
+    let interval = part("INTERVAL")
+        .and_then(|interval| interval.parse::<i64>().ok())
+        .filter(|interval| *interval > 0)
+        .unwrap_or(1);
+    let count = part("COUNT").and_then(|count| count.parse::<usize>().ok());
+    // A date-only UNTIL includes occurrences later that day
+    let until = part("UNTIL").and_then(|until| match parse_date(until) {
+        Some(date) => Some(PrimitiveDateTime::new(date, Time::MAX)),
+        None => parse_date_time(until, "").map(|(until, _)| until),
+    });
+    let mut weekdays: Vec<Weekday> = part("BYDAY")
+        .map(|days| days.split(',').filter_map(weekday).collect())
+        .unwrap_or_default();
+    weekdays.sort_by_key(|day| day.number_days_from_monday());
+
+    let mut starts = vec![];
+    for period in 0.. {
+        let steps = period * interval;
+        // The first day of the period, and the occurrences in it
+        let (first, candidates) = match part("FREQ") {
+            Some("DAILY") => {
+                let candidate = start + Duration::days(steps);
+                (candidate.date(), vec![candidate])
+            }
+            Some("WEEKLY") => {
+                let week = start + Duration::weeks(steps);
+                let monday =
+                    week.date() - Duration::days(week.weekday().number_days_from_monday().into());
+                let candidates = match weekdays.is_empty() {
+                    true => vec![week],
+                    false => weekdays
+                        .iter()
+                        .map(|day| {
+                            (monday + Duration::days(day.number_days_from_monday().into()))
+                                .with_time(start.time())
+                        })
+                        .collect(),
+                };
+                (monday, candidates)
+            }
+            Some("MONTHLY") => {
+                let months = i64::from(u8::from(start.month()) - 1) + steps;
+                let Ok(year) = i32::try_from(i64::from(start.year()) + months / 12) else {
+                    break;
+                };
+                let Ok(month) = Month::try_from((months % 12 + 1) as u8) else {
+                    break;
+                };
+                let Ok(first) = Date::from_calendar_date(year, month, 1) else {
+                    break;
+                };
+                // Months without the day are skipped, as RFC 5545 says
+                let candidate = Date::from_calendar_date(year, month, start.day())
+                    .ok()
+                    .map(|date| date.with_time(start.time()));
+                (first, candidate.into_iter().collect())
+            }
+            Some("YEARLY") => {
+                let Ok(year) = i32::try_from(i64::from(start.year()) + steps) else {
+                    break;
+                };
+                let Ok(first) = Date::from_ordinal_date(year, 1) else {
+                    break;
+                };
+                let candidate = Date::from_calendar_date(year, start.month(), start.day())
+                    .ok()
+                    .map(|date| date.with_time(start.time()));
+                (first, candidate.into_iter().collect())
+            }
+            _ => return vec![start],
+        };
+        if first > last {
+            break;
+        }
+        for candidate in candidates
+            .into_iter()
+            .filter(|candidate| *candidate >= start)
+        {
+            if candidate.date() > last
+                || until.is_some_and(|until| candidate > until)
+                || count.is_some_and(|count| starts.len() >= count)
+            {
+                return starts;
+            }
+            starts.push(candidate);
+        }
+    }
+    starts
+}
+
+fn weekday(day: &str) -> Option<Weekday> {
+    Some(match day {
+        "MO" => Weekday::Monday,
+        "TU" => Weekday::Tuesday,
+        "WE" => Weekday::Wednesday,
+        "TH" => Weekday::Thursday,
+        "FR" => Weekday::Friday,
+        "SA" => Weekday::Saturday,
+        "SU" => Weekday::Sunday,
+        _ => return None,
+    })
+}
+
+/// Writes every profile's cached Canvas items to `out` as an iCalendar file, one event at the
+/// due time of each, for importing deadlines into other calendar apps
+pub fn export(out: &Path) -> Result<()> {
+    redact::check_export()?;
+    let config = Config::load()?;
+    let annotations = Annotations::load().unwrap_or_default();
+    let utc_format = format_description::parse("[year][month][day]T[hour][minute][second]Z")?;
+    let local_offset = UtcOffset::current_local_offset()?;
+    let stamp = OffsetDateTime::now_utc().format(&utc_format)?;
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//CanvasTUI//EN".to_string(),
+    ];
+    // Reading the cache needs no credentials, so their absence doesn't stop the export
+    let mut profiles = vec![config.default_profile().unwrap_or(Profile::offline())];
+    for profile in &config.profiles {
+        if !profiles.iter().any(|listed| listed.name == profile.name) {
+            profiles.push(profile.clone());
+        }
+    }
+    let mut exported = 0;
+    for profile in &profiles {
+        let Ok(mut calendar) = load_cache(profile) else {
+            eprintln!(
+                "Leaving out {}, which has no cached planner data",
+                profile.name
+            );
+            continue;
+        };
+        calendar.apply_annotations(&annotations);
+        let canvas_url = profile.url.parse::<Url>().ok();
+        for event in calendar
+            .dates
+            .iter()
+            .flat_map(|date| &date.events)
+            .filter(|event| !event.is_external())
+        {
+            // Calendars get the deadline on Canvas, not the one the item was moved up to
+            let start = event
+                .official_due_at
+                .unwrap_or(event.due_at)
+                .assume_offset(local_offset)
+                .to_offset(UtcOffset::UTC)
+                .format(&utc_format)?;
+            let url = match &canvas_url {
+                Some(base) => base.join(&event.html_url)?.to_string(),
+                None => event.html_url.clone(),
+            };
+            lines.extend([
+                "BEGIN:VEVENT".to_string(),
+                format!("UID:{}", uid(event)),
+                format!("DTSTAMP:{stamp}"),
+                format!("DTSTART:{start}"),
+                format!(
+                    "SUMMARY:{}",
+                    escape(&format!("{}: {}", event.course_name, event.title))
+                ),
+                format!("URL:{url}"),
+                // Events have no completion status of their own in iCalendar
+                format!(
+                    "X-CANVASTUI-COMPLETED:{}",
+                    if event.is_done() { "TRUE" } else { "FALSE" }
+                ),
+                "END:VEVENT".to_string(),
+            ]);
+            exported += 1;
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+    std::fs::write(out, lines.join("\r\n") + "\r\n")?;
+    println!("Exported {exported} item(s) to {}", out.display());
+    Ok(())
+}
+
+/// Identifies an item across exports and syncs, so calendar apps update it instead of adding it
+/// again
+pub fn uid(event: &CalendarEvent) -> String {
+    let id: String = event
+        .html_url
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("canvastui{id}")
+}
+
+/// Returns the value of the first occurrence of a property, ignoring its parameters
+pub fn property(body: &str, property: &str) -> Option<String> {
+    unfold(body).into_iter().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        let name = name.split_once(';').map_or(name, |(name, _)| name);
+        (name == property).then(|| unescape(value))
+    })
+}
+
+/// Joins folded content lines, which continue with a leading space or tab
+fn unfold(body: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in body.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+pub fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+        .replace('\n', "\\n")
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+fn parse_date(value: &str) -> Option<Date> {
+    let format = format_description::parse("[year][month][day]").ok()?;
+    Date::parse(value, &format).ok()
+}
+
+/// Parses a DTSTART, EXDATE or RECURRENCE-ID value into its wall-clock time and the zone that
+/// is in. Dates start at midnight local time
+fn parse_date_time(value: &str, params: &str) -> Option<(PrimitiveDateTime, Zone)> {
+    if params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME") {
+        return Some((parse_date(value)?.midnight(), Zone::Local));
+    }
+
+    let format = format_description::parse("[year][month][day]T[hour][minute][second]").ok()?;
+    match value.strip_suffix('Z') {
+        Some(utc) => Some((PrimitiveDateTime::parse(utc, &format).ok()?, Zone::Utc)),
+        None => Some((
+            PrimitiveDateTime::parse(value, &format).ok()?,
+            Zone::from_params(params),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(month: Month, day: u8, hour: u8) -> PrimitiveDateTime {
+        Date::from_calendar_date(2026, month, day)
+            .unwrap()
+            .with_hms(hour, 0, 0)
+            .unwrap()
+    }
+
+    fn october() -> RangeInclusive<Date> {
+        at(Month::October, 1, 0).date()..=at(Month::October, 31, 0).date()
+    }
+
+    fn feed(events: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{events}END:VCALENDAR\r\n")
+    }
+
+    #[test]
+    fn expands_recurring_events() {
+        let body = feed(
+            "BEGIN:VEVENT\r\n\
+             UID:lab\r\n\
+             SUMMARY:Lab\r\n\
+             DTSTART:20261005T090000\r\n\
+             RRULE:FREQ=WEEKLY;BYDAY=WE,MO;COUNT=6\r\n\
+             EXDATE:20261007T090000\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:lab\r\n\
+             SUMMARY:Lab (moved)\r\n\
+             RECURRENCE-ID:20261012T090000\r\n\
+             DTSTART:20261013T100000\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:review\r\n\
+             SUMMARY:Review\r\n\
+             URL:https://example.com/review\r\n\
+             DTSTART;VALUE=DATE:20260901\r\n\
+             RRULE:FREQ=MONTHLY\r\n\
+             END:VEVENT\r\n",
+        );
+        let mut events = parse(&body, "School", october());
+        events.sort_by_key(|event| event.due_at);
+        let occurrences: Vec<(PrimitiveDateTime, &str)> = events
+            .iter()
+            .map(|event| (event.due_at, event.title.as_str()))
+            .collect();
+        assert_eq!(
+            occurrences,
+            [
+                (at(Month::October, 1, 0), "Review"),
+                (at(Month::October, 5, 9), "Lab"),
+                (at(Month::October, 13, 10), "Lab (moved)"),
+                (at(Month::October, 14, 9), "Lab"),
+                (at(Month::October, 19, 9), "Lab"),
+                (at(Month::October, 21, 9), "Lab"),
+            ]
+        );
+
+        assert_eq!(events[0].html_url, "https://example.com/review");
+        // Events without a url still need keys of their own for their annotations
+        assert!(
+            events[1..]
+                .iter()
+                .all(|event| event.html_url.starts_with(UNLINKED))
+        );
+        assert_ne!(events[1].html_url, events[3].html_url);
+    }
+
+    #[test]
+    fn honours_tzid() {
+        let body = feed(
+            "BEGIN:VEVENT\r\n\
+             SUMMARY:Seminar\r\n\
+             DTSTART;TZID=America/New_York:20261030T090000\r\n\
+             RRULE:FREQ=DAILY;INTERVAL=3;UNTIL=20261105\r\n\
+             END:VEVENT\r\n",
+        );
+        let window = at(Month::October, 1, 0).date()..=at(Month::November, 30, 0).date();
+        let starts: Vec<PrimitiveDateTime> = parse(&body, "School", window)
+            .iter()
+            .map(|event| event.due_at)
+            .collect();
+
+        let local = |utc: PrimitiveDateTime| {
+            let utc = utc.assume_utc();
+            let local = utc.to_offset(UtcOffset::local_offset_at(utc).unwrap_or(UtcOffset::UTC));
+            PrimitiveDateTime::new(local.date(), local.time())
+        };
+        // New York leaves daylight saving time on November 1st
+        assert_eq!(
+            starts,
+            [
+                local(at(Month::October, 30, 13)),
+                local(at(Month::November, 2, 14)),
+                local(at(Month::November, 5, 14)),
+            ]
+        );
+    }
+}
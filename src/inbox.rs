@@ -0,0 +1,392 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout},
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget, Wrap},
+};
+use serde::Deserialize;
+use serde_json::json;
+use time::OffsetDateTime;
+
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::{Action, Navigation, View},
+    component::{Component, Context},
+    config::Profile,
+    fetch::{get_json, post_json, to_local},
+    locale, redact,
+    textarea::TextArea,
+    theme,
+};
+
+/// Rows the reply box takes under the thread
+const COMPOSE_HEIGHT: u16 = 8;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Participant {
+    pub id: u64,
+    pub name: String,
+}
+
+/// A thread in the Canvas inbox as listed, without its messages
+#[derive(Debug, Clone, Deserialize)]
+pub struct Conversation {
+    pub id: u64,
+    pub subject: Option<String>,
+    /// unread, read or archived
+    pub workflow_state: String,
+    #[serde(
+        default,
+        deserialize_with = "time::serde::iso8601::option::deserialize"
+    )]
+    pub last_message_at: Option<OffsetDateTime>,
+    #[serde(default)]
+    pub message_count: u32,
+    /// Course or group the conversation belongs to
+    pub context_name: Option<String>,
+}
+
+impl Conversation {
+    pub fn is_unread(&self) -> bool {
+        self.workflow_state == "unread"
+    }
+
+    fn subject(&self) -> &str {
+        match self.subject.as_deref() {
+            Some(subject) if !subject.is_empty() => subject,
+            _ => "(no subject)",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConversationMessage {
+    pub author_id: u64,
+    #[serde(with = "time::serde::iso8601")]
+    pub created_at: OffsetDateTime,
+    /// Plain text
+    pub body: String,
+}
+
+/// A conversation with its messages, newest first as Canvas sends them
+#[derive(Debug, Clone, Deserialize)]
+pub struct Thread {
+    pub id: u64,
+    #[serde(default)]
+    pub participants: Vec<Participant>,
+    #[serde(default)]
+    pub messages: Vec<ConversationMessage>,
+}
+
+impl Thread {
+    fn author(&self, id: u64) -> &str {
+        self.participants
+            .iter()
+            .find(|participant| participant.id == id)
+            .map_or("Unknown", |participant| participant.name.as_str())
+    }
+}
+
+/// Fetches the threads in the inbox, most recent first
+pub async fn fetch_conversations(profile: &Profile) -> Result<Vec<Conversation>> {
+    get_json(profile, "/api/v1/conversations", &[("per_page", "50")]).await
+}
+
+/// Fetches a thread with its messages, which Canvas marks read
+pub async fn fetch_thread(profile: &Profile, id: u64) -> Result<Thread> {
+    get_json(profile, &format!("/api/v1/conversations/{id}"), &[]).await
+}
+
+/// Adds a message to the thread and fetches it again with the message in it
+pub async fn reply(profile: &Profile, id: u64, body: &str) -> Result<Thread> {
+    let _: serde_json::Value = post_json(
+        profile,
+        &format!("/api/v1/conversations/{id}/add_message"),
+        &json!({ "body": body }),
+    )
+    .await?;
+    fetch_thread(profile, id).await
+}
+
+/// Conversation threads with their unread counts, one of them open for reading and replying
+pub struct InboxScreen {
+    /// None while they are being fetched
+    conversations: Option<Vec<Conversation>>,
+    pub table_state: TableState,
+    /// The thread being read, None while it is being fetched
+    pub thread: Option<Option<Thread>>,
+    /// Lines the thread is scrolled down by
+    pub scroll: u16,
+    /// Reply being written to the open thread
+    pub compose: Option<TextArea>,
+    /// Whether the reply is on its way to Canvas
+    pub sending: bool,
+}
+
+impl InboxScreen {
+    pub fn loading() -> Self {
+        Self {
+            conversations: None,
+            table_state: TableState::default(),
+            thread: None,
+            scroll: 0,
+            compose: None,
+            sending: false,
+        }
+    }
+
+    fn set_conversations(&mut self, conversations: Vec<Conversation>) {
+        self.table_state
+            .select((!conversations.is_empty()).then_some(0));
+        self.conversations = Some(conversations);
+    }
+
+    pub fn selected(&self) -> Option<&Conversation> {
+        self.conversations
+            .as_ref()?
+            .get(self.table_state.selected()?)
+    }
+
+    fn unread_count(&self) -> usize {
+        self.conversations
+            .iter()
+            .flatten()
+            .filter(|c| c.is_unread())
+            .count()
+    }
+
+    /// Opens the selected conversation while its messages are fetched, marking it read
+    pub fn open_selected(&mut self) -> Option<u64> {
+        let index = self.table_state.selected()?;
+        let conversation = self.conversations.as_mut()?.get_mut(index)?;
+        conversation.workflow_state = "read".to_string();
+        self.thread = Some(None);
+        self.scroll = 0;
+        Some(conversation.id)
+    }
+
+    /// Shows the fetched thread if it is still the one open
+    fn set_thread(&mut self, thread: Thread) {
+        let is_open = self.thread.is_some()
+            && self
+                .selected()
+                .is_some_and(|selected| selected.id == thread.id);
+        if is_open {
+            if let Some(conversation) = self
+                .conversations
+                .iter_mut()
+                .flatten()
+                .find(|conversation| conversation.id == thread.id)
+                && let Some(message) = thread.messages.first()
+            {
+                conversation.last_message_at = Some(message.created_at);
+                conversation.message_count = thread.messages.len() as u32;
+            }
+            self.thread = Some(Some(thread));
+        }
+    }
+
+    fn render_list(&mut self, area: Rect, buf: &mut Buffer) {
+        let [title_area, table_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(area);
+        let Some(conversations) = &self.conversations else {
+            Paragraph::new("Loading inbox...").render(area, buf);
+            return;
+        };
+        Paragraph::new(format!(
+            "Inbox · {} unread · Enter read · r reply",
+            self.unread_count()
+        ))
+        .style(Style::default().fg(theme::current().heading).bold())
+        .render(title_area, buf);
+        if conversations.is_empty() {
+            Paragraph::new("No conversations").render(table_area, buf);
+            return;
+        }
+
+        let header = ["", "Date", "Course", "Subject", "Messages"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(Style::default().fg(theme::current().heading));
+        let rows: Vec<Row> = conversations
+            .iter()
+            .map(|conversation| {
+                let date = conversation
+                    .last_message_at
+                    .map(|at| locale::current().format(to_local(at).date(), "%a %b %-d"))
+                    .unwrap_or_default();
+                let mut subject = Line::raw(redact::mask(conversation.subject()));
+                let style = match conversation.is_unread() {
+                    true => {
+                        subject = subject.bold();
+                        Style::default().fg(theme::current().text)
+                    }
+                    false => Style::default().fg(theme::current().secondary),
+                };
+                Row::new([
+                    Cell::from(match conversation.is_unread() {
+                        true => "●",
+                        false => "",
+                    })
+                    .style(Style::default().fg(theme::current().warning)),
+                    Cell::from(date),
+                    Cell::from(redact::mask(
+                        conversation.context_name.as_deref().unwrap_or_default(),
+                    ))
+                    .style(Style::default().fg(theme::current().accent)),
+                    Cell::from(subject),
+                    Cell::from(conversation.message_count.to_string()),
+                ])
+                .style(style)
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(1),
+                Constraint::Length(12),
+                Constraint::Percentage(25),
+                Constraint::Fill(1),
+                Constraint::Length(8),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::default().bg(theme::current().selection));
+        StatefulWidget::render(table, table_area, buf, &mut self.table_state);
+    }
+
+    fn render_thread(&mut self, area: Rect, buf: &mut Buffer) {
+        let (thread_area, compose_area) = match &self.compose {
+            Some(_) => {
+                let [thread_area, compose_area] =
+                    Layout::vertical([Constraint::Fill(1), Constraint::Length(COMPOSE_HEIGHT)])
+                        .areas(area);
+                (thread_area, Some(compose_area))
+            }
+            None => (area, None),
+        };
+        let [title_area, messages_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(thread_area);
+        let subject = self
+            .selected()
+            .map(|conversation| redact::mask(conversation.subject()))
+            .unwrap_or_default();
+        Paragraph::new(format!("{subject} · r reply"))
+            .style(Style::default().fg(theme::current().heading).bold())
+            .render(title_area, buf);
+
+        match self.thread.as_ref().and_then(Option::as_ref) {
+            None => Paragraph::new("Loading messages...").render(messages_area, buf),
+            Some(thread) => {
+                let mut lines = vec![];
+                for message in thread.messages.iter().rev() {
+                    let at = to_local(message.created_at);
+                    lines.push(Line::styled(
+                        format!(
+                            "{} · {} {:02}:{:02}",
+                            redact::mask(thread.author(message.author_id)),
+                            locale::current().format(at.date(), "%a %b %-d"),
+                            at.hour(),
+                            at.minute()
+                        ),
+                        Style::default().fg(theme::current().accent).bold(),
+                    ));
+                    lines.extend(
+                        message
+                            .body
+                            .lines()
+                            .map(|line| Line::raw(redact::mask(line))),
+                    );
+                    lines.push(Line::raw(""));
+                }
+                Paragraph::new(lines)
+                    .wrap(Wrap { trim: false })
+                    .scroll((self.scroll, 0))
+                    .style(Style::default().fg(theme::current().text))
+                    .render(messages_area, buf);
+            }
+        }
+
+        if let (Some(compose), Some(compose_area)) = (&mut self.compose, compose_area) {
+            let title = match self.sending {
+                true => " Sending... ",
+                false => " Reply · Ctrl-S send · Esc discard ",
+            };
+            compose.render(compose_area, buf, title);
+        }
+    }
+}
+
+impl Component for InboxScreen {
+    fn init(&mut self, profile: &Profile, action_tx: &UnboundedSender<Action>) -> Result<()> {
+        let profile = profile.clone();
+        let action_tx = action_tx.clone();
+        tokio::spawn(async move {
+            let action = match fetch_conversations(&profile).await {
+                Ok(conversations) => Action::ConversationsFetched(conversations),
+                Err(e) => Action::Error(format!("Loading the inbox failed: {e}")),
+            };
+            let _ = action_tx.send(action);
+        });
+        Ok(())
+    }
+
+    fn handle_event(&mut self, key: KeyEvent, bound: Action) -> Action {
+        match key.code {
+            KeyCode::Enter if self.thread.is_none() => return Action::OpenConversation,
+            KeyCode::Char('r') => return Action::ComposeReply,
+            _ => {}
+        }
+        match bound {
+            Action::Quit
+            | Action::Navigate(Navigation::PrevEvent | Navigation::NextEvent)
+            | Action::ToggleView(View::Inbox) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, _context: &Context) -> Option<Action> {
+        let navigation = match action {
+            Action::Navigate(navigation) => navigation,
+            Action::ConversationsFetched(conversations) => {
+                self.set_conversations(conversations.clone());
+                return None;
+            }
+            Action::ConversationFetched(thread) => {
+                self.set_thread(thread.clone());
+                return None;
+            }
+            Action::ReplySent(thread) => {
+                self.sending = false;
+                self.compose = None;
+                self.set_thread(thread.clone());
+                return None;
+            }
+            Action::ReplyFailed { .. } => {
+                self.sending = false;
+                return None;
+            }
+            _ => return None,
+        };
+        match (navigation, self.thread.is_some()) {
+            (Navigation::PrevEvent, true) => self.scroll = self.scroll.saturating_sub(1),
+            (Navigation::NextEvent, true) => self.scroll = self.scroll.saturating_add(1),
+            (Navigation::PrevEvent, false) => self.table_state.select_previous(),
+            (Navigation::NextEvent, false) => self.table_state.select_next(),
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, _context: &Context) {
+        match &self.thread {
+            Some(_) => self.render_thread(area, buf),
+            None => self.render_list(area, buf),
+        }
+    }
+}
@@ -0,0 +1,363 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use color_eyre::eyre::{Report, Result, eyre};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    action::{Action, Navigation, PromptKind, View},
+    config::LinkFormat,
+};
+
+/// Default bindings by action name, each a list of alternative key sequences
+const DEFAULT_BINDINGS: &[(&str, &[&str])] = &[
+    ("quit", &["q", "C-c"]),
+    ("reset_date", &["0", "g g", "Home"]),
+    ("today", &["g t"]),
+    ("last_date", &["g e", "End"]),
+    ("goto_date", &[":"]),
+    ("prev_event", &["k", "Up"]),
+    ("next_event", &["j", "Down"]),
+    ("prev_date", &["h", "Left"]),
+    ("next_date", &["l", "Right"]),
+    ("fetch", &["u", "r", "F5"]),
+    ("open_url", &["o", "Enter"]),
+    ("show_detail", &["i"]),
+    ("open_prompt", &["O"]),
+    ("search", &["/"]),
+    ("edit_note", &["N"]),
+    ("link_file", &["F"]),
+    ("edit_files", &["e"]),
+    ("set_soft_deadline", &["d"]),
+    ("undo", &["C-o"]),
+    ("focus_next", &["w"]),
+    ("switch_profile", &["p"]),
+    ("next_panel", &["Tab"]),
+    ("toggle_digest", &["a"]),
+    ("toggle_fold", &["Space"]),
+    ("mark_read", &["M"]),
+    ("toggle_receipts", &["L"]),
+    ("toggle_audit", &["H"]),
+    ("toggle_modules", &["m"]),
+    ("open_syllabus", &["g s"]),
+    ("toggle_bookmark", &["b"]),
+    ("toggle_completed", &["x"]),
+    ("yank_markdown", &["y m"]),
+    ("yank_org", &["y o"]),
+    ("open_day", &["g o"]),
+    ("yank_day", &["y d"]),
+    ("export_reminder", &["E"]),
+    ("recheck_submission", &["R"]),
+    ("submit", &["s"]),
+    ("toggle_bookmarks", &["B"]),
+    ("toggle_reader", &["v"]),
+    ("toggle_links", &["U"]),
+    ("open_in_pager", &["P"]),
+    ("toggle_courses", &["c"]),
+    ("toggle_grades", &["G"]),
+    ("toggle_catch_up", &["C"]),
+    ("toggle_agenda", &["A"]),
+    ("cycle_layout", &["V"]),
+    ("toggle_triage", &["T"]),
+    ("toggle_course_filter", &["f"]),
+    ("focus_course", &["z"]),
+    ("toggle_announcements", &["n"]),
+    ("toggle_inbox", &["I"]),
+    ("toggle_todo", &["t"]),
+    ("toggle_metrics", &["D"]),
+    ("toggle_redact", &["S"]),
+    ("toggle_help", &["?"]),
+];
+
+fn bindable_action(name: &str) -> Option<Action> {
+    Some(match name {
+        "quit" => Action::Quit,
+        "reset_date" => Action::Navigate(Navigation::ResetDate),
+        "today" => Action::Navigate(Navigation::Today),
+        "last_date" => Action::Navigate(Navigation::LastDate),
+        "goto_date" => Action::OpenPrompt(PromptKind::Goto),
+        "prev_event" => Action::Navigate(Navigation::PrevEvent),
+        "next_event" => Action::Navigate(Navigation::NextEvent),
+        "prev_date" => Action::Navigate(Navigation::PrevDate),
+        "next_date" => Action::Navigate(Navigation::NextDate),
+        "fetch" => Action::Fetch,
+        "open_url" => Action::OpenURL,
+        "show_detail" => Action::ShowDetail,
+        "open_prompt" => Action::OpenPrompt(PromptKind::Open),
+        "search" => Action::OpenPrompt(PromptKind::Search),
+        "edit_note" => Action::EditNote,
+        "link_file" => Action::OpenPrompt(PromptKind::Link),
+        "edit_files" => Action::EditFiles,
+        "set_soft_deadline" => Action::OpenPrompt(PromptKind::SoftDeadline),
+        "undo" => Action::Undo,
+        "focus_next" => Action::FocusNext,
+        "switch_profile" => Action::SwitchProfile,
+        "next_panel" => Action::NextPanel,
+        "toggle_digest" => Action::ToggleDigest,
+        "toggle_fold" => Action::ToggleFold,
+        "mark_read" => Action::MarkRead,
+        "toggle_receipts" => Action::ToggleView(View::Receipts),
+        "toggle_audit" => Action::ToggleView(View::Audit),
+        "toggle_modules" => Action::ToggleView(View::Modules),
+        "open_syllabus" => Action::OpenSyllabus,
+        "toggle_bookmark" => Action::ToggleBookmark,
+        "toggle_completed" => Action::ToggleCompleted,
+        "yank_markdown" => Action::YankLink(LinkFormat::Markdown),
+        "yank_org" => Action::YankLink(LinkFormat::Org),
+        "open_day" => Action::OpenDay,
+        "yank_day" => Action::YankDay,
+        "export_reminder" => Action::ExportReminder,
+        "recheck_submission" => Action::RecheckSubmission,
+        "submit" => Action::OpenPrompt(PromptKind::Submit),
+        "toggle_bookmarks" => Action::ToggleView(View::Bookmarks),
+        "toggle_reader" => Action::ToggleView(View::Reader),
+        "toggle_links" => Action::ToggleView(View::Links),
+        "open_in_pager" => Action::OpenInPager,
+        "toggle_courses" => Action::ToggleView(View::Courses),
+        "toggle_grades" => Action::ToggleView(View::Grades),
+        "toggle_catch_up" => Action::ToggleView(View::CatchUp),
+        "toggle_agenda" => Action::ToggleView(View::Agenda),
+        "cycle_layout" => Action::CycleLayout,
+        "toggle_triage" => Action::ToggleView(View::Triage),
+        "focus_course" => Action::ToggleCourseFocus,
+        "toggle_course_filter" => Action::ToggleView(View::CourseFilter),
+        "toggle_announcements" => Action::ToggleView(View::Announcements),
+        "toggle_inbox" => Action::ToggleView(View::Inbox),
+        "toggle_todo" => Action::ToggleView(View::Todo),
+        "toggle_metrics" => Action::ToggleMetrics,
+        "toggle_redact" => Action::ToggleRedact,
+        "toggle_help" => Action::ToggleHelp,
+        _ => return None,
+    })
+}
+
+/// A key with its modifiers. Shift is dropped for characters since it is already part of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        if !matches!(event.code, KeyCode::Char(_)) {
+            modifiers |= event.modifiers & KeyModifiers::SHIFT;
+        }
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = Report;
+
+    /// Parses keys like `j`, `C-o` (Ctrl), `A-x` (Alt), `S-Tab`, `F5` or `PageDown`
+    fn from_str(s: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut name = s;
+        while name.len() > 2 {
+            let modifier = match &name[..2] {
+                "C-" => KeyModifiers::CONTROL,
+                "A-" => KeyModifiers::ALT,
+                "S-" => KeyModifiers::SHIFT,
+                _ => break,
+            };
+            modifiers |= modifier;
+            name = &name[2..];
+        }
+        let code = match name {
+            "Up" => KeyCode::Up,
+            "Down" => KeyCode::Down,
+            "Left" => KeyCode::Left,
+            "Right" => KeyCode::Right,
+            "Enter" => KeyCode::Enter,
+            "Esc" => KeyCode::Esc,
+            "Tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            "Tab" => KeyCode::Tab,
+            "Backspace" => KeyCode::Backspace,
+            "Delete" => KeyCode::Delete,
+            "Home" => KeyCode::Home,
+            "End" => KeyCode::End,
+            "PageUp" => KeyCode::PageUp,
+            "PageDown" => KeyCode::PageDown,
+            "Space" => KeyCode::Char(' '),
+            _ if name.len() > 1 && name.starts_with('F') => {
+                KeyCode::F(name[1..].parse().map_err(|_| eyre!("Unknown key {s:?}"))?)
+            }
+            _ => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(eyre!("Unknown key {s:?}")),
+                }
+            }
+        };
+        Ok(Key::from(KeyEvent::new(code, modifiers)))
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "C-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "A-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// Maps key sequences to actions, keeping track of a partially typed sequence
+pub struct Keymap {
+    bindings: Vec<(Vec<Key>, Action)>,
+    /// Key sequences of each action by name, for the help overlay
+    listing: Vec<(&'static str, Vec<String>)>,
+    pending: Vec<Key>,
+}
+
+impl Keymap {
+    /// Builds the default keymap, with the bindings of actions named in `overrides` replaced
+    pub fn new(overrides: &BTreeMap<String, Vec<String>>) -> Result<Self> {
+        if let Some(unknown) = overrides
+            .keys()
+            .find(|name| bindable_action(name).is_none())
+        {
+            return Err(eyre!("Unknown action {unknown:?} in [keys]"));
+        }
+        let mut bindings = vec![];
+        let mut listing = vec![];
+        for (name, default_sequences) in DEFAULT_BINDINGS {
+            let sequences = match overrides.get(*name) {
+                Some(sequences) => sequences.iter().map(String::as_str).collect(),
+                None => default_sequences.to_vec(),
+            };
+            let mut shown = vec![];
+            for sequence in sequences {
+                let keys = sequence
+                    .split_whitespace()
+                    .map(str::parse)
+                    .collect::<Result<Vec<Key>>>()?;
+                // Esc backs out before the keymap sees it, so a binding would never fire
+                if keys.iter().any(|key| key.code == KeyCode::Esc) {
+                    return Err(eyre!(
+                        "Esc is reserved for backing out and can't be bound to {name} in [keys]"
+                    ));
+                }
+                if !keys.is_empty() {
+                    shown.push(
+                        keys.iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    );
+                    bindings.push((keys, bindable_action(name).unwrap()));
+                }
+            }
+            listing.push((*name, shown));
+        }
+        Ok(Self {
+            bindings,
+            listing,
+            pending: vec![],
+        })
+    }
+
+    /// Feeds a key press in. Returns the bound action once a whole sequence is typed,
+    /// and `Action::None` while a sequence is incomplete or nothing matches
+    pub fn resolve(&mut self, event: KeyEvent) -> Action {
+        self.pending.push(Key::from(event));
+        if let Some(action) = self.lookup() {
+            return action;
+        }
+        if self.is_prefix() {
+            return Action::None;
+        }
+        // The sequence broke off, so start over from the key just pressed
+        self.pending = vec![Key::from(event)];
+        match self.lookup() {
+            Some(action) => action,
+            None if self.is_prefix() => Action::None,
+            None => {
+                self.pending.clear();
+                Action::None
+            }
+        }
+    }
+
+    /// Every bindable action by name with the key sequences bound to it
+    pub fn listing(&self) -> Vec<(&'static str, Vec<String>)> {
+        self.listing.clone()
+    }
+
+    /// Keys typed so far of an incomplete sequence
+    pub fn pending(&self) -> &[Key] {
+        &self.pending
+    }
+
+    pub fn clear_pending(&mut self) {
+        self.pending.clear();
+    }
+
+    fn lookup(&mut self) -> Option<Action> {
+        let action = self
+            .bindings
+            .iter()
+            .find(|(keys, _)| *keys == self.pending)
+            .map(|(_, action)| action.clone())?;
+        self.pending.clear();
+        Some(action)
+    }
+
+    fn is_prefix(&self) -> bool {
+        self.bindings
+            .iter()
+            .any(|(keys, _)| keys.len() > self.pending.len() && keys.starts_with(&self.pending))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(name: &str, sequence: &str) -> BTreeMap<String, Vec<String>> {
+        BTreeMap::from([(name.to_string(), vec![sequence.to_string()])])
+    }
+
+    #[test]
+    fn rejects_esc_bindings() {
+        assert!(Keymap::new(&keys("quit", "C-q")).is_ok());
+        for sequence in ["Esc", "g Esc", "C-Esc"] {
+            let error = Keymap::new(&keys("quit", sequence)).err().unwrap();
+            assert!(error.to_string().contains("reserved"), "{error}");
+        }
+    }
+
+    #[test]
+    fn default_bindings_do_not_collide() {
+        let mut seen = BTreeMap::new();
+        for (name, sequences) in DEFAULT_BINDINGS {
+            for sequence in *sequences {
+                if let Some(other) = seen.insert(*sequence, *name) {
+                    panic!("{sequence:?} is bound to both {other} and {name}");
+                }
+            }
+        }
+        for (sequence, name) in &seen {
+            let prefix = format!("{sequence} ");
+            if let Some((longer, other)) = seen.iter().find(|(other, _)| other.starts_with(&prefix))
+            {
+                panic!("{sequence:?} of {name} shadows {longer:?} of {other}");
+            }
+        }
+        assert_eq!(seen["s"], "submit");
+        assert_eq!(seen["R"], "recheck_submission");
+    }
+}
@@ -0,0 +1,297 @@
+use std::io::Write;
+
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout},
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    widgets::{Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use reqwest::Url;
+use time::format_description;
+
+use crate::{
+    action::{Action, Navigation, View},
+    component::{Component, Context},
+    fetch::CalendarEvent,
+    locale,
+    reader::{decode_entities, tag_name},
+    redact, theme,
+    workload::find_ignore_case,
+};
+
+/// A link found in a description
+#[derive(Debug, Clone)]
+pub struct Link {
+    /// Text of the anchor, empty for bare urls
+    text: String,
+    pub url: String,
+}
+
+/// Value of an attribute in a tag, e.g. `href`
+pub fn attribute(tag: &str, name: &str) -> Option<String> {
+    let start = find_ignore_case(tag, &format!("{name}="))? + name.len() + 1;
+    let rest = &tag[start..];
+    let value = match rest.chars().next()? {
+        quote @ ('"' | '\'') => rest[1..].split(quote).next()?,
+        _ => rest.split([' ', '>']).next()?,
+    };
+    Some(decode_entities(value.trim()))
+}
+
+/// Bare http(s) urls in text, without trailing punctuation
+fn bare_urls(text: &str) -> impl Iterator<Item = &str> {
+    text.split_whitespace()
+        .filter_map(|word| Some(&word[word.find("http")?..]))
+        .filter(|word| word.starts_with("https://") || word.starts_with("http://"))
+        .map(|url| url.trim_end_matches(['.', ',', ';', ':', ')', ']', '!', '?', '"', '\'']))
+}
+
+/// Collects the links of a description in order of appearance: anchors, embedded frames and urls
+/// written out in the text. Relative urls are resolved against the Canvas instance
+pub fn extract(html: &str, base: &str) -> Vec<Link> {
+    let base = base.parse::<Url>().ok();
+    let mut links: Vec<Link> = vec![];
+    let mut push = |text: String, url: &str| {
+        let url = match &base {
+            Some(base) => base.join(url).map_or(url.to_string(), String::from),
+            None => url.to_string(),
+        };
+        let scheme_ok = url.starts_with("http://") || url.starts_with("https://");
+        if scheme_ok && !links.iter().any(|link| link.url == url) {
+            links.push(Link { text, url });
+        }
+    };
+
+    let mut rest = html;
+    // Anchor being read, with the text seen so far
+    let mut anchor: Option<(String, String)> = None;
+    while !rest.is_empty() {
+        let tag_start = rest.find('<').unwrap_or(rest.len());
+        let text = decode_entities(&rest[..tag_start]);
+        match &mut anchor {
+            Some((_, anchor_text)) => anchor_text.push_str(&text),
+            None => bare_urls(&text).for_each(|url| push(String::new(), url)),
+        }
+        rest = &rest[tag_start..];
+        if rest.is_empty() {
+            break;
+        }
+        let tag_end = rest.find('>').map_or(rest.len(), |end| end + 1);
+        let tag = &rest[..tag_end];
+        rest = &rest[tag_end..];
+        let closing = tag.starts_with("</");
+        match (tag_name(tag).as_str(), closing) {
+            ("a", false) => anchor = attribute(tag, "href").map(|href| (href, String::new())),
+            ("a", true) => {
+                if let Some((href, text)) = anchor.take() {
+                    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                    push(text, &href);
+                }
+            }
+            ("iframe", false) => {
+                if let Some(src) = attribute(tag, "src") {
+                    push("Embedded content".to_string(), &src);
+                }
+            }
+            _ => {}
+        }
+    }
+    links
+}
+
+/// Fills the placeholders of a yank template in with the item, leaving unknown ones as they are
+pub fn fill_template(template: &str, event: &CalendarEvent, url: &str) -> String {
+    let time_format = format_description::parse("[hour]:[minute]").unwrap();
+    let time = event.due_at.format(&time_format).unwrap();
+    let date = event.due_at.date();
+    let mut filled = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        match &rest[1..end] {
+            "course" => filled.push_str(&event.course_name),
+            "title" => filled.push_str(&event.title),
+            "url" => filled.push_str(url),
+            "due" => filled.push_str(&format!("{} {time}", locale::current().format(date, "%a"))),
+            "deadline" => filled.push_str(&format!(
+                "{date} {} {time}",
+                // Org mode reads its timestamps with English day names
+                &event.due_at.weekday().to_string()[..3]
+            )),
+            _ => filled.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// Where copied text goes
+#[derive(Debug, Default)]
+pub enum Clipboard {
+    /// The clipboard of the terminal, set with an OSC 52 escape, which also works over SSH
+    #[default]
+    Terminal,
+    /// Kept in the order it was copied instead, for tests to read back
+    #[cfg(test)]
+    Captured(Vec<String>),
+}
+
+impl Clipboard {
+    pub fn copy(&mut self, text: &str) -> Result<()> {
+        match self {
+            Clipboard::Terminal => {
+                let mut stderr = std::io::stderr();
+                write!(stderr, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+                stderr.flush()?;
+            }
+            #[cfg(test)]
+            Clipboard::Captured(copied) => copied.push(text.to_string()),
+        }
+        Ok(())
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let value = chunk.iter().enumerate().fold(0u32, |value, (index, byte)| {
+            value | (*byte as u32) << (16 - 8 * index)
+        });
+        for index in 0..4 {
+            match index <= chunk.len() {
+                true => encoded.push(ALPHABET[(value >> (18 - 6 * index) & 0x3f) as usize] as char),
+                false => encoded.push('='),
+            }
+        }
+    }
+    encoded
+}
+
+/// Screen listing the links in an item's description
+pub struct LinksScreen {
+    pub html_url: String,
+    title: String,
+    /// None while the description is being fetched
+    links: Option<Vec<Link>>,
+    pub table_state: TableState,
+    /// Canvas instance relative links are resolved against
+    base: String,
+}
+
+impl LinksScreen {
+    pub fn new(html_url: String, title: String, base: String, description: Option<&str>) -> Self {
+        let mut screen = Self {
+            html_url,
+            title,
+            links: None,
+            table_state: TableState::default(),
+            base,
+        };
+        if let Some(description) = description {
+            screen.set_description(description);
+        }
+        screen
+    }
+
+    pub fn set_description(&mut self, description: &str) {
+        let links = extract(description, &self.base);
+        self.table_state = TableState::default().with_selected((!links.is_empty()).then_some(0));
+        self.links = Some(links);
+    }
+
+    /// Link by its number in the list, counting from 1
+    pub fn select_number(&mut self, number: usize) -> Option<&Link> {
+        let index = number.checked_sub(1)?;
+        let link = self.links.as_ref()?.get(index)?;
+        self.table_state.select(Some(index));
+        Some(link)
+    }
+
+    pub fn selected(&self) -> Option<&Link> {
+        self.links.as_ref()?.get(self.table_state.selected()?)
+    }
+}
+
+impl Component for LinksScreen {
+    fn handle_event(&mut self, key: KeyEvent, bound: Action) -> Action {
+        match key.code {
+            KeyCode::Char(c @ '1'..='9') => return Action::OpenLink(c as usize - '0' as usize),
+            KeyCode::Char('y') => return Action::CopyLink,
+            _ => {}
+        }
+        match bound {
+            Action::Quit
+            | Action::OpenURL
+            | Action::Navigate(Navigation::PrevEvent | Navigation::NextEvent)
+            | Action::ToggleView(View::Links) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, _context: &Context) -> Option<Action> {
+        match action {
+            Action::Navigate(Navigation::PrevEvent) => self.table_state.select_previous(),
+            Action::Navigate(Navigation::NextEvent) => self.table_state.select_next(),
+            Action::DetailsFetched {
+                html_url,
+                description,
+                ..
+            } if *html_url == self.html_url => self.set_description(description),
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, _context: &Context) {
+        let [title_area, table_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(area);
+        Paragraph::new(format!(
+            "Links in {} · 1-9 or o open · y copy",
+            redact::mask(&self.title)
+        ))
+        .style(Style::default().fg(theme::current().heading).bold())
+        .render(title_area, buf);
+        let Some(links) = &self.links else {
+            Paragraph::new("Loading description...").render(table_area, buf);
+            return;
+        };
+        if links.is_empty() {
+            Paragraph::new("No links in the description").render(table_area, buf);
+            return;
+        }
+        let rows = links.iter().enumerate().map(|(index, link)| {
+            Row::new([
+                Cell::from(format!("{:>2}.", index + 1))
+                    .style(Style::default().fg(theme::current().accent)),
+                Cell::from(redact::mask(&link.text)),
+                Cell::from(redact::mask(&link.url))
+                    .style(Style::default().fg(theme::current().external)),
+            ])
+        });
+        let text_width = links
+            .iter()
+            .map(|link| link.text.chars().count() as u16)
+            .max()
+            .unwrap_or_default()
+            .min(40);
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(3),
+                Constraint::Length(text_width),
+                Constraint::Fill(1),
+            ],
+        )
+        .row_highlight_style(Style::default().bg(theme::current().selection))
+        .style(Style::default().fg(theme::current().text));
+        StatefulWidget::render(table, table_area, buf, &mut self.table_state);
+    }
+}
@@ -0,0 +1,115 @@
+use color_eyre::eyre::Result;
+use reqwest::Url;
+use serde::Serialize;
+use time::{OffsetDateTime, format_description};
+
+use crate::{
+    config::Config,
+    due::{fetch_or_cache, parse_window},
+    fetch::{local_offset, to_local},
+    locale, redact,
+};
+
+/// How `canvastui list` prints the items
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListFormat {
+    /// Aligned for reading in a terminal
+    Text,
+    /// One tab-separated line per item, for status bars and `cut`
+    Plain,
+    Json,
+}
+
+/// An item as printed by `canvastui list --json`
+#[derive(Serialize)]
+struct ListedItem<'a> {
+    course: &'a str,
+    title: &'a str,
+    /// e.g. assignment, quiz or discussion_topic
+    kind: &'a str,
+    #[serde(with = "time::serde::rfc3339")]
+    due_at: OffsetDateTime,
+    url: String,
+    submitted: bool,
+    /// Handed in, or marked done on Canvas or locally
+    done: bool,
+}
+
+/// Prints the default profile's items due between now and `within` from now, fetched from
+/// Canvas or read from the cache when it can't be reached, without starting the TUI
+pub async fn print(within: &str, format: ListFormat) -> Result<()> {
+    let config = Config::load()?;
+    locale::init(&config.locale)?;
+    let within = parse_window(within)?;
+    let profile = config.default_profile()?;
+    let calendar = fetch_or_cache(&profile).await?;
+
+    let now = to_local(OffsetDateTime::now_utc());
+    let local_offset = local_offset();
+    let canvas_url = profile.url.parse::<Url>().ok();
+    let mut items = vec![];
+    for event in calendar
+        .dates
+        .iter()
+        .flat_map(|date| &date.events)
+        .filter(|event| !event.is_external() && event.due_at >= now && event.due_at <= now + within)
+    {
+        items.push(ListedItem {
+            course: &event.course_name,
+            title: &event.title,
+            kind: &event.plannable_type,
+            due_at: event.due_at.assume_offset(local_offset),
+            url: match &canvas_url {
+                Some(base) => base.join(&event.html_url)?.to_string(),
+                None => event.html_url.clone(),
+            },
+            submitted: event.submitted,
+            done: event.is_done(),
+        });
+    }
+
+    match format {
+        ListFormat::Json => {
+            redact::check_export()?;
+            println!("{}", serde_json::to_string_pretty(&items)?);
+        }
+        ListFormat::Plain => {
+            let due_format = format_description::parse("[year]-[month]-[day] [hour]:[minute]")?;
+            for item in &items {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    item.due_at.format(&due_format)?,
+                    redact::mask(item.course),
+                    redact::mask(item.title),
+                    match item.done {
+                        true => "done",
+                        false => "todo",
+                    }
+                );
+            }
+        }
+        ListFormat::Text => {
+            let locale = locale::current();
+            let width = items
+                .iter()
+                .map(|item| item.course.chars().count())
+                .max()
+                .unwrap_or_default();
+            for item in &items {
+                println!(
+                    "{} {:02}:{:02}  {:width$}  {}{}",
+                    locale.format(item.due_at.date(), "%a %b %-d"),
+                    item.due_at.hour(),
+                    item.due_at.minute(),
+                    redact::mask(item.course),
+                    redact::mask(item.title),
+                    match item.done {
+                        true => " (done)",
+                        false => "",
+                    }
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,176 @@
+use std::{env, sync::OnceLock};
+
+use color_eyre::eyre::{Result, eyre};
+use pure_rust_locales::{Locale, locale_match};
+use time::{Date, Duration};
+
+use crate::config::LocaleConfig;
+
+const DEFAULT_HEADER_FORMAT: &str = "%A %b %-d";
+
+/// Today, tomorrow and yesterday by language, for locales without configured labels
+const RELATIVE_LABELS: &[(&str, [&str; 3])] = &[
+    ("en", ["Today", "Tomorrow", "Yesterday"]),
+    ("de", ["Heute", "Morgen", "Gestern"]),
+    ("fr", ["Aujourd'hui", "Demain", "Hier"]),
+    ("es", ["Hoy", "Mañana", "Ayer"]),
+    ("it", ["Oggi", "Domani", "Ieri"]),
+    ("pt", ["Hoje", "Amanhã", "Ontem"]),
+    ("nl", ["Vandaag", "Morgen", "Gisteren"]),
+];
+
+static CURRENT: OnceLock<DateLocale> = OnceLock::new();
+
+/// Date names and formats for the configured locale
+#[derive(Debug)]
+pub struct DateLocale {
+    days: &'static [&'static str],
+    abbreviated_days: &'static [&'static str],
+    months: &'static [&'static str],
+    abbreviated_months: &'static [&'static str],
+    header_format: String,
+    /// Today, tomorrow and yesterday, or None when relative labels are off
+    relative: Option<[String; 3]>,
+}
+
+/// Sets the locale used for the rest of the program
+pub fn init(config: &LocaleConfig) -> Result<()> {
+    let locale = DateLocale::new(config)?;
+    let _ = CURRENT.set(locale);
+    Ok(())
+}
+
+pub fn current() -> &'static DateLocale {
+    CURRENT.get_or_init(|| {
+        DateLocale::new(&LocaleConfig::default()).expect("the default locale is valid")
+    })
+}
+
+/// Locale name from the environment the way the C library picks it, e.g. de_DE from de_DE.UTF-8
+fn env_locale() -> Option<String> {
+    ["LC_ALL", "LC_TIME", "LANG"]
+        .into_iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| {
+            value
+                .split(['.', '@'])
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        })
+}
+
+impl DateLocale {
+    pub fn new(config: &LocaleConfig) -> Result<Self> {
+        let locale = match &config.name {
+            Some(name) => {
+                Locale::try_from(name.as_str()).map_err(|_| eyre!("Unknown locale {name:?}"))?
+            }
+            // C, POSIX and anything unknown in the environment fall back to English names
+            None => env_locale()
+                .and_then(|name| Locale::try_from(name.as_str()).ok())
+                .unwrap_or(Locale::POSIX),
+        };
+        let language = match locale {
+            Locale::POSIX => "en".to_string(),
+            locale => locale
+                .to_string()
+                .split('_')
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        };
+        let builtin = RELATIVE_LABELS
+            .iter()
+            .find(|(code, _)| *code == language)
+            .or(RELATIVE_LABELS.first())
+            .map(|(_, labels)| labels.map(str::to_string))
+            .unwrap_or_default();
+        let relative = config.relative_dates.then(|| {
+            [&config.today, &config.tomorrow, &config.yesterday]
+                .into_iter()
+                .zip(builtin)
+                .map(|(configured, builtin)| configured.clone().unwrap_or(builtin))
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap()
+        });
+
+        Ok(Self {
+            days: locale_match!(locale => LC_TIME::DAY),
+            abbreviated_days: locale_match!(locale => LC_TIME::ABDAY),
+            months: locale_match!(locale => LC_TIME::MON),
+            abbreviated_months: locale_match!(locale => LC_TIME::ABMON),
+            header_format: config
+                .header_format
+                .clone()
+                .unwrap_or(DEFAULT_HEADER_FORMAT.to_string()),
+            relative,
+        })
+    }
+
+    /// Formats a date with a subset of strftime: %A %a %B %b %d %-d %e %m %-m %Y %y and %%
+    pub fn format(&self, date: Date, format: &str) -> String {
+        let weekday = date.weekday().number_days_from_sunday() as usize;
+        let month = date.month() as usize - 1;
+        let mut formatted = String::new();
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                formatted.push(c);
+                continue;
+            }
+            let mut specifier = chars.next();
+            let unpadded = specifier == Some('-');
+            if unpadded {
+                specifier = chars.next();
+            }
+            match specifier {
+                Some('A') => formatted.push_str(self.days[weekday]),
+                Some('a') => formatted.push_str(self.abbreviated_days[weekday]),
+                Some('B') => formatted.push_str(self.months[month]),
+                Some('b') => formatted.push_str(self.abbreviated_months[month]),
+                Some('d') if unpadded => formatted.push_str(&date.day().to_string()),
+                Some('d') => formatted.push_str(&format!("{:02}", date.day())),
+                Some('e') => formatted.push_str(&format!("{:>2}", date.day())),
+                Some('m') if unpadded => formatted.push_str(&(month + 1).to_string()),
+                Some('m') => formatted.push_str(&format!("{:02}", month + 1)),
+                Some('Y') => formatted.push_str(&date.year().to_string()),
+                Some('y') => formatted.push_str(&format!("{:02}", date.year() % 100)),
+                Some('%') => formatted.push('%'),
+                Some(other) => {
+                    formatted.push('%');
+                    if unpadded {
+                        formatted.push('-');
+                    }
+                    formatted.push(other);
+                }
+                None => formatted.push('%'),
+            }
+        }
+        formatted
+    }
+
+    pub fn abbreviated_weekday(&self, date: Date) -> &'static str {
+        self.abbreviated_days[date.weekday().number_days_from_sunday() as usize]
+    }
+
+    /// Header for a date, prefixed with a relative label when it is today, tomorrow or yesterday
+    pub fn header(&self, date: Date, today: Date) -> String {
+        let formatted = self.format(date, &self.header_format);
+        let label =
+            self.relative
+                .as_ref()
+                .and_then(|[today_label, tomorrow, yesterday]| match date {
+                    date if date == today => Some(today_label),
+                    date if date == today + Duration::days(1) => Some(tomorrow),
+                    date if date == today - Duration::days(1) => Some(yesterday),
+                    _ => None,
+                });
+        match label {
+            Some(label) => format!("{label} · {formatted}"),
+            None => formatted,
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Flex, Layout},
+    prelude::{Buffer, Rect},
+    style::Style,
+    text::Line,
+    widgets::{Paragraph, Widget},
+};
+
+use crate::{config::LockConfig, theme};
+
+/// Hides the planner after a period without input
+pub struct IdleLock {
+    config: Option<LockConfig>,
+    last_input: Instant,
+    /// Passphrase typed so far, Some while locked
+    input: Option<String>,
+    wrong_passphrase: bool,
+}
+
+impl IdleLock {
+    pub fn new(config: Option<LockConfig>) -> Self {
+        Self {
+            config,
+            last_input: Instant::now(),
+            input: None,
+            wrong_passphrase: false,
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.input.is_some()
+    }
+
+    /// Records user input so the idle time starts over
+    pub fn touch(&mut self) {
+        self.last_input = Instant::now();
+    }
+
+    /// Locks once the configured idle time has passed
+    pub fn check_idle(&mut self) {
+        let Some(config) = &self.config else {
+            return;
+        };
+        if !self.is_locked()
+            && self.last_input.elapsed() >= Duration::from_secs(config.idle_minutes * 60)
+        {
+            self.input = Some(String::new());
+            self.wrong_passphrase = false;
+        }
+    }
+
+    /// Handles a key pressed while locked. Without a passphrase any key unlocks
+    pub fn key(&mut self, code: KeyCode) {
+        let passphrase = self
+            .config
+            .as_ref()
+            .and_then(|config| config.passphrase.as_deref());
+        let (Some(passphrase), Some(input)) = (passphrase, self.input.as_mut()) else {
+            self.input = None;
+            return;
+        };
+        match code {
+            KeyCode::Enter if input == passphrase => self.input = None,
+            KeyCode::Enter => {
+                input.clear();
+                self.wrong_passphrase = true;
+            }
+            KeyCode::Esc => input.clear(),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+}
+
+impl Widget for &IdleLock {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let Some(input) = &self.input else {
+            return;
+        };
+        let has_passphrase = self
+            .config
+            .as_ref()
+            .is_some_and(|config| config.passphrase.is_some());
+        let mut lines = vec![Line::from("Locked after inactivity")];
+        match has_passphrase {
+            true => {
+                lines.push(Line::from(format!(
+                    "Passphrase: {}█",
+                    "*".repeat(input.chars().count())
+                )));
+                if self.wrong_passphrase {
+                    lines.push(
+                        Line::from("Wrong passphrase")
+                            .style(Style::default().fg(theme::current().error)),
+                    );
+                }
+            }
+            false => lines.push(Line::from("Press any key to resume")),
+        }
+        let [area] = Layout::vertical([Constraint::Length(lines.len() as u16)])
+            .flex(Flex::Center)
+            .areas(area);
+        Paragraph::new(lines)
+            .centered()
+            .style(Style::default().fg(theme::current().heading))
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,254 @@
+//! Signing in with an access token kept in the OS keyring (Secret Service, Keychain or Credential
+//! Manager) instead of the config file or the environment
+
+use std::path::PathBuf;
+
+use color_eyre::eyre::{Result, eyre};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Flex, Layout},
+    style::Style,
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph, Widget, Wrap},
+};
+use serde::Deserialize;
+
+use crate::{
+    config::Profile,
+    fetch::get_json,
+    store::data_dir,
+    theme,
+    tui::{Event, Tui},
+};
+
+/// Service the token is stored under in the keyring, with the Canvas url as the user
+const KEYRING_SERVICE: &str = "canvastui";
+/// Canvas url entered in the login form, which isn't secret and stays out of the keyring
+const URL_FILE: &str = "canvastui/canvas_url";
+const FORM_WIDTH: u16 = 70;
+const FORM_HEIGHT: u16 = 12;
+
+#[derive(Deserialize)]
+struct CanvasUser {
+    name: String,
+}
+
+fn url_path() -> Option<PathBuf> {
+    Some(data_dir()?.join(URL_FILE))
+}
+
+/// Canvas url saved by the last login
+pub fn saved_url() -> Option<String> {
+    let url = std::fs::read_to_string(url_path()?).ok()?;
+    Some(url.trim().to_string()).filter(|url| !url.is_empty())
+}
+
+/// Runs a keyring call on a thread of its own. The Secret Service client blocks on a runtime of
+/// its own, which can't be started from the app's
+fn off_runtime<T: Send + 'static>(call: impl FnOnce() -> T + Send + 'static) -> T {
+    std::thread::spawn(call)
+        .join()
+        .expect("the keyring call panicked")
+}
+
+/// Token stored in the keyring for the Canvas instance, None if there is none or no keyring
+pub fn keyring_token(url: &str) -> Option<String> {
+    let url = url.to_string();
+    off_runtime(move || {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, &url)?;
+        entry.get_password()
+    })
+    .inspect_err(|e| {
+        if !matches!(e, keyring::Error::NoEntry) {
+            log::error!("Could not read the token from the keyring: {e}");
+        }
+    })
+    .ok()
+}
+
+/// Stores the token in the keyring and remembers the url it is for
+fn store(url: &str, token: &str) -> Result<()> {
+    let (url_owned, token) = (url.to_string(), token.to_string());
+    off_runtime(move || {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, &url_owned)?;
+        entry.set_password(&token)
+    })
+    .map_err(|e| eyre!("Could not save the token to the keyring: {e}"))?;
+    let path = url_path().ok_or(eyre!("No home directory to save the url in"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, url)?;
+    Ok(())
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Url,
+    Token,
+}
+
+/// Asks for the Canvas url and an access token in the terminal
+struct LoginForm {
+    url: String,
+    token: String,
+    focus: Field,
+    /// What went wrong with the last attempt, or that one is being checked
+    message: Option<(String, bool)>,
+}
+
+impl LoginForm {
+    /// Handles a key, returning true once the form is to be submitted
+    fn key(&mut self, key: KeyEvent) -> bool {
+        let input = match self.focus {
+            Field::Url => &mut self.url,
+            Field::Token => &mut self.token,
+        };
+        match key.code {
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                self.focus = match self.focus {
+                    Field::Url => Field::Token,
+                    Field::Token => Field::Url,
+                };
+            }
+            KeyCode::Enter if self.focus == Field::Url => self.focus = Field::Token,
+            KeyCode::Enter => return true,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => input.clear(),
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+        false
+    }
+
+    fn render(&self, frame: &mut ratatui::Frame) {
+        let [area] = Layout::vertical([Constraint::Length(FORM_HEIGHT)])
+            .flex(Flex::Center)
+            .areas(frame.area());
+        let [area] = Layout::horizontal([Constraint::Length(FORM_WIDTH)])
+            .flex(Flex::Center)
+            .areas(area);
+        Clear.render(area, frame.buffer_mut());
+        let block = Block::default()
+            .title(" Sign in to Canvas ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme::current().heading));
+        let inner = block.inner(area);
+        block.render(area, frame.buffer_mut());
+
+        let field = |name: &str, value: String, field: Field| {
+            let cursor = if self.focus == field { "█" } else { "" };
+            Line::styled(
+                format!("{name:<14}{value}{cursor}"),
+                Style::default().fg(match self.focus == field {
+                    true => theme::current().text,
+                    false => theme::current().secondary,
+                }),
+            )
+        };
+        let mut lines = vec![
+            Line::styled(
+                "Generate a token in Canvas under Account > Settings > New Access Token",
+                Style::default().fg(theme::current().muted),
+            ),
+            Line::raw(""),
+            field("Canvas url", self.url.clone(), Field::Url),
+            // The token is as good as a password
+            field(
+                "Access token",
+                "•".repeat(self.token.chars().count()),
+                Field::Token,
+            ),
+            Line::raw(""),
+        ];
+        if let Some((message, failed)) = &self.message {
+            lines.push(Line::styled(
+                message.as_str(),
+                Style::default().fg(match failed {
+                    true => theme::current().error,
+                    false => theme::current().warning,
+                }),
+            ));
+        }
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .render(inner, frame.buffer_mut());
+        let [_, help_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner);
+        Paragraph::new("Tab next field · Enter sign in · Esc quit")
+            .style(Style::default().fg(theme::current().muted))
+            .render(help_area, frame.buffer_mut());
+    }
+}
+
+/// The url as typed, with `https://` added when it was left out
+fn normalize(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    match url.contains("://") {
+        true => url.to_string(),
+        false => format!("https://{url}"),
+    }
+}
+
+/// Checks the url and token against Canvas, returning the name of the user they sign in as
+async fn validate(url: &str, token: &str) -> Result<String> {
+    let profile = Profile {
+        url: url.to_string(),
+        token: token.to_string(),
+        ..Profile::offline()
+    };
+    let user: CanvasUser = get_json(&profile, "/api/v1/users/self", &[]).await?;
+    Ok(user.name)
+}
+
+/// Shows the login form until Canvas accepts what was entered and it is stored, returning the
+/// url and the user signed in as. None if the user quit instead
+pub async fn form(tui: &mut Tui, url: Option<String>) -> Result<Option<(String, String)>> {
+    let mut form = LoginForm {
+        focus: match url {
+            Some(_) => Field::Token,
+            None => Field::Url,
+        },
+        url: url.unwrap_or_default(),
+        token: String::new(),
+        message: None,
+    };
+    loop {
+        match tui.next().await? {
+            Event::Render => {
+                tui.draw(|frame| form.render(frame))?;
+            }
+            Event::Key(key)
+                if key.code == KeyCode::Esc
+                    || (key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL)) =>
+            {
+                return Ok(None);
+            }
+            Event::Key(key) => {
+                if !form.key(key) {
+                    continue;
+                }
+                if form.url.trim().is_empty() || form.token.trim().is_empty() {
+                    form.message = Some(("Enter both the url and a token".to_string(), true));
+                    continue;
+                }
+                form.message = Some(("Checking with Canvas...".to_string(), false));
+                tui.draw(|frame| form.render(frame))?;
+                let url = normalize(&form.url);
+                let token = form.token.trim();
+                let signed_in = match validate(&url, token).await {
+                    Ok(name) => store(&url, token).map(|_| name),
+                    Err(e) => Err(eyre!("Canvas didn't accept them: {e}")),
+                };
+                match signed_in {
+                    Ok(name) => return Ok(Some((url, name))),
+                    Err(e) => form.message = Some((e.to_string(), true)),
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use color_eyre::eyre::{Result, eyre};
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::{
+    config::Profile,
+    fetch::{Calendar, get_json},
+};
+
+/// Something the user pasted to find a planner item with
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    /// Path of a Canvas url, e.g. /courses/1/assignments/2
+    Path(String),
+    /// Bare assignment id
+    Id(u64),
+}
+
+impl Target {
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if let Ok(id) = input.parse() {
+            return Some(Target::Id(id));
+        }
+        let path = match input.parse::<Url>() {
+            Ok(url) => url.path().to_string(),
+            Err(_) if input.starts_with('/') => input
+                .split(['?', '#'])
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            Err(_) => return None,
+        };
+        Some(Target::Path(path.trim_end_matches('/').to_string()))
+    }
+
+    /// Course and assignment id for urls pointing at an assignment, and the assignment id alone
+    /// for bare ids
+    pub fn assignment_ids(&self) -> Option<(Option<u64>, u64)> {
+        let path = match self {
+            Target::Id(id) => return Some((None, *id)),
+            Target::Path(path) => path,
+        };
+        match path.split('/').collect::<Vec<_>>()[..] {
+            ["", "courses", course_id, "assignments", assignment_id] => {
+                Some((Some(course_id.parse().ok()?), assignment_id.parse().ok()?))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Reduces a course code or name to lowercase alphanumerics so "CS 101" matches "cs101"
+pub fn normalize_course(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_lowercase()
+}
+
+impl Calendar {
+    /// Returns the date and event index of the item matching the target
+    pub fn find(&self, target: &Target) -> Option<(usize, usize)> {
+        self.dates
+            .iter()
+            .enumerate()
+            .find_map(|(date_index, date)| {
+                date.events
+                    .iter()
+                    .position(|event| match target {
+                        // Quizzes and discussions have ids of their own that can be the same
+                        Target::Id(id) => {
+                            event.plannable_type == "assignment" && event.plannable_id == *id
+                        }
+                        Target::Path(path) => {
+                            event.html_url.split(['?', '#']).next() == Some(path.as_str())
+                        }
+                    })
+                    .map(|event_index| (date_index, event_index))
+            })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Course {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub course_code: String,
+}
+
+/// Every course the user is enrolled in, including concluded ones
+pub async fn fetch_courses(profile: &Profile) -> Result<Vec<Course>> {
+    get_json(
+        profile,
+        "/api/v1/courses",
+        &[
+            ("per_page", "100"),
+            ("state[]", "available"),
+            ("state[]", "completed"),
+        ],
+    )
+    .await
+}
+
+/// Finds an enrolled course by id, course code or name
+pub async fn find_course(profile: &Profile, query: &str) -> Result<Course> {
+    let courses = fetch_courses(profile).await?;
+    let query = normalize_course(query);
+    courses
+        .into_iter()
+        .find(|course| {
+            course.id.to_string() == query
+                || normalize_course(&course.course_code).contains(&query)
+                || normalize_course(&course.name).contains(&query)
+        })
+        .ok_or(eyre!("No enrolled course matches {query:?}"))
+}
@@ -1,375 +1,3088 @@
+mod action;
+mod agenda;
+mod announcements;
+mod archive;
+mod audit;
+mod backup;
+mod bookmarks;
+mod caldav;
+mod canvas;
+mod capabilities;
+mod catchup;
+mod cli;
+mod compat;
+mod component;
+mod config;
+mod confirm;
+mod course_filter;
+mod courses;
+mod dashboard;
+mod detail;
+mod due;
+#[cfg(test)]
+mod e2e;
+mod editor;
+mod feed;
 mod fetch;
+mod grades;
+mod grading;
+mod grid;
+mod header;
+mod help;
+mod ics;
+mod inbox;
+mod keymap;
+mod links;
+mod list;
+mod locale;
+mod lock;
+mod login;
+mod lookup;
+mod metrics;
+mod mode;
+mod modules;
+#[cfg(feature = "netsim")]
+mod netsim;
+mod notes;
+mod notify;
+mod pager;
+mod permissions;
+mod popups;
+mod posting;
+mod preferences;
+mod printout;
+mod ratelimit;
+mod reader;
+mod receipts;
+mod redact;
+mod reminders;
+mod screen;
+mod sections;
+mod session;
+mod shell_prompt;
+mod sinks;
+mod sso;
+mod startup;
+mod status_bar;
+mod store;
+mod streak;
+mod submission;
+mod syllabus;
+mod sync;
+mod textarea;
+mod theme;
+mod toast;
+mod todo;
+mod triage;
 mod tui;
+mod workload;
 
-use std::env;
+use std::{
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
 
-use crossterm::event::KeyCode::Char;
+use clap::Parser;
+use crossterm::event::{
+    KeyCode::{self, Char},
+    KeyEvent, KeyModifiers,
+};
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{Result, eyre};
 use ratatui::{
     Frame,
-    layout::{Alignment, Constraint, Layout},
+    layout::{Constraint, Layout},
     prelude::{Buffer, Rect},
-    style::{Color, Style, Stylize},
-    widgets::{
-        Block, BorderType, Borders, Cell, Padding, Paragraph, Row, StatefulWidget, Table, Widget,
-        calendar::{CalendarEventStore, Monthly},
-    },
+    style::Style,
+    widgets::{Block, Borders, Paragraph, Widget},
 };
 use reqwest::Url;
-use time::{OffsetDateTime, format_description};
+use time::{Date, OffsetDateTime, PrimitiveDateTime, format_description};
 use tokio::sync::mpsc::{self, UnboundedSender};
 use tui::Event;
 
-use crate::fetch::{Calendar, fetch};
+use crate::{
+    action::{Action, Dispatcher, Navigation, Position, PromptKind, View},
+    agenda::AgendaScreen,
+    announcements::AnnouncementsScreen,
+    audit::{AuditLog, AuditScreen},
+    bookmarks::{Bookmark, Bookmarks, BookmarksScreen},
+    catchup::{CatchUpScreen, PlanEdit},
+    cli::{Cli, Command},
+    component::{Component, Context},
+    config::{
+        CalDavConfig, CatchUpConfig, Config, ConfirmConfig, DefaultView, GradesConfig, Profile,
+        RemindersConfig, YankConfig,
+    },
+    course_filter::{CourseFilterSidebar, HiddenCourses},
+    courses::{CourseContext, CourseRoute, CoursesScreen, fetch_route, syllabus_url},
+    dashboard::{DASHBOARD_MIN_WIDTH, Dashboard, Panel, fetch_dashboard},
+    detail::{DetailPopup, fetch_detail},
+    fetch::{
+        Calendar, CalendarEvent, fetch, fetch_assignment, fetch_grade_comment, fetch_missing,
+        fetch_submitted, now_local, put, set_planner_completed,
+    },
+    grading::GradingScreen,
+    grid::{GridLayout, GridScreen},
+    header::Header,
+    help::HelpOverlay,
+    inbox::InboxScreen,
+    keymap::Keymap,
+    links::{Clipboard, LinksScreen},
+    list::ListFormat,
+    lock::IdleLock,
+    lookup::Target,
+    mode::Mode,
+    modules::ModulesScreen,
+    notes::DateNotes,
+    notify::Notifier,
+    permissions::Capability,
+    popups::Popups,
+    posting::Draft,
+    reader::ReaderScreen,
+    receipts::ReceiptsScreen,
+    screen::{Screen, Screens},
+    sections::SectionFilters,
+    session::{FetchState, SearchDirection, Session},
+    startup::Step,
+    status_bar::StatusBar,
+    store::{Annotations, Reminder, Snooze, SoftDeadline},
+    streak::History,
+    submission::Submission,
+    textarea::TextArea,
+    toast::Toast,
+    todo::TodoScreen,
+    triage::{Change, ChangeKind, Decision, TriageScreen},
+};
 
 const CACHE_FILE: &str = "/tmp/canvastui.json";
 
+/// Frames of the spinner shown while a refresh is in flight, in ASCII so every terminal has them
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/// Columns taken by the course filter sidebar
+const COURSE_FILTER_WIDTH: u16 = 32;
+
 struct App {
-    calendar: Calendar,
+    /// One session per profile, rendered side by side
+    sessions: Vec<Session>,
+    /// Index of the session receiving navigation keys
+    focus: usize,
     should_quit: bool,
     action_tx: UnboundedSender<Action>,
-    longest_item_lens: (u16, u16, u16),
-    received_fetch: bool,
-    current_date_index: usize,
-}
-
-#[derive(Clone)]
-pub enum Action {
-    Tick,
-    FetchComplete(Calendar),
-    FileFetchComplete(Calendar),
-    Fetch,
-    Quit,
-    Render,
-    NextEvent,
-    PrevEvent,
-    ResetDate,
-    NextDate,
-    PrevDate,
-    OpenURL,
-    None,
+    notifier: Notifier,
+    /// Read-only events from external ICS calendars
+    overlay: Vec<CalendarEvent>,
+    annotations: Annotations,
+    /// Screens shown instead of the sessions while open
+    screens: Screens,
+    /// Detail, metrics, help and errors over whatever screen is shown
+    popups: Popups,
+    status_bar: StatusBar,
+    /// Date whose note is edited in the user's editor once the current actions are handled
+    note_to_edit: Option<Date>,
+    /// Post written in the user's editor once the current actions are handled
+    draft_to_edit: Option<Draft>,
+    /// Markdown shown in the user's pager once the current actions are handled
+    to_page: Option<String>,
+    /// Files opened in the user's editor once the current actions are handled
+    files_to_edit: Option<Vec<PathBuf>>,
+    /// Item to jump to once planner data arrives, from `canvastui open`
+    pending_open: Option<Target>,
+    bookmarks: Bookmarks,
+    /// Items due on past days and whether they got done, for the streak
+    completion: History,
+    /// Section grade entry is filtered to in each course
+    section_filters: SectionFilters,
+    /// Courses left out of the planner views with the course filter, per profile
+    hidden_courses: HiddenCourses,
+    /// Course checkboxes, shown beside the planner while open
+    course_filter: Option<CourseFilterSidebar>,
+    /// New and changed items waiting to be triaged, oldest first
+    triage_queue: Vec<Change>,
+    catch_up_config: CatchUpConfig,
+    grades_config: GradesConfig,
+    yank_config: YankConfig,
+    reminders_config: RemindersConfig,
+    clipboard: Clipboard,
+    /// Which actions ask before they are carried out
+    confirm_config: ConfirmConfig,
+    caldav_config: Option<CalDavConfig>,
+    /// Minutes between automatic refetches
+    refresh_minutes: Option<u64>,
+    last_refresh: Instant,
+    /// Local date as of the last tick, to notice midnight passing
+    today: Date,
+    /// Resolves instructors when startup steps are run again
+    show_instructors: bool,
+    /// Profiles the focused session can be switched to, in the order they are cycled through
+    profiles: Vec<Profile>,
+    /// Positions before past navigations, most recent last
+    history: Vec<Position>,
+    /// What recent actions led to, for the audit trail
+    audit: AuditLog,
+    dashboard: Dashboard,
+    keymap: Keymap,
+    lock: IdleLock,
 }
 
 impl App {
-    pub fn calculate_longest_item_lens(&mut self) {
-        self.calendar.dates.iter().for_each(|date| {
-            date.events.iter().for_each(|event| {
-                let course_name_len = event.course_name.len() as u16;
-                let title_len = event.title.len() as u16;
-                let due_at_len = event
-                    .due_at
-                    .format(&format_description::parse("  [hour]:[minute]").unwrap())
-                    .unwrap()
-                    .len() as u16;
-                self.longest_item_lens = (
-                    course_name_len.max(self.longest_item_lens.0),
-                    title_len.max(self.longest_item_lens.1),
-                    due_at_len.max(self.longest_item_lens.2),
-                );
-            });
-        });
+    /// Sets up the app for the profiles, with nothing fetched yet
+    fn new(
+        config: Config,
+        profiles: Vec<Profile>,
+        agenda: bool,
+        annotations: Annotations,
+        action_tx: UnboundedSender<Action>,
+    ) -> Result<Self> {
+        let notes = DateNotes::load()?;
+        let hidden_courses = HiddenCourses::load()?;
+        let sessions: Vec<Session> = profiles
+            .iter()
+            .cloned()
+            .map(|profile| Session {
+                notes: notes.clone(),
+                hidden_courses: hidden_courses.get(&profile.name),
+                time_buckets: config.time_buckets,
+                ..Session::new(profile)
+            })
+            .collect();
+        Ok(Self {
+            screens: Screens {
+                agenda: agenda.then(|| AgendaScreen::new(&sessions)),
+                triage: (config.default_view == DefaultView::Triage).then(TriageScreen::default),
+                ..Screens::default()
+            },
+            sessions,
+            focus: 0,
+            should_quit: false,
+            action_tx,
+            notifier: Notifier::new(config.notifications),
+            overlay: vec![],
+            annotations,
+            popups: Popups::default(),
+            status_bar: StatusBar::default(),
+            note_to_edit: None,
+            draft_to_edit: None,
+            to_page: None,
+            files_to_edit: None,
+            pending_open: None,
+            bookmarks: Bookmarks::load()?,
+            completion: History::load()?,
+            section_filters: SectionFilters::load()?,
+            hidden_courses,
+            course_filter: None,
+            triage_queue: vec![],
+            refresh_minutes: config.refresh_minutes,
+            last_refresh: Instant::now(),
+            today: OffsetDateTime::now_local()?.date(),
+            show_instructors: config.show_instructors,
+            profiles,
+            catch_up_config: config.catch_up,
+            grades_config: config.grades,
+            yank_config: config.yank,
+            reminders_config: config.reminders,
+            clipboard: Clipboard::default(),
+            confirm_config: config.confirm,
+            caldav_config: config.caldav,
+            history: vec![],
+            audit: AuditLog::default(),
+            dashboard: Dashboard::new(),
+            keymap: Keymap::new(&config.keys)?,
+            lock: IdleLock::new(config.lock),
+        })
     }
-}
 
-impl Widget for &mut App {
-    fn render(self, area: Rect, buf: &mut Buffer)
-    where
-        Self: Sized,
-    {
-        if self.calendar.dates.is_empty() {
-            Paragraph::new("Waiting for data...").render(area, buf);
+    /// Whether keys go to the reply being written in the inbox
+    fn composing(&self) -> bool {
+        self.screens
+            .inbox
+            .as_ref()
+            .is_some_and(|inbox| inbox.compose.is_some())
+    }
+
+    /// Opens the selected conversation in the inbox and fetches its messages
+    fn open_conversation(&mut self) {
+        let Some(id) = self
+            .screens
+            .inbox
+            .as_mut()
+            .and_then(InboxScreen::open_selected)
+        else {
             return;
+        };
+        let profile = self.sessions[self.focus].profile.clone();
+        let action_tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let action = match inbox::fetch_thread(&profile, id).await {
+                Ok(thread) => Action::ConversationFetched(thread),
+                Err(e) => Action::Error(format!("Loading the conversation failed: {e}")),
+            };
+            let _ = action_tx.send(action);
+        });
+    }
+
+    /// What failed to load while the rest is shown, if anything did
+    fn partial_data_banner(&self) -> Option<String> {
+        let failures: Vec<String> = self
+            .sessions
+            .iter()
+            .filter_map(|session| {
+                let planner_failed = matches!(session.fetch_state, FetchState::Failed(_));
+                let failed: Vec<&str> = Step::ALL
+                    .iter()
+                    .filter(|step| match step {
+                        Step::Planner => planner_failed,
+                        step => session.progress.failed.contains(step),
+                    })
+                    .map(Step::name)
+                    .collect();
+                if failed.is_empty() {
+                    return None;
+                }
+                Some(match self.sessions.len() {
+                    1 => failed.join(", "),
+                    _ => format!("{}: {}", session.profile.name, failed.join(", ")),
+                })
+            })
+            .collect();
+        (!failures.is_empty()).then(|| {
+            format!(
+                " Partial data, couldn't load {} · u retry",
+                failures.join(" · ")
+            )
+        })
+    }
+
+    fn mode(&self) -> Mode {
+        if self.status_bar.confirmation.is_some() {
+            Mode::Confirm
+        } else if self.status_bar.prompt.is_some() {
+            Mode::Prompt
+        } else if self.composing() {
+            Mode::Compose
+        } else if !self.keymap.pending().is_empty() {
+            let keys: Vec<String> = self
+                .keymap
+                .pending()
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            Mode::PendingKeys(keys.join(" "))
+        } else if self.popups.help.is_some() {
+            Mode::Help
+        } else if self.popups.detail.is_some() {
+            Mode::Detail
+        } else if self.course_filter.is_some() {
+            Mode::CourseFilter
+        } else if let Some(mode) = self.screen_mode() {
+            mode
+        } else if self.popups.metrics {
+            Mode::Metrics
+        } else if self.dashboard.active && self.dashboard.focus != Panel::Agenda {
+            Mode::Panel(self.dashboard.focus)
+        } else if self.sessions[self.focus].filter.is_some() {
+            Mode::Search
+        } else {
+            Mode::Normal
         }
+    }
 
-        let [date_area, event_table_area, calendar_area] = Layout::vertical([
-            Constraint::Length(1),
-            Constraint::Fill(1),
-            Constraint::Length(10), /* Month, Weekday, 5 Weeks */
-        ])
-        .areas(area);
-
-        let current_cal_date = &mut self.calendar.dates[self.current_date_index];
-        Paragraph::new(
-            current_cal_date
-                .events
-                .first()
-                .unwrap()
-                .due_at
-                .format(
-                    &format_description::parse(
-                        "[weekday repr:long] [month repr:short] [day padding:none]",
-                    )
-                    .unwrap(),
-                )
-                .unwrap(),
-        )
-        .style(Style::default().fg(Color::Magenta).bold())
-        .render(date_area, buf);
+    /// Sets up a screen opened for the focused profile, starting to load what it shows
+    fn open<C: Component>(&mut self, mut screen: C) -> Option<C> {
+        match screen.init(&self.sessions[self.focus].profile, &self.action_tx) {
+            Ok(()) => Some(screen),
+            Err(e) => {
+                self.status_bar.status = Some(e.to_string());
+                None
+            }
+        }
+    }
 
-        let header = ["Course", "Assignment", "Due"]
-            .into_iter()
-            .map(Cell::from)
-            .collect::<Row>()
-            .height(1)
-            .style(Style::default().fg(Color::Magenta));
-        let rows = current_cal_date.events.iter().map(|e| {
-            Row::new([
-                Cell::from(e.course_name.to_string()),
-                Cell::from(e.title.to_string()),
-                match e.submitted {
-                    true => Cell::from(
-                        e.due_at
-                            .format(&format_description::parse("[hour]:[minute] 󰸞").unwrap())
-                            .unwrap(),
-                    ),
-                    false => Cell::from(
-                        e.due_at
-                            .format(&format_description::parse("[hour]:[minute]  ").unwrap())
-                            .unwrap(),
-                    ),
+    /// Mode of the screen in front, None for the planner
+    fn screen_mode(&self) -> Option<Mode> {
+        Some(match self.screens.current() {
+            Screen::PostPreview => Mode::PostPreview,
+            Screen::Grading => Mode::Grading,
+            Screen::Receipts => Mode::Receipts,
+            Screen::Audit => Mode::Audit,
+            Screen::Modules => Mode::Modules,
+            Screen::Bookmarks => Mode::Bookmarks,
+            Screen::Links => Mode::Links,
+            Screen::Reader => Mode::Reader,
+            Screen::Courses => {
+                let courses = self.screens.courses.as_ref()?;
+                match (&courses.page, courses.show_grades) {
+                    (Some(_), _) => Mode::CoursePage,
+                    (None, true) => Mode::Grades,
+                    (None, false) => Mode::Courses,
+                }
+            }
+            Screen::Announcements => Mode::Announcements,
+            Screen::Inbox => match self.screens.inbox.as_ref()?.thread {
+                Some(_) => Mode::Thread,
+                None => Mode::Inbox,
+            },
+            Screen::Todo => Mode::Todo,
+            Screen::Triage => Mode::Triage,
+            Screen::CatchUp => Mode::CatchUp,
+            Screen::Agenda => Mode::Agenda,
+            Screen::Grid => Mode::Grid(self.screens.grid.as_ref()?.layout),
+            Screen::Planner => return None,
+        })
+    }
+
+    /// Hands in what was typed into the submit prompt for the selected assignment
+    fn submit(&mut self, input: &str) {
+        let id = self.focus;
+        let session = &self.sessions[id];
+        let Some(event) = session.selected_event().cloned() else {
+            return;
+        };
+        let submission = match Submission::parse(input, session.requirements.get(&event.html_url)) {
+            Ok(submission) => submission,
+            Err(e) => {
+                self.status_bar.status = Some(e.to_string());
+                return;
+            }
+        };
+        let title = redact::mask(&event.title);
+        let what = submission.describe();
+        self.status_bar.status = Some(format!("Submitting {what} for {title}..."));
+        let profile = session.profile.clone();
+        let action_tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let progress_tx = action_tx.clone();
+            let progress_status = format!("Uploading {what} for {title}");
+            let last_percent = std::sync::atomic::AtomicU64::new(0);
+            let progress = move |sent: u64, total: u64| {
+                let percent = sent * 100 / total.max(1);
+                // One message per tenth of the file
+                if percent / 10
+                    > last_percent.swap(percent, std::sync::atomic::Ordering::Relaxed) / 10
+                {
+                    let _ =
+                        progress_tx.send(Action::Status(format!("{progress_status} {percent}%")));
+                }
+            };
+            let action = match submission::submit(&profile, &event, &submission, progress).await {
+                Ok(()) => Action::Submitted {
+                    session: id,
+                    html_url: event.html_url,
                 },
-            ])
-            .style(Style::default().fg(match e.submitted {
-                true => Color::Green,
-                false => Color::White,
-            }))
+                Err(e) => Action::Error(format!("Submitting {title} failed: {e}")),
+            };
+            let _ = action_tx.send(action);
         });
-        let event_table = Table::new(
-            rows,
-            [
-                Constraint::Min(self.longest_item_lens.0 + 2),
-                Constraint::Min(self.longest_item_lens.1.max("Assignment".len() as u16) + 2),
-                Constraint::Min(self.longest_item_lens.2 + 1),
-            ],
-        )
-        .header(header)
-        .row_highlight_style(Style::default().bg(Color::Black))
-        .style(Style::default().fg(Color::White));
-        StatefulWidget::render(
-            event_table,
-            event_table_area,
-            buf,
-            &mut current_cal_date.table_state,
-        );
-
-        let mut list =
-            CalendarEventStore::today(Style::default().bg(Color::White).fg(Color::Black).bold());
-        let chosen_date = current_cal_date.events.first().unwrap().due_at.date();
+    }
 
-        let assignment_style = Style::default().fg(Color::Yellow).bg(Color::Black);
+    /// Moves to what was typed into the goto prompt, or the nearest date with items after it
+    fn goto_date(&mut self, input: &str) {
+        let date = match printout::parse_date(input.trim()) {
+            Ok(date) => date,
+            Err(e) => {
+                self.status_bar.status = Some(e.to_string());
+                return;
+            }
+        };
+        if let Some(grid) = &mut self.screens.grid {
+            grid.cursor = date;
+            return;
+        }
+        let Some(shown) = self.session().jump_to_date(date) else {
+            self.status_bar.status = Some("Nothing to go to".to_string());
+            return;
+        };
+        if shown != date {
+            let locale = locale::current();
+            self.status_bar.status = Some(format!(
+                "Nothing due on {}, showing {}",
+                locale.format(date, "%a %b %-d"),
+                locale.format(shown, "%a %b %-d")
+            ));
+        }
+        self.request_estimates();
+    }
 
-        let current_date = OffsetDateTime::now_local().unwrap().date();
+    /// Runs the steps of the initial load that failed again
+    fn retry_failed(&mut self, id: usize) {
+        let session = &mut self.sessions[id];
+        let steps = session.progress.retry();
+        startup::spawn_steps(
+            id,
+            &session.profile,
+            &steps,
+            self.show_instructors,
+            &self.action_tx,
+        );
+    }
 
-        self.calendar.dates.iter().for_each(|calendar_date| {
-            let date = calendar_date.events.first().unwrap().due_at.date();
-            if date == current_date {
+    /// Links the file at the typed path to the selected item, or unlinks it if it already is
+    fn link_file(&mut self, input: &str) {
+        let Some(event) = self.sessions[self.focus].selected_event() else {
+            return;
+        };
+        let input = input.trim();
+        if input.is_empty() {
+            return;
+        }
+        let path = match link_path(input) {
+            Ok(path) => path,
+            Err(e) => {
+                self.status_bar.status = Some(format!("Could not link {input}: {e}"));
                 return;
             }
-            list.add(date, assignment_style);
+        };
+        let title = redact::mask(&event.title);
+        let linked = self.annotations.toggle_file(&event.html_url, path.clone());
+        self.status_bar.status = Some(match self.annotations.save() {
+            Err(e) => format!("Could not save the linked files: {e}"),
+            Ok(()) if linked => format!("Linked {} to {title}", path.display()),
+            Ok(()) => format!("Unlinked {} from {title}", path.display()),
         });
+    }
 
-        let current_date = OffsetDateTime::now_local().unwrap().date();
-        match self.calendar.dates.first().unwrap().events.is_empty() {
-            true => list.add(
-                current_date,
-                Style::default().bg(Color::White).fg(Color::Black),
-            ),
-            false => list.add(
-                current_date,
-                Style::default().bg(Color::Yellow).fg(Color::Black),
+    /// Sets how long before the official deadline the selected item is to be finished, or clears
+    /// it when nothing was typed. The item moves to its new date and stays selected
+    fn set_soft_deadline(&mut self, input: &str) {
+        let Some(event) = self.sessions[self.focus].selected_event().cloned() else {
+            return;
+        };
+        let input = input.trim();
+        let title = redact::mask(&event.title);
+        let soft_deadline = match due::parse_window(input) {
+            _ if input.is_empty() => None,
+            Ok(lead) if lead.is_positive() => Some(SoftDeadline {
+                minutes_early: lead.whole_minutes(),
+            }),
+            _ => {
+                self.status_bar.status =
+                    Some(format!("{input:?} is not a lead time, use e.g. 2d or 36h"));
+                return;
+            }
+        };
+        self.annotations
+            .set_soft_deadline(&event.html_url, soft_deadline);
+        let session = &mut self.sessions[self.focus];
+        session.calendar.apply_annotations(&self.annotations);
+        if let Some((date_index, row)) =
+            session
+                .calendar
+                .dates
+                .iter()
+                .enumerate()
+                .find_map(|(date_index, date)| {
+                    let row = date
+                        .events
+                        .iter()
+                        .position(|moved| moved.html_url == event.html_url)?;
+                    Some((date_index, row))
+                })
+        {
+            session.current_date_index = date_index;
+            session.calendar.dates[date_index]
+                .table_state
+                .select(Some(row));
+        }
+        self.status_bar.status = Some(match (self.annotations.save(), session.selected_event()) {
+            (Err(e), _) => format!("Could not save the soft deadline: {e}"),
+            (Ok(()), Some(moved)) if moved.official_due_at.is_some() => format!(
+                "Finishing {title} by {}",
+                locale::current().format(moved.due_at.date(), "%a %b %-d")
             ),
+            (Ok(()), _) => format!("Back to the official deadline for {title}"),
+        });
+    }
+
+    /// Replaces the focused session with the next profile that isn't open elsewhere, loading it
+    /// from its cache and then from Canvas like on startup
+    fn switch_profile(&mut self) {
+        let session = &self.sessions[self.focus];
+        // Results still on their way would land in the new profile's session
+        if !session.progress.is_done() || matches!(session.fetch_state, FetchState::Loading) {
+            self.status_bar.status = Some(format!(
+                "Wait for {} to finish loading before switching",
+                session.profile.name
+            ));
+            return;
         }
+        let current = self
+            .profiles
+            .iter()
+            .position(|profile| profile.name == session.profile.name)
+            .unwrap_or_default();
+        let Some(profile) = (1..self.profiles.len())
+            .map(|step| &self.profiles[(current + step) % self.profiles.len()])
+            .find(|profile| {
+                !self
+                    .sessions
+                    .iter()
+                    .any(|session| session.profile.name == profile.name)
+            })
+            .cloned()
+        else {
+            self.status_bar.status =
+                Some("No other profile to switch to, add one under [[profiles]]".to_string());
+            return;
+        };
+        let name = profile.name.clone();
+        let profile = match profile.with_token() {
+            Ok(profile) => profile,
+            Err(e) => {
+                self.status_bar.status = Some(format!("Could not switch to {name}: {e}"));
+                return;
+            }
+        };
 
-        list.add(
-            chosen_date,
-            Style::default().fg(Color::Black).bg(Color::Red).bold(),
-        );
-        let calendar_widget = Monthly::new(
-            time::Date::from_calendar_date(chosen_date.year(), chosen_date.month(), 1).unwrap(),
-            list,
-        )
-        .show_weekdays_header(Style::default())
-        .default_style(Style::default().bg(Color::Black));
+        let id = self.focus;
+        let notes = self.sessions[id].notes.clone();
+        self.sessions[id] = Session {
+            notes,
+            hidden_courses: self.hidden_courses.get(&profile.name),
+            time_buckets: self.sessions[id].time_buckets,
+            ..Session::new(profile.clone())
+        };
+        self.triage_queue.retain(|change| change.session != id);
+        self.history.clear();
+        startup::spawn_cached(id, &profile, &self.action_tx);
+        startup::spawn(id, &profile, self.show_instructors, &self.action_tx);
+        self.status_bar.status = Some(format!("Switched to {}", profile.name));
+    }
+
+    /// Moves on to a new day after midnight: views left on the old today follow to the new one,
+    /// and the planner is refetched for the window starting today
+    fn roll_over(&mut self, today: Date) {
+        let yesterday = std::mem::replace(&mut self.today, today);
+        for session in &mut self.sessions {
+            if session.current_date().is_none_or(|date| date == yesterday) {
+                session.jump_to_date(today);
+            }
+        }
+        if let Some(grid) = &mut self.screens.grid
+            && grid.cursor == yesterday
+        {
+            grid.cursor = today;
+        }
+        self.last_refresh = Instant::now();
+        for id in 0..self.sessions.len() {
+            self.spawn_fetch(id);
+        }
+        self.request_estimates();
+    }
 
-        calendar_widget.render(calendar_area, buf);
+    fn session(&mut self) -> &mut Session {
+        &mut self.sessions[self.focus]
     }
-}
 
-fn ui(frame: &mut Frame, app: &mut App) {
-    let block = Block::default()
-        .title(" CanvasTUI ")
-        .title_alignment(Alignment::Center)
-        .borders(Borders::ALL)
-        .border_type(BorderType::Thick)
-        .style(Style::default().fg(Color::Blue))
-        .padding(Padding::horizontal(1))
-        .title_alignment(Alignment::Center);
-    let block_area = block.inner(frame.area());
-    block.render(frame.area(), frame.buffer_mut());
-    app.render(block_area, frame.buffer_mut())
-}
+    pub fn spawn_fetch(&mut self, id: usize) {
+        self.sessions[id].fetch_state = FetchState::Loading;
+        let profile = self.sessions[id].profile.clone();
+        let action_tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let action = match fetch(&profile).await {
+                Ok(calendar) => Action::FetchComplete {
+                    session: id,
+                    calendar,
+                },
+                Err(e) => Action::FetchFailed {
+                    session: id,
+                    error: e.to_string(),
+                },
+            };
+            let _ = action_tx.send(action);
+        });
+    }
 
-fn get_action(_app: &App, event: Event) -> Action {
-    match event {
-        Event::Error => Action::None,
-        Event::Tick => Action::Tick,
-        Event::Render => Action::Render,
-        Event::Key(key) => match key.code {
-            Char('q') => Action::Quit,
-            Char('0') => Action::ResetDate,
-            Char('k') => Action::PrevEvent,
-            Char('j') => Action::NextEvent,
-            Char('h') => Action::PrevDate,
-            Char('u') => Action::Fetch,
-            Char('l') => Action::NextDate,
-            Char('o') => Action::OpenURL,
-            _ => Action::None,
-        },
+    /// Loads a page of a course, which arrives as `CoursePageFetched`
+    fn fetch_course_route(&self, context: CourseContext, route: CourseRoute) {
+        let profile = self.sessions[self.focus].profile.clone();
+        let action_tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let action = match fetch_route(&profile, &context, route).await {
+                Ok(page) => Action::CoursePageFetched {
+                    course_id: context.id,
+                    route,
+                    page,
+                },
+                Err(e) => Action::Error(format!(
+                    "Loading {} failed: {e}",
+                    redact::mask(&context.page_title(route))
+                )),
+            };
+            let _ = action_tx.send(action);
+        });
     }
-}
 
-fn update(app: &mut App, action: Action) {
-    match action {
-        Action::Quit => app.should_quit = true,
-        Action::Fetch => {
-            let mut action_tx = app.action_tx.clone();
-            tokio::spawn(async move {
-                fetch(&mut action_tx).await.unwrap();
+    pub fn request_estimates(&mut self) {
+        let action_tx = self.action_tx.clone();
+        let focus = self.focus;
+        self.session().request_estimates(focus, &action_tx);
+    }
+
+    /// Puts items whose snooze is over back into triage, once their session has them loaded
+    fn requeue_snoozed(&mut self, now: OffsetDateTime) {
+        let mut requeued = vec![];
+        for html_url in self.annotations.due_snoozes(now) {
+            let found = self.sessions.iter().enumerate().find_map(|(id, session)| {
+                let event = session
+                    .calendar
+                    .dates
+                    .iter()
+                    .flat_map(|date| &date.events)
+                    .find(|event| event.html_url == html_url)?;
+                Some(Change {
+                    session: id,
+                    event: event.clone(),
+                    kind: ChangeKind::Snoozed,
+                })
             });
+            if let Some(change) = found {
+                self.annotations.snoozed.remove(&html_url);
+                requeued.push(change);
+            }
         }
-        Action::FetchComplete(data) => {
-            app.calendar = data;
-            app.received_fetch = true;
-            app.calculate_longest_item_lens();
-            // app.current_date_index = 0;
+        if requeued.is_empty() {
+            return;
+        }
+        triage::enqueue(&mut self.triage_queue, requeued);
+        if let Err(e) = self.annotations.save() {
+            log::error!("Failed to save snoozes: {e}");
+        }
+    }
+
+    /// Filters the focused session by `query` as it is typed, selecting the first match from the
+    /// selected item on. An empty query shows everything again
+    fn preview_search(&mut self, query: &str) {
+        let session = &mut self.sessions[self.focus];
+        session.filter = (!query.is_empty()).then(|| query.to_string());
+        if session.filter.is_some() {
+            self.jump_to_match(SearchDirection::Here);
+        }
+    }
+
+    /// Selects the next match of the focused session's search in `direction`, across every date
+    fn jump_to_match(&mut self, direction: SearchDirection) {
+        let session = &mut self.sessions[self.focus];
+        let Some(query) = session.filter.clone() else {
+            return;
+        };
+        let Some((date_index, event_index)) = session.search(&query, direction) else {
+            self.status_bar.status = Some(format!("Nothing matches {query:?}"));
+            return;
+        };
+        session.current_date_index = date_index;
+        session.calendar.dates[date_index]
+            .table_state
+            .select(Some(event_index));
+        let (position, count) = session.match_position(&query);
+        self.status_bar.status = Some(format!(
+            "Match {} of {count} · {}",
+            position.unwrap_or_default(),
+            describe(&session.calendar.dates[date_index].events[event_index])
+        ));
+        self.request_estimates();
+    }
+
+    /// Stores the text from the editor as the date's note in every session
+    fn set_note(&mut self, date: Date, text: &str) {
+        for session in &mut self.sessions {
+            session.notes.set(date, text);
         }
-        Action::FileFetchComplete(data) => {
-            if app.received_fetch {
+        self.status_bar.status = match self.sessions[0].notes.save() {
+            Ok(()) => Some("Saved the note".to_string()),
+            Err(e) => Some(format!("Saving the note failed: {e}")),
+        };
+    }
+
+    /// Reads the post back from the editor and shows it for confirmation
+    fn set_draft(&mut self, mut draft: Draft, edited: Result<String>) {
+        let text = match edited {
+            Ok(text) => text,
+            Err(e) => {
+                self.status_bar.status = Some(format!("Could not edit the post: {e}"));
                 return;
             }
-            app.calendar = data;
-            app.calculate_longest_item_lens();
-        }
-        Action::Tick => {}
-        Action::Render => {}
-        Action::PrevEvent => {
-            if let Some(current_date) = app.calendar.dates.get_mut(app.current_date_index) {
-                if current_date.table_state.selected().unwrap() == 0 {
-                    current_date.table_state.select_last();
-                } else {
-                    current_date.table_state.select_previous();
-                }
+        };
+        match draft.update(&text) {
+            Ok(true) => {}
+            Ok(false) => {
+                self.status_bar.status = Some("Discarded the post without a title".to_string());
+                return;
             }
+            Err(e) => self.status_bar.status = Some(format!("{e}, press e to fix it")),
         }
-        Action::NextEvent => {
-            if let Some(current_date) = app.calendar.dates.get_mut(app.current_date_index) {
-                if current_date.table_state.selected().unwrap() == current_date.events.len() - 1 {
-                    current_date.table_state.select_first();
-                } else {
-                    current_date.table_state.select_next();
-                }
-            }
+        self.screens.post_preview = Some(draft);
+    }
+
+    /// Jumps to the item matching the target, optionally fetching it when it isn't loaded.
+    /// Returns whether the item was found among the loaded items
+    pub fn open_target(&mut self, target: Target, fetch_if_missing: bool) -> bool {
+        let session = &mut self.sessions[self.focus];
+        if let Some((date_index, event_index)) = session.calendar.find(&target) {
+            session.current_date_index = date_index;
+            let date = &mut session.calendar.dates[date_index];
+            date.table_state.select(Some(event_index));
+            self.status_bar.status = Some(describe(&date.events[event_index]));
+            self.request_estimates();
+            return true;
         }
-        Action::ResetDate => {
-            app.current_date_index = 0;
+
+        match target.assignment_ids() {
+            Some((course_id, assignment_id)) if fetch_if_missing => {
+                self.status_bar.status = Some("Looking up item...".to_string());
+                let (focus, profile) = (self.focus, session.profile.clone());
+                let action_tx = self.action_tx.clone();
+                tokio::spawn(async move {
+                    let action = match fetch_assignment(&profile, course_id, assignment_id).await {
+                        Ok(event) => Action::TargetFetched {
+                            session: focus,
+                            event,
+                        },
+                        Err(e) => Action::Error(format!("Could not open item: {e}")),
+                    };
+                    let _ = action_tx.send(action);
+                });
+            }
+            _ => self.status_bar.status = Some("No loaded item matches that url or id".to_string()),
         }
-        Action::NextDate => {
-            app.current_date_index = app
-                .current_date_index
-                .saturating_add(1)
-                .min(app.calendar.dates.len().saturating_sub(1));
+        false
+    }
+
+    /// Opens an item of a session in the browser, showing why in a toast if it can't
+    fn open_item(&mut self, session: usize, url: &str) -> bool {
+        if url.starts_with(ics::UNLINKED) {
+            self.status_bar.status = Some("That event has no link".to_string());
+            return false;
         }
-        Action::PrevDate => {
-            app.current_date_index = app.current_date_index.saturating_sub(1).max(0);
+        match open_in_browser(&self.sessions[session].profile.url, url) {
+            Ok(()) => true,
+            Err(e) => {
+                self.popups.error = Some(Toast::new(format!(
+                    "Could not open {}: {e}",
+                    redact::mask(url)
+                )));
+                false
+            }
         }
-        Action::OpenURL => {
-            let selected_idx = app.calendar.dates[app.current_date_index]
-                .table_state
-                .selected()
-                .expect("Something should always be selected from list");
-            let selected_event = &app.calendar.dates[app.current_date_index].events[selected_idx];
-            let url = env::var("CANVAS_URL")
-                .unwrap()
-                .parse::<Url>()
-                .unwrap()
-                .join(&selected_event.html_url)
-                .unwrap();
-            webbrowser::open(url.as_str()).unwrap();
-        }
-        Action::None => {}
-    };
+    }
 }
 
-async fn run() -> Result<()> {
-    let (action_tx, mut action_rx) = mpsc::unbounded_channel(); // new
+/// The path typed into the link prompt, kept absolute so the link still works when the app is
+/// started from elsewhere
+fn link_path(input: &str) -> std::io::Result<PathBuf> {
+    std::path::absolute(sync::expand_home(Path::new(input)))
+}
 
-    {
-        let action_tx = action_tx.clone();
-        tokio::spawn(async move {
-            let cached_body_bytes = match tokio::fs::read(CACHE_FILE).await {
-                Ok(bytes) => bytes,
-                Err(_) => {
-                    return;
-                }
-            };
-            let calendar: Calendar = serde_json::from_slice(&cached_body_bytes).unwrap();
-            action_tx.send(Action::FileFetchComplete(calendar)).unwrap();
-        });
+/// Opens `url` in the browser, relative to the Canvas instance at `base` unless it is absolute
+fn open_in_browser(base: &str, url: &str) -> Result<()> {
+    let base = base.parse::<Url>().ok();
+    let url = Url::options().base_url(base.as_ref()).parse(url)?;
+    webbrowser::open(url.as_str())?;
+    Ok(())
+}
+
+fn describe(event: &CalendarEvent) -> String {
+    let due_at = event
+        .due_at
+        .format(
+            &format_description::parse(
+                "[weekday repr:short] [month repr:short] [day] [hour]:[minute]",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    let now = now_local();
+    let mut state = match (event.submitted, event.completed) {
+        (true, _) => " · submitted".to_string(),
+        (false, true) => " · done".to_string(),
+        (false, false) => match event.lock_reason(PrimitiveDateTime::new(now.date(), now.time())) {
+            Some(reason) => format!(" · {reason}"),
+            None => String::new(),
+        },
+    };
+    if let Some(attempts) = event.attempts {
+        state.push_str(&format!(" · {}", attempts.describe()));
     }
-    {
-        let mut action_tx = action_tx.clone();
-        tokio::spawn(async move {
-            fetch(&mut action_tx).await.unwrap();
-        });
+    format!(
+        "{} · {} · due {due_at}{state}",
+        redact::mask(&event.course_name),
+        redact::mask(&event.title)
+    )
+}
+
+impl App {
+    /// What components show of the app's state
+    fn context(&self) -> Context<'_> {
+        Context {
+            session: &self.sessions[self.focus],
+            focus: self.focus,
+            bookmarks: &self.bookmarks,
+            triage_queue: &self.triage_queue,
+            audit: &self.audit,
+            annotations: &self.annotations,
+        }
     }
 
-    let mut tui = tui::Tui::new()?;
-    tui.enter()?;
+    /// Mode with what Esc does, after whatever is in progress
+    fn mode_text(&self) -> String {
+        let mode = self.mode();
+        let mut mode_text = match mode.escape_hint() {
+            Some(hint) => format!(" {} · Esc {hint}", mode.label()),
+            None => format!(" {}", mode.label()),
+        };
+        if redact::enabled() {
+            mode_text.insert_str(0, " REDACTED ·");
+        }
+        if ratelimit::is_low() {
+            mode_text.insert_str(0, " API budget low ·");
+        }
+        // Startup shows its own progress below
+        if self.sessions.iter().any(|session| {
+            session.progress.is_done() && matches!(session.fetch_state, FetchState::Loading)
+        }) {
+            let elapsed = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default();
+            let frame = SPINNER[(elapsed.as_millis() / 100) as usize % SPINNER.len()];
+            mode_text.insert_str(0, &format!(" {frame} refreshing... ·"));
+        }
+        if self.screens.agenda.is_some() {
+            let states: Vec<String> = self
+                .sessions
+                .iter()
+                .map(|session| format!("{} {}", session.profile.name, session.fetch_state.label()))
+                .collect();
+            mode_text.insert_str(0, &format!(" {} ·", states.join(" · ")));
+        }
+        if self
+            .sessions
+            .iter()
+            .any(|session| !session.progress.is_done())
+        {
+            let finished: usize = self
+                .sessions
+                .iter()
+                .map(|session| session.progress.finished.len())
+                .sum();
+            let total = self.sessions.len() * Step::ALL.len();
+            mode_text.insert_str(0, &format!(" {finished}/{total} loaded ·"));
+        }
+        mode_text
+    }
 
-    let mut app = App {
-        should_quit: false,
-        action_tx: action_tx.clone(),
-        longest_item_lens: (0, 0, 0),
-        received_fetch: false,
-        current_date_index: 0,
-        calendar: Calendar { dates: vec![] },
-    };
+    /// Lends out a part of the app to render or update it with the context of the rest. The part
+    /// is taken out of the app meanwhile, so the context can borrow everything else
+    fn with_part<T: Default, R>(
+        &mut self,
+        part: fn(&mut App) -> &mut T,
+        lend: impl FnOnce(&mut T, &Context) -> R,
+    ) -> R {
+        let mut taken = std::mem::take(part(self));
+        let result = lend(&mut taken, &self.context());
+        *part(self) = taken;
+        result
+    }
 
-    loop {
-        let e = tui.next().await?;
-        match e {
-            tui::Event::Tick => action_tx.send(Action::Tick)?,
-            tui::Event::Render => action_tx.send(Action::Render)?,
-            tui::Event::Key(_) => {
-                let action = get_action(&app, e);
-                action_tx.send(action.clone())?;
-            }
-            _ => {}
-        };
+    /// Keeps the popups and open screens in step with an action, returning what they ask the app
+    /// to do next and whether the action was theirs alone. Keys moving a selection only go to the
+    /// part in front, and reach the planner when nothing else is open
+    fn update_parts(&mut self, action: &Action) -> (Vec<Action>, bool) {
+        if let Action::Navigate(_) = action {
+            let follow_up = if self.popups.has_keys() {
+                self.with_part(
+                    |app| &mut app.popups,
+                    |popups, context| popups.update(action, context),
+                )
+            } else if self.course_filter.is_some() {
+                self.with_part(
+                    |app| &mut app.course_filter,
+                    |sidebar, context| sidebar.as_mut()?.update(action, context),
+                )
+            } else if self.screens.current() != Screen::Planner {
+                self.with_part(
+                    |app| &mut app.screens,
+                    |screens, context| screens.focused()?.update(action, context),
+                )
+            } else {
+                return (vec![], false);
+            };
+            return (follow_up.into_iter().collect(), true);
+        }
+        let mut follow_ups: Vec<Action> = self
+            .with_part(
+                |app| &mut app.popups,
+                |popups, context| popups.update(action, context),
+            )
+            .into_iter()
+            .collect();
+        follow_ups.extend(self.with_part(
+            |app| &mut app.screens,
+            |screens, context| screens.update(action, context),
+        ));
+        (follow_ups, false)
+    }
 
-        while let Ok(action) = action_rx.try_recv() {
-            update(&mut app, action.clone());
-            if let Action::Render = action {
-                tui.draw(|f| {
-                    ui(f, &mut app);
-                })?;
+    /// The day view of every profile side by side, or the dashboard when there is one profile
+    /// and room for it
+    fn render_planner(&mut self, area: Rect, buf: &mut Buffer) {
+        if let [session] = self.sessions.as_mut_slice() {
+            match area.width >= DASHBOARD_MIN_WIDTH {
+                true => {
+                    self.dashboard.active = true;
+                    self.dashboard.render(area, buf, session);
+                }
+                false => Widget::render(session, area, buf),
             }
+            return;
+        }
+        let session_areas =
+            Layout::horizontal(self.sessions.iter().map(|_| Constraint::Fill(1))).split(area);
+        for (id, (session, session_area)) in self
+            .sessions
+            .iter_mut()
+            .zip(session_areas.iter())
+            .enumerate()
+        {
+            let name = match session.profile.environment_badge() {
+                Some(badge) => format!(" {} · {badge} ", session.profile.name),
+                None => format!(" {} ", session.profile.name),
+            };
+            let block = Block::default()
+                .title(name)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(match id == self.focus {
+                    true => theme::current().heading,
+                    false => theme::current().muted,
+                }));
+            let inner = block.inner(*session_area);
+            block.render(*session_area, buf);
+            Widget::render(session, inner, buf);
         }
+    }
+}
 
-        if app.should_quit {
-            break;
+impl Widget for &mut App {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        if self.lock.is_locked() {
+            self.lock.render(area, buf);
+            return;
+        }
+
+        let [mut screen_area, status_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        if let Some(banner) = self.partial_data_banner() {
+            let [banner_area, rest] =
+                Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(screen_area);
+            Paragraph::new(banner)
+                .style(
+                    Style::default()
+                        .fg(theme::current().inverse)
+                        .bg(theme::current().warning),
+                )
+                .render(banner_area, buf);
+            screen_area = rest;
+        }
+        if self.course_filter.is_some() {
+            let [sidebar_area, rest] =
+                Layout::horizontal([Constraint::Length(COURSE_FILTER_WIDTH), Constraint::Fill(1)])
+                    .areas(screen_area);
+            self.with_part(
+                |app| &mut app.course_filter,
+                |sidebar, context| {
+                    if let Some(sidebar) = sidebar {
+                        sidebar.render(sidebar_area, buf, context);
+                    }
+                },
+            );
+            screen_area = rest;
+        }
+
+        self.dashboard.active = false;
+        match self.screens.focused().is_some() {
+            true => self.with_part(
+                |app| &mut app.screens,
+                |screens, context| {
+                    if let Some(screen) = screens.focused() {
+                        screen.render(screen_area, buf, context);
+                    }
+                },
+            ),
+            false => self.render_planner(screen_area, buf),
         }
+
+        self.status_bar.mode = self.mode_text();
+        let today = now_local().date();
+        self.status_bar.summary = self.completion.summary(today);
+        self.with_part(
+            |app| &mut app.popups,
+            |popups, context| popups.render(screen_area, buf, context),
+        );
+        self.with_part(
+            |app| &mut app.status_bar,
+            |status_bar, context| status_bar.render(status_area, buf, context),
+        );
     }
-    tui.exit()?;
+}
 
-    Ok(())
+fn ui(frame: &mut Frame, app: &mut App) {
+    let context = app.context();
+    Header.render(frame.area(), frame.buffer_mut(), &context);
+    app.render(Header::inner(frame.area()), frame.buffer_mut());
+    capabilities::current().degrade(frame.buffer_mut());
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    color_eyre::install()?;
-    let result = run().await;
+fn get_action(app: &mut App, event: Event) -> Action {
+    if let Event::Key(_) = event {
+        app.lock.touch();
+    }
+    match event {
+        Event::Error => Action::None,
+        Event::Tick => Action::Tick,
+        Event::Render => Action::Render,
+        Event::Key(key) if app.lock.is_locked() => Action::LockKey(key.code),
+        Event::Key(key) if app.status_bar.confirmation.is_some() => {
+            app.status_bar.handle_event(key, Action::None)
+        }
+        Event::Key(key) if key.code == KeyCode::Esc => Action::Escape,
+        Event::Key(key) if app.status_bar.prompt.is_some() => {
+            app.status_bar.handle_event(key, Action::None)
+        }
+        Event::Key(key) if app.composing() => Action::ComposeKey(key),
+        Event::Key(key) => {
+            let action = app.keymap.resolve(key);
+            if app.popups.has_keys() {
+                return hand_key(&mut app.popups, &mut app.keymap, key, action);
+            }
+            if let Some(sidebar) = &mut app.course_filter {
+                return hand_key(sidebar, &mut app.keymap, key, action);
+            }
+            if let Some(screen) = app.screens.focused() {
+                return hand_key(screen, &mut app.keymap, key, action);
+            }
+            if app.mode() == Mode::Search {
+                let action = match key.code {
+                    KeyCode::Char('n') => Action::Navigate(Navigation::NextMatch),
+                    KeyCode::Char('N') => Action::Navigate(Navigation::PrevMatch),
+                    _ => action,
+                };
+                // Only matches are shown, so moving between items moves between matches
+                return match action {
+                    Action::Navigate(Navigation::NextEvent | Navigation::NextMatch) => {
+                        app.keymap.clear_pending();
+                        Action::Navigate(Navigation::NextMatch)
+                    }
+                    Action::Navigate(Navigation::PrevEvent | Navigation::PrevMatch) => {
+                        app.keymap.clear_pending();
+                        Action::Navigate(Navigation::PrevMatch)
+                    }
+                    action => action,
+                };
+            }
+            match action {
+                Action::NextPanel if !app.dashboard.active => Action::None,
+                Action::ToggleDigest | Action::ToggleFold | Action::MarkRead
+                    if !app.dashboard.active || app.dashboard.focus != Panel::Announcements =>
+                {
+                    Action::None
+                }
+                Action::Navigate(Navigation::NextEvent)
+                    if app.dashboard.active && app.dashboard.focus == Panel::Week =>
+                {
+                    Action::Navigate(Navigation::NextDate)
+                }
+                Action::Navigate(Navigation::PrevEvent)
+                    if app.dashboard.active && app.dashboard.focus == Panel::Week =>
+                {
+                    Action::Navigate(Navigation::PrevDate)
+                }
+                action => action,
+            }
+        }
+    }
+}
+
+/// Gives a key to the component in front. A key it acts on ends any sequence being typed, since
+/// it may have used the key for something of its own
+fn hand_key(
+    component: &mut (impl Component + ?Sized),
+    keymap: &mut Keymap,
+    key: KeyEvent,
+    bound: Action,
+) -> Action {
+    let action = component.handle_event(key, bound);
+    if !matches!(action, Action::None) {
+        keymap.clear_pending();
+    }
+    action
+}
+
+fn update(app: &mut App, action: Action) {
+    // Popups and screens keep their own state in step, the arms below change the rest of the app
+    let (follow_ups, handled) = app.update_parts(&action);
+    for follow_up in follow_ups {
+        update(app, follow_up);
+    }
+    if handled {
+        return;
+    }
+    match action {
+        Action::Quit => app.should_quit = true,
+        Action::Fetch if app.screens.agenda.is_some() => {
+            for id in 0..app.sessions.len() {
+                app.spawn_fetch(id);
+                app.retry_failed(id);
+            }
+        }
+        Action::Fetch => {
+            app.spawn_fetch(app.focus);
+            app.retry_failed(app.focus);
+        }
+        Action::FetchComplete {
+            session: id,
+            calendar: mut data,
+        } => {
+            data.apply_annotations(&app.annotations);
+            let session = &mut app.sessions[id];
+            data.apply_attempts(&session.attempts);
+            for event in app.notifier.process(&session.calendar, &data) {
+                let profile = session.profile.clone();
+                let action_tx = app.action_tx.clone();
+                tokio::spawn(async move {
+                    let comment = fetch_grade_comment(&profile, &event)
+                        .await
+                        .unwrap_or_else(|e| {
+                            log::error!("Failed to fetch the comment on {}: {e}", event.title);
+                            None
+                        });
+                    let _ = action_tx.send(Action::GradeCommentFetched { event, comment });
+                });
+            }
+            let changes = triage::changes(id, &session.calendar, &data);
+            if !changes.is_empty() && app.screens.triage.is_none() {
+                app.status_bar.status = Some(format!(
+                    "{} new or changed item(s) to triage",
+                    app.triage_queue.len() + changes.len()
+                ));
+            }
+            triage::enqueue(&mut app.triage_queue, changes);
+            // Dates come and go between fetches, so the date shown is kept rather than its index
+            let shown = session.current_date();
+            session.calendar = data.with_overlay(&app.overlay);
+            if let Some(date) = shown {
+                session.jump_to_date(date);
+            }
+            session.received_fetch = true;
+            session.updated_at = Some(OffsetDateTime::now_utc());
+            let now = now_local();
+            if app.completion.record(
+                &session.calendar,
+                PrimitiveDateTime::new(now.date(), now.time()),
+            ) && let Err(e) = app.completion.save()
+            {
+                log::error!("Failed to save the completion history: {e}");
+            }
+            session.fetch_state = FetchState::Loaded;
+            session.apply_course_names();
+            session.request_estimates(id, &app.action_tx);
+            session.request_permissions(id, &app.action_tx);
+            session.request_gradebooks(id, &app.action_tx);
+            if id == 0 {
+                app.dashboard.requested = false;
+            }
+            if id == app.focus
+                && let Some(target) = app.pending_open.take()
+            {
+                app.open_target(target, true);
+            }
+            if let Some(agenda) = &mut app.screens.agenda {
+                agenda.refresh(&app.sessions);
+            }
+        }
+        Action::FileFetchComplete {
+            session: id,
+            calendar: mut data,
+            updated_at,
+        } => {
+            let session = &mut app.sessions[id];
+            if session.received_fetch {
+                return;
+            }
+            data.apply_annotations(&app.annotations);
+            data.apply_attempts(&session.attempts);
+            session.calendar = data.with_overlay(&app.overlay);
+            session.updated_at = updated_at;
+            session.apply_course_names();
+            session.request_estimates(id, &app.action_tx);
+            if id == app.focus
+                && let Some(target) = app.pending_open.clone()
+                && app.open_target(target, false)
+            {
+                app.pending_open = None;
+            }
+            if let Some(agenda) = &mut app.screens.agenda {
+                agenda.refresh(&app.sessions);
+            }
+        }
+        Action::GradeCommentFetched { event, comment } => {
+            let mut status = format!("Graded: {}", redact::mask(&event.title));
+            if let Some(comment) = &comment {
+                status.push_str(&format!(" · “{}”", redact::mask(comment)));
+            }
+            app.status_bar.status = Some(status);
+            app.notifier.graded(event, comment);
+        }
+        Action::GradebookFetched {
+            session: id,
+            course_id,
+            gradebook,
+        } => {
+            app.sessions[id]
+                .gradebooks
+                .insert(course_id, Some(gradebook));
+        }
+        Action::PermissionsFetched {
+            session: id,
+            course_id,
+            permissions,
+        } => {
+            app.sessions[id]
+                .permissions
+                .insert(course_id, Some(permissions));
+        }
+        Action::DetailsFetched {
+            session: id,
+            html_url,
+            estimate,
+            description,
+            attempts,
+            requirements,
+        } => {
+            let session = &mut app.sessions[id];
+            session.descriptions.insert(html_url.clone(), description);
+            session.requirements.insert(html_url.clone(), requirements);
+            if let Some(attempts) = attempts {
+                session.attempts.insert(html_url.clone(), attempts);
+                session.calendar.apply_attempts(&session.attempts);
+            }
+            session.estimates.insert(html_url, Some(estimate));
+        }
+        Action::OpenPrompt(PromptKind::Submit) => {
+            let Some(event) = app.sessions[app.focus].selected_event() else {
+                return;
+            };
+            if event.plannable_type != "assignment" {
+                app.status_bar.status =
+                    Some("Only assignments can be submitted from here".to_string());
+                return;
+            }
+            app.status_bar.prompt = Some((PromptKind::Submit, String::new()));
+        }
+        Action::OpenPrompt(PromptKind::Link) => {
+            if app.sessions[app.focus].selected_event().is_none() {
+                app.status_bar.status = Some("No item to link a file to".to_string());
+                return;
+            }
+            app.status_bar.prompt = Some((PromptKind::Link, String::new()));
+        }
+        Action::OpenPrompt(PromptKind::SoftDeadline) => {
+            let Some(event) = app.sessions[app.focus]
+                .selected_event()
+                .filter(|event| !event.is_external())
+            else {
+                app.status_bar.status = Some("No item to set a soft deadline for".to_string());
+                return;
+            };
+            // Starts from the lead time already set, to change or clear it
+            let input = app
+                .annotations
+                .soft_deadlines
+                .get(&event.html_url)
+                .map(SoftDeadline::describe)
+                .unwrap_or_default();
+            app.status_bar.prompt = Some((PromptKind::SoftDeadline, input));
+        }
+        Action::OpenPrompt(kind) => {
+            // Grades and comments start from what was entered before
+            let input = match (
+                kind,
+                app.screens
+                    .grading
+                    .as_mut()
+                    .and_then(GradingScreen::selected),
+            ) {
+                (PromptKind::Grade, Some(row)) => row.new_grade.clone().unwrap_or_default(),
+                (PromptKind::Comment, Some(row)) => row.comment.clone(),
+                _ => String::new(),
+            };
+            app.status_bar.prompt = Some((kind, input));
+        }
+        Action::EditNote => match app.sessions[app.focus].current_date() {
+            Some(date) => app.note_to_edit = Some(date),
+            None => app.status_bar.status = Some("No date to attach a note to yet".to_string()),
+        },
+        Action::SaveNote(date, text) => app.set_note(date, &text),
+        Action::EditFiles => {
+            let Some(event) = app.sessions[app.focus].selected_event() else {
+                return;
+            };
+            let files = app.annotations.files(&event.html_url);
+            match files.is_empty() {
+                true => {
+                    app.status_bar.status = Some(format!(
+                        "No files linked to {}, F links one",
+                        redact::mask(&event.title)
+                    ))
+                }
+                false => app.files_to_edit = Some(files.to_vec()),
+            }
+        }
+        // The toast is on top of everything, so it goes first
+        Action::Escape if app.popups.error.is_some() => app.popups.error = None,
+        Action::Escape => match app.mode() {
+            Mode::Confirm => app.status_bar.confirmation = None,
+            Mode::Prompt => {
+                if let Some((PromptKind::Search, _)) = app.status_bar.prompt.take() {
+                    app.session().filter = None;
+                    app.status_bar.status = None;
+                }
+            }
+            Mode::PendingKeys(_) => app.keymap.clear_pending(),
+            Mode::Help => app.popups.help = None,
+            Mode::Detail => app.popups.detail = None,
+            Mode::CourseFilter => app.course_filter = None,
+            Mode::Grading => {
+                if let Some(grading) = app.screens.grading.take()
+                    && grading.unsaved() > 0
+                {
+                    app.status_bar.status =
+                        Some(format!("Discarded {} unsaved grade(s)", grading.unsaved()));
+                }
+            }
+            Mode::PostPreview => {
+                app.screens.post_preview = None;
+                app.status_bar.status = Some("Discarded the post".to_string());
+            }
+            Mode::Receipts => app.screens.receipts = None,
+            Mode::Audit => app.screens.audit = None,
+            Mode::Modules => app.screens.modules = None,
+            Mode::Bookmarks => app.screens.bookmarks = None,
+            Mode::Links => app.screens.links = None,
+            Mode::Reader => app.screens.reader = None,
+            Mode::CoursePage => {
+                if let Some(courses) = &mut app.screens.courses {
+                    courses.page = None;
+                }
+            }
+            Mode::Courses | Mode::Grades => app.screens.courses = None,
+            Mode::Announcements => app.screens.announcements = None,
+            Mode::Compose => {
+                if let Some(inbox) = &mut app.screens.inbox
+                    && !inbox.sending
+                {
+                    inbox.compose = None;
+                }
+            }
+            Mode::Thread => {
+                if let Some(inbox) = &mut app.screens.inbox {
+                    inbox.thread = None;
+                }
+            }
+            Mode::Inbox => app.screens.inbox = None,
+            Mode::Todo => app.screens.todo = None,
+            Mode::Triage => app.screens.triage = None,
+            Mode::CatchUp => app.screens.catch_up = None,
+            Mode::Agenda => app.screens.agenda = None,
+            Mode::Grid(_) => app.screens.grid = None,
+            Mode::Metrics => app.popups.metrics = false,
+            Mode::Panel(_) => app.dashboard.focus = Panel::Agenda,
+            Mode::Search => {
+                app.session().filter = None;
+                app.status_bar.status = None;
+            }
+            Mode::Normal => app.status_bar.status = None,
+        },
+        Action::PromptKey(code) => match code {
+            KeyCode::Enter => {
+                let Some((kind, input)) = app.status_bar.prompt.take() else {
+                    return;
+                };
+                match kind {
+                    PromptKind::Open => match Target::parse(&input) {
+                        Some(target) => {
+                            app.open_target(target, true);
+                        }
+                        None => {
+                            app.status_bar.status =
+                                Some(format!("{input:?} is not a Canvas url or id"))
+                        }
+                    },
+                    PromptKind::Search => {
+                        app.preview_search(&input);
+                        // Nothing is left to filter by when nothing matches
+                        if app.sessions[app.focus].match_position(&input).1 == 0 {
+                            app.session().filter = None;
+                        }
+                    }
+                    PromptKind::Grade => {
+                        if let Some(grading) = &mut app.screens.grading {
+                            grading.set_grade(&input);
+                        }
+                    }
+                    PromptKind::Comment => {
+                        if let Some(grading) = &mut app.screens.grading {
+                            grading.set_comment(&input);
+                        }
+                    }
+                    PromptKind::Submit => app.submit(&input),
+                    PromptKind::Goto => app.goto_date(&input),
+                    PromptKind::Link => app.link_file(&input),
+                    PromptKind::SoftDeadline => app.set_soft_deadline(&input),
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some((_, input)) = app.status_bar.prompt.as_mut() {
+                    input.pop();
+                }
+                if let Some((PromptKind::Search, input)) = app.status_bar.prompt.clone() {
+                    app.preview_search(&input);
+                }
+            }
+            Char(c) => {
+                if let Some((_, input)) = app.status_bar.prompt.as_mut() {
+                    input.push(c);
+                }
+                if let Some((PromptKind::Search, input)) = app.status_bar.prompt.clone() {
+                    app.preview_search(&input);
+                }
+            }
+            _ => {}
+        },
+        Action::TargetFetched {
+            session: id,
+            mut event,
+        } => {
+            let session = &mut app.sessions[id];
+            if let Some(known) = session
+                .calendar
+                .dates
+                .iter()
+                .flat_map(|date| &date.events)
+                .find(|known| known.course_id == event.course_id)
+            {
+                event.course_name = known.course_name.clone();
+            }
+            let target = Target::Path(event.html_url.clone());
+            session.calendar =
+                std::mem::replace(&mut session.calendar, Calendar { dates: vec![] }).insert(event);
+            session.apply_course_names();
+            app.focus = id;
+            app.open_target(target, false);
+        }
+        Action::StartupLoaded { session, data } => {
+            app.sessions[session].apply_loaded(data);
+            if let Some(agenda) = &mut app.screens.agenda {
+                agenda.refresh(&app.sessions);
+            }
+        }
+        Action::StartupStep {
+            session,
+            step,
+            failed,
+        } => app.sessions[session].progress.record(step, failed),
+        Action::FetchFailed { session: id, error } => {
+            let session = &mut app.sessions[id];
+            app.popups.error = Some(Toast::new(format!(
+                "Fetching {} failed: {error}",
+                session.profile.name
+            )));
+            session.fetch_state = FetchState::Failed(error);
+        }
+        Action::Status(status) => app.status_bar.status = Some(status),
+        Action::Error(error) => {
+            log::error!("{error}");
+            app.popups.error = Some(Toast::new(error));
+        }
+        Action::OverlayFetchComplete { name, events } => {
+            app.overlay.retain(|event| event.course_name != name);
+            app.overlay.extend(events);
+            for session in &mut app.sessions {
+                session.calendar =
+                    std::mem::replace(&mut session.calendar, Calendar { dates: vec![] })
+                        .with_overlay(&app.overlay);
+                session.current_date_index = session
+                    .current_date_index
+                    .min(session.calendar.dates.len().saturating_sub(1));
+                session.calculate_longest_item_lens();
+            }
+        }
+        Action::LockKey(code) => app.lock.key(code),
+        Action::Tick => {
+            app.lock.check_idle();
+            app.notifier.flush();
+            app.notifier
+                .watch(app.sessions.iter().map(|session| &session.calendar));
+            let now = OffsetDateTime::now_utc();
+            let due = app.annotations.take_due_reminders(now);
+            if !due.is_empty() {
+                app.status_bar.status =
+                    Some(format!("Time to work on {}", redact::mask(&due[0].title)));
+                app.notifier.remind(due);
+                if let Err(e) = app.annotations.save() {
+                    log::error!("Failed to save delivered reminders: {e}");
+                }
+            }
+            app.requeue_snoozed(now);
+            let today = now_local().date();
+            if today != app.today {
+                app.roll_over(today);
+            }
+            if let Some(minutes) = app.refresh_minutes
+                && app.last_refresh.elapsed().as_secs() >= minutes * 60
+            {
+                app.last_refresh = Instant::now();
+                for id in 0..app.sessions.len() {
+                    app.spawn_fetch(id);
+                }
+            }
+            if app.dashboard.active && !app.dashboard.requested {
+                app.dashboard.requested = true;
+                let profile = app.sessions[0].profile.clone();
+                let action_tx = app.action_tx.clone();
+                tokio::spawn(async move {
+                    let action = match fetch_dashboard(&profile).await {
+                        Ok(data) => Action::DashboardFetched(data),
+                        Err(e) => Action::Error(format!("Loading the dashboard failed: {e}")),
+                    };
+                    let _ = action_tx.send(action);
+                });
+            }
+        }
+        Action::DashboardFetched(data) => app.dashboard.data = Some(data),
+        Action::ToggleDigest => app.dashboard.toggle_digest(),
+        Action::ToggleFold => app.dashboard.toggle_fold(),
+        Action::MarkRead => {
+            let announcements = app.dashboard.up_to_selection();
+            if announcements.is_empty() {
+                return;
+            }
+            app.status_bar.status = Some(format!(
+                "Marking {} announcement(s) read...",
+                announcements.len()
+            ));
+            let profile = app.sessions[0].profile.clone();
+            let action_tx = app.action_tx.clone();
+            tokio::spawn(async move {
+                let mut ids = vec![];
+                let mut error = None;
+                for announcement in announcements {
+                    let Some(endpoint) = announcement.read_endpoint() else {
+                        continue;
+                    };
+                    match put(&profile, &endpoint).await {
+                        Ok(()) => ids.push(announcement.id),
+                        Err(e) => error = Some(e),
+                    }
+                }
+                let status = match error {
+                    Some(e) => format!(
+                        "Marked {} announcement(s) read, some failed: {e}",
+                        ids.len()
+                    ),
+                    None => format!("Marked {} announcement(s) read", ids.len()),
+                };
+                let _ = action_tx.send(Action::AnnouncementsRead { ids });
+                let _ = action_tx.send(Action::Status(status));
+            });
+        }
+        Action::AnnouncementsRead { ids } => app.dashboard.remove_read(&ids),
+        Action::YankLink(format) => {
+            let session = &app.sessions[app.focus];
+            let Some(event) = session.selected_event() else {
+                return;
+            };
+            let url = session.absolute_url(event);
+            let text = links::fill_template(app.yank_config.template(format), event, &url);
+            app.status_bar.status = Some(match app.clipboard.copy(&text) {
+                Ok(()) => format!(
+                    "Copied {} as {}",
+                    redact::mask(&event.title),
+                    format.label()
+                ),
+                Err(e) => format!("Could not copy the link: {e}"),
+            });
+        }
+        Action::OpenDay => {
+            let session = &app.sessions[app.focus];
+            let urls: Vec<String> = session
+                .unfinished_on_current_date()
+                .iter()
+                .map(|event| event.html_url.clone())
+                .collect();
+            if urls.is_empty() {
+                app.status_bar.status = Some("Nothing left to hand in on this day".to_string());
+                return;
+            }
+            let opened = urls
+                .iter()
+                .filter(|url| app.open_item(app.focus, url))
+                .count();
+            app.status_bar.status = Some(format!("Opened {opened} item(s) in the browser"));
+        }
+        Action::YankDay => {
+            let session = &app.sessions[app.focus];
+            let events = session.unfinished_on_current_date();
+            if events.is_empty() {
+                app.status_bar.status = Some("Nothing left to hand in on this day".to_string());
+                return;
+            }
+            let urls: Vec<String> = events
+                .iter()
+                .map(|event| session.absolute_url(event))
+                .collect();
+            app.status_bar.status = Some(match app.clipboard.copy(&urls.join("\n")) {
+                Ok(()) => format!("Copied {} url(s)", urls.len()),
+                Err(e) => format!("Could not copy the urls: {e}"),
+            });
+        }
+        Action::ExportReminder => {
+            let session = &app.sessions[app.focus];
+            let Some(event) = session.selected_event().cloned() else {
+                return;
+            };
+            if event.is_external() {
+                app.status_bar.status =
+                    Some("Events from external calendars already have a calendar".to_string());
+                return;
+            }
+            app.status_bar.status = Some(format!(
+                "Creating a reminder for {}...",
+                redact::mask(&event.title)
+            ));
+            let config = app.reminders_config.clone();
+            let caldav = app.caldav_config.clone();
+            let canvas_url = session.profile.url.clone();
+            let action_tx = app.action_tx.clone();
+            tokio::spawn(async move {
+                let action =
+                    match reminders::create(&config, caldav.as_ref(), &canvas_url, &event).await {
+                        Ok(()) => Action::Status(format!(
+                            "Reminder for {} goes off {} minutes before it is due",
+                            redact::mask(&event.title),
+                            config.minutes_before
+                        )),
+                        Err(e) => Action::Error(format!("Could not create the reminder: {e}")),
+                    };
+                let _ = action_tx.send(action);
+            });
+        }
+        Action::ToggleCompleted => {
+            let id = app.focus;
+            let session = &mut app.sessions[id];
+            let Some(event) = session.selected_event().cloned() else {
+                return;
+            };
+            if event.is_external() {
+                app.status_bar.status =
+                    Some("Events from external calendars can't be marked done".to_string());
+                return;
+            }
+            let html_url = event.html_url.clone();
+            let completed = !event.completed;
+            // Shown right away and put back if Canvas refuses
+            if let Some(event) = session.calendar.event_mut(&html_url) {
+                event.completed = completed;
+            }
+            let title = redact::mask(&event.title);
+            app.status_bar.status = Some(match completed {
+                true => format!("Marking {title} done..."),
+                false => format!("Marking {title} not done..."),
+            });
+            let profile = session.profile.clone();
+            let action_tx = app.action_tx.clone();
+            tokio::spawn(async move {
+                let action = match set_planner_completed(&profile, &event, completed).await {
+                    Ok(override_id) => Action::CompletionSaved {
+                        session: id,
+                        html_url,
+                        completed,
+                        override_id,
+                    },
+                    Err(e) => Action::CompletionFailed {
+                        session: id,
+                        html_url,
+                        completed: !completed,
+                        error: e.to_string(),
+                    },
+                };
+                let _ = action_tx.send(action);
+            });
+        }
+        Action::RecheckSubmission => {
+            let id = app.focus;
+            let session = &app.sessions[id];
+            let Some(event) = session.selected_event().cloned() else {
+                return;
+            };
+            if event.is_external() {
+                app.status_bar.status =
+                    Some("Events from external calendars have no submission".to_string());
+                return;
+            }
+            app.status_bar.status = Some(format!(
+                "Checking the submission of {}...",
+                redact::mask(&event.title)
+            ));
+            let profile = session.profile.clone();
+            let action_tx = app.action_tx.clone();
+            tokio::spawn(async move {
+                let action = match fetch_submitted(&profile, &event).await {
+                    Ok(submitted) => Action::SubmissionRechecked {
+                        session: id,
+                        html_url: event.html_url,
+                        submitted,
+                    },
+                    Err(e) => Action::Error(format!(
+                        "Checking the submission of {} failed: {e}",
+                        redact::mask(&event.title)
+                    )),
+                };
+                let _ = action_tx.send(action);
+            });
+        }
+        Action::SubmissionRechecked {
+            session: id,
+            html_url,
+            submitted,
+        } => {
+            // Grades are left to the next fetch so it still notices them
+            if let Some(event) = app.sessions[id].calendar.event_mut(&html_url) {
+                event.submitted = submitted;
+                let title = redact::mask(&event.title);
+                app.status_bar.status = Some(match submitted {
+                    true => format!("{title} is submitted"),
+                    false => format!("{title} is not submitted yet"),
+                });
+            }
+        }
+        Action::Submitted {
+            session: id,
+            html_url,
+        } => {
+            if let Some(event) = app.sessions[id].calendar.event_mut(&html_url) {
+                event.submitted = true;
+                app.status_bar.status = Some(format!("Submitted {}", redact::mask(&event.title)));
+            }
+        }
+        Action::CompletionSaved {
+            session: id,
+            html_url,
+            completed,
+            override_id,
+        } => {
+            if let Some(event) = app.sessions[id].calendar.event_mut(&html_url) {
+                event.override_id = Some(override_id);
+                app.status_bar.status = Some(match completed {
+                    true => format!("Marked {} done", redact::mask(&event.title)),
+                    false => format!("Marked {} not done", redact::mask(&event.title)),
+                });
+            }
+            // A local mark would otherwise bring the item back as done on the next fetch
+            if !completed && app.annotations.completed.contains(&html_url) {
+                app.annotations.set_completed(&html_url, false);
+                if let Err(e) = app.annotations.save() {
+                    app.status_bar.status = Some(format!("Could not save annotations: {e}"));
+                }
+            }
+        }
+        Action::CompletionFailed {
+            session: id,
+            html_url,
+            completed,
+            error,
+        } => {
+            if let Some(event) = app.sessions[id].calendar.event_mut(&html_url) {
+                event.completed = completed;
+                app.popups.error = Some(Toast::new(format!(
+                    "Could not update {}: {error}",
+                    redact::mask(&event.title)
+                )));
+            }
+        }
+        Action::ShowDetail => {
+            let session = &app.sessions[app.focus];
+            let Some(event) = session.selected_event().cloned() else {
+                return;
+            };
+            if event.plannable_type != "assignment" || event.course_id.is_none() {
+                app.status_bar.status = Some("Details are only shown for assignments".to_string());
+                return;
+            }
+            app.popups.detail = Some(DetailPopup::new(&event));
+            let profile = session.profile.clone();
+            let action_tx = app.action_tx.clone();
+            tokio::spawn(async move {
+                let detail = fetch_detail(&profile, &event)
+                    .await
+                    .map_err(|e| e.to_string());
+                let _ = action_tx.send(Action::DetailFetched {
+                    html_url: event.html_url,
+                    detail,
+                });
+            });
+        }
+        Action::CloseDetail => app.popups.detail = None,
+        Action::OpenGrading => {
+            let Some(page) = app
+                .screens
+                .courses
+                .as_ref()
+                .and_then(|courses| courses.page.as_ref())
+            else {
+                return;
+            };
+            let Some((assignment_id, title)) = page
+                .selected_entry()
+                .and_then(|entry| Some((entry.assignment_id?, entry.title.clone())))
+            else {
+                return;
+            };
+            let course = page.context.clone();
+            let id = app.focus;
+            let session = &mut app.sessions[id];
+            let permissions = session.permissions.get(&course.id).copied().flatten();
+            if let Err(reason) = Capability::Grade.check_role(permissions.as_ref()) {
+                session.request_course_permissions(id, course.id, &app.action_tx);
+                app.status_bar.status =
+                    Some(format!("Can't grade {}: {reason}", redact::mask(&title)));
+                return;
+            }
+            app.screens.grading =
+                Some(GradingScreen::new(id, course.clone(), assignment_id, title));
+            let profile = session.profile.clone();
+            let action_tx = app.action_tx.clone();
+            tokio::spawn(async move {
+                let rows = grading::fetch_rows(&profile, course.id, assignment_id)
+                    .await
+                    .map_err(|e| e.to_string());
+                // Without sections the rows just can't be filtered
+                let sections = sections::fetch_sections(&profile, course.id)
+                    .await
+                    .unwrap_or_else(|e| {
+                        log::warn!("Could not load the sections of course {}: {e}", course.id);
+                        vec![]
+                    });
+                let _ = action_tx.send(Action::GradingFetched {
+                    assignment_id,
+                    rows,
+                    sections,
+                });
+            });
+        }
+        Action::GradingFetched {
+            assignment_id,
+            rows,
+            sections,
+        } => {
+            let Some(grading) = app
+                .screens
+                .grading
+                .as_mut()
+                .filter(|grading| grading.assignment_id == assignment_id)
+            else {
+                return;
+            };
+            match rows {
+                Ok(rows) => {
+                    let section = app.section_filters.get(grading.course.id);
+                    grading.set_rows(rows, sections, section);
+                }
+                Err(e) => {
+                    app.screens.grading = None;
+                    app.status_bar.status = Some(format!("Could not load the submissions: {e}"));
+                }
+            }
+        }
+        Action::CycleSection => {
+            let Some(grading) = &mut app.screens.grading else {
+                return;
+            };
+            let section = grading.cycle_section();
+            app.section_filters.set(grading.course.id, section);
+            app.status_bar.status = Some(match app.section_filters.save() {
+                Ok(()) => format!("Showing {}", redact::mask(&grading.section_name())),
+                Err(e) => format!("Could not save the section filter: {e}"),
+            });
+        }
+        Action::SaveGrades => {
+            let Some(grading) = &mut app.screens.grading else {
+                return;
+            };
+            let edits = grading.take_edits();
+            if edits.is_empty() {
+                app.status_bar.status = Some("No grades to save".to_string());
+                return;
+            }
+            app.status_bar.status = Some(format!("Saving {} grade(s)...", edits.len()));
+            grading::save(
+                app.sessions[grading.session].profile.clone(),
+                grading.course.id,
+                grading.assignment_id,
+                edits,
+                app.action_tx.clone(),
+            );
+        }
+        Action::ComposePost(kind) => {
+            let Some(course) = app
+                .screens
+                .courses
+                .as_ref()
+                .and_then(CoursesScreen::context)
+                .cloned()
+            else {
+                return;
+            };
+            let id = app.focus;
+            let session = &mut app.sessions[id];
+            let permissions = session.permissions.get(&course.id).copied().flatten();
+            match Capability::Post.check_role(permissions.as_ref()) {
+                Ok(()) => app.draft_to_edit = Some(Draft::new(id, kind, course)),
+                Err(reason) => {
+                    session.request_course_permissions(id, course.id, &app.action_tx);
+                    app.status_bar.status = Some(format!(
+                        "Can't post to {}: {reason}",
+                        redact::mask(&course.name)
+                    ));
+                }
+            }
+        }
+        Action::EditPost => app.draft_to_edit = app.screens.post_preview.take(),
+        Action::ConfirmPost => {
+            let Some(draft) = app.screens.post_preview.take() else {
+                return;
+            };
+            app.status_bar.status = Some(format!("Posting the {}...", draft.kind.label()));
+            let profile = app.sessions[draft.session].profile.clone();
+            let action_tx = app.action_tx.clone();
+            tokio::spawn(async move {
+                let result = draft.post(&profile).await.map_err(|e| e.to_string());
+                let _ = action_tx.send(Action::Posted { draft, result });
+            });
+        }
+        Action::Posted { draft, result } => match result {
+            Ok(_) => {
+                app.status_bar.status = Some(format!(
+                    "Posted the {} “{}” to {}",
+                    draft.kind.label(),
+                    redact::mask(&draft.title),
+                    redact::mask(&draft.course.name)
+                ))
+            }
+            Err(e) => {
+                app.status_bar.status =
+                    Some(format!("Posting the {} failed: {e}", draft.kind.label()));
+                app.screens.post_preview = Some(draft);
+            }
+        },
+        Action::OpenCourseRoute(route) => {
+            let Some(context) = app
+                .screens
+                .courses
+                .as_ref()
+                .and_then(CoursesScreen::context)
+                .cloned()
+            else {
+                return;
+            };
+            match route {
+                CourseRoute::Syllabus => {
+                    app.screens.reader = Some(ReaderScreen::new(
+                        syllabus_url(context.id),
+                        context.page_title(route),
+                        None,
+                    ))
+                }
+                route => {
+                    if let Some(courses) = &mut app.screens.courses {
+                        courses.open_page(context.clone(), route);
+                    }
+                }
+            }
+            app.fetch_course_route(context, route);
+        }
+        Action::OpenSyllabus => {
+            let Some(event) = app.sessions[app.focus].selected_event() else {
+                return;
+            };
+            let Some(course_id) = event.course_id.filter(|_| !event.is_external()) else {
+                app.status_bar.status = Some(format!(
+                    "{} doesn't belong to a course",
+                    redact::mask(&event.title)
+                ));
+                return;
+            };
+            let context = CourseContext {
+                id: course_id,
+                name: event.course_name.clone(),
+            };
+            let mut reader = ReaderScreen::new(
+                syllabus_url(course_id),
+                context.page_title(CourseRoute::Syllabus),
+                None,
+            );
+            reader.jump_to = Some(event.due_at.date());
+            app.popups.detail = None;
+            app.screens.reader = Some(reader);
+            app.fetch_course_route(context, CourseRoute::Syllabus);
+        }
+        Action::ToggleSolver => {
+            if let Some(page) = app
+                .screens
+                .courses
+                .as_mut()
+                .and_then(|courses| courses.page.as_mut())
+            {
+                page.target = match page.target {
+                    Some(_) => None,
+                    None => Some(app.grades_config.target(&page.context)),
+                };
+            }
+        }
+        Action::EditPlan(PlanEdit::Accept) => {
+            let Some(screen) = app.screens.catch_up.take() else {
+                return;
+            };
+            let reminders = screen.reminders();
+            let count = reminders.len();
+            app.annotations.reminders.extend(reminders);
+            app.status_bar.status = Some(match app.annotations.save() {
+                Ok(()) => format!("Created {count} reminder(s)"),
+                Err(e) => format!("Could not save reminders: {e}"),
+            });
+        }
+        Action::NextPanel => app.dashboard.focus = app.dashboard.focus.next(),
+        Action::Render => {}
+        Action::Navigate(Navigation::PrevEvent) if app.dashboard.list_state().is_some() => {
+            if let Some(list_state) = app.dashboard.list_state() {
+                list_state.select_previous();
+            }
+        }
+        Action::Navigate(Navigation::NextEvent) if app.dashboard.list_state().is_some() => {
+            if let Some(list_state) = app.dashboard.list_state() {
+                list_state.select_next();
+            }
+        }
+        Action::Navigate(Navigation::NextMatch) => app.jump_to_match(SearchDirection::Forward),
+        Action::Navigate(Navigation::PrevMatch) => app.jump_to_match(SearchDirection::Backward),
+        Action::Navigate(navigation) => {
+            let date_index = app.session().current_date_index;
+            app.session().navigate(navigation);
+            if app.session().current_date_index != date_index {
+                app.request_estimates();
+            }
+        }
+        Action::OpenURL if app.screens.bookmarks.is_some() => {
+            let selected = app
+                .screens
+                .bookmarks
+                .as_ref()
+                .and_then(|screen| screen.table_state.selected())
+                .and_then(|index| app.bookmarks.items.get(index));
+            if let Some(url) = selected.map(|bookmark| bookmark.url.clone()) {
+                app.open_item(app.focus, &url);
+            }
+        }
+        Action::OpenURL if app.screens.links.is_some() => {
+            if let Some(url) = app
+                .screens
+                .links
+                .as_ref()
+                .and_then(LinksScreen::selected)
+                .map(|link| link.url.clone())
+            {
+                app.open_item(app.focus, &url);
+            }
+        }
+        Action::OpenLink(number) => {
+            if let Some(link) = app
+                .screens
+                .links
+                .as_mut()
+                .and_then(|links| links.select_number(number))
+            {
+                let url = link.url.clone();
+                app.open_item(app.focus, &url);
+            }
+        }
+        Action::CopyLink => {
+            let Some(link) = app.screens.links.as_ref().and_then(LinksScreen::selected) else {
+                return;
+            };
+            app.status_bar.status = Some(match app.clipboard.copy(&link.url) {
+                Ok(()) => format!("Copied {}", redact::mask(&link.url)),
+                Err(e) => format!("Could not copy the link: {e}"),
+            });
+        }
+        Action::OpenURL
+            if app.screens.reader.is_some()
+                || app.screens.courses.is_some()
+                || app.screens.announcements.is_some()
+                || app.screens.todo.is_some()
+                || app.screens.catch_up.is_some() =>
+        {
+            let url = match (
+                &app.screens.reader,
+                &app.screens.courses,
+                &app.screens.announcements,
+                &app.screens.todo,
+            ) {
+                (Some(reader), _, _, _) => Some(reader.html_url.as_str()),
+                (None, Some(courses), _, _) => courses
+                    .page
+                    .as_ref()
+                    .and_then(|page| page.selected_entry()?.url.as_deref()),
+                (None, None, Some(screen), _) => screen
+                    .selected()
+                    .map(|announcement| announcement.html_url.as_str()),
+                (None, None, None, Some(screen)) => screen
+                    .selected()
+                    .map(|item| item.assignment.html_url.as_str()),
+                (None, None, None, None) => app
+                    .screens
+                    .catch_up
+                    .as_ref()
+                    .and_then(|screen| screen.selected_event())
+                    .map(|event| event.html_url.as_str()),
+            };
+            let Some(url) = url.map(str::to_string) else {
+                return;
+            };
+            app.open_item(app.focus, &url);
+        }
+        Action::OpenURL if app.screens.agenda.is_some() => {
+            let Some((id, event)) = app.screens.agenda.as_ref().and_then(AgendaScreen::selected)
+            else {
+                return;
+            };
+            // External events carry their own absolute url
+            let url = event.html_url.clone();
+            app.open_item(id, &url);
+        }
+        Action::OpenURL => {
+            let session = &app.sessions[app.focus];
+            let modules_event = app
+                .screens
+                .modules
+                .as_ref()
+                .map(ModulesScreen::selected_event);
+            let detail = app.popups.detail.as_ref();
+            let html_url = match (detail, modules_event, app.dashboard.selected_url()) {
+                (Some(detail), _, _) => &detail.html_url,
+                (None, Some(None), _) => return,
+                (None, Some(Some(event)), _) => &event.html_url,
+                (None, None, Some(html_url)) => html_url,
+                (None, None, None) => match session.selected_event() {
+                    Some(event) => &event.html_url,
+                    None => return,
+                },
+            };
+            let html_url = html_url.to_string();
+            app.open_item(app.focus, &html_url);
+        }
+        Action::ToggleView(View::Receipts) => {
+            app.screens.receipts = match app.screens.receipts {
+                Some(_) => None,
+                None => match ReceiptsScreen::load() {
+                    Ok(receipts) => Some(receipts),
+                    Err(e) => {
+                        app.status_bar.status = Some(format!("Could not read receipts: {e}"));
+                        None
+                    }
+                },
+            }
+        }
+        Action::ToggleView(View::Audit) => {
+            app.screens.audit = match app.screens.audit {
+                Some(_) => None,
+                None => Some(AuditScreen::new()),
+            }
+        }
+        Action::ToggleView(View::Announcements) => {
+            if app.screens.announcements.take().is_some() {
+                return;
+            }
+            app.screens.announcements = app.open(AnnouncementsScreen::loading());
+        }
+        Action::OpenAnnouncement => {
+            let Some(announcement) = app
+                .screens
+                .announcements
+                .as_ref()
+                .and_then(AnnouncementsScreen::selected)
+            else {
+                return;
+            };
+            app.screens.reader = Some(ReaderScreen::new(
+                announcement.html_url.clone(),
+                announcement.title.clone(),
+                Some(announcement.message.clone().unwrap_or_default()),
+            ));
+        }
+        Action::ToggleView(View::Inbox) => {
+            if app.screens.inbox.take().is_some() {
+                return;
+            }
+            app.screens.inbox = app.open(InboxScreen::loading());
+        }
+        Action::OpenConversation => app.open_conversation(),
+        Action::ComposeReply => {
+            if app
+                .screens
+                .inbox
+                .as_ref()
+                .is_some_and(|inbox| inbox.thread.is_none())
+            {
+                app.open_conversation();
+            }
+            if let Some(inbox) = &mut app.screens.inbox
+                && inbox.thread.is_some()
+            {
+                inbox.compose = Some(TextArea::default());
+            }
+        }
+        Action::ComposeKey(key) => {
+            let Some(inbox) = &mut app.screens.inbox else {
+                return;
+            };
+            let Some(compose) = &mut inbox.compose else {
+                return;
+            };
+            if inbox.sending {
+                return;
+            }
+            let send =
+                key.code == KeyCode::Char('s') && key.modifiers.contains(KeyModifiers::CONTROL);
+            if !send {
+                compose.input(key);
+                return;
+            }
+            if compose.is_empty() {
+                app.status_bar.status = Some("Write something before sending".to_string());
+                return;
+            }
+            let Some(thread) = inbox.thread.as_ref().and_then(Option::as_ref) else {
+                app.status_bar.status =
+                    Some("Wait for the conversation to load before sending".to_string());
+                return;
+            };
+            inbox.sending = true;
+            let (id, body) = (thread.id, compose.text());
+            let profile = app.sessions[app.focus].profile.clone();
+            let action_tx = app.action_tx.clone();
+            tokio::spawn(async move {
+                let action = match inbox::reply(&profile, id, body.trim()).await {
+                    Ok(thread) => Action::ReplySent(thread),
+                    Err(e) => Action::ReplyFailed {
+                        error: e.to_string(),
+                    },
+                };
+                let _ = action_tx.send(action);
+            });
+        }
+        Action::ReplySent(_) => {
+            app.status_bar.status = Some("Reply sent".to_string());
+        }
+        Action::ReplyFailed { error } => {
+            app.popups.error = Some(Toast::new(format!(
+                "Sending the reply failed, it is still in the box: {error}"
+            )));
+        }
+        Action::ToggleView(View::Todo) => {
+            if app.screens.todo.take().is_some() {
+                return;
+            }
+            app.screens.todo = app.open(TodoScreen::loading());
+        }
+        Action::ToggleView(View::Modules) => {
+            if app.screens.modules.take().is_some() {
+                return;
+            }
+            app.popups.detail = None;
+            let Some(event) = app.sessions[app.focus].selected_event() else {
+                return;
+            };
+            let Some(course_id) = event.course_id.filter(|_| !event.is_external()) else {
+                app.status_bar.status = Some(format!(
+                    "{} doesn't belong to a course",
+                    redact::mask(&event.title)
+                ));
+                return;
+            };
+            let screen = ModulesScreen::loading(
+                course_id,
+                event.course_name.clone(),
+                event.html_url.clone(),
+            );
+            app.screens.modules = app.open(screen);
+        }
+        Action::ToggleView(View::Reader) => {
+            if app.screens.reader.take().is_some() {
+                return;
+            }
+            let session = &app.sessions[app.focus];
+            let Some(event) = session.selected_event() else {
+                return;
+            };
+            if event.is_external() {
+                app.status_bar.status =
+                    Some("Events from external calendars have no description".to_string());
+                return;
+            }
+            app.screens.reader = Some(ReaderScreen::new(
+                event.html_url.clone(),
+                event.title.clone(),
+                session.descriptions.get(&event.html_url).cloned(),
+            ));
+        }
+        Action::OpenInPager => {
+            let session = &app.sessions[app.focus];
+            let (title, description) = match &app.screens.reader {
+                Some(reader) => (reader.title.clone(), reader.description()),
+                None => {
+                    let Some(event) = session.selected_event() else {
+                        return;
+                    };
+                    if event.is_external() {
+                        app.status_bar.status =
+                            Some("Events from external calendars have no description".to_string());
+                        return;
+                    }
+                    (
+                        event.title.clone(),
+                        session
+                            .descriptions
+                            .get(&event.html_url)
+                            .map(String::as_str),
+                    )
+                }
+            };
+            let Some(description) = description else {
+                app.status_bar.status = Some("The description is still loading".to_string());
+                return;
+            };
+            let markdown = pager::to_markdown(description, &session.profile.url);
+            app.to_page = Some(redact::mask(&format!("# {title}\n\n{markdown}")));
+        }
+        Action::ToggleView(View::Links) => {
+            if app.screens.links.take().is_some() {
+                return;
+            }
+            let session = &app.sessions[app.focus];
+            let base = session.profile.url.clone();
+            app.screens.links = match &app.screens.reader {
+                Some(reader) => Some(LinksScreen::new(
+                    reader.html_url.clone(),
+                    reader.title.clone(),
+                    base,
+                    reader.description(),
+                )),
+                None => {
+                    let Some(event) = session.selected_event() else {
+                        return;
+                    };
+                    if event.is_external() {
+                        app.status_bar.status =
+                            Some("Events from external calendars have no description".to_string());
+                        return;
+                    }
+                    Some(LinksScreen::new(
+                        event.html_url.clone(),
+                        event.title.clone(),
+                        base,
+                        session
+                            .descriptions
+                            .get(&event.html_url)
+                            .map(String::as_str),
+                    ))
+                }
+            };
+        }
+        Action::CycleLayout => {
+            app.screens.grid = match app.screens.grid.take() {
+                None => {
+                    let cursor = app.sessions[app.focus]
+                        .current_date()
+                        .unwrap_or_else(|| now_local().date());
+                    Some(GridScreen::new(GridLayout::Week, cursor))
+                }
+                Some(grid) => match grid.layout {
+                    GridLayout::Week => Some(GridScreen::new(GridLayout::Month, grid.cursor)),
+                    GridLayout::Month => None,
+                },
+            }
+        }
+        Action::OpenGridDay => {
+            let Some(day) = app.screens.grid.as_ref().map(|grid| grid.cursor) else {
+                return;
+            };
+            let session = app.session();
+            let index = session.calendar.dates.iter().position(|date| {
+                date.events
+                    .first()
+                    .is_some_and(|event| event.due_at.date() == day)
+            });
+            match index {
+                Some(index) => {
+                    session.current_date_index = index;
+                    app.screens.grid = None;
+                    app.request_estimates();
+                }
+                None => {
+                    app.status_bar.status = Some(format!(
+                        "Nothing is due on {}",
+                        locale::current().format(day, "%a %b %-d")
+                    ));
+                }
+            }
+        }
+        Action::ToggleView(View::Agenda) => {
+            app.screens.agenda = match app.screens.agenda {
+                Some(_) => None,
+                None => Some(AgendaScreen::new(&app.sessions)),
+            }
+        }
+        Action::ToggleView(View::CatchUp) => {
+            if app.screens.catch_up.take().is_some() {
+                return;
+            }
+            let today = now_local().date();
+            app.screens.catch_up = Some(CatchUpScreen::loading(app.catch_up_config.clone(), today));
+            let id = app.focus;
+            let profile = app.sessions[id].profile.clone();
+            let action_tx = app.action_tx.clone();
+            tokio::spawn(async move {
+                let events = match fetch_missing(&profile).await {
+                    Ok(events) => events,
+                    Err(e) => {
+                        let _ = action_tx.send(Action::Status(format!(
+                            "Could not fetch overdue items, planning upcoming ones only: {e}"
+                        )));
+                        vec![]
+                    }
+                };
+                let _ = action_tx.send(Action::MissingFetched {
+                    session: id,
+                    events,
+                });
+            });
+        }
+        Action::ToggleView(View::Triage) => {
+            app.screens.triage = match app.screens.triage {
+                Some(_) => None,
+                None => Some(TriageScreen::default()),
+            }
+        }
+        Action::Triage(decision) => {
+            if app.triage_queue.is_empty() {
+                return;
+            }
+            let change = app.triage_queue.remove(0);
+            let event = &change.event;
+            let now = OffsetDateTime::now_utc();
+            let title = redact::mask(&event.title);
+            app.status_bar.status = Some(match decision {
+                Decision::Open => {
+                    if !app.open_item(change.session, &event.html_url) {
+                        return;
+                    }
+                    format!("Opened {title}")
+                }
+                Decision::Remind => {
+                    app.annotations.reminders.insert(
+                        event.html_url.clone(),
+                        Reminder {
+                            at: now + triage::REMIND_AFTER,
+                            title: event.title.clone(),
+                            course_name: event.course_name.clone(),
+                        },
+                    );
+                    format!(
+                        "Reminding you about {title} in {}h",
+                        triage::REMIND_AFTER.whole_hours()
+                    )
+                }
+                Decision::Snooze => {
+                    app.annotations.snoozed.insert(
+                        event.html_url.clone(),
+                        Snooze {
+                            until: now + triage::SNOOZE_FOR,
+                        },
+                    );
+                    format!("Snoozed {title} until tomorrow")
+                }
+                Decision::Done => {
+                    app.annotations.set_completed(&event.html_url, true);
+                    let calendar = &mut app.sessions[change.session].calendar;
+                    calendar.apply_annotations(&app.annotations);
+                    format!("Marked {title} done")
+                }
+                Decision::Skip => return,
+            });
+            if let Err(e) = app.annotations.save() {
+                app.status_bar.status = Some(format!("Could not save the decision: {e}"));
+            }
+        }
+        Action::ToggleView(View::Courses) => {
+            app.screens.courses = match app.screens.courses {
+                Some(_) => None,
+                None => {
+                    // Permissions decide whether the course can be posted to
+                    let id = app.focus;
+                    let session = &mut app.sessions[id];
+                    let course_ids: Vec<u64> = session.courses.keys().copied().collect();
+                    for course_id in course_ids {
+                        session.request_course_permissions(id, course_id, &app.action_tx);
+                    }
+                    Some(CoursesScreen::new(session))
+                }
+            }
+        }
+        Action::ToggleView(View::Grades) => {
+            if app
+                .screens
+                .courses
+                .take()
+                .is_some_and(|courses| courses.show_grades)
+            {
+                return;
+            }
+            let id = app.focus;
+            let session = &mut app.sessions[id];
+            let course_ids: Vec<u64> = session.courses.keys().copied().collect();
+            for course_id in course_ids {
+                session.request_course_permissions(id, course_id, &app.action_tx);
+            }
+            app.screens.courses = Some(CoursesScreen::with_grades(session));
+            let profile = session.profile.clone();
+            let action_tx = app.action_tx.clone();
+            tokio::spawn(async move {
+                let grades = courses::fetch_course_grades(&profile)
+                    .await
+                    .map_err(|e| e.to_string());
+                let _ = action_tx.send(Action::CourseGradesFetched { grades });
+            });
+        }
+        Action::ToggleView(View::CourseFilter) => {
+            app.course_filter = match app.course_filter {
+                Some(_) => None,
+                None => Some(CourseFilterSidebar::new(&app.sessions[app.focus])),
+            }
+        }
+        Action::ToggleCourseHidden => {
+            let Some((course_id, name)) = app
+                .course_filter
+                .as_ref()
+                .and_then(|sidebar| sidebar.selected().cloned())
+            else {
+                return;
+            };
+            let session = &mut app.sessions[app.focus];
+            let hidden = app.hidden_courses.toggle(&session.profile.name, course_id);
+            session.hidden_courses = app.hidden_courses.get(&session.profile.name);
+            session.reveal_selection();
+            app.status_bar.status = Some(match hidden {
+                true => format!("Hiding {}", redact::mask(&name)),
+                false => format!("Showing {}", redact::mask(&name)),
+            });
+            if let Err(e) = app.hidden_courses.save() {
+                app.status_bar.status = Some(format!("Could not save the course filter: {e}"));
+            }
+        }
+        Action::ToggleCourseFocus => {
+            let session = &mut app.sessions[app.focus];
+            match session.focused_course.take() {
+                Some(course) => {
+                    app.status_bar.status = Some(format!(
+                        "Showing every course again, not just {}",
+                        redact::mask(&course.name)
+                    ));
+                }
+                None => {
+                    let Some(course) = session.selected_event().and_then(|event| {
+                        Some(CourseContext {
+                            id: event.course_id.filter(|_| !event.is_external())?,
+                            name: event.course_name.clone(),
+                        })
+                    }) else {
+                        app.status_bar.status = Some("No course to focus on".to_string());
+                        return;
+                    };
+                    app.status_bar.status = Some(format!(
+                        "Focused on {}, z shows every course again",
+                        redact::mask(&course.name)
+                    ));
+                    session.focused_course = Some(course);
+                }
+            }
+            session.reveal_selection();
+        }
+        Action::ToggleView(View::Bookmarks) => {
+            app.screens.bookmarks = match app.screens.bookmarks {
+                Some(_) => None,
+                None => Some(BookmarksScreen::new()),
+            }
+        }
+        Action::ToggleBookmark => {
+            let status = match &app.screens.bookmarks {
+                Some(screen) => {
+                    let Some(index) = screen
+                        .table_state
+                        .selected()
+                        .filter(|index| *index < app.bookmarks.items.len())
+                    else {
+                        return;
+                    };
+                    let bookmark = app.bookmarks.items.remove(index);
+                    format!("Removed bookmark {}", redact::mask(&bookmark.title))
+                }
+                None => {
+                    let session = &app.sessions[app.focus];
+                    let event = match &app.screens.modules {
+                        Some(modules) => modules.selected_event(),
+                        None => session.selected_event(),
+                    };
+                    let Some(event) = event else {
+                        return;
+                    };
+                    if event.is_external() {
+                        app.status_bar.status =
+                            Some("Events from external calendars can't be bookmarked".to_string());
+                        return;
+                    }
+                    let title = event.title.clone();
+                    let base = session.profile.url.parse::<Url>().ok();
+                    let url = match Url::options()
+                        .base_url(base.as_ref())
+                        .parse(&event.html_url)
+                    {
+                        Ok(url) => url,
+                        Err(e) => {
+                            app.status_bar.status =
+                                Some(format!("Could not bookmark {}: {e}", redact::mask(&title)));
+                            return;
+                        }
+                    };
+                    let added = app.bookmarks.toggle(Bookmark {
+                        url: url.to_string(),
+                        title: title.clone(),
+                        course_name: event.course_name.clone(),
+                        kind: event.plannable_type.clone(),
+                        added_at: OffsetDateTime::now_utc(),
+                    });
+                    match added {
+                        true => format!("Bookmarked {}", redact::mask(&title)),
+                        false => format!("Removed bookmark {}", redact::mask(&title)),
+                    }
+                }
+            };
+            app.status_bar.status = Some(match app.bookmarks.save() {
+                Ok(()) => status,
+                Err(e) => format!("Could not save bookmarks: {e}"),
+            });
+        }
+        Action::Undo => match app.history.pop() {
+            Some(position) => {
+                position.restore(app);
+                app.request_estimates();
+            }
+            None => app.status_bar.status = Some("Nothing to undo".to_string()),
+        },
+        Action::ToggleMetrics => app.popups.metrics = !app.popups.metrics,
+        Action::ToggleHelp => {
+            app.popups.help = match app.popups.help {
+                Some(_) => None,
+                None => Some(HelpOverlay::new(&app.keymap)),
+            };
+        }
+        Action::ToggleRedact => {
+            if let Err(e) = redact::set(!redact::enabled()) {
+                app.status_bar.status = Some(format!("Could not save redacted mode: {e}"));
+            }
+        }
+        Action::FocusNext => {
+            app.focus = (app.focus + 1) % app.sessions.len();
+        }
+        Action::SwitchProfile => app.switch_profile(),
+        // Only the popups and screens they are for keep anything of these
+        Action::DetailFetched { .. }
+        | Action::CoursePageFetched { .. }
+        | Action::AdjustTarget(_)
+        | Action::CourseGradesFetched { .. }
+        | Action::ModulesFetched { .. }
+        | Action::MissingFetched { .. }
+        | Action::AnnouncementsFetched(_)
+        | Action::ConversationsFetched(_)
+        | Action::ConversationFetched(_)
+        | Action::TodoFetched(_)
+        | Action::GradeSaved { .. }
+        | Action::EditPlan(_) => {}
+        // Answered by the dispatcher before actions get here
+        Action::Confirm(_) | Action::None => {}
+    };
+}
+
+/// Shows the login form in the terminal, None if the user quit it
+async fn show_login(config: &Config) -> Result<Option<(String, String)>> {
+    let mut tui = tui::Tui::new()?;
+    tui.enter()?;
+    let signed_in = login::form(&mut tui, config.production_url()).await;
+    tui.exit()?;
+    signed_in
+}
+
+/// Signs in with an access token through the login form, replacing the stored one
+async fn login() -> Result<()> {
+    let config = Config::load()?;
+    theme::init(&config.theme)?;
+    if let Some((url, name)) = show_login(&config).await? {
+        println!("Signed in to {url} as {name}, the token is stored in the keyring");
+    }
+    Ok(())
+}
+
+async fn run(open: Option<Target>, split: Option<String>, agenda: bool) -> Result<()> {
+    let config = Config::load()?;
+    let (action_tx, mut action_rx) = mpsc::unbounded_channel(); // new
+
+    locale::init(&config.locale)?;
+    capabilities::init(&config.terminal);
+    theme::init(&config.theme)?;
+
+    let agenda = agenda || config.default_view == DefaultView::Agenda;
+    let show_instructors = config.show_instructors;
+    let main = match config.default_profile() {
+        Ok(profile) => profile,
+        // First run, nothing to sign in with yet
+        Err(_) if config.needs_login() => match show_login(&config).await? {
+            Some(_) => config.default_profile()?,
+            None => return Ok(()),
+        },
+        Err(e) => return Err(e),
+    };
+    let mut profiles = vec![main];
+    if let Some(name) = split {
+        profiles.push(config.profile(&name)?);
+    }
+    if agenda {
+        for profile in &config.profiles {
+            if !profiles.iter().any(|loaded| loaded.name == profile.name) {
+                profiles.push(profile.clone().with_token()?);
+            }
+        }
+    }
+
+    for (id, profile) in profiles.iter().enumerate() {
+        startup::spawn_cached(id, profile, &action_tx);
+    }
+
+    for calendar in config.calendars.clone() {
+        let (action_tx, horizon) = (action_tx.clone(), config.horizon);
+        tokio::spawn(async move {
+            match ics::fetch_external(&calendar, horizon).await {
+                Ok(events) => {
+                    let _ = action_tx.send(Action::OverlayFetchComplete {
+                        name: calendar.name,
+                        events,
+                    });
+                }
+                Err(e) => log::error!("Failed to fetch calendar {}: {e}", calendar.name),
+            }
+        });
+    }
+
+    let mut annotations = Annotations::load()?;
+    let mut status = None;
+    if let Some(sync) = &config.sync
+        && let Err(e) = sync::sync_annotations(sync, &mut annotations)
+    {
+        status = Some(format!("Syncing annotations failed: {e}"));
+    }
+
+    let mut switchable = profiles.clone();
+    // The default profile stays reachable when --profile replaced it
+    if !switchable
+        .iter()
+        .any(|profile| profile.name == config.main_profile_name())
+        && let Ok(main) = config.main_profile()
+    {
+        switchable.insert(0, main);
+    }
+    for profile in &config.profiles {
+        if !switchable.iter().any(|open| open.name == profile.name) {
+            switchable.push(profile.clone());
+        }
+    }
+
+    let mut app = App::new(config, profiles, agenda, annotations, action_tx.clone())?;
+    app.profiles = switchable;
+    app.pending_open = open;
+    app.status_bar.status = status;
+    let mut tui = tui::Tui::new()?;
+    tui.enter()?;
+    for (id, session) in app.sessions.iter().enumerate() {
+        startup::spawn(id, &session.profile, show_instructors, &action_tx);
+    }
+    let mut dispatcher = Dispatcher::new();
+
+    loop {
+        let e = tui.next().await?;
+        match e {
+            tui::Event::Tick => action_tx.send(Action::Tick)?,
+            tui::Event::Render => action_tx.send(Action::Render)?,
+            tui::Event::Key(_) => {
+                let action = get_action(&mut app, e);
+                action_tx.send(action.clone())?;
+            }
+            _ => {}
+        };
+
+        metrics::record_queue_depth(action_rx.len());
+        while let Ok(action) = action_rx.try_recv() {
+            let render = matches!(action, Action::Render);
+            dispatcher.dispatch(&mut app, action);
+            if render {
+                let started = Instant::now();
+                tui.draw(|f| {
+                    ui(f, &mut app);
+                })?;
+                metrics::record_frame(started);
+            }
+        }
+        if let Some(date) = app.note_to_edit.take() {
+            tui.exit()?;
+            let note = app.sessions[app.focus].notes.get(date).unwrap_or_default();
+            let edited = notes::edit(date, note);
+            tui.enter()?;
+            tui.clear()?;
+            match edited {
+                Ok(text) => dispatcher.dispatch(&mut app, Action::SaveNote(date, text)),
+                Err(e) => app.status_bar.status = Some(format!("Could not edit the note: {e}")),
+            }
+        }
+        if let Some(draft) = app.draft_to_edit.take() {
+            tui.exit()?;
+            let edited = editor::edit("post", &draft.to_text());
+            tui.enter()?;
+            tui.clear()?;
+            app.set_draft(draft, edited);
+        }
+        if let Some(paths) = app.files_to_edit.take() {
+            tui.exit()?;
+            let opened = editor::open(&paths);
+            tui.enter()?;
+            tui.clear()?;
+            if let Err(e) = opened {
+                app.status_bar.status = Some(format!("Could not open the editor: {e}"));
+            }
+        }
+        if let Some(markdown) = app.to_page.take() {
+            tui.exit()?;
+            let shown = pager::show("description", &markdown);
+            tui.enter()?;
+            tui.clear()?;
+            if let Err(e) = shown {
+                app.status_bar.status = Some(format!("Could not open the pager: {e}"));
+            }
+        }
+
+        if app.should_quit {
+            break;
+        }
+    }
+    tui.exit()?;
+
+    Ok(())
+}
+
+/// Syncs annotations through the sync directory and deadlines with the CalDAV server, whichever
+/// are configured
+async fn sync(config: &Config) -> Result<()> {
+    if config.sync.is_none() && config.caldav.is_none() {
+        return Err(eyre!("No [sync] or [caldav] section in the config file"));
+    }
+    if let Some(sync) = &config.sync {
+        sync::sync_annotations(sync, &mut Annotations::load()?)?;
+    }
+    if config.caldav.is_some() {
+        caldav::sync(config).await?;
+        // Publish completion status pulled from the CalDAV server to other devices
+        if let Some(sync) = &config.sync {
+            sync::sync_annotations(sync, &mut Annotations::load()?)?;
+        }
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    color_eyre::install()?;
+    let cli = Cli::parse();
+    if let Some(profile) = cli.profile {
+        config::select_profile(profile);
+    }
+    redact::load();
+    let result = match cli.command {
+        Some(Command::Feed { course, out }) => feed::write_feed(&course, &out),
+        Some(Command::Archive { course, dir, files }) => {
+            archive::archive(&course, &dir, files).await
+        }
+        Some(Command::WhatIf { course, weights }) => grades::what_if(&course, &weights).await,
+        Some(Command::Export { out, ics: true }) => {
+            ics::export(&out.unwrap_or("canvastui.ics".into()))
+        }
+        Some(Command::Export { out, ics: false }) => {
+            backup::export(&out.unwrap_or("canvastui-export.json".into()))
+        }
+        Some(Command::Import { file }) => backup::import(&file),
+        Some(Command::Prompt { plain }) => shell_prompt::print(plain),
+        Some(Command::Print { date, pdf }) => printout::print(&date, pdf.as_deref()).await,
+        Some(Command::Completions { shell }) => {
+            cli::print_completions(shell);
+            Ok(())
+        }
+        Some(Command::Man { dir }) => cli::print_man(dir.as_deref()),
+        Some(Command::ImportNotifications { apply }) => preferences::import(apply).await,
+        Some(Command::Sync) => sync(&Config::load()?).await,
+        Some(Command::Due {
+            within,
+            course,
+            unsubmitted,
+        }) => match due::print(&within, course.as_deref(), unsubmitted).await {
+            Ok(true) => std::process::exit(1),
+            result => result.map(|_| ()),
+        },
+        Some(Command::List {
+            within,
+            json,
+            plain,
+        }) => {
+            let format = match (json, plain) {
+                (true, _) => ListFormat::Json,
+                (false, true) => ListFormat::Plain,
+                (false, false) => ListFormat::Text,
+            };
+            list::print(&within, format).await
+        }
+        Some(Command::Login) => login().await,
+        Some(Command::CookieLogin) => sso::login().await,
+        Some(Command::Open { target }) => match Target::parse(&target) {
+            Some(target) => run(Some(target), cli.split, cli.agenda).await,
+            None => Err(eyre!("{target:?} is not a Canvas url or id")),
+        },
+        None => run(None, cli.split, cli.agenda).await,
+    };
 
     result?;
 
@@ -0,0 +1,185 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    layout::Rect,
+    prelude::Buffer,
+    style::Style,
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+
+use crate::{ratelimit, theme};
+
+/// Number of recent frames FPS and frame time are averaged over
+const FRAME_WINDOW: usize = 60;
+
+/// Lightweight runtime counters shown in the performance overlay
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    frame_times: VecDeque<Duration>,
+    frame_starts: VecDeque<Instant>,
+    queue_depth: usize,
+    max_queue_depth: usize,
+    /// Duration of the last request to each endpoint, with ids replaced by `:id`
+    fetch_durations: BTreeMap<String, Duration>,
+    cache_hits: u64,
+    cache_misses: u64,
+    actions_handled: u64,
+    action_time: Duration,
+}
+
+static METRICS: LazyLock<Mutex<Metrics>> = LazyLock::new(Mutex::default);
+
+fn with_metrics(f: impl FnOnce(&mut Metrics)) {
+    if let Ok(mut metrics) = METRICS.lock() {
+        f(&mut metrics);
+    }
+}
+
+pub fn record_frame(started: Instant) {
+    with_metrics(|metrics| {
+        metrics.frame_times.push_back(started.elapsed());
+        metrics.frame_starts.push_back(started);
+        if metrics.frame_times.len() > FRAME_WINDOW {
+            metrics.frame_times.pop_front();
+            metrics.frame_starts.pop_front();
+        }
+    });
+}
+
+/// Records how many actions were waiting when the main loop started draining the queue
+pub fn record_queue_depth(depth: usize) {
+    with_metrics(|metrics| {
+        metrics.queue_depth = depth;
+        metrics.max_queue_depth = metrics.max_queue_depth.max(depth);
+    });
+}
+
+pub fn record_fetch(path: &str, duration: Duration) {
+    let endpoint = path
+        .split('/')
+        .map(|segment| match segment.parse::<u64>() {
+            Ok(_) => ":id",
+            Err(_) => segment,
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    with_metrics(|metrics| {
+        metrics.fetch_durations.insert(endpoint, duration);
+    });
+}
+
+pub fn record_cache(hit: bool) {
+    with_metrics(|metrics| match hit {
+        true => metrics.cache_hits += 1,
+        false => metrics.cache_misses += 1,
+    });
+}
+
+pub fn record_action(duration: Duration) {
+    with_metrics(|metrics| {
+        metrics.actions_handled += 1;
+        metrics.action_time += duration;
+    });
+}
+
+pub fn snapshot() -> Metrics {
+    METRICS
+        .lock()
+        .map(|metrics| metrics.clone())
+        .unwrap_or_default()
+}
+
+impl Metrics {
+    fn fps(&self) -> f64 {
+        match (self.frame_starts.front(), self.frame_starts.back()) {
+            (Some(first), Some(last)) if self.frame_starts.len() > 1 => {
+                (self.frame_starts.len() - 1) as f64 / (*last - *first).as_secs_f64()
+            }
+            _ => 0.0,
+        }
+    }
+
+    fn average_frame_time(&self) -> Duration {
+        match self.frame_times.len() {
+            0 => Duration::ZERO,
+            len => self.frame_times.iter().sum::<Duration>() / len as u32,
+        }
+    }
+}
+
+/// Popup showing the metrics in the top right corner of the area it is rendered in
+pub struct MetricsOverlay;
+
+impl Widget for MetricsOverlay {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let metrics = snapshot();
+        let cache_lookups = metrics.cache_hits + metrics.cache_misses;
+        let mut lines = vec![
+            Line::from(format!(
+                "{:.1} fps · {:.2?} per frame",
+                metrics.fps(),
+                metrics.average_frame_time()
+            )),
+            Line::from(format!(
+                "Queue depth {} (max {})",
+                metrics.queue_depth, metrics.max_queue_depth
+            )),
+            Line::from(format!(
+                "{} actions in {:.2?}",
+                metrics.actions_handled, metrics.action_time
+            )),
+            Line::from(match cache_lookups {
+                0 => "Cache hit rate -".to_string(),
+                _ => format!(
+                    "Cache hit rate {:.0}% ({}/{cache_lookups})",
+                    metrics.cache_hits as f64 / cache_lookups as f64 * 100.0,
+                    metrics.cache_hits
+                ),
+            }),
+            Line::from(match ratelimit::remaining() {
+                Some(remaining) => format!("API budget {remaining:.0} left"),
+                None => "API budget -".to_string(),
+            }),
+            Line::from("Last fetches:".to_string()),
+        ];
+        lines.extend(
+            metrics
+                .fetch_durations
+                .iter()
+                .map(|(endpoint, duration)| Line::from(format!("  {duration:>9.1?} {endpoint}"))),
+        );
+
+        let width = lines
+            .iter()
+            .map(Line::width)
+            .max()
+            .unwrap_or_default()
+            .min(area.width.saturating_sub(2) as usize) as u16
+            + 2;
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup = Rect {
+            x: area.right().saturating_sub(width),
+            y: area.y,
+            width,
+            height,
+        };
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title(" Performance ")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme::current().warning)),
+            )
+            .style(Style::default().fg(theme::current().text))
+            .render(popup, buf);
+    }
+}
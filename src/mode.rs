@@ -0,0 +1,123 @@
+use crate::{dashboard::Panel, grid::GridLayout};
+
+/// What the app is currently doing, in the order Esc backs out of it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mode {
+    /// A held back action waits for a yes or no
+    Confirm,
+    Prompt,
+    /// A reply is being written in the inbox
+    Compose,
+    /// Part of a multi-key sequence has been typed
+    PendingKeys(String),
+    /// The keybindings shown over everything else
+    Help,
+    /// Details of an assignment shown over the planner
+    Detail,
+    /// The course filter sidebar has the keys
+    CourseFilter,
+    /// A drafted announcement or discussion topic waiting to be posted
+    PostPreview,
+    /// Grade entry for an assignment
+    Grading,
+    Receipts,
+    /// What recent actions led to
+    Audit,
+    Modules,
+    Bookmarks,
+    Links,
+    Reader,
+    /// A page of a course opened from the courses view
+    CoursePage,
+    Courses,
+    /// The courses view opened for current grades
+    Grades,
+    Announcements,
+    /// A conversation opened from the inbox
+    Thread,
+    Inbox,
+    Todo,
+    Triage,
+    CatchUp,
+    Agenda,
+    /// Week or month grid of days
+    Grid(GridLayout),
+    Metrics,
+    Panel(Panel),
+    /// The planner is filtered by a search
+    Search,
+    Normal,
+}
+
+impl Mode {
+    pub fn label(&self) -> String {
+        match self {
+            Mode::Confirm => "CONFIRM".to_string(),
+            Mode::Prompt => "PROMPT".to_string(),
+            Mode::Compose => "COMPOSE".to_string(),
+            Mode::PendingKeys(keys) => format!("{keys}…"),
+            Mode::Help => "HELP".to_string(),
+            Mode::PostPreview => "POST".to_string(),
+            Mode::Detail => "DETAIL".to_string(),
+            Mode::CourseFilter => "COURSE FILTER".to_string(),
+            Mode::Grading => "GRADING".to_string(),
+            Mode::Receipts => "RECEIPTS".to_string(),
+            Mode::Audit => "AUDIT".to_string(),
+            Mode::Modules => "MODULES".to_string(),
+            Mode::Bookmarks => "BOOKMARKS".to_string(),
+            Mode::Links => "LINKS".to_string(),
+            Mode::Reader => "READER".to_string(),
+            Mode::CoursePage => "COURSE".to_string(),
+            Mode::Courses => "COURSES".to_string(),
+            Mode::Grades => "GRADES".to_string(),
+            Mode::Announcements => "ANNOUNCEMENTS".to_string(),
+            Mode::Thread => "THREAD".to_string(),
+            Mode::Inbox => "INBOX".to_string(),
+            Mode::Todo => "TO-DO".to_string(),
+            Mode::Triage => "TRIAGE".to_string(),
+            Mode::CatchUp => "CATCH-UP".to_string(),
+            Mode::Agenda => "AGENDA".to_string(),
+            Mode::Grid(layout) => format!("{layout:?}").to_uppercase(),
+            Mode::Metrics => "METRICS".to_string(),
+            Mode::Panel(panel) => format!("{panel:?}").to_uppercase(),
+            Mode::Search => "SEARCH".to_string(),
+            Mode::Normal => "NORMAL".to_string(),
+        }
+    }
+
+    /// What pressing Esc does in this mode
+    pub fn escape_hint(&self) -> Option<&'static str> {
+        match self {
+            Mode::Confirm => Some("cancel"),
+            Mode::Prompt => Some("cancel"),
+            Mode::Compose => Some("discard reply"),
+            Mode::PendingKeys(_) => Some("clear keys"),
+            Mode::Help => Some("close help"),
+            Mode::PostPreview => Some("discard post"),
+            Mode::Detail => Some("close details"),
+            Mode::CourseFilter => Some("close course filter"),
+            Mode::Grading => Some("close grading"),
+            Mode::Receipts => Some("close receipts"),
+            Mode::Audit => Some("close audit trail"),
+            Mode::Modules => Some("close modules"),
+            Mode::Bookmarks => Some("close bookmarks"),
+            Mode::Links => Some("close links"),
+            Mode::Reader => Some("close reader"),
+            Mode::CoursePage => Some("back to courses"),
+            Mode::Courses => Some("close courses"),
+            Mode::Grades => Some("close grades"),
+            Mode::Announcements => Some("close announcements"),
+            Mode::Thread => Some("back to inbox"),
+            Mode::Inbox => Some("close inbox"),
+            Mode::Todo => Some("close to-do list"),
+            Mode::Triage => Some("close triage"),
+            Mode::CatchUp => Some("discard plan"),
+            Mode::Agenda => Some("close agenda"),
+            Mode::Grid(_) => Some("back to day view"),
+            Mode::Metrics => Some("hide metrics"),
+            Mode::Panel(_) => Some("back to agenda"),
+            Mode::Search => Some("clear search"),
+            Mode::Normal => None,
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use color_eyre::eyre::Result;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Constraint,
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    widgets::{Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use serde::Deserialize;
+use time::format_description;
+
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::{Action, Navigation, View},
+    component::{Component, Context},
+    config::Profile,
+    fetch::{CalendarEvent, get_json, now_local},
+    redact, theme,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Module {
+    name: String,
+    position: u32,
+    /// Missing when the module has too many items to be included
+    #[serde(default)]
+    items: Vec<ModuleItem>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ModuleItem {
+    position: u32,
+    #[serde(rename = "type")]
+    kind: String,
+    content_id: Option<u64>,
+}
+
+impl ModuleItem {
+    /// Whether the module item links to the planner item
+    fn links_to(&self, event: &CalendarEvent) -> bool {
+        let kind = match event.plannable_type.as_str() {
+            "assignment" => "Assignment",
+            "quiz" => "Quiz",
+            "discussion_topic" => "Discussion",
+            _ => return false,
+        };
+        self.kind == kind && self.content_id == Some(event.plannable_id)
+    }
+}
+
+pub async fn fetch_modules(profile: &Profile, course_id: u64) -> Result<Vec<Module>> {
+    get_json(
+        profile,
+        &format!("/api/v1/courses/{course_id}/modules"),
+        &[("include[]", "items"), ("per_page", "100")],
+    )
+    .await
+}
+
+enum ModuleRow {
+    Heading(String),
+    Item(CalendarEvent),
+}
+
+/// Screen listing a course's upcoming items in the order of its modules instead of by due date
+pub struct ModulesScreen {
+    pub course_id: u64,
+    course_name: String,
+    /// Url of the item the screen was opened from, selected once the modules are in
+    pub html_url: String,
+    /// None while the modules are being fetched
+    rows: Option<Vec<ModuleRow>>,
+    pub table_state: TableState,
+}
+
+impl ModulesScreen {
+    pub fn loading(course_id: u64, course_name: String, html_url: String) -> Self {
+        Self {
+            course_id,
+            course_name,
+            html_url,
+            rows: None,
+            table_state: TableState::default(),
+        }
+    }
+
+    /// Groups the upcoming items under the modules that link to them, in module order. Items
+    /// outside any module go last, by due date. The item the screen was opened from is selected,
+    /// or the first one if it isn't listed
+    fn set_modules(&mut self, mut modules: Vec<Module>, mut upcoming: Vec<CalendarEvent>) {
+        modules.sort_by_key(|module| module.position);
+        upcoming.sort_by_key(|event| event.due_at);
+        let mut rows = vec![];
+        for mut module in modules {
+            module.items.sort_by_key(|item| item.position);
+            let events: Vec<CalendarEvent> = module
+                .items
+                .iter()
+                .filter_map(|item| {
+                    let index = upcoming.iter().position(|event| item.links_to(event))?;
+                    Some(upcoming.remove(index))
+                })
+                .collect();
+            if events.is_empty() {
+                continue;
+            }
+            rows.push(ModuleRow::Heading(module.name));
+            rows.extend(events.into_iter().map(ModuleRow::Item));
+        }
+        if !upcoming.is_empty() {
+            rows.push(ModuleRow::Heading("Not in a module".to_string()));
+            rows.extend(upcoming.into_iter().map(ModuleRow::Item));
+        }
+        let opened_from = rows.iter().position(
+            |row| matches!(row, ModuleRow::Item(event) if event.html_url == self.html_url),
+        );
+        self.table_state = TableState::default().with_selected(
+            opened_from.or(rows
+                .iter()
+                .position(|row| matches!(row, ModuleRow::Item(_)))),
+        );
+        self.rows = Some(rows);
+    }
+
+    pub fn selected_event(&self) -> Option<&CalendarEvent> {
+        match self.rows.as_ref()?.get(self.table_state.selected()?)? {
+            ModuleRow::Item(event) => Some(event),
+            ModuleRow::Heading(_) => None,
+        }
+    }
+
+    /// Moves the selection to the next item, skipping module headings
+    pub fn select_next(&mut self) {
+        self.select_by(|index, _| index + 1);
+    }
+
+    pub fn select_previous(&mut self) {
+        self.select_by(|index, len| index + len - 1);
+    }
+
+    fn select_by(&mut self, step: impl Fn(usize, usize) -> usize) {
+        let (Some(rows), Some(mut index)) = (&self.rows, self.table_state.selected()) else {
+            return;
+        };
+        for _ in 0..rows.len() {
+            index = step(index, rows.len()) % rows.len();
+            if let ModuleRow::Item(_) = rows[index] {
+                self.table_state.select(Some(index));
+                return;
+            }
+        }
+    }
+}
+
+impl Component for ModulesScreen {
+    fn init(&mut self, profile: &Profile, action_tx: &UnboundedSender<Action>) -> Result<()> {
+        let course_id = self.course_id;
+        let profile = profile.clone();
+        let action_tx = action_tx.clone();
+        tokio::spawn(async move {
+            let action = match fetch_modules(&profile, course_id).await {
+                Ok(modules) => Action::ModulesFetched { course_id, modules },
+                Err(e) => Action::Error(format!("Loading modules failed: {e}")),
+            };
+            let _ = action_tx.send(action);
+        });
+        Ok(())
+    }
+
+    fn handle_event(&mut self, _key: KeyEvent, bound: Action) -> Action {
+        match bound {
+            Action::Quit
+            | Action::OpenURL
+            | Action::Navigate(Navigation::PrevEvent | Navigation::NextEvent)
+            | Action::ToggleBookmark
+            | Action::ToggleView(View::Modules) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, context: &Context) -> Option<Action> {
+        match action {
+            Action::Navigate(Navigation::PrevEvent) => self.select_previous(),
+            Action::Navigate(Navigation::NextEvent) => self.select_next(),
+            Action::ModulesFetched { course_id, modules } if *course_id == self.course_id => {
+                let today = now_local().date();
+                let upcoming = context
+                    .session
+                    .calendar
+                    .dates
+                    .iter()
+                    .flat_map(|date| &date.events)
+                    .filter(|event| {
+                        event.course_id == Some(self.course_id)
+                            && (event.due_at.date() >= today || event.html_url == self.html_url)
+                    })
+                    .cloned()
+                    .collect();
+                self.set_modules(modules.clone(), upcoming);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, _context: &Context) {
+        let course_name = redact::mask(&self.course_name);
+        let Some(rows) = &self.rows else {
+            Paragraph::new(format!("Loading modules of {course_name}...")).render(area, buf);
+            return;
+        };
+        if rows.is_empty() {
+            Paragraph::new(format!("{course_name} has no upcoming items")).render(area, buf);
+            return;
+        }
+
+        let due_format = format_description::parse(
+            "[weekday repr:short] [month repr:short] [day padding:none] [hour]:[minute]",
+        )
+        .unwrap();
+        let header = [format!("{course_name} by module"), "Due".to_string()]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(Style::default().fg(theme::current().heading));
+        let rows = rows.iter().map(|row| match row {
+            ModuleRow::Heading(name) => Row::new([redact::mask(name), String::new()])
+                .style(Style::default().fg(theme::current().accent).bold()),
+            ModuleRow::Item(event) => Row::new([
+                format!("  {}", redact::mask(&event.title)),
+                event.due_at.format(&due_format).unwrap(),
+            ])
+            .style(Style::default().fg(match event.is_done() {
+                true => theme::current().done,
+                false => theme::current().text,
+            })),
+        });
+        let table = Table::new(rows, [Constraint::Fill(1), Constraint::Length(16)])
+            .header(header)
+            .row_highlight_style(Style::default().bg(theme::current().selection))
+            .style(Style::default().fg(theme::current().text));
+        StatefulWidget::render(table, area, buf, &mut self.table_state);
+    }
+}
@@ -0,0 +1,48 @@
+//! Simulated network conditions for exercising retry, progress and error paths during development.
+//! Only compiled with the `netsim` feature and configured through environment variables:
+//! - `CANVASTUI_NETSIM_LATENCY_MS`: delay added to every request
+//! - `CANVASTUI_NETSIM_FAILURE_RATE`: chance from 0 to 1 that a request fails
+//! - `CANVASTUI_NETSIM_TRUNCATE_RATE`: chance from 0 to 1 that a response body is cut in half
+
+use std::{
+    env,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bytes::Bytes;
+use color_eyre::eyre::{Result, eyre};
+
+static SEED: AtomicU64 = AtomicU64::new(0);
+
+fn env_var<T: std::str::FromStr>(name: &str) -> Option<T> {
+    env::var(name).ok()?.parse().ok()
+}
+
+/// Xorshift random number in 0..1, good enough for deciding which requests to break
+fn random() -> f64 {
+    let mut x = SEED.load(Ordering::Relaxed);
+    if x == 0 {
+        x = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(1, |time| time.as_nanos() as u64 | 1);
+    }
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    SEED.store(x, Ordering::Relaxed);
+    (x >> 11) as f64 / (1u64 << 53) as f64
+}
+
+pub async fn simulate(body: Bytes) -> Result<Bytes> {
+    if let Some(latency) = env_var("CANVASTUI_NETSIM_LATENCY_MS") {
+        tokio::time::sleep(Duration::from_millis(latency)).await;
+    }
+    if random() < env_var("CANVASTUI_NETSIM_FAILURE_RATE").unwrap_or(0.0) {
+        return Err(eyre!("Simulated network failure"));
+    }
+    if random() < env_var("CANVASTUI_NETSIM_TRUNCATE_RATE").unwrap_or(0.0) {
+        return Ok(body.slice(..body.len() / 2));
+    }
+    Ok(body)
+}
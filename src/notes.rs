@@ -0,0 +1,67 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+use time::{Date, format_description::well_known::Iso8601};
+
+use crate::{editor, store::data_dir};
+
+const NOTES_FILE: &str = "canvastui/notes.json";
+
+/// Free-form notes attached to calendar dates, e.g. "review session at 6pm, room 204"
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DateNotes {
+    /// Notes keyed by date in YYYY-MM-DD form
+    notes: BTreeMap<String, String>,
+}
+
+fn key(date: Date) -> String {
+    date.format(&Iso8601::DATE).unwrap()
+}
+
+impl DateNotes {
+    fn path() -> Option<PathBuf> {
+        Some(data_dir()?.join(NOTES_FILE))
+    }
+
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match std::fs::read(&path) {
+            Ok(contents) => Ok(serde_json::from_slice(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, date: Date) -> Option<&str> {
+        self.notes.get(&key(date)).map(String::as_str)
+    }
+
+    /// Sets the note of a date, removing it when the text is blank
+    pub fn set(&mut self, date: Date, text: &str) {
+        let text = text.trim();
+        match text.is_empty() {
+            true => self.notes.remove(&key(date)),
+            false => self.notes.insert(key(date), text.to_string()),
+        };
+    }
+}
+
+/// Lets the user edit the note of a date in their editor
+pub fn edit(date: Date, text: &str) -> Result<String> {
+    editor::edit(&format!("note-{}", key(date)), text)
+}
@@ -0,0 +1,263 @@
+use std::{collections::HashSet, path::PathBuf, sync::Arc, time::Instant};
+
+use color_eyre::eyre::Result;
+use time::{Duration, OffsetDateTime, PrimitiveDateTime, format_description};
+
+use crate::{
+    config::NotificationConfig,
+    fetch::{Calendar, CalendarEvent, now_local, to_local},
+    preferences::ImportedRules,
+    sinks::{self, NotificationSink},
+    store::{Reminder, data_dir},
+};
+
+const QUEUE_FILE: &str = "canvastui/notification-queue.json";
+/// How often the loaded planner is looked through for deadlines coming into the window between
+/// fetches
+const WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+pub enum Notification {
+    Deadline(CalendarEvent),
+    Graded {
+        event: CalendarEvent,
+        /// Preview of the grader's comment
+        comment: Option<String>,
+    },
+    Reminder(Reminder),
+}
+
+impl Notification {
+    /// Deadlines of items with limited attempts can't wait, so they aren't held in quiet hours
+    fn is_urgent(&self) -> bool {
+        matches!(self, Notification::Deadline(event) if event.attempts.is_some())
+    }
+
+    pub fn message(&self) -> String {
+        let format = format_description::parse("[weekday repr:short] [hour]:[minute]").unwrap();
+        match self {
+            Notification::Deadline(event) => format!(
+                "⏰ **{}** · {} is due {}{}",
+                event.course_name,
+                event.title,
+                event.due_at.format(&format).unwrap(),
+                match event.attempts {
+                    Some(attempts) => format!(" · {}", attempts.describe()),
+                    None => String::new(),
+                }
+            ),
+            Notification::Graded { event, comment } => {
+                let mut message = format!(
+                    "📝 **{}** · {} has been graded",
+                    event.course_name, event.title
+                );
+                if let Some(comment) = comment {
+                    message.push_str(&format!("\n> {comment}"));
+                }
+                message
+            }
+            Notification::Reminder(reminder) => format!(
+                "🔔 **{}** · time to work on {}",
+                reminder.course_name, reminder.title
+            ),
+        }
+    }
+}
+
+pub struct Notifier {
+    config: NotificationConfig,
+    sinks: Vec<Arc<dyn NotificationSink>>,
+    /// Rules imported from Canvas with the config's settings applied on top
+    rules: ImportedRules,
+    /// Urls of events that already had a deadline notification sent
+    notified_deadlines: HashSet<String>,
+    /// When the loaded planner was last looked through for deadlines
+    watched_at: Option<Instant>,
+    /// Messages held back during quiet hours. Kept on disk so they survive a restart
+    queued: Vec<String>,
+}
+
+impl Notifier {
+    pub fn new(config: NotificationConfig) -> Self {
+        let queued = Self::queue_path()
+            .and_then(|path| std::fs::read(path).ok())
+            .and_then(|contents| serde_json::from_slice(&contents).ok())
+            .unwrap_or_default();
+        let mut rules = ImportedRules::load().unwrap_or_else(|e| {
+            log::error!("Failed to read the imported notification rules: {e}");
+            ImportedRules::default()
+        });
+        rules.deadlines = config.deadlines.unwrap_or(rules.deadlines);
+        rules.grades = config.grades.unwrap_or(rules.grades);
+        for course_id in &config.muted_courses {
+            rules
+                .muted_courses
+                .entry(*course_id)
+                .or_insert_with(|| course_id.to_string());
+        }
+        Self {
+            sinks: sinks::build(&config),
+            rules,
+            config,
+            notified_deadlines: HashSet::new(),
+            watched_at: None,
+            queued,
+        }
+    }
+
+    fn queue_path() -> Option<PathBuf> {
+        Some(data_dir()?.join(QUEUE_FILE))
+    }
+
+    fn save_queue(&self) -> Result<()> {
+        let Some(path) = Self::queue_path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec(&self.queued)?)?;
+        Ok(())
+    }
+
+    fn is_quiet(&self) -> bool {
+        self.config
+            .quiet_hours
+            .as_ref()
+            .is_some_and(|quiet_hours| quiet_hours.contains(to_local(OffsetDateTime::now_utc())))
+    }
+
+    /// Hands the message to every sink in the background
+    fn send(&self, message: String) {
+        for sink in &self.sinks {
+            let sink = sink.clone();
+            let message = message.clone();
+            tokio::spawn(async move {
+                if let Err(e) = sink.send(&message).await {
+                    log::error!("Failed to send {} notification: {e}", sink.name());
+                }
+            });
+        }
+    }
+
+    /// Sends the messages held back during quiet hours as one digest once they are over
+    pub fn flush(&mut self) {
+        if self.queued.is_empty() || self.is_quiet() {
+            return;
+        }
+        let digest = format!(
+            "🌙 While notifications were paused:\n{}",
+            self.queued.join("\n")
+        );
+        self.send(digest);
+        self.queued.clear();
+        if let Err(e) = self.save_queue() {
+            log::error!("Failed to save the notification queue: {e}");
+        }
+    }
+
+    /// Compares a freshly fetched calendar against the previous one and sends out deadline
+    /// notifications. Returns the newly graded items, which are announced with `graded` once
+    /// their comments are fetched
+    pub fn process(&mut self, previous: &Calendar, current: &Calendar) -> Vec<CalendarEvent> {
+        let previously_ungraded: HashSet<&str> = previous
+            .dates
+            .iter()
+            .flat_map(|date| &date.events)
+            .filter(|event| !event.graded)
+            .map(|event| event.html_url.as_str())
+            .collect();
+
+        let graded = current
+            .dates
+            .iter()
+            .flat_map(|date| &date.events)
+            .filter(|event| {
+                self.rules.grades
+                    && !self.is_muted(event)
+                    && event.graded
+                    && previously_ungraded.contains(event.html_url.as_str())
+            })
+            .cloned()
+            .collect();
+
+        let notifications = self.due_soon(current);
+        self.dispatch(notifications);
+        graded
+    }
+
+    /// Looks through the loaded planners for deadlines that came into the window since the last
+    /// fetch, at most once every `WATCH_INTERVAL`, so they are announced on time without a refresh
+    pub fn watch<'a>(&mut self, calendars: impl IntoIterator<Item = &'a Calendar>) {
+        if self
+            .watched_at
+            .is_some_and(|watched_at| watched_at.elapsed() < WATCH_INTERVAL)
+        {
+            return;
+        }
+        self.watched_at = Some(Instant::now());
+        let notifications = calendars
+            .into_iter()
+            .flat_map(|calendar| self.due_soon(calendar))
+            .collect();
+        self.dispatch(notifications);
+    }
+
+    fn is_muted(&self, event: &CalendarEvent) -> bool {
+        event
+            .course_id
+            .is_some_and(|course_id| self.rules.muted_courses.contains_key(&course_id))
+    }
+
+    /// Unfinished items due within `deadline_hours` that weren't announced yet, marked as
+    /// announced
+    fn due_soon(&mut self, calendar: &Calendar) -> Vec<Notification> {
+        if !self.rules.deadlines {
+            return vec![];
+        }
+        let now = now_local();
+        let now = PrimitiveDateTime::new(now.date(), now.time());
+        let threshold = now + Duration::hours(self.config.deadline_hours);
+        let mut notifications = vec![];
+        for event in calendar.dates.iter().flat_map(|date| &date.events) {
+            if !self.is_muted(event)
+                && !event.is_done()
+                && !event.is_external()
+                && event.due_at > now
+                && event.due_at <= threshold
+                && self.notified_deadlines.insert(event.html_url.clone())
+            {
+                notifications.push(Notification::Deadline(event.clone()));
+            }
+        }
+        notifications
+    }
+
+    /// Sends a grade notification with a preview of the grader's comment
+    pub fn graded(&mut self, event: CalendarEvent, comment: Option<String>) {
+        self.dispatch(vec![Notification::Graded { event, comment }]);
+    }
+
+    /// Sends reminders that are due
+    pub fn remind(&mut self, reminders: Vec<Reminder>) {
+        self.dispatch(reminders.into_iter().map(Notification::Reminder).collect());
+    }
+
+    /// Sends notifications, or queues them during quiet hours unless they are urgent
+    fn dispatch(&mut self, notifications: Vec<Notification>) {
+        if self.sinks.is_empty() {
+            return;
+        }
+        let (held, notifications): (Vec<_>, Vec<_>) = notifications
+            .into_iter()
+            .partition(|notification| self.is_quiet() && !notification.is_urgent());
+        if !held.is_empty() {
+            self.queued.extend(held.iter().map(Notification::message));
+            if let Err(e) = self.save_queue() {
+                log::error!("Failed to save the notification queue: {e}");
+            }
+        }
+        for notification in notifications {
+            self.send(notification.message());
+        }
+    }
+}
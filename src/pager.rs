@@ -0,0 +1,229 @@
+use std::{env, process::Command};
+
+use color_eyre::eyre::{Result, eyre};
+use reqwest::Url;
+
+use crate::{
+    links::attribute,
+    reader::{decode_entities, tag_name},
+    workload::find_ignore_case,
+};
+
+/// Converts a description to Markdown. Relative links and images are resolved against the Canvas
+/// instance at `base`
+pub fn to_markdown(html: &str, base: &str) -> String {
+    let base = base.parse::<Url>().ok();
+    let resolve = |url: String| match &base {
+        Some(base) => base.join(&url).map_or(url, String::from),
+        None => url,
+    };
+    let mut markdown = Markdown::default();
+    // Href of the anchor being read, with the text seen so far
+    let mut anchor: Option<(String, String)> = None;
+    // Next number of each open list, None for bulleted lists
+    let mut lists: Vec<Option<usize>> = vec![];
+
+    let mut rest = html;
+    while !rest.is_empty() {
+        let tag_start = rest.find('<').unwrap_or(rest.len());
+        let text = decode_entities(&rest[..tag_start]);
+        match &mut anchor {
+            Some((_, anchor_text)) => anchor_text.push_str(&text),
+            None => markdown.text(&text),
+        }
+        rest = &rest[tag_start..];
+        if rest.is_empty() {
+            break;
+        }
+        let tag_end = rest.find('>').map_or(rest.len(), |end| end + 1);
+        let tag = &rest[..tag_end];
+        rest = &rest[tag_end..];
+        let closing = tag.starts_with("</");
+        let name = tag_name(tag);
+
+        match (name.as_str(), closing) {
+            ("script" | "style", false) => {
+                let close = format!("</{name}");
+                rest = &rest[find_ignore_case(rest, &close).unwrap_or(rest.len())..];
+            }
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                markdown.paragraph();
+                let level = name[1..].parse().unwrap_or(1);
+                markdown.push(&format!("{} ", "#".repeat(level)));
+            }
+            ("p" | "div" | "table" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6", _) => {
+                markdown.paragraph()
+            }
+            ("br" | "tr", _) => markdown.line(),
+            ("td" | "th", false) => markdown.text(" "),
+            ("hr", _) => {
+                markdown.paragraph();
+                markdown.push("---");
+                markdown.paragraph();
+            }
+            ("ul" | "ol", false) => {
+                // Nested lists continue right under their item
+                match lists.is_empty() {
+                    true => markdown.paragraph(),
+                    false => markdown.line(),
+                }
+                lists.push((name == "ol").then_some(1));
+            }
+            ("ul" | "ol", true) => {
+                lists.pop();
+                match lists.is_empty() {
+                    true => markdown.paragraph(),
+                    false => markdown.line(),
+                }
+            }
+            ("li", false) => {
+                markdown.line();
+                let indent = "  ".repeat(lists.len().saturating_sub(1));
+                let marker = match lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                markdown.push(&format!("{indent}{marker}"));
+            }
+            ("blockquote", false) => {
+                markdown.paragraph();
+                markdown.quote += 1;
+            }
+            ("blockquote", true) => {
+                markdown.quote = markdown.quote.saturating_sub(1);
+                markdown.paragraph();
+            }
+            ("pre", false) => {
+                markdown.paragraph();
+                markdown.push("```");
+                markdown.line();
+                markdown.preformatted = true;
+            }
+            ("pre", true) => {
+                markdown.preformatted = false;
+                markdown.line();
+                markdown.push("```");
+                markdown.paragraph();
+            }
+            ("code", _) if !markdown.preformatted => markdown.push("`"),
+            ("strong" | "b", _) => markdown.push("**"),
+            ("em" | "i", _) => markdown.push("*"),
+            ("a", false) => {
+                anchor = attribute(tag, "href").map(|href| (resolve(href), String::new()))
+            }
+            ("a", true) => {
+                if let Some((href, text)) = anchor.take() {
+                    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                    match text.is_empty() {
+                        true => markdown.push(&format!("<{href}>")),
+                        false => markdown.push(&format!("[{text}]({href})")),
+                    }
+                }
+            }
+            ("img", _) => {
+                if let Some(src) = attribute(tag, "src") {
+                    let alt = attribute(tag, "alt").unwrap_or_default();
+                    markdown.push(&format!("![{alt}]({})", resolve(src)));
+                }
+            }
+            _ => {}
+        }
+    }
+    markdown.finish()
+}
+
+/// Markdown being written, with whitespace collapsed outside of code blocks
+#[derive(Default)]
+struct Markdown {
+    output: String,
+    /// Blank lines still owed before the next text, 1 for a line break and 2 for a paragraph
+    pending_breaks: usize,
+    /// Whether a space is owed before the next word on the line
+    pending_space: bool,
+    /// Depth of the blockquotes the text is in
+    quote: usize,
+    preformatted: bool,
+}
+
+impl Markdown {
+    fn text(&mut self, text: &str) {
+        if self.preformatted {
+            for (index, line) in text.split('\n').enumerate() {
+                if index > 0 {
+                    self.line();
+                }
+                if !line.is_empty() {
+                    self.push(line);
+                }
+            }
+            return;
+        }
+        if text.starts_with(char::is_whitespace) {
+            self.pending_space = true;
+        }
+        let mut words = text.split_whitespace().peekable();
+        while let Some(word) = words.next() {
+            self.push(word);
+            if words.peek().is_some() {
+                self.pending_space = true;
+            }
+        }
+        if text.ends_with(char::is_whitespace) {
+            self.pending_space = true;
+        }
+    }
+
+    /// Writes `text` as is, after any breaks or space owed
+    fn push(&mut self, text: &str) {
+        if self.output.is_empty() {
+            self.pending_breaks = 0;
+        }
+        if self.pending_breaks > 0 {
+            for _ in 0..self.pending_breaks {
+                self.output.push('\n');
+            }
+            self.pending_breaks = 0;
+            self.pending_space = false;
+        }
+        if self.output.is_empty() || self.output.ends_with('\n') {
+            self.output.push_str(&"> ".repeat(self.quote));
+            self.pending_space = false;
+        }
+        if std::mem::take(&mut self.pending_space) && !self.output.ends_with(' ') {
+            self.output.push(' ');
+        }
+        self.output.push_str(text);
+    }
+
+    fn line(&mut self) {
+        self.pending_breaks = self.pending_breaks.max(1);
+    }
+
+    fn paragraph(&mut self) {
+        self.pending_breaks = 2;
+    }
+
+    fn finish(mut self) -> String {
+        self.output.push('\n');
+        self.output
+    }
+}
+
+/// Shows text in `$PAGER`, falling back to less. `name` tells the temporary files apart. The
+/// terminal must be out of raw mode while the pager runs
+pub fn show(name: &str, text: &str) -> Result<()> {
+    let pager = env::var("PAGER").unwrap_or_else(|_| "less".to_string());
+    let mut words = pager.split_whitespace();
+    let program = words.next().ok_or(eyre!("$PAGER is empty"))?;
+    let path = env::temp_dir().join(format!("canvastui-{name}.md"));
+    std::fs::write(&path, text)?;
+    let status = Command::new(program).args(words).arg(&path).status();
+    let _ = std::fs::remove_file(&path);
+    if !status?.success() {
+        return Err(eyre!("{program} exited with an error"));
+    }
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use color_eyre::eyre::Result;
+use serde::Deserialize;
+use time::PrimitiveDateTime;
+
+use crate::{
+    config::Profile,
+    fetch::{CalendarEvent, get_json},
+};
+
+/// The subset of Canvas course permissions that gate actions in the app
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct CoursePermissions {
+    pub manage_grades: bool,
+    pub moderate_forum: bool,
+}
+
+pub async fn fetch_permissions(profile: &Profile, course_id: u64) -> Result<CoursePermissions> {
+    get_json(
+        profile,
+        &format!("/api/v1/courses/{course_id}/permissions"),
+        &[
+            ("permissions[]", "manage_grades"),
+            ("permissions[]", "moderate_forum"),
+        ],
+    )
+    .await
+}
+
+/// Something the user can do to an item that Canvas might not allow
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub enum Capability {
+    Submit,
+    Grade,
+    /// Post announcements and discussion topics to a course
+    Post,
+}
+
+impl Capability {
+    /// Checks whether the action is allowed on the item, explaining why when it isn't
+    #[allow(dead_code)]
+    pub fn check(
+        self,
+        event: &CalendarEvent,
+        permissions: Option<&CoursePermissions>,
+        now: PrimitiveDateTime,
+    ) -> Result<(), String> {
+        match self {
+            Capability::Submit => {
+                if event.is_external() {
+                    return Err("events from external calendars can't be submitted".to_string());
+                }
+                if !matches!(event.plannable_type.as_str(), "assignment" | "quiz") {
+                    return Err("only assignments and quizzes take submissions".to_string());
+                }
+                match event.lock_reason(now) {
+                    Some(reason) => Err(format!("the assignment is {reason}")),
+                    None => Ok(()),
+                }
+            }
+            Capability::Grade | Capability::Post => self.check_role(permissions),
+        }
+    }
+
+    /// Checks whether the user's role in the course allows the action, explaining why when it
+    /// doesn't. Items don't matter for these
+    pub fn check_role(self, permissions: Option<&CoursePermissions>) -> Result<(), String> {
+        let Some(permissions) = permissions else {
+            return Err("course permissions haven't loaded yet".to_string());
+        };
+        let (allowed, what) = match self {
+            Capability::Submit => return Ok(()),
+            Capability::Grade => (permissions.manage_grades, "grading"),
+            Capability::Post => (
+                permissions.moderate_forum,
+                "posting announcements and discussions",
+            ),
+        };
+        match allowed {
+            true => Ok(()),
+            false => Err(format!(
+                "{what} requires a teacher or TA role in this course"
+            )),
+        }
+    }
+}
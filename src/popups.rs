@@ -0,0 +1,73 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    prelude::{Buffer, Rect},
+    widgets::Widget,
+};
+
+use crate::{
+    action::Action,
+    component::{Component, Context},
+    detail::DetailPopup,
+    help::HelpOverlay,
+    metrics::MetricsOverlay,
+    toast::Toast,
+};
+
+/// Everything drawn over the screen in front, from the bottom up
+#[derive(Default)]
+pub struct Popups {
+    /// Details of the selected assignment, shown over the planner while open
+    pub detail: Option<DetailPopup>,
+    /// Whether the performance overlay is shown
+    pub metrics: bool,
+    /// Keybindings shown over everything else while open
+    pub help: Option<HelpOverlay>,
+    /// Last failure, shown until dismissed or a while has passed
+    pub error: Option<Toast>,
+}
+
+impl Popups {
+    /// Whether a popup takes the keys instead of the screen under it
+    pub fn has_keys(&self) -> bool {
+        self.help.is_some() || self.detail.is_some()
+    }
+}
+
+impl Component for Popups {
+    fn handle_event(&mut self, key: KeyEvent, bound: Action) -> Action {
+        match (&mut self.help, &mut self.detail) {
+            (Some(help), _) => help.handle_event(key, bound),
+            (None, Some(detail)) => detail.handle_event(key, bound),
+            (None, None) => bound,
+        }
+    }
+
+    fn update(&mut self, action: &Action, context: &Context) -> Option<Action> {
+        if let Action::Tick = action
+            && self.error.as_ref().is_some_and(Toast::expired)
+        {
+            self.error = None;
+        }
+        match (action, &mut self.help, &mut self.detail) {
+            // Keys moving a selection go to the popup on top
+            (Action::Navigate(_), Some(help), _) => help.update(action, context),
+            (_, _, Some(detail)) => detail.update(action, context),
+            _ => None,
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, context: &Context) {
+        if let Some(detail) = &mut self.detail {
+            detail.render(area, buf, context);
+        }
+        if self.metrics {
+            MetricsOverlay.render(area, buf);
+        }
+        if let Some(help) = &mut self.help {
+            help.render(area, buf, context);
+        }
+        if let Some(error) = &self.error {
+            error.render(area, buf);
+        }
+    }
+}
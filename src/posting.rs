@@ -0,0 +1,210 @@
+use color_eyre::eyre::{Result, eyre};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout},
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Paragraph, Widget, Wrap},
+};
+use serde::Deserialize;
+use time::{
+    PrimitiveDateTime, UtcOffset, format_description, format_description::well_known::Rfc3339,
+};
+
+use crate::{
+    action::Action,
+    component::{Component, Context},
+    config::Profile,
+    courses::CourseContext,
+    feed::escape,
+    fetch::{now_local, post_json},
+    locale, redact, theme,
+};
+
+const TITLE_FIELD: &str = "Title:";
+const POST_AT_FIELD: &str = "Post at:";
+/// Separates the fields from the body in the editor
+const BODY_SEPARATOR: &str = "---";
+const POST_AT_FORMAT: &str = "[year]-[month]-[day] [hour]:[minute]";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PostKind {
+    Announcement,
+    Discussion,
+}
+
+impl PostKind {
+    pub fn from_key(c: char) -> Option<Self> {
+        match c {
+            'p' => Some(PostKind::Announcement),
+            'd' => Some(PostKind::Discussion),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PostKind::Announcement => "announcement",
+            PostKind::Discussion => "discussion topic",
+        }
+    }
+}
+
+/// An announcement or discussion topic written in the editor and not posted yet
+#[derive(Debug, Clone)]
+pub struct Draft {
+    pub session: usize,
+    pub kind: PostKind,
+    pub course: CourseContext,
+    pub title: String,
+    /// Plain text, turned into paragraphs when posted
+    pub body: String,
+    /// Local time Canvas publishes the post at, right away when unset
+    pub post_at: Option<PrimitiveDateTime>,
+}
+
+#[derive(Deserialize)]
+struct CanvasTopic {
+    html_url: String,
+}
+
+impl Draft {
+    pub fn new(session: usize, kind: PostKind, course: CourseContext) -> Self {
+        Self {
+            session,
+            kind,
+            course,
+            title: String::new(),
+            body: String::new(),
+            post_at: None,
+        }
+    }
+
+    /// The draft as the text handed to the editor
+    pub fn to_text(&self) -> String {
+        let format = format_description::parse(POST_AT_FORMAT).unwrap();
+        let post_at = self
+            .post_at
+            .map(|post_at| post_at.format(&format).unwrap())
+            .unwrap_or_default();
+        format!(
+            "{TITLE_FIELD} {}\n{POST_AT_FIELD} {post_at}\n\
+             # New {} in {}. Leave the title empty to cancel. Set \"{POST_AT_FIELD}\" to a local \
+             time such as 2026-01-12 09:00 to delay posting. Write the body below the line\n\
+             {BODY_SEPARATOR}\n{}",
+            self.title,
+            self.kind.label(),
+            self.course.name,
+            self.body
+        )
+    }
+
+    /// Reads the fields back from the edited text. Returns false when the title was left empty.
+    /// The title and body are kept even if the posting time can't be read
+    pub fn update(&mut self, text: &str) -> Result<bool> {
+        let (fields, body) = text.split_once(BODY_SEPARATOR).unwrap_or((text, ""));
+        let field = |name: &str| {
+            fields
+                .lines()
+                .find_map(|line| line.strip_prefix(name))
+                .map(str::trim)
+                .unwrap_or_default()
+        };
+        self.title = field(TITLE_FIELD).to_string();
+        self.body = body.trim().to_string();
+        if self.title.is_empty() {
+            return Ok(false);
+        }
+        self.post_at = match field(POST_AT_FIELD) {
+            "" => None,
+            post_at => {
+                let format = format_description::parse(POST_AT_FORMAT).unwrap();
+                Some(
+                    PrimitiveDateTime::parse(post_at, &format)
+                        .map_err(|_| eyre!("\"{post_at}\" is not a time like 2026-01-12 09:00"))?,
+                )
+            }
+        };
+        Ok(true)
+    }
+
+    /// The body as HTML, a paragraph per block of lines
+    fn message(&self) -> String {
+        self.body
+            .split("\n\n")
+            .map(str::trim)
+            .filter(|paragraph| !paragraph.is_empty())
+            .map(|paragraph| {
+                let lines: Vec<String> = paragraph.lines().map(escape).collect();
+                format!("<p>{}</p>", lines.join("<br>"))
+            })
+            .collect()
+    }
+
+    /// Creates the announcement or discussion topic and returns its url
+    pub async fn post(&self, profile: &Profile) -> Result<String> {
+        let mut body = serde_json::json!({
+            "title": self.title,
+            "message": self.message(),
+            "is_announcement": self.kind == PostKind::Announcement,
+            "published": true,
+        });
+        if let Some(post_at) = self.post_at {
+            let offset = UtcOffset::current_local_offset()?;
+            body["delayed_post_at"] = post_at.assume_offset(offset).format(&Rfc3339)?.into();
+        }
+        let topic: CanvasTopic = post_json(
+            profile,
+            &format!("/api/v1/courses/{}/discussion_topics", self.course.id),
+            &body,
+        )
+        .await?;
+        Ok(topic.html_url)
+    }
+}
+
+impl Component for Draft {
+    fn handle_event(&mut self, key: KeyEvent, _bound: Action) -> Action {
+        match key.code {
+            KeyCode::Char('y') => Action::ConfirmPost,
+            KeyCode::Char('e') => Action::EditPost,
+            _ => Action::None,
+        }
+    }
+
+    /// Shows the draft as it will be posted, for a last look before posting
+    fn render(&mut self, area: Rect, buf: &mut Buffer, _context: &Context) {
+        let [help_area, draft_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(area);
+        Paragraph::new(format!(
+            "Post this {} to {}? · y post · e edit",
+            self.kind.label(),
+            redact::mask(&self.course.name)
+        ))
+        .style(Style::default().fg(theme::current().heading).bold())
+        .render(help_area, buf);
+
+        let now = now_local();
+        let when = match self.post_at {
+            Some(post_at) if post_at.assume_offset(now.offset()) > now => format!(
+                "Goes up {} at {:02}:{:02}",
+                locale::current().format(post_at.date(), "%a %b %-d"),
+                post_at.hour(),
+                post_at.minute()
+            ),
+            Some(_) => "The posting time has passed, it goes up right away".to_string(),
+            None => "Goes up right away".to_string(),
+        };
+        let mut lines = vec![
+            Line::styled(redact::mask(&self.title), Style::default().bold()),
+            Line::styled(when, Style::default().fg(theme::current().accent)),
+            Line::raw(""),
+        ];
+        lines.extend(self.body.lines().map(|line| Line::raw(redact::mask(line))));
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .style(Style::default().fg(theme::current().text))
+            .render(draft_area, buf);
+    }
+}
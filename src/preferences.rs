@@ -0,0 +1,238 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{Config, Profile},
+    fetch::{get_json, graphql},
+    store::data_dir,
+};
+
+const RULES_FILE: &str = "canvastui/notification-rules.json";
+
+/// Notification rules taken from the user's Canvas notification preferences. They are the
+/// defaults the `[notifications]` config section overrides
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImportedRules {
+    pub deadlines: bool,
+    pub grades: bool,
+    /// Courses with notifications turned off in Canvas, names by course id
+    pub muted_courses: BTreeMap<u64, String>,
+}
+
+impl Default for ImportedRules {
+    fn default() -> Self {
+        Self {
+            deadlines: true,
+            grades: true,
+            muted_courses: BTreeMap::new(),
+        }
+    }
+}
+
+impl ImportedRules {
+    fn path() -> Option<PathBuf> {
+        Some(data_dir()?.join(RULES_FILE))
+    }
+
+    /// The rules from the last import, or everything on if nothing was imported
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match std::fs::read(&path) {
+            Ok(contents) => Ok(serde_json::from_slice(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Lines describing what applying `new` would change, empty if nothing would
+    fn diff(&self, new: &ImportedRules) -> Vec<String> {
+        let on_off = |enabled: bool| match enabled {
+            true => "on",
+            false => "off",
+        };
+        let mut lines = vec![];
+        if self.deadlines != new.deadlines {
+            lines.push(format!(
+                "~ deadline notifications: {} -> {}",
+                on_off(self.deadlines),
+                on_off(new.deadlines)
+            ));
+        }
+        if self.grades != new.grades {
+            lines.push(format!(
+                "~ grade notifications: {} -> {}",
+                on_off(self.grades),
+                on_off(new.grades)
+            ));
+        }
+        for (id, name) in &new.muted_courses {
+            if !self.muted_courses.contains_key(id) {
+                lines.push(format!("+ mute {name} ({id})"));
+            }
+        }
+        for (id, name) in &self.muted_courses {
+            if !new.muted_courses.contains_key(id) {
+                lines.push(format!("- mute {name} ({id})"));
+            }
+        }
+        lines
+    }
+}
+
+#[derive(Deserialize)]
+struct CanvasChannel {
+    id: u64,
+}
+
+#[derive(Deserialize)]
+struct CanvasPreferences {
+    notification_preferences: Vec<CanvasPreference>,
+}
+
+#[derive(Deserialize)]
+struct CanvasPreference {
+    category: String,
+    /// "immediately", "daily", "weekly" or "never"
+    frequency: String,
+}
+
+#[derive(Deserialize)]
+struct CanvasUser {
+    id: u64,
+}
+
+#[derive(Deserialize)]
+struct CanvasCourse {
+    id: u64,
+    name: Option<String>,
+    course_code: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CourseSettingsData {
+    #[serde(rename = "legacyNode")]
+    user: HashMap<String, bool>,
+}
+
+/// Reads the notification preferences of every communication channel. A category counts as on
+/// if any channel receives it, and as on if Canvas has no preference for it
+async fn fetch_rules(profile: &Profile) -> Result<ImportedRules> {
+    let channels: Vec<CanvasChannel> =
+        get_json(profile, "/api/v1/users/self/communication_channels", &[]).await?;
+    let mut frequencies: HashMap<String, Vec<String>> = HashMap::new();
+    for channel in channels {
+        let preferences: CanvasPreferences = get_json(
+            profile,
+            &format!(
+                "/api/v1/users/self/communication_channels/{}/notification_preferences",
+                channel.id
+            ),
+            &[],
+        )
+        .await?;
+        for preference in preferences.notification_preferences {
+            frequencies
+                .entry(preference.category)
+                .or_default()
+                .push(preference.frequency);
+        }
+    }
+    let enabled = |category: &str| {
+        frequencies
+            .get(category)
+            .is_none_or(|frequencies| frequencies.iter().any(|frequency| frequency != "never"))
+    };
+
+    Ok(ImportedRules {
+        deadlines: enabled("due_date"),
+        grades: enabled("grading"),
+        muted_courses: fetch_muted_courses(profile).await?,
+    })
+}
+
+/// Courses the user turned notifications off for. Canvas only exposes this setting through
+/// GraphQL, one field per course
+async fn fetch_muted_courses(profile: &Profile) -> Result<BTreeMap<u64, String>> {
+    let user: CanvasUser = get_json(profile, "/api/v1/users/self", &[]).await?;
+    let courses: Vec<CanvasCourse> = get_json(
+        profile,
+        "/api/v1/courses",
+        &[("enrollment_state", "active"), ("per_page", "100")],
+    )
+    .await?;
+    if courses.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    let fields: Vec<String> = courses
+        .iter()
+        .map(|course| {
+            format!(
+                "course_{0}: notificationPreferencesEnabled(contextType: Course, courseId: \"{0}\")",
+                course.id
+            )
+        })
+        .collect();
+    let query = format!(
+        "query {{ legacyNode(_id: \"{}\", type: User) {{ ... on User {{ {} }} }} }}",
+        user.id,
+        fields.join(" ")
+    );
+    let settings: CourseSettingsData = graphql(profile, &query).await?;
+    Ok(courses
+        .into_iter()
+        .filter(|course| settings.user.get(&format!("course_{}", course.id)) == Some(&false))
+        .map(|course| {
+            let name = course
+                .course_code
+                .or(course.name)
+                .unwrap_or_else(|| course.id.to_string());
+            (course.id, name)
+        })
+        .collect())
+}
+
+/// Shows how the notification rules would change to follow the Canvas preferences, and saves
+/// them when `apply` is set
+pub async fn import(apply: bool) -> Result<()> {
+    let profile = Config::load()?.default_profile()?;
+    let current = ImportedRules::load()?;
+    let new = fetch_rules(&profile).await?;
+    let diff = current.diff(&new);
+    if diff.is_empty() {
+        println!("Notification rules already match your Canvas preferences");
+        return Ok(());
+    }
+    println!("Notification rules from your Canvas preferences:");
+    for line in &diff {
+        println!("  {line}");
+    }
+    match apply {
+        true => {
+            new.save()?;
+            println!(
+                "\nSaved. Settings in the [notifications] config section still take precedence"
+            );
+        }
+        false => println!("\nNothing was changed, run again with --apply to use these rules"),
+    }
+    Ok(())
+}
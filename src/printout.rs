@@ -0,0 +1,273 @@
+use std::path::Path;
+
+use color_eyre::eyre::{Result, eyre};
+use time::{Date, Duration, OffsetDateTime, format_description};
+
+use crate::{
+    agenda,
+    config::{Config, Profile},
+    fetch::{Calendar, load_cache, to_local},
+    ics, locale, redact,
+    session::Session,
+    store::Annotations,
+};
+
+/// Columns a line may take before it is wrapped, what fits across the page in the PDF
+const WIDTH: usize = 85;
+/// Lines that fit on the PDF page below the title
+const PAGE_LINES: usize = 46;
+/// Letter size in points
+const PAGE_SIZE: (u32, u32) = (612, 792);
+const MARGIN: u32 = 50;
+const FONT_SIZE: u32 = 10;
+const TITLE_SIZE: u32 = 16;
+const LEADING: u32 = 14;
+
+/// A day's schedule and deadlines laid out for paper
+struct Printout {
+    title: String,
+    sections: Vec<(&'static str, Vec<String>)>,
+}
+
+/// Prints the schedule and deadlines of `date` across every profile, from the cache and the
+/// external calendars. Writes a PDF to `pdf` instead when it is given
+pub async fn print(date: &str, pdf: Option<&Path>) -> Result<()> {
+    let config = Config::load()?;
+    locale::init(&config.locale)?;
+    let date = parse_date(date)?;
+
+    let mut overlay = vec![];
+    for calendar in &config.calendars {
+        match ics::fetch_external(calendar, config.horizon).await {
+            Ok(events) => overlay.extend(events),
+            Err(e) => eprintln!("Leaving out calendar {}: {e}", calendar.name),
+        }
+    }
+    let annotations = Annotations::load().unwrap_or_default();
+    let mut profiles = vec![Profile::offline()];
+    profiles.extend(config.profiles.iter().cloned());
+    let sessions: Vec<Session> = profiles
+        .into_iter()
+        .enumerate()
+        .filter_map(|(id, profile)| {
+            let mut calendar = match load_cache(&profile) {
+                Ok(calendar) => calendar,
+                // The external calendars still belong on the page without a cache
+                Err(_) if id == 0 => Calendar { dates: vec![] },
+                Err(_) => return None,
+            };
+            calendar.apply_annotations(&annotations);
+            let mut session = Session::new(profile);
+            session.calendar = calendar.with_overlay(&overlay);
+            Some(session)
+        })
+        .collect();
+
+    let printout = Printout::new(&sessions, date);
+    match pdf {
+        Some(path) => {
+            std::fs::write(path, printout.to_pdf())?;
+            println!("Wrote {}", path.display());
+        }
+        None => print!("{}", printout.to_text()),
+    }
+    Ok(())
+}
+
+/// Reads `today`, `tomorrow`, `yesterday` or a date like 2025-03-14
+pub fn parse_date(date: &str) -> Result<Date> {
+    let today = to_local(OffsetDateTime::now_utc()).date();
+    match date {
+        "today" => Ok(today),
+        "tomorrow" => Ok(today + Duration::days(1)),
+        "yesterday" => Ok(today - Duration::days(1)),
+        _ => {
+            let format = format_description::parse("[year]-[month]-[day]")?;
+            Date::parse(date, &format).map_err(|_| {
+                eyre!("{date:?} is not a date, use today, tomorrow or a date like 2025-03-14")
+            })
+        }
+    }
+}
+
+impl Printout {
+    fn new(sessions: &[Session], date: Date) -> Self {
+        let time_format = format_description::parse("[hour]:[minute]").unwrap();
+        // Profile badges only tell items apart when several profiles are printed
+        let badges = sessions.len() > 1;
+        let mut schedule = vec![];
+        let mut deadlines = vec![];
+        for entry in agenda::entries(sessions) {
+            let event = &entry.event;
+            if event.due_at.date() != date {
+                continue;
+            }
+            let time = event.due_at.format(&time_format).unwrap();
+            let title = redact::mask(&event.title);
+            let course = redact::mask(&event.course_name);
+            match event.is_external() {
+                true => schedule.push(format!("{time}  {title} ({course})")),
+                false => {
+                    let check = match event.is_done() {
+                        true => "[x]",
+                        false => "[ ]",
+                    };
+                    let badge = match badges {
+                        true => format!(" [{}]", entry.profile),
+                        false => String::new(),
+                    };
+                    deadlines.push(format!("{check} {time}  {course}: {title}{badge}"));
+                }
+            }
+        }
+        if schedule.is_empty() {
+            schedule.push("Nothing scheduled".to_string());
+        }
+        if deadlines.is_empty() {
+            deadlines.push("Nothing due".to_string());
+        }
+        Self {
+            title: locale::current().format(date, "%A, %B %-d %Y"),
+            sections: vec![("Schedule", schedule), ("Deadlines", deadlines)],
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = format!(
+            "{}\n{}\n",
+            self.title,
+            "=".repeat(self.title.chars().count())
+        );
+        for (heading, lines) in &self.sections {
+            text.push_str(&format!("\n{heading}\n"));
+            for line in lines {
+                for wrapped in wrap(line, WIDTH - 2) {
+                    text.push_str(&format!("  {wrapped}\n"));
+                }
+            }
+        }
+        text
+    }
+
+    /// A single page PDF in the standard Courier fonts, so nothing has to be embedded
+    fn to_pdf(&self) -> Vec<u8> {
+        // Body lines with whether they are headings
+        let mut lines: Vec<(String, bool)> = vec![];
+        for (heading, section) in &self.sections {
+            lines.push((String::new(), false));
+            lines.push((heading.to_string(), true));
+            for line in section {
+                lines.extend(wrap(line, WIDTH - 2).map(|wrapped| (format!("  {wrapped}"), false)));
+            }
+        }
+        if lines.len() > PAGE_LINES {
+            let left_out = lines.len() - PAGE_LINES + 1;
+            lines.truncate(PAGE_LINES - 1);
+            lines.push((format!("  ... and {left_out} more lines"), false));
+        }
+
+        let (width, height) = PAGE_SIZE;
+        let mut content = format!(
+            "BT\n{LEADING} TL\n{MARGIN} {} Td\n/F2 {TITLE_SIZE} Tf\n",
+            height - MARGIN - TITLE_SIZE
+        )
+        .into_bytes();
+        content.extend(pdf_string(&self.title));
+        content.extend(b" Tj\nT*\n");
+        for (line, heading) in &lines {
+            let font = match heading {
+                true => "F2",
+                false => "F1",
+            };
+            content.extend(format!("/{font} {FONT_SIZE} Tf\n").into_bytes());
+            content.extend(pdf_string(line));
+            content.extend(b" Tj\nT*\n");
+        }
+        content.extend(b"ET");
+
+        let mut objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {width} {height}] \
+                 /Resources << /Font << /F1 4 0 R /F2 5 0 R >> >> /Contents 6 0 R >>"
+            )
+            .into_bytes(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier-Bold /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+        ];
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend(content);
+        stream.extend(b"\nendstream");
+        objects.push(stream);
+
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = vec![];
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend(format!("{} 0 obj\n", index + 1).into_bytes());
+            pdf.extend(object);
+            pdf.extend(b"\nendobj\n");
+        }
+        let xref = pdf.len();
+        pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+        for offset in offsets {
+            pdf.extend(format!("{offset:010} 00000 n \n").into_bytes());
+        }
+        pdf.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+                objects.len() + 1
+            )
+            .into_bytes(),
+        );
+        pdf
+    }
+}
+
+/// Breaks a line at spaces into lines of at most `width` characters, indenting the continuations
+fn wrap(line: &str, width: usize) -> impl Iterator<Item = String> {
+    let mut lines = vec![];
+    let mut current = String::new();
+    for word in line.split(' ') {
+        let length = current.chars().count();
+        if length > 0 && length + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+            current.push_str("    ");
+        }
+        if !current.trim().is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    lines.push(current);
+    lines.into_iter()
+}
+
+/// Writes text as a PDF string in WinAnsiEncoding, with what it can't encode as ?
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut bytes = vec![b'('];
+    for c in text.chars() {
+        let byte = match c {
+            '(' | ')' | '\\' => {
+                bytes.push(b'\\');
+                c as u8
+            }
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            ' '..='~' | '\u{a0}'..='\u{ff}' => c as u8,
+            _ => b'?',
+        };
+        bytes.push(byte);
+    }
+    bytes.push(b')');
+    bytes
+}
@@ -0,0 +1,48 @@
+//! Canvas throttles API requests with a leaky bucket and reports what is left of it in the
+//! `X-Rate-Limit-Remaining` header of every response. Requests are refused once it runs out.
+
+use std::{sync::Mutex, time::Duration};
+
+use reqwest::header::HeaderMap;
+
+const REMAINING_HEADER: &str = "X-Rate-Limit-Remaining";
+/// Budget under which background prefetches start to be spaced out
+const LOW_BUDGET: f64 = 200.0;
+/// Delay before a background prefetch when the budget is empty
+const MAX_PACE: Duration = Duration::from_secs(5);
+
+/// Budget left after the latest response, unknown until a response carries the header
+static REMAINING: Mutex<Option<f64>> = Mutex::new(None);
+
+/// Budget a response reports, if it carries the header
+pub fn remaining_in(headers: &HeaderMap) -> Option<f64> {
+    headers.get(REMAINING_HEADER)?.to_str().ok()?.parse().ok()
+}
+
+/// Records the budget reported by a response
+pub fn record(headers: &HeaderMap) {
+    let Some(remaining) = remaining_in(headers) else {
+        return;
+    };
+    if let Ok(mut current) = REMAINING.lock() {
+        *current = Some(remaining);
+    }
+}
+
+pub fn remaining() -> Option<f64> {
+    REMAINING.lock().ok().and_then(|remaining| *remaining)
+}
+
+pub fn is_low() -> bool {
+    remaining().is_some_and(|remaining| remaining < LOW_BUDGET)
+}
+
+/// Waits before a background prefetch, longer the closer the budget is to running out, so the
+/// bucket has time to drain and requests the user is waiting for still go through
+pub async fn pace() {
+    let Some(remaining) = remaining().filter(|remaining| *remaining < LOW_BUDGET) else {
+        return;
+    };
+    let shortfall = (1.0 - remaining.max(0.0) / LOW_BUDGET).clamp(0.0, 1.0);
+    tokio::time::sleep(MAX_PACE.mul_f64(shortfall)).await;
+}
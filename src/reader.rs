@@ -0,0 +1,334 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::{Constraint, Layout},
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    widgets::{Paragraph, Widget},
+};
+use time::Date;
+
+use crate::{
+    action::{Action, Navigation, View},
+    component::{Component, Context},
+    courses::{CoursePage, syllabus_url},
+    redact, syllabus, theme,
+    workload::find_ignore_case,
+};
+
+/// HTML laid out per step. Long pages take several steps, spread over the frames that need them
+const CHUNK_BYTES: usize = 8 * 1024;
+/// Lines laid out past the bottom of the view so scrolling doesn't wait on layout
+const MARGIN_LINES: usize = 50;
+
+/// Elements that start a new line
+const BLOCK_TAGS: &[&str] = &["div", "br", "li", "tr", "section"];
+/// Elements that are set apart from the surrounding text by a blank line
+const PARAGRAPH_TAGS: &[&str] = &[
+    "p",
+    "ul",
+    "ol",
+    "table",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "blockquote",
+    "pre",
+];
+
+/// HTML body turned into wrapped lines on demand, so huge pages open without converting all of
+/// it up front
+pub struct Document {
+    html: String,
+    /// Byte offset up to which the HTML has been laid out
+    parsed_to: usize,
+    /// Text of the paragraph being laid out, carried over between steps
+    paragraph: String,
+    lines: Vec<String>,
+    /// Index of the first line of every paragraph laid out so far
+    paragraphs: Vec<usize>,
+    width: usize,
+}
+
+impl Document {
+    pub fn new(html: String) -> Self {
+        Self {
+            html,
+            parsed_to: 0,
+            paragraph: String::new(),
+            lines: vec![],
+            paragraphs: vec![],
+            width: 0,
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.parsed_to >= self.html.len() && self.paragraph.is_empty()
+    }
+
+    /// Lays out lines until there are at least `count`, starting over if the width changed
+    fn ensure_lines(&mut self, count: usize, width: usize) {
+        if width != self.width {
+            self.width = width;
+            self.parsed_to = 0;
+            self.paragraph.clear();
+            self.lines.clear();
+            self.paragraphs.clear();
+        }
+        while self.lines.len() < count && !self.is_complete() {
+            self.step();
+        }
+    }
+
+    /// Up to `count` lines from `scroll` on, pulling `scroll` back when it is past the end
+    pub fn page(&mut self, scroll: &mut usize, count: usize, width: usize) -> &[String] {
+        self.ensure_lines(*scroll + count + MARGIN_LINES, width);
+        if self.is_complete() {
+            *scroll = (*scroll).min(self.lines.len().saturating_sub(count));
+        }
+        let end = (*scroll + count).min(self.lines.len());
+        &self.lines[(*scroll).min(end)..end]
+    }
+
+    /// First line of the paragraph `distance` puts closest, laying out the whole document.
+    /// Paragraphs it gives no distance are skipped
+    pub fn closest_paragraph(
+        &mut self,
+        width: usize,
+        distance: impl Fn(&str) -> Option<i64>,
+    ) -> Option<usize> {
+        self.ensure_lines(usize::MAX, width);
+        self.paragraphs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &start)| {
+                let end = self
+                    .paragraphs
+                    .get(i + 1)
+                    .copied()
+                    .unwrap_or(self.lines.len());
+                Some((distance(&self.lines[start..end].join(" "))?, start))
+            })
+            .min()
+            .map(|(_, start)| start)
+    }
+
+    /// Converts the next chunk of HTML, ending on a tag boundary
+    fn step(&mut self) {
+        let end = (self.parsed_to + CHUNK_BYTES).min(self.html.len());
+        while self.parsed_to < end {
+            let rest = &self.html[self.parsed_to..];
+            let Some(tag_start) = rest.find('<') else {
+                self.paragraph.push_str(&decode_entities(rest));
+                self.parsed_to = self.html.len();
+                break;
+            };
+            self.paragraph
+                .push_str(&decode_entities(&rest[..tag_start]));
+            let tag_end = rest[tag_start..]
+                .find('>')
+                .map_or(rest.len(), |end| tag_start + end + 1);
+            let tag = &rest[tag_start..tag_end];
+            let name = tag_name(tag);
+            self.parsed_to += tag_end;
+
+            if (name == "script" || name == "style") && !tag.starts_with("</") {
+                let close = format!("</{name}");
+                let rest = &self.html[self.parsed_to..];
+                self.parsed_to += find_ignore_case(rest, &close).unwrap_or(rest.len());
+            } else if PARAGRAPH_TAGS.contains(&name.as_str()) {
+                self.flush_paragraph(true);
+            } else if BLOCK_TAGS.contains(&name.as_str()) {
+                self.flush_paragraph(false);
+            } else {
+                self.paragraph.push(' ');
+            }
+        }
+        if self.parsed_to >= self.html.len() {
+            self.flush_paragraph(false);
+        }
+    }
+
+    /// Wraps the current paragraph into lines, optionally followed by a blank line
+    fn flush_paragraph(&mut self, gap: bool) {
+        if self.paragraph.split_whitespace().next().is_some() {
+            self.paragraphs.push(self.lines.len());
+        }
+        let mut line = String::new();
+        for word in self.paragraph.split_whitespace() {
+            let line_width = line.chars().count();
+            if line_width > 0 && line_width + 1 + word.chars().count() > self.width {
+                self.lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        if !line.is_empty() {
+            self.lines.push(line);
+        }
+        if gap && self.lines.last().is_some_and(|line| !line.is_empty()) {
+            self.lines.push(String::new());
+        }
+        self.paragraph.clear();
+    }
+}
+
+pub fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches('<')
+        .trim_start_matches('/')
+        .chars()
+        .take_while(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+pub fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Screen showing the description of an item
+pub struct ReaderScreen {
+    pub html_url: String,
+    pub title: String,
+    /// None while the description is being fetched
+    document: Option<Document>,
+    /// Index of the first visible line
+    pub scroll: usize,
+    /// Height of the text area in the last frame, for paging
+    pub page_height: usize,
+    /// Date to scroll to the paragraph mentioning the closest date to once the text is loaded,
+    /// e.g. the due date of an item when reading its course's syllabus
+    pub jump_to: Option<Date>,
+}
+
+impl ReaderScreen {
+    pub fn new(html_url: String, title: String, description: Option<String>) -> Self {
+        Self {
+            html_url,
+            title,
+            document: description.map(Document::new),
+            scroll: 0,
+            page_height: 0,
+            jump_to: None,
+        }
+    }
+
+    pub fn set_description(&mut self, description: String) {
+        self.document = Some(Document::new(description));
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        Some(&self.document.as_ref()?.html)
+    }
+
+    pub fn scroll_by(&mut self, lines: isize) {
+        self.scroll = self.scroll.saturating_add_signed(lines);
+    }
+}
+
+impl Component for ReaderScreen {
+    fn handle_event(&mut self, _key: KeyEvent, bound: Action) -> Action {
+        match bound {
+            Action::Quit
+            | Action::OpenURL
+            | Action::Navigate(
+                Navigation::PrevEvent
+                | Navigation::NextEvent
+                | Navigation::PrevDate
+                | Navigation::NextDate,
+            )
+            | Action::OpenInPager
+            | Action::ToggleView(View::Reader | View::Links) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, _context: &Context) -> Option<Action> {
+        let navigation = match action {
+            Action::Navigate(navigation) => navigation,
+            Action::DetailsFetched {
+                html_url,
+                description,
+                ..
+            } if *html_url == self.html_url => {
+                self.set_description(description.clone());
+                return None;
+            }
+            Action::CoursePageFetched {
+                course_id,
+                page: CoursePage::Html(html),
+                ..
+            } if syllabus_url(*course_id) == self.html_url => {
+                self.set_description(html.clone());
+                return None;
+            }
+            _ => return None,
+        };
+        let page = self.page_height.max(1) as isize;
+        self.scroll_by(match navigation {
+            Navigation::PrevEvent => -1,
+            Navigation::NextEvent => 1,
+            Navigation::PrevDate => -page,
+            Navigation::NextDate => page,
+            Navigation::ResetDate
+            | Navigation::Today
+            | Navigation::LastDate
+            | Navigation::NextMatch
+            | Navigation::PrevMatch => return None,
+        });
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, _context: &Context) {
+        let [title_area, text_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(area);
+        Paragraph::new(redact::mask(&self.title))
+            .style(Style::default().fg(theme::current().heading).bold())
+            .render(title_area, buf);
+        let Some(document) = &mut self.document else {
+            Paragraph::new("Loading description...").render(text_area, buf);
+            return;
+        };
+
+        let height = text_area.height as usize;
+        self.page_height = height;
+        if let Some(date) = self.jump_to.take()
+            && let Some(line) = document.closest_paragraph(text_area.width as usize, |text| {
+                syllabus::days_from(text, date)
+            })
+        {
+            self.scroll = line;
+        }
+        document.ensure_lines(
+            self.scroll + height + MARGIN_LINES,
+            text_area.width as usize,
+        );
+        if document.is_complete() {
+            self.scroll = self.scroll.min(document.lines.len().saturating_sub(height));
+        }
+        if document.is_complete() && document.lines.is_empty() {
+            Paragraph::new("No description").render(text_area, buf);
+            return;
+        }
+        let visible: Vec<String> = document
+            .lines
+            .iter()
+            .skip(self.scroll)
+            .take(height)
+            .map(|line| redact::mask(line))
+            .collect();
+        Paragraph::new(visible.join("\n"))
+            .style(Style::default().fg(theme::current().text))
+            .render(text_area, buf);
+    }
+}
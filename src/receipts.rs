@@ -0,0 +1,173 @@
+use std::{io::Write, path::PathBuf};
+
+use color_eyre::eyre::Result;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Constraint,
+    prelude::{Buffer, Rect},
+    style::Style,
+    widgets::{Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use serde::{Deserialize, Serialize};
+use time::{OffsetDateTime, format_description};
+
+use crate::{
+    action::{Action, Navigation, View},
+    component::{Component, Context},
+    fetch::local_offset,
+    redact,
+    store::data_dir,
+    theme,
+};
+
+const RECEIPTS_FILE: &str = "canvastui/receipts.jsonl";
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptKind {
+    FileUpload,
+    TextEntry,
+    Comment,
+    MarkDone,
+}
+
+impl ReceiptKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReceiptKind::FileUpload => "File upload",
+            ReceiptKind::TextEntry => "Text entry",
+            ReceiptKind::Comment => "Comment",
+            ReceiptKind::MarkDone => "Mark done",
+        }
+    }
+}
+
+/// Record of a submission-related action performed through the app
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Receipt {
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    pub kind: ReceiptKind,
+    pub html_url: String,
+    pub title: String,
+    /// Id Canvas returned for the created submission, comment or override
+    pub confirmation_id: Option<String>,
+}
+
+impl Receipt {
+    fn path() -> Option<PathBuf> {
+        Some(data_dir()?.join(RECEIPTS_FILE))
+    }
+
+    /// Appends the receipt to the log. The log is append-only so past receipts are never rewritten
+    pub fn record(&self) -> Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{}", serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Reads every receipt in the log, newest first
+    pub fn load_all() -> Result<Vec<Receipt>> {
+        let Some(path) = Self::path() else {
+            return Ok(vec![]);
+        };
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+        let mut receipts = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<Receipt>, _>>()?;
+        receipts.sort_by_key(|receipt| std::cmp::Reverse(receipt.timestamp));
+        Ok(receipts)
+    }
+}
+
+/// Screen listing the receipt log
+pub struct ReceiptsScreen {
+    pub receipts: Vec<Receipt>,
+    pub table_state: TableState,
+}
+
+impl ReceiptsScreen {
+    pub fn load() -> Result<Self> {
+        Ok(Self {
+            receipts: Receipt::load_all()?,
+            table_state: TableState::default().with_selected(0),
+        })
+    }
+}
+
+impl Component for ReceiptsScreen {
+    fn handle_event(&mut self, _key: KeyEvent, bound: Action) -> Action {
+        match bound {
+            Action::Quit
+            | Action::Navigate(Navigation::PrevEvent | Navigation::NextEvent)
+            | Action::ToggleView(View::Receipts) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, _context: &Context) -> Option<Action> {
+        match action {
+            Action::Navigate(Navigation::PrevEvent) => self.table_state.select_previous(),
+            Action::Navigate(Navigation::NextEvent) => self.table_state.select_next(),
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, _context: &Context) {
+        if self.receipts.is_empty() {
+            Paragraph::new("No submissions recorded yet").render(area, buf);
+            return;
+        }
+
+        let local_offset = local_offset();
+        let timestamp_format =
+            format_description::parse("[year]-[month]-[day] [hour]:[minute]:[second]").unwrap();
+        let header = ["Time", "Action", "Item", "Confirmation"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(Style::default().fg(theme::current().heading));
+        let rows = self.receipts.iter().map(|receipt| {
+            Row::new([
+                receipt
+                    .timestamp
+                    .to_offset(local_offset)
+                    .format(&timestamp_format)
+                    .unwrap(),
+                receipt.kind.label().to_string(),
+                redact::mask(&receipt.title),
+                receipt.confirmation_id.clone().unwrap_or("-".to_string()),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(19),
+                Constraint::Length(11),
+                Constraint::Fill(1),
+                Constraint::Length(14),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::default().bg(theme::current().selection))
+        .style(Style::default().fg(theme::current().text));
+        StatefulWidget::render(table, area, buf, &mut self.table_state);
+    }
+}
@@ -0,0 +1,72 @@
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use color_eyre::eyre::{Result, eyre};
+
+use crate::store::data_dir;
+
+/// Exists while redacted mode is on, so it lasts across restarts and covers the CLI commands
+const REDACTED_FILE: &str = "canvastui/redacted";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+fn path() -> Option<PathBuf> {
+    Some(data_dir()?.join(REDACTED_FILE))
+}
+
+/// Reads whether redacted mode was left on
+pub fn load() {
+    let enabled = path().is_some_and(|path| path.exists());
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn set(enabled: bool) -> Result<()> {
+    ENABLED.store(enabled, Ordering::Relaxed);
+    let Some(path) = path() else {
+        return Ok(());
+    };
+    match enabled {
+        true => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, "")?;
+        }
+        false => match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        },
+    }
+    Ok(())
+}
+
+/// Replaces every visible character with a placeholder while redacted mode is on, keeping the
+/// text's width so layouts don't shift
+pub fn mask(text: &str) -> String {
+    match enabled() {
+        true => text
+            .chars()
+            .map(|c| match c.is_whitespace() {
+                true => c,
+                false => '•',
+            })
+            .collect(),
+        false => text.to_string(),
+    }
+}
+
+/// Refuses to write academic data to disk while redacted mode is on
+pub fn check_export() -> Result<()> {
+    match enabled() {
+        true => Err(eyre!(
+            "Exports are disabled while redacted mode is on, press S in the app to turn it off"
+        )),
+        false => Ok(()),
+    }
+}
@@ -0,0 +1,75 @@
+use std::process::Stdio;
+
+use color_eyre::eyre::{Result, eyre};
+use reqwest::Url;
+use time::OffsetDateTime;
+use tokio::process::Command;
+
+use crate::{
+    caldav,
+    config::{CalDavConfig, ReminderBackend, RemindersConfig},
+    fetch::{CalendarEvent, to_local},
+};
+
+/// Creates a reminder for an item on the configured backend, going off `minutes_before` its
+/// deadline so the phone notifies even with the computer off
+pub async fn create(
+    config: &RemindersConfig,
+    caldav: Option<&CalDavConfig>,
+    canvas_url: &str,
+    event: &CalendarEvent,
+) -> Result<()> {
+    match config.backend {
+        ReminderBackend::Apple => create_in_reminders_app(config, canvas_url, event).await,
+        ReminderBackend::CalDav => {
+            let caldav = caldav.ok_or(eyre!("No [caldav] section in the config file"))?;
+            caldav::push_reminder(caldav, canvas_url, event, config.minutes_before).await
+        }
+    }
+}
+
+/// Adds the item to Reminders.app unless an open reminder with its name is already there
+async fn create_in_reminders_app(
+    config: &RemindersConfig,
+    canvas_url: &str,
+    event: &CalendarEvent,
+) -> Result<()> {
+    let url = match canvas_url.parse::<Url>() {
+        Ok(base) => base.join(&event.html_url)?.to_string(),
+        Err(_) => event.html_url.clone(),
+    };
+    let name = format!("{}: {}", event.course_name, event.title);
+    let list = match &config.list {
+        Some(list) => format!("list {list:?}"),
+        None => "default list".to_string(),
+    };
+    // Date literals are read in the system locale, so dates are built from today's midnight
+    let midnight = to_local(OffsetDateTime::now_utc()).date().midnight();
+    let due = (event.due_at - midnight).whole_seconds();
+    let remind = due - 60 * config.minutes_before as i64;
+    let script = format!(
+        "set today to (current date) - (time of (current date))
+tell application \"Reminders\"
+    tell {list}
+        if not (exists (reminders whose name is {name:?} and completed is false)) then
+            make new reminder with properties {{name:{name:?}, body:{url:?}, \
+due date:(today + {due}), remind me date:(today + {remind})}}
+        end if
+    end tell
+end tell"
+    );
+    let output = Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .stdin(Stdio::null())
+        .output()
+        .await?;
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(eyre!(
+            "osascript exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+    }
+}
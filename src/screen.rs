@@ -0,0 +1,198 @@
+use crate::{
+    action::Action,
+    agenda::AgendaScreen,
+    announcements::AnnouncementsScreen,
+    audit::AuditScreen,
+    bookmarks::BookmarksScreen,
+    catchup::CatchUpScreen,
+    component::{Component, Context},
+    courses::CoursesScreen,
+    grading::GradingScreen,
+    grid::GridScreen,
+    inbox::InboxScreen,
+    links::LinksScreen,
+    modules::ModulesScreen,
+    posting::Draft,
+    reader::ReaderScreen,
+    receipts::ReceiptsScreen,
+    todo::TodoScreen,
+    triage::TriageScreen,
+};
+
+/// What fills the space under the header, in the order screens cover each other
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Screen {
+    /// A drafted post shown for confirmation before it is sent
+    PostPreview,
+    /// Grade entry for an assignment opened from the courses view
+    Grading,
+    Receipts,
+    /// What recent actions led to
+    Audit,
+    /// Upcoming items of one course in module order
+    Modules,
+    Bookmarks,
+    /// Links in the description of an item, over the reader
+    Links,
+    /// Description of an item
+    Reader,
+    Courses,
+    /// Unread announcements by course
+    Announcements,
+    /// Canvas inbox conversations
+    Inbox,
+    /// The Canvas To-Do list
+    Todo,
+    Triage,
+    CatchUp,
+    /// Items of every profile in one timeline
+    Agenda,
+    /// Week or month grid of the focused profile's items
+    Grid,
+    /// The day view or dashboard of every profile, shown when nothing else is open
+    Planner,
+}
+
+/// Screens shown instead of the planner, each kept while open
+#[derive(Default)]
+pub struct Screens {
+    pub post_preview: Option<Draft>,
+    pub grading: Option<GradingScreen>,
+    pub receipts: Option<ReceiptsScreen>,
+    pub audit: Option<AuditScreen>,
+    pub modules: Option<ModulesScreen>,
+    pub bookmarks: Option<BookmarksScreen>,
+    pub links: Option<LinksScreen>,
+    pub reader: Option<ReaderScreen>,
+    pub courses: Option<CoursesScreen>,
+    pub announcements: Option<AnnouncementsScreen>,
+    pub inbox: Option<InboxScreen>,
+    pub todo: Option<TodoScreen>,
+    pub triage: Option<TriageScreen>,
+    pub catch_up: Option<CatchUpScreen>,
+    pub agenda: Option<AgendaScreen>,
+    pub grid: Option<GridScreen>,
+}
+
+impl Screens {
+    /// The open screen on top, the planner if none is
+    pub fn current(&self) -> Screen {
+        if self.post_preview.is_some() {
+            Screen::PostPreview
+        } else if self.grading.is_some() {
+            Screen::Grading
+        } else if self.receipts.is_some() {
+            Screen::Receipts
+        } else if self.audit.is_some() {
+            Screen::Audit
+        } else if self.modules.is_some() {
+            Screen::Modules
+        } else if self.bookmarks.is_some() {
+            Screen::Bookmarks
+        } else if self.links.is_some() {
+            Screen::Links
+        } else if self.reader.is_some() {
+            Screen::Reader
+        } else if self.courses.is_some() {
+            Screen::Courses
+        } else if self.announcements.is_some() {
+            Screen::Announcements
+        } else if self.inbox.is_some() {
+            Screen::Inbox
+        } else if self.todo.is_some() {
+            Screen::Todo
+        } else if self.triage.is_some() {
+            Screen::Triage
+        } else if self.catch_up.is_some() {
+            Screen::CatchUp
+        } else if self.agenda.is_some() {
+            Screen::Agenda
+        } else if self.grid.is_some() {
+            Screen::Grid
+        } else {
+            Screen::Planner
+        }
+    }
+
+    /// The screen on top, which gets the keys and is drawn. None for the planner, which the app
+    /// draws itself
+    pub fn focused(&mut self) -> Option<&mut dyn Component> {
+        fn open<C: Component>(screen: &mut Option<C>) -> Option<&mut dyn Component> {
+            screen.as_mut().map(|screen| screen as &mut dyn Component)
+        }
+        match self.current() {
+            Screen::PostPreview => open(&mut self.post_preview),
+            Screen::Grading => open(&mut self.grading),
+            Screen::Receipts => open(&mut self.receipts),
+            Screen::Audit => open(&mut self.audit),
+            Screen::Modules => open(&mut self.modules),
+            Screen::Bookmarks => open(&mut self.bookmarks),
+            Screen::Links => open(&mut self.links),
+            Screen::Reader => open(&mut self.reader),
+            Screen::Courses => open(&mut self.courses),
+            Screen::Announcements => open(&mut self.announcements),
+            Screen::Inbox => open(&mut self.inbox),
+            Screen::Todo => open(&mut self.todo),
+            Screen::Triage => open(&mut self.triage),
+            Screen::CatchUp => open(&mut self.catch_up),
+            Screen::Agenda => open(&mut self.agenda),
+            Screen::Grid => open(&mut self.grid),
+            Screen::Planner => None,
+        }
+    }
+
+    /// Every open screen, on top or covered
+    fn open(&mut self) -> Vec<&mut dyn Component> {
+        fn open<C: Component>(screen: &mut Option<C>) -> Option<&mut dyn Component> {
+            screen.as_mut().map(|screen| screen as &mut dyn Component)
+        }
+        let Self {
+            post_preview,
+            grading,
+            receipts,
+            audit,
+            modules,
+            bookmarks,
+            links,
+            reader,
+            courses,
+            announcements,
+            inbox,
+            todo,
+            triage,
+            catch_up,
+            agenda,
+            grid,
+        } = self;
+        [
+            open(post_preview),
+            open(grading),
+            open(receipts),
+            open(audit),
+            open(modules),
+            open(bookmarks),
+            open(links),
+            open(reader),
+            open(courses),
+            open(announcements),
+            open(inbox),
+            open(todo),
+            open(triage),
+            open(catch_up),
+            open(agenda),
+            open(grid),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Keeps every open screen in step with an action, even covered ones, which still take in
+    /// what was fetched for them. Returns what they ask the app to do next
+    pub fn update(&mut self, action: &Action, context: &Context) -> Vec<Action> {
+        self.open()
+            .into_iter()
+            .filter_map(|screen| screen.update(action, context))
+            .collect()
+    }
+}
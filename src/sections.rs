@@ -0,0 +1,104 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::PathBuf,
+};
+
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Profile, fetch::get_json, store::data_dir};
+
+const SECTIONS_FILE: &str = "canvastui/sections.json";
+
+#[derive(Deserialize)]
+struct CanvasSection {
+    id: u64,
+    name: String,
+    /// Missing or null when the section has no students
+    students: Option<Vec<CanvasSectionStudent>>,
+}
+
+#[derive(Deserialize)]
+struct CanvasSectionStudent {
+    id: u64,
+}
+
+/// A section of a course and the students enrolled in it
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub id: u64,
+    pub name: String,
+    pub student_ids: HashSet<u64>,
+}
+
+/// Fetches the sections of a course with their students
+pub async fn fetch_sections(profile: &Profile, course_id: u64) -> Result<Vec<Section>> {
+    let sections: Vec<CanvasSection> = get_json(
+        profile,
+        &format!("/api/v1/courses/{course_id}/sections"),
+        &[("include[]", "students"), ("per_page", "100")],
+    )
+    .await?;
+    Ok(sections
+        .into_iter()
+        .map(|section| Section {
+            id: section.id,
+            name: section.name,
+            student_ids: section
+                .students
+                .into_iter()
+                .flatten()
+                .map(|student| student.id)
+                .collect(),
+        })
+        .collect())
+}
+
+/// The section instructor views are filtered to in each course, kept across restarts since a TA
+/// usually only looks after their own
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SectionFilters {
+    /// Section ids by course id
+    courses: BTreeMap<u64, u64>,
+}
+
+impl SectionFilters {
+    fn path() -> Option<PathBuf> {
+        Some(data_dir()?.join(SECTIONS_FILE))
+    }
+
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match std::fs::read(&path) {
+            Ok(contents) => Ok(serde_json::from_slice(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, course_id: u64) -> Option<u64> {
+        self.courses.get(&course_id).copied()
+    }
+
+    /// Filters the course to a section, or shows every section again when None
+    pub fn set(&mut self, course_id: u64, section_id: Option<u64>) {
+        match section_id {
+            Some(section_id) => self.courses.insert(course_id, section_id),
+            None => self.courses.remove(&course_id),
+        };
+    }
+}
@@ -0,0 +1,969 @@
+use std::collections::{HashMap, HashSet};
+
+use ratatui::{
+    layout::{Constraint, Layout},
+    prelude::{Buffer, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget,
+        calendar::{CalendarEventStore, Monthly},
+    },
+};
+use reqwest::Url;
+use time::{Date, OffsetDateTime, PrimitiveDateTime, Time, format_description};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::{Action, Navigation},
+    capabilities,
+    component::{Component, Context},
+    config::Profile,
+    courses::CourseContext,
+    fetch::{Attempts, Calendar, CalendarEvent, Requirements, fetch_details, now_local},
+    grades::Gradebook,
+    locale, metrics,
+    notes::DateNotes,
+    permissions::{CoursePermissions, fetch_permissions},
+    ratelimit, redact,
+    startup::{Loaded, Progress},
+    theme,
+    workload::{Estimate, find_ignore_case},
+};
+
+/// Items worth at least this many percentage points of the course grade are flagged
+const HIGH_IMPACT: f64 = 10.0;
+
+/// Lines of a date's note shown under the date header, longer notes are cut off
+const NOTE_LINES: usize = 3;
+
+/// Items with this many attempts left or fewer get a warning next to their title
+const ATTEMPT_WARNING: u32 = 2;
+
+/// Part of the day an item is due in, shown as headings between a day's items with
+/// `time_buckets`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimeBucket {
+    Morning,
+    Afternoon,
+    Evening,
+    /// The default Canvas due time, which most items of a heavy day share
+    Midnight,
+}
+
+impl TimeBucket {
+    fn of(time: Time) -> Self {
+        match (time.hour(), time.minute()) {
+            (23, 59) => TimeBucket::Midnight,
+            (0..12, _) => TimeBucket::Morning,
+            (12..17, _) => TimeBucket::Afternoon,
+            _ => TimeBucket::Evening,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            TimeBucket::Morning => "Morning",
+            TimeBucket::Afternoon => "Afternoon",
+            TimeBucket::Evening => "Evening",
+            TimeBucket::Midnight => "11:59pm",
+        }
+    }
+
+    fn heading(self) -> Row<'static> {
+        Row::new([Cell::from(self.label())])
+            .style(Style::default().fg(theme::current().muted).bold())
+    }
+}
+
+/// Rule between the items already due and the ones still ahead, spanning `columns` table columns
+pub fn now_marker(now: Time, columns: usize) -> Row<'static> {
+    let rule = "─".repeat(200);
+    let mut cells = vec![Cell::from(format!(
+        "── now {:02}:{:02} {rule}",
+        now.hour(),
+        now.minute()
+    ))];
+    cells.extend((1..columns).map(|_| Cell::from(rule.clone())));
+    Row::new(cells).style(Style::default().fg(theme::current().error))
+}
+
+/// Outcome of a session's latest planner fetch
+#[derive(Debug, Clone, Default)]
+pub enum FetchState {
+    #[default]
+    Loading,
+    Loaded,
+    Failed(String),
+}
+
+impl FetchState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FetchState::Loading => "loading",
+            FetchState::Loaded => "ok",
+            FetchState::Failed(_) => "failed",
+        }
+    }
+}
+
+/// Planner data and navigation state for one profile
+pub struct Session {
+    pub profile: Profile,
+    pub calendar: Calendar,
+    pub longest_item_lens: (u16, u16, u16),
+    pub received_fetch: bool,
+    pub fetch_state: FetchState,
+    /// When the shown planner data was fetched, from the cache file until a fetch succeeds
+    pub updated_at: Option<OffsetDateTime>,
+    pub current_date_index: usize,
+    /// Reading-time estimates keyed by html_url, None while the description is being fetched
+    pub estimates: HashMap<String, Option<Estimate>>,
+    /// Permissions keyed by course id, None while they are being fetched
+    pub permissions: HashMap<u64, Option<CoursePermissions>>,
+    /// Attempt counts of items with limited attempts keyed by html_url, kept across fetches
+    pub attempts: HashMap<String, Attempts>,
+    /// Submission requirements keyed by html_url, fetched along with estimates
+    pub requirements: HashMap<String, Requirements>,
+    /// HTML descriptions keyed by html_url, fetched along with estimates
+    pub descriptions: HashMap<String, String>,
+    pub progress: Progress,
+    /// Course codes by course id
+    pub courses: HashMap<u64, String>,
+    /// Ids of the courses left out of every view, known once the course list is loaded
+    pub excluded_courses: HashSet<u64>,
+    pub nicknames: HashMap<u64, String>,
+    /// Primary instructor by course id, only resolved with `show_instructors`
+    pub instructors: HashMap<u64, String>,
+    /// Colors picked for courses in Canvas, by course id
+    pub colors: HashMap<u64, Color>,
+    pub user_name: Option<String>,
+    /// Grading setups keyed by course id, None while they are being fetched
+    pub gradebooks: HashMap<u64, Option<Gradebook>>,
+    /// Notes attached to dates, the same for every profile
+    pub notes: DateNotes,
+    /// Search typed with `/`. While it is set only matching items are shown, with the match
+    /// highlighted
+    pub filter: Option<String>,
+    /// Courses unchecked in the course filter sidebar, their items are left out of the day view,
+    /// the grids and the agenda
+    pub hidden_courses: HashSet<u64>,
+    /// Course everything is scoped to in focused course mode, its items the only ones shown
+    pub focused_course: Option<CourseContext>,
+    /// Groups the day's items under Morning, Afternoon, Evening and 11:59pm headings
+    pub time_buckets: bool,
+}
+
+/// Where to look for the next search match, relative to the selected item
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchDirection {
+    /// The selected item itself or the next match after it, while the search is being typed
+    Here,
+    Forward,
+    Backward,
+}
+
+impl Session {
+    pub fn new(profile: Profile) -> Self {
+        Self {
+            profile,
+            calendar: Calendar { dates: vec![] },
+            longest_item_lens: (0, 0, 0),
+            received_fetch: false,
+            fetch_state: FetchState::default(),
+            updated_at: None,
+            current_date_index: 0,
+            estimates: HashMap::new(),
+            permissions: HashMap::new(),
+            attempts: HashMap::new(),
+            requirements: HashMap::new(),
+            descriptions: HashMap::new(),
+            progress: Progress::default(),
+            courses: HashMap::new(),
+            excluded_courses: HashSet::new(),
+            nicknames: HashMap::new(),
+            instructors: HashMap::new(),
+            colors: HashMap::new(),
+            user_name: None,
+            gradebooks: HashMap::new(),
+            notes: DateNotes::default(),
+            filter: None,
+            hidden_courses: HashSet::new(),
+            focused_course: None,
+            time_buckets: false,
+        }
+    }
+
+    /// Says how old the shown data is when the latest fetch failed and older data is kept
+    pub fn staleness(&self) -> Option<String> {
+        let FetchState::Failed(_) = self.fetch_state else {
+            return None;
+        };
+        let minutes = (OffsetDateTime::now_utc() - self.updated_at?)
+            .whole_minutes()
+            .max(0);
+        Some(match minutes {
+            ..60 => format!("stale (last updated {minutes} min ago)"),
+            60..1440 => format!("stale (last updated {} h ago)", minutes / 60),
+            _ => format!("stale (last updated {} days ago)", minutes / 1440),
+        })
+    }
+
+    pub fn apply_loaded(&mut self, data: Loaded) {
+        match data {
+            Loaded::Courses {
+                codes,
+                instructors,
+                excluded,
+            } => {
+                self.courses = codes;
+                self.instructors = instructors;
+                self.excluded_courses = excluded;
+            }
+            Loaded::Colors(colors) => self.colors = colors,
+            Loaded::Nicknames(nicknames) => self.nicknames = nicknames,
+            Loaded::Profile(name) => self.user_name = Some(name),
+        }
+        self.apply_course_names();
+    }
+
+    /// Renames courses in the calendar with what is known about them so far
+    pub fn apply_course_names(&mut self) {
+        self.calendar
+            .apply_course_names(&self.courses, &self.nicknames);
+        self.calculate_longest_item_lens();
+    }
+
+    /// Fetches details of the current date's items to estimate their reading time and count
+    /// attempts
+    pub fn request_estimates(&mut self, id: usize, action_tx: &UnboundedSender<Action>) {
+        let Some(current_date) = self.calendar.dates.get(self.current_date_index) else {
+            return;
+        };
+        for event in &current_date.events {
+            if event.is_external() {
+                continue;
+            }
+            let cached = self.estimates.contains_key(&event.html_url);
+            metrics::record_cache(cached);
+            if cached {
+                continue;
+            }
+            self.estimates.insert(event.html_url.clone(), None);
+            let event = event.clone();
+            let profile = self.profile.clone();
+            let action_tx = action_tx.clone();
+            tokio::spawn(async move {
+                ratelimit::pace().await;
+                if let Ok(details) = fetch_details(&profile, &event).await {
+                    let _ = action_tx.send(Action::DetailsFetched {
+                        session: id,
+                        html_url: event.html_url,
+                        estimate: Estimate::from_html(&details.description),
+                        description: details.description,
+                        attempts: details.attempts,
+                        requirements: details.requirements,
+                    });
+                }
+            });
+        }
+    }
+
+    /// Fetches permissions for every course that has items, to know which actions are allowed
+    pub fn request_permissions(&mut self, id: usize, action_tx: &UnboundedSender<Action>) {
+        let course_ids: Vec<u64> = self
+            .calendar
+            .dates
+            .iter()
+            .flat_map(|date| &date.events)
+            .filter_map(|event| event.course_id)
+            .collect();
+        for course_id in course_ids {
+            self.request_course_permissions(id, course_id, action_tx);
+        }
+    }
+
+    /// Fetches the permissions of one course unless they are known or on their way
+    pub fn request_course_permissions(
+        &mut self,
+        id: usize,
+        course_id: u64,
+        action_tx: &UnboundedSender<Action>,
+    ) {
+        if self.permissions.contains_key(&course_id) {
+            return;
+        }
+        self.permissions.insert(course_id, None);
+        let profile = self.profile.clone();
+        let action_tx = action_tx.clone();
+        tokio::spawn(async move {
+            if let Ok(permissions) = fetch_permissions(&profile, course_id).await {
+                let _ = action_tx.send(Action::PermissionsFetched {
+                    session: id,
+                    course_id,
+                    permissions,
+                });
+            }
+        });
+    }
+
+    /// Fetches the grading setup of every course in the calendar to estimate how much each item
+    /// weighs on the course grade
+    pub fn request_gradebooks(&mut self, id: usize, action_tx: &UnboundedSender<Action>) {
+        let course_ids: Vec<u64> = self
+            .calendar
+            .dates
+            .iter()
+            .flat_map(|date| &date.events)
+            .filter_map(|event| event.course_id)
+            .collect();
+        for course_id in course_ids {
+            if self.gradebooks.contains_key(&course_id) {
+                continue;
+            }
+            self.gradebooks.insert(course_id, None);
+            let profile = self.profile.clone();
+            let action_tx = action_tx.clone();
+            tokio::spawn(async move {
+                ratelimit::pace().await;
+                if let Ok(gradebook) = Gradebook::fetch(&profile, course_id).await {
+                    let _ = action_tx.send(Action::GradebookFetched {
+                        session: id,
+                        course_id,
+                        gradebook,
+                    });
+                }
+            });
+        }
+    }
+
+    /// Percentage points of the course grade an item is worth, once its gradebook is fetched
+    fn impact(&self, event: &CalendarEvent) -> Option<f64> {
+        self.gradebooks
+            .get(&event.course_id?)?
+            .as_ref()?
+            .impact(&event.plannable_type, event.plannable_id)
+    }
+
+    fn estimate(&self, event: &CalendarEvent) -> Option<Estimate> {
+        self.estimates.get(&event.html_url).copied().flatten()
+    }
+
+    pub fn selected_event(&self) -> Option<&CalendarEvent> {
+        let current_date = self.calendar.dates.get(self.current_date_index)?;
+        current_date
+            .events
+            .get(current_date.table_state.selected()?)
+    }
+
+    /// Items of the current date still to hand in, as far as the course filter and search show
+    /// them
+    pub fn unfinished_on_current_date(&self) -> Vec<&CalendarEvent> {
+        let Some(date) = self.calendar.dates.get(self.current_date_index) else {
+            return vec![];
+        };
+        date.events
+            .iter()
+            .enumerate()
+            .filter(|(row, event)| {
+                !event.is_external()
+                    && !event.is_done()
+                    && self.is_visible(self.current_date_index, *row)
+            })
+            .map(|(_, event)| event)
+            .collect()
+    }
+
+    /// Full url of an item, resolved against the profile's Canvas instance
+    pub fn absolute_url(&self, event: &CalendarEvent) -> String {
+        match self.profile.url.parse::<Url>() {
+            Ok(base) => base
+                .join(&event.html_url)
+                .map_or(event.html_url.clone(), String::from),
+            Err(_) => event.html_url.clone(),
+        }
+    }
+
+    pub fn current_date(&self) -> Option<Date> {
+        let current_date = self.calendar.dates.get(self.current_date_index)?;
+        Some(current_date.events.first()?.due_at.date())
+    }
+
+    /// Whether the item at `row` of a date has `query` in its title or course, or is the first
+    /// item of a date whose note has it
+    fn matches(&self, date_index: usize, row: usize, query: &str) -> bool {
+        let event = &self.calendar.dates[date_index].events[row];
+        let note_matches = row == 0
+            && self
+                .notes
+                .get(event.due_at.date())
+                .is_some_and(|note| find_ignore_case(note, query).is_some());
+        note_matches
+            || find_ignore_case(&event.title, query).is_some()
+            || find_ignore_case(&event.course_name, query).is_some()
+    }
+
+    /// Date index and row of every shown item matching `query`, in planner order
+    fn match_positions(&self, query: &str) -> Vec<(usize, usize)> {
+        if query.is_empty() {
+            return vec![];
+        }
+        self.calendar
+            .dates
+            .iter()
+            .enumerate()
+            .flat_map(|(date_index, date)| (0..date.events.len()).map(move |row| (date_index, row)))
+            .filter(|(date_index, row)| {
+                !self.is_hidden(&self.calendar.dates[*date_index].events[*row])
+                    && self.matches(*date_index, *row, query)
+            })
+            .collect()
+    }
+
+    /// Whether the item's course is unchecked in the course filter, or isn't the focused course
+    pub fn is_hidden(&self, event: &CalendarEvent) -> bool {
+        if let Some(course) = &self.focused_course {
+            return event.course_id != Some(course.id);
+        }
+        event
+            .course_id
+            .is_some_and(|course_id| self.hidden_courses.contains(&course_id))
+    }
+
+    /// Whether the item at `row` of a date is shown under the course filter and current search
+    pub fn is_visible(&self, date_index: usize, row: usize) -> bool {
+        !self.is_hidden(&self.calendar.dates[date_index].events[row])
+            && self
+                .filter
+                .as_ref()
+                .is_none_or(|query| self.matches(date_index, row, query))
+    }
+
+    /// Moves the selection to the next or previous shown item of the date, wrapping around
+    pub fn step_event(&mut self, forward: bool) {
+        let date_index = self.current_date_index;
+        let Some(date) = self.calendar.dates.get(date_index) else {
+            return;
+        };
+        let count = date.events.len();
+        let selected = date.table_state.selected().unwrap_or_default();
+        let next = (1..=count)
+            .map(|step| match forward {
+                true => (selected + step) % count,
+                false => (selected + count * step - step) % count,
+            })
+            .find(|row| self.is_visible(date_index, *row));
+        if let Some(row) = next {
+            self.calendar.dates[date_index]
+                .table_state
+                .select(Some(row));
+        }
+    }
+
+    /// Keeps the selection on a shown item after the course filter changed, moving to another
+    /// date when nothing on the current one is shown anymore
+    pub fn reveal_selection(&mut self) {
+        let date_index = self.current_date_index;
+        let Some(date) = self.calendar.dates.get(date_index) else {
+            return;
+        };
+        let selected = date.table_state.selected().unwrap_or_default();
+        if selected < date.events.len() && self.is_visible(date_index, selected) {
+            return;
+        }
+        match (0..date.events.len()).find(|row| self.is_visible(date_index, *row)) {
+            Some(row) => self.calendar.dates[date_index]
+                .table_state
+                .select(Some(row)),
+            None => {
+                self.step_date(true);
+                if self.current_date_index == date_index {
+                    self.step_date(false);
+                }
+            }
+        }
+    }
+
+    /// Next item in `direction` from the selected one, wrapping around, whose title or course
+    /// contains `query`, or the first item of a date whose note does. Returns the date index and row
+    pub fn search(&self, query: &str, direction: SearchDirection) -> Option<(usize, usize)> {
+        let matches = self.match_positions(query);
+        let selected = self
+            .calendar
+            .dates
+            .get(self.current_date_index)
+            .and_then(|date| date.table_state.selected())
+            .unwrap_or_default();
+        let current = (self.current_date_index, selected);
+        match direction {
+            SearchDirection::Here => matches.iter().find(|position| **position >= current),
+            SearchDirection::Forward => matches.iter().find(|position| **position > current),
+            SearchDirection::Backward => matches.iter().rev().find(|position| **position < current),
+        }
+        .or(match direction {
+            SearchDirection::Backward => matches.last(),
+            _ => matches.first(),
+        })
+        .copied()
+    }
+
+    /// Where the selected item is among the matches of `query`, counting from 1, and how many
+    /// matches there are
+    pub fn match_position(&self, query: &str) -> (Option<usize>, usize) {
+        let matches = self.match_positions(query);
+        let selected = self
+            .calendar
+            .dates
+            .get(self.current_date_index)
+            .and_then(|date| date.table_state.selected());
+        let position = selected.and_then(|row| {
+            matches
+                .iter()
+                .position(|position| *position == (self.current_date_index, row))
+        });
+        (position.map(|position| position + 1), matches.len())
+    }
+
+    /// Moves to the next or previous date with a shown item, selecting its first one when the
+    /// selected one is hidden
+    pub fn step_date(&mut self, forward: bool) {
+        let current = self.current_date_index;
+        let last = self.calendar.dates.len().saturating_sub(1);
+        let has_visible = |index: &usize| {
+            (0..self.calendar.dates[*index].events.len()).any(|row| self.is_visible(*index, row))
+        };
+        let next = match forward {
+            true => (current + 1..=last).find(has_visible),
+            false => (0..current).rev().find(has_visible),
+        };
+        let Some(index) = next else {
+            return;
+        };
+        self.current_date_index = index;
+        self.select_visible(index);
+    }
+
+    /// Moves to the first date from `date` on with a shown item, or the last one before it when
+    /// there is none. Returns the date moved to
+    pub fn jump_to_date(&mut self, date: Date) -> Option<Date> {
+        let dates = &self.calendar.dates;
+        let shown: Vec<usize> = (0..dates.len())
+            .filter(|index| (0..dates[*index].events.len()).any(|row| self.is_visible(*index, row)))
+            .collect();
+        let date_at = |index: usize| dates[index].events.first().map(|event| event.due_at.date());
+        let index = shown
+            .iter()
+            .copied()
+            .find(|index| date_at(*index).is_some_and(|at| at >= date))
+            .or(shown.last().copied())?;
+        self.current_date_index = index;
+        self.select_visible(index);
+        self.current_date()
+    }
+
+    /// Selects the first shown item of a date unless its selected one is shown, so each date
+    /// keeps its selection when moved back to
+    fn select_visible(&mut self, index: usize) {
+        let date = &self.calendar.dates[index];
+        if date
+            .table_state
+            .selected()
+            .is_none_or(|row| row >= date.events.len() || !self.is_visible(index, row))
+        {
+            let first = (0..date.events.len()).find(|row| self.is_visible(index, *row));
+            self.calendar.dates[index].table_state.select(first);
+        }
+    }
+
+    pub fn calculate_longest_item_lens(&mut self) {
+        self.calendar.dates.iter().for_each(|date| {
+            date.events.iter().for_each(|event| {
+                let course_name_len = event.course_name.len() as u16;
+                let title_len = event.title.len() as u16;
+                let due_at_len = event
+                    .due_at
+                    .format(&format_description::parse("  [hour]:[minute]").unwrap())
+                    .unwrap()
+                    .len() as u16;
+                self.longest_item_lens = (
+                    course_name_len.max(self.longest_item_lens.0),
+                    title_len.max(self.longest_item_lens.1),
+                    due_at_len.max(self.longest_item_lens.2),
+                );
+            });
+        });
+    }
+
+    /// Moves through the dates and their items
+    pub fn navigate(&mut self, navigation: Navigation) {
+        match navigation {
+            Navigation::PrevEvent => self.step_event(false),
+            Navigation::NextEvent => self.step_event(true),
+            Navigation::PrevDate => self.step_date(false),
+            Navigation::NextDate => self.step_date(true),
+            Navigation::ResetDate => {
+                self.jump_to_date(Date::MIN);
+            }
+            Navigation::Today => {
+                self.jump_to_date(now_local().date());
+            }
+            Navigation::LastDate => {
+                self.jump_to_date(Date::MAX);
+            }
+            // Matches are reported in the status bar, which the app owns
+            Navigation::NextMatch | Navigation::PrevMatch => {}
+        }
+    }
+}
+
+impl Component for Session {
+    fn update(&mut self, action: &Action, _context: &Context) -> Option<Action> {
+        if let Action::Navigate(navigation) = action {
+            self.navigate(*navigation);
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, _context: &Context) {
+        Widget::render(self, area, buf);
+    }
+}
+
+impl Widget for &mut Session {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        if self.calendar.dates.is_empty() {
+            Paragraph::new("Waiting for data...").render(area, buf);
+            return;
+        }
+
+        let note: Vec<String> = self
+            .current_date()
+            .and_then(|date| self.notes.get(date))
+            .map(|note| note.lines().take(NOTE_LINES).map(redact::mask).collect())
+            .unwrap_or_default();
+        let [
+            date_area,
+            note_area,
+            event_table_area,
+            details_area,
+            calendar_area,
+        ] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(note.len() as u16),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(10), /* Month, Weekday, 5 Weeks */
+        ])
+        .areas(area);
+        Paragraph::new(note.join("\n"))
+            .style(Style::default().fg(theme::current().warning))
+            .render(note_area, buf);
+
+        let estimates: Vec<Option<Estimate>> = self.calendar.dates[self.current_date_index]
+            .events
+            .iter()
+            .map(|e| self.estimate(e))
+            .collect();
+        let workload = estimates.iter().flatten().copied().reduce(|a, b| a + b);
+
+        let selected = self.selected_event();
+        let details = [
+            selected
+                .and_then(|event| self.requirements.get(&event.html_url))
+                .map(Requirements::summary),
+            selected
+                .and_then(|event| self.instructors.get(&event.course_id?))
+                .map(|instructor| format!("Instructor: {}", redact::mask(instructor))),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|detail| !detail.is_empty())
+        .collect::<Vec<_>>()
+        .join(" · ");
+        Paragraph::new(details)
+            .style(Style::default().fg(theme::current().secondary))
+            .render(details_area, buf);
+        let icons: Vec<String> = self.calendar.dates[self.current_date_index]
+            .events
+            .iter()
+            .map(|e| {
+                self.requirements
+                    .get(&e.html_url)
+                    .map(Requirements::icons)
+                    .unwrap_or_default()
+            })
+            .collect();
+        let impacts: Vec<Option<f64>> = self.calendar.dates[self.current_date_index]
+            .events
+            .iter()
+            .map(|e| self.impact(e))
+            .collect();
+
+        let date_index = self.current_date_index;
+        let visible: Vec<bool> = (0..self.calendar.dates[date_index].events.len())
+            .map(|row| self.is_visible(date_index, row))
+            .collect();
+        let has_visible: Vec<bool> = (0..self.calendar.dates.len())
+            .map(|index| {
+                (0..self.calendar.dates[index].events.len()).any(|row| self.is_visible(index, row))
+            })
+            .collect();
+        let filter = self.filter.clone().unwrap_or_default();
+        let time_buckets = self.time_buckets;
+
+        let current_cal_date = &mut self.calendar.dates[self.current_date_index];
+        let mut date_header = locale::current().header(
+            current_cal_date.events.first().unwrap().due_at.date(),
+            now_local().date(),
+        );
+        if let Some(workload) = workload.filter(|workload| workload.words > 0) {
+            date_header.push_str(&format!(
+                " · ~{} min reading ({} words)",
+                workload.minutes, workload.words
+            ));
+        }
+        Paragraph::new(date_header)
+            .style(Style::default().fg(theme::current().heading).bold())
+            .render(date_area, buf);
+
+        let header = ["Course", "Assignment", "Submit", "Due", "Read", "Impact"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .height(1)
+            .style(Style::default().fg(theme::current().heading));
+        let now = now_local();
+        let now = PrimitiveDateTime::new(now.date(), now.time());
+        let event_rows = current_cal_date
+            .events
+            .iter()
+            .zip(estimates)
+            .zip(icons)
+            .zip(impacts)
+            .zip(&visible)
+            .filter(|(_, visible)| **visible)
+            .map(|((((e, estimate), icons), impact), _)| {
+                let locked = e.lock_reason(now).is_some();
+                let overdue = e.is_overdue(now);
+                let mut title = match e.attempts {
+                    Some(attempts) if !e.is_done() && attempts.remaining() <= ATTEMPT_WARNING => {
+                        let mut title = highlight(&e.title, &filter);
+                        title.push_span(Span::raw(" "));
+                        title.push_span(Span::styled(
+                            format!("({})", attempts.describe()),
+                            Style::default().fg(theme::current().error),
+                        ));
+                        title
+                    }
+                    _ => highlight(&e.title, &filter),
+                };
+                if overdue {
+                    title.push_span(Span::raw(" "));
+                    title.push_span(Span::styled(
+                        "overdue",
+                        Style::default().fg(theme::current().error).bold(),
+                    ));
+                }
+                // Shown at the soft deadline, the official one is in the detail popup
+                if e.official_due_at.is_some() {
+                    title.push_span(Span::raw(" "));
+                    title.push_span(Span::styled(
+                        "soft",
+                        Style::default().fg(theme::current().muted).italic(),
+                    ));
+                }
+                let row = Row::new([
+                    Cell::from(highlight(&e.course_name, &filter)).style(
+                        match e.course_id.and_then(|id| self.colors.get(&id)) {
+                            Some(color) => Style::default().fg(*color),
+                            None => Style::default(),
+                        },
+                    ),
+                    Cell::from(title),
+                    Cell::from(icons),
+                    Cell::from(format!(
+                        "{} {}",
+                        e.due_at
+                            .format(&format_description::parse("[hour]:[minute]").unwrap())
+                            .unwrap(),
+                        match (e.is_done(), locked, capabilities::current().nerd_fonts()) {
+                            (true, _, true) => "\u{f0e1e}",
+                            (true, _, false) => "x",
+                            (false, true, true) => "\u{f023}",
+                            (false, true, false) => "-",
+                            (false, false, _) => " ",
+                        }
+                    )),
+                    Cell::from(match estimate {
+                        Some(estimate) if estimate.words > 0 => format!("~{}m", estimate.minutes),
+                        _ => String::new(),
+                    }),
+                    match impact {
+                        Some(impact) if impact >= HIGH_IMPACT && !e.is_done() => {
+                            Cell::from(format!("▲{impact:.1}%"))
+                                .style(Style::default().fg(theme::current().error).bold())
+                        }
+                        Some(impact) => Cell::from(format!(" {impact:.1}%")),
+                        None => Cell::from(""),
+                    },
+                ])
+                .style(Style::default().fg(
+                    match (e.is_external(), e.is_done(), overdue, locked) {
+                        (true, _, _, _) => theme::current().external,
+                        (false, true, _, _) => theme::current().done,
+                        (false, false, true, _) => theme::current().error,
+                        (false, false, false, true) => theme::current().muted,
+                        (false, false, false, false) => theme::current().text,
+                    },
+                ));
+                (e.due_at, TimeBucket::of(e.due_at.time()), row)
+            });
+        // Headings and the now marker only take up rows, so shown items are mapped to the row
+        // they end up in
+        let mut rows = vec![];
+        let mut item_rows = vec![];
+        let mut bucket = None;
+        let mut marker_pending =
+            current_cal_date.events.first().unwrap().due_at.date() == now.date();
+        let now_bucket = TimeBucket::of(now.time());
+        for (due_at, item_bucket, row) in event_rows {
+            let heading = time_buckets && bucket.replace(item_bucket) != Some(item_bucket);
+            let marker = marker_pending && due_at > now;
+            // The marker goes under the heading of the part of the day it falls in
+            if marker && item_bucket != now_bucket {
+                rows.push(now_marker(now.time(), 6));
+                marker_pending = false;
+            }
+            if heading {
+                rows.push(item_bucket.heading());
+            }
+            if marker && marker_pending {
+                rows.push(now_marker(now.time(), 6));
+                marker_pending = false;
+            }
+            item_rows.push(rows.len());
+            rows.push(row);
+        }
+        if marker_pending && !item_rows.is_empty() {
+            rows.push(now_marker(now.time(), 6));
+        }
+        let extra_rows = rows.len() != item_rows.len();
+        let label_width = match time_buckets {
+            true => TimeBucket::Afternoon.label().len() as u16,
+            false => 0,
+        };
+        let event_table = Table::new(
+            rows,
+            [
+                Constraint::Min(self.longest_item_lens.0.max(label_width) + 2),
+                Constraint::Min(self.longest_item_lens.1.max("Assignment".len() as u16) + 2),
+                Constraint::Min(7),
+                Constraint::Min(self.longest_item_lens.2 + 1),
+                Constraint::Min(5),
+                Constraint::Min(7),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::default().bg(theme::current().selection))
+        .style(Style::default().fg(theme::current().text));
+        match visible.contains(&false) || extra_rows {
+            // Rows are hidden or added, so the selection is counted among the shown rows
+            true => {
+                let selected = current_cal_date
+                    .table_state
+                    .selected()
+                    .filter(|row| visible.get(*row).copied().unwrap_or_default())
+                    .map(|row| item_rows[visible[..row].iter().filter(|shown| **shown).count()]);
+                let mut table_state = TableState::default().with_selected(selected);
+                StatefulWidget::render(event_table, event_table_area, buf, &mut table_state);
+            }
+            false => StatefulWidget::render(
+                event_table,
+                event_table_area,
+                buf,
+                &mut current_cal_date.table_state,
+            ),
+        }
+
+        let mut list = CalendarEventStore::today(
+            Style::default()
+                .bg(theme::current().text)
+                .fg(theme::current().inverse)
+                .bold(),
+        );
+        let chosen_date = current_cal_date.events.first().unwrap().due_at.date();
+
+        let assignment_style = Style::default()
+            .fg(theme::current().warning)
+            .bg(theme::current().selection);
+
+        let current_date = now_local().date();
+
+        self.calendar
+            .dates
+            .iter()
+            .zip(has_visible)
+            .for_each(|(calendar_date, shown)| {
+                let date = calendar_date.events.first().unwrap().due_at.date();
+                if date == current_date || !shown {
+                    return;
+                }
+                list.add(date, assignment_style);
+            });
+
+        let current_date = now_local().date();
+        match self.calendar.dates.first().unwrap().events.is_empty() {
+            true => list.add(
+                current_date,
+                Style::default()
+                    .bg(theme::current().text)
+                    .fg(theme::current().inverse),
+            ),
+            false => list.add(
+                current_date,
+                Style::default()
+                    .bg(theme::current().warning)
+                    .fg(theme::current().inverse),
+            ),
+        }
+
+        list.add(
+            chosen_date,
+            Style::default()
+                .fg(theme::current().inverse)
+                .bg(theme::current().error)
+                .bold(),
+        );
+        let calendar_widget = Monthly::new(
+            time::Date::from_calendar_date(chosen_date.year(), chosen_date.month(), 1).unwrap(),
+            list,
+        )
+        .show_weekdays_header(Style::default())
+        .default_style(Style::default().bg(theme::current().selection));
+
+        calendar_widget.render(calendar_area, buf);
+    }
+}
+
+/// Masks `text`, highlighting every occurrence of `query` in it
+fn highlight(text: &str, query: &str) -> Line<'static> {
+    let mut spans = vec![];
+    let mut rest = text;
+    while !query.is_empty()
+        && let Some(start) = find_ignore_case(rest, query)
+    {
+        spans.push(Span::raw(redact::mask(&rest[..start])));
+        spans.push(Span::styled(
+            redact::mask(&rest[start..start + query.len()]),
+            Style::default()
+                .fg(theme::current().inverse)
+                .bg(theme::current().warning),
+        ));
+        rest = &rest[start + query.len()..];
+    }
+    spans.push(Span::raw(redact::mask(rest)));
+    Line::from(spans)
+}
@@ -0,0 +1,56 @@
+use color_eyre::eyre::Result;
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+    config::Profile,
+    fetch::{load_cache, to_local},
+    store::Annotations,
+};
+
+/// Items due within this long, or already overdue, mark the count as urgent
+const URGENT_HOURS: i64 = 3;
+
+/// Prints how many unfinished items are due today, e.g. `3!` when one is due within a few hours.
+/// Only reads the cache so it stays fast enough to run on every shell prompt. Prints nothing when
+/// nothing is due or there is no cache
+pub fn print(plain: bool) -> Result<()> {
+    let Ok(mut calendar) = load_cache(&Profile::offline()) else {
+        return Ok(());
+    };
+    calendar.apply_annotations(&Annotations::load().unwrap_or_default());
+
+    let now = to_local(OffsetDateTime::now_utc());
+    let due_today: Vec<_> = calendar
+        .dates
+        .iter()
+        .flat_map(|date| &date.events)
+        .filter(|event| event.due_at.date() == now.date() && !event.is_done())
+        .collect();
+    if due_today.is_empty() {
+        return Ok(());
+    }
+    let urgent = due_today
+        .iter()
+        .any(|event| event.due_at <= now + Duration::hours(URGENT_HOURS));
+
+    let text = format!(
+        "{}{}",
+        due_today.len(),
+        match urgent {
+            true => "!",
+            false => "",
+        }
+    );
+    match plain || std::env::var_os("NO_COLOR").is_some() {
+        true => println!("{text}"),
+        // Red when urgent, yellow otherwise
+        false => println!(
+            "\x1b[{}m{text}\x1b[0m",
+            match urgent {
+                true => 31,
+                false => 33,
+            }
+        ),
+    }
+    Ok(())
+}
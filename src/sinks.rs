@@ -0,0 +1,193 @@
+use std::{io::Write, process::Stdio, sync::Arc};
+
+use color_eyre::eyre::{Result, eyre};
+use futures::{FutureExt, future::BoxFuture};
+use serde_json::json;
+use tokio::process::Command;
+
+use crate::config::{NotificationConfig, SinkConfig, Webhook, WebhookKind};
+
+/// Somewhere notifications are delivered. Every configured sink receives every message
+///
+/// Adding a sink takes an implementation of this trait and a variant in [`SinkConfig`] that
+/// [`build`] turns into it
+pub trait NotificationSink: Send + Sync {
+    /// Short name used in error logs
+    fn name(&self) -> &'static str;
+
+    /// Delivers a message, which may contain `**bold**` markdown
+    fn send<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<()>>;
+}
+
+/// Creates a sink for every configured one, including the webhooks from the older
+/// `notifications.webhooks` list
+pub fn build(config: &NotificationConfig) -> Vec<Arc<dyn NotificationSink>> {
+    let webhooks = config.webhooks.iter().cloned().map(SinkConfig::Webhook);
+    config
+        .sinks
+        .iter()
+        .cloned()
+        .chain(webhooks)
+        .map(|sink| -> Arc<dyn NotificationSink> {
+            match sink {
+                SinkConfig::Webhook(webhook) => Arc::new(WebhookSink(webhook)),
+                SinkConfig::Desktop => Arc::new(DesktopSink),
+                SinkConfig::Bell => Arc::new(BellSink),
+                SinkConfig::Osc => Arc::new(OscSink),
+                SinkConfig::Command { command } => Arc::new(CommandSink { command }),
+            }
+        })
+        .collect()
+}
+
+/// Drops markdown for sinks that show plain text
+fn plain(message: &str) -> String {
+    message.replace("**", "")
+}
+
+/// Posts to a Discord or Slack webhook
+struct WebhookSink(Webhook);
+
+impl NotificationSink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn send<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<()>> {
+        async move {
+            let body = match self.0.kind {
+                WebhookKind::Discord => json!({ "content": message }),
+                WebhookKind::Slack => json!({ "text": message.replace("**", "*") }),
+            };
+            reqwest::Client::new()
+                .post(&self.0.url)
+                .json(&body)
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+/// Shows a desktop notification through notify-send, or osascript on macOS
+struct DesktopSink;
+
+impl NotificationSink for DesktopSink {
+    fn name(&self) -> &'static str {
+        "desktop"
+    }
+
+    fn send<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<()>> {
+        async move {
+            let message = plain(message);
+            let mut command = match cfg!(target_os = "macos") {
+                true => {
+                    let mut command = Command::new("osascript");
+                    command.arg("-e").arg(format!(
+                        "display notification {:?} with title \"CanvasTUI\"",
+                        message
+                    ));
+                    command
+                }
+                false => {
+                    let mut command = Command::new("notify-send");
+                    command.arg("CanvasTUI").arg(message);
+                    command
+                }
+            };
+            // Anything the command prints would end up on top of the TUI
+            let status = command
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .await?;
+            match status.success() {
+                true => Ok(()),
+                false => Err(eyre!("the notification command exited with {status}")),
+            }
+        }
+        .boxed()
+    }
+}
+
+/// Rings the terminal bell, which most terminals turn into an urgency hint
+struct BellSink;
+
+impl NotificationSink for BellSink {
+    fn name(&self) -> &'static str {
+        "bell"
+    }
+
+    fn send<'a>(&'a self, _message: &'a str) -> BoxFuture<'a, Result<()>> {
+        async move {
+            // The TUI draws to stderr, so that is the stream attached to the terminal
+            let mut stderr = std::io::stderr();
+            stderr.write_all(b"\x07")?;
+            stderr.flush()?;
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+/// Sends the message as an OSC 9 escape, shown as a notification by terminals such as iTerm2,
+/// WezTerm, kitty and foot, even over SSH
+struct OscSink;
+
+impl NotificationSink for OscSink {
+    fn name(&self) -> &'static str {
+        "osc"
+    }
+
+    fn send<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<()>> {
+        async move {
+            // Control characters would end the escape sequence early
+            let message: String = plain(message)
+                .chars()
+                .map(|c| match c.is_control() {
+                    true => ' ',
+                    false => c,
+                })
+                .collect();
+            let mut stderr = std::io::stderr();
+            write!(stderr, "\x1b]9;{message}\x07")?;
+            stderr.flush()?;
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+/// Runs a command with the message in the CANVASTUI_MESSAGE environment variable
+struct CommandSink {
+    command: Vec<String>,
+}
+
+impl NotificationSink for CommandSink {
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
+    fn send<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<()>> {
+        async move {
+            let [program, args @ ..] = self.command.as_slice() else {
+                return Err(eyre!("the command sink has an empty command"));
+            };
+            let status = Command::new(program)
+                .args(args)
+                .env("CANVASTUI_MESSAGE", plain(message))
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .await?;
+            match status.success() {
+                true => Ok(()),
+                false => Err(eyre!("{program} exited with {status}")),
+            }
+        }
+        .boxed()
+    }
+}
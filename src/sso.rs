@@ -0,0 +1,147 @@
+//! Best-effort sign-in with a browser session cookie, for institutions that disable access token
+//! generation. Canvas accepts the session cookie of its web app on the API too, but it expires
+//! with the browser session and writes need the CSRF token that comes with it.
+
+use std::{
+    env,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{Result, eyre};
+use serde::Deserialize;
+
+use crate::{
+    config::{AuthMode, Config, Profile},
+    fetch::get_json,
+    store::data_dir,
+    sync::expand_home,
+};
+
+const COOKIE_FILE: &str = "canvastui/cookie";
+
+#[derive(Deserialize)]
+struct CanvasUser {
+    name: String,
+}
+
+/// Where `cookie-login` saves the cookie unless `cookie_file` says otherwise
+pub fn default_cookie_path() -> Option<PathBuf> {
+    Some(data_dir()?.join(COOKIE_FILE))
+}
+
+/// Reads a session cookie, written as the value of a `Cookie` header or exported from the
+/// browser in the Netscape cookies.txt format
+pub fn read_cookie(path: &Path) -> Result<String> {
+    let path = expand_home(path);
+    let contents = std::fs::read_to_string(&path).map_err(|e| {
+        eyre!(
+            "Could not read the session cookie from {}: {e}. Run canvastui cookie-login to sign in",
+            path.display()
+        )
+    })?;
+    parse_cookie(&contents).ok_or(eyre!("No cookies in {}", path.display()))
+}
+
+/// Joins the cookies of a `Cookie` header value or a cookies.txt file into a header value
+fn parse_cookie(contents: &str) -> Option<String> {
+    let mut pairs = vec![];
+    for line in contents.lines().map(str::trim) {
+        // cookies.txt comments out HttpOnly cookies, which the session cookie is
+        let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split('\t').collect::<Vec<_>>().as_slice() {
+            [_, _, _, _, _, name, value] => pairs.push(format!("{name}={value}")),
+            _ => pairs.push(
+                line.strip_prefix("Cookie:")
+                    .unwrap_or(line)
+                    .trim()
+                    .to_string(),
+            ),
+        }
+    }
+    (!pairs.is_empty()).then(|| pairs.join("; "))
+}
+
+/// The token Canvas wants in `X-CSRF-Token` on writes: the URL-decoded `_csrf_token` cookie
+pub fn csrf_token(cookie: &str) -> Option<String> {
+    let value = cookie
+        .split(';')
+        .find_map(|pair| pair.trim().strip_prefix("_csrf_token="))?;
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = value
+            .get(i + 1..i + 3)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    Some(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+/// Walks through copying the session cookie from a browser signed in to Canvas, checks that
+/// Canvas accepts it and saves it for `auth = "cookie"`
+pub async fn login() -> Result<()> {
+    let config = Config::load()?;
+    let url = config
+        .canvas_url
+        .clone()
+        .or_else(|| env::var("CANVAS_URL").ok())
+        .filter(|url| !url.is_empty())
+        .ok_or(eyre!(
+            "No Canvas url, set canvas_url in the config file or CANVAS_URL"
+        ))?;
+    let path = config
+        .cookie_path()
+        .ok_or(eyre!("No home directory to save the cookie in"))?;
+    let path = expand_home(&path);
+
+    println!("1. Open {url} in your browser and sign in through your institution");
+    println!("2. Open the developer tools and go to Storage (Firefox) or Application (Chrome),");
+    println!("   then Cookies, then {url}");
+    println!("3. Paste the canvas_session and _csrf_token cookies below as");
+    println!("   canvas_session=...; _csrf_token=...");
+    println!("   or paste the Cookie header of a request to {url} from the Network tab");
+    print!("\nCookie: ");
+    io::stdout().flush()?;
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    let cookie = parse_cookie(&line).ok_or(eyre!("Nothing was pasted"))?;
+
+    let profile = Profile {
+        url,
+        cookie: Some(cookie.clone()),
+        ..Profile::offline()
+    };
+    let user: CanvasUser = get_json(&profile, "/api/v1/users/self", &[])
+        .await
+        .map_err(|e| eyre!("Canvas didn't accept the cookie: {e}"))?;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, &cookie)?;
+    // The cookie is as good as a password until the session ends
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    println!("\nSigned in as {}, saved to {}", user.name, path.display());
+    if config.auth != AuthMode::Cookie {
+        println!("Set auth = \"cookie\" in the config file to use it");
+    }
+    Ok(())
+}
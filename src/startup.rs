@@ -0,0 +1,273 @@
+use std::collections::{HashMap, HashSet};
+
+use color_eyre::eyre::Result;
+use ratatui::style::Color;
+use serde::Deserialize;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::{Action, SessionId},
+    config::Profile,
+    fetch::{cache_updated_at, fetch, get_json, load_cache},
+};
+
+/// Requests made in parallel when the app starts. Each one is shown as soon as it arrives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Planner,
+    Courses,
+    Colors,
+    Nicknames,
+    Profile,
+}
+
+impl Step {
+    pub const ALL: [Step; 5] = [
+        Step::Planner,
+        Step::Courses,
+        Step::Colors,
+        Step::Nicknames,
+        Step::Profile,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Step::Planner => "planner items",
+            Step::Courses => "courses",
+            Step::Colors => "course colors",
+            Step::Nicknames => "course nicknames",
+            Step::Profile => "profile",
+        }
+    }
+}
+
+/// Result of a step other than the planner, which arrives as `Action::FetchComplete`
+#[derive(Debug, Clone)]
+pub enum Loaded {
+    Courses {
+        /// Course codes by course id
+        codes: HashMap<u64, String>,
+        /// Display names of each course's primary instructor, empty unless asked for
+        instructors: HashMap<u64, String>,
+        /// Ids of the courses left out by `exclude_courses`
+        excluded: HashSet<u64>,
+    },
+    /// Colors the user picked for their courses in Canvas, by course id
+    Colors(HashMap<u64, Color>),
+    Nicknames(HashMap<u64, String>),
+    /// Display name of the user
+    Profile(String),
+}
+
+/// Steps of the initial load that finished, successfully or not
+#[derive(Debug, Default)]
+pub struct Progress {
+    pub finished: Vec<Step>,
+    /// Finished steps that failed, other than the planner whose failures show in the fetch state
+    pub failed: Vec<Step>,
+}
+
+impl Progress {
+    pub fn is_done(&self) -> bool {
+        self.finished.len() == Step::ALL.len()
+    }
+
+    pub fn record(&mut self, step: Step, failed: bool) {
+        self.finished.push(step);
+        if failed && step != Step::Planner {
+            self.failed.push(step);
+        }
+    }
+
+    /// Takes the failed steps to run again, counting them as unfinished until they are
+    pub fn retry(&mut self) -> Vec<Step> {
+        let failed = std::mem::take(&mut self.failed);
+        self.finished.retain(|step| !failed.contains(step));
+        failed
+    }
+}
+
+/// Starts every step of the initial load of a session at once. Instructors of the courses are
+/// only resolved when `instructors` is set
+pub fn spawn(
+    id: SessionId,
+    profile: &Profile,
+    instructors: bool,
+    action_tx: &UnboundedSender<Action>,
+) {
+    spawn_steps(id, profile, &Step::ALL, instructors, action_tx);
+}
+
+/// Starts some steps of the initial load, e.g. the ones that failed the first time
+pub fn spawn_steps(
+    id: SessionId,
+    profile: &Profile,
+    steps: &[Step],
+    instructors: bool,
+    action_tx: &UnboundedSender<Action>,
+) {
+    for &step in steps {
+        let profile = profile.clone();
+        let action_tx = action_tx.clone();
+        tokio::spawn(async move {
+            let result = match step {
+                Step::Planner => fetch(&profile).await.map(|calendar| Action::FetchComplete {
+                    session: id,
+                    calendar,
+                }),
+                step => load(&profile, step, instructors)
+                    .await
+                    .map(|data| Action::StartupLoaded { session: id, data }),
+            };
+            let failed = result.is_err();
+            match result {
+                Ok(action) => {
+                    let _ = action_tx.send(action);
+                }
+                // Without the planner there is nothing to show, the rest only adds polish
+                Err(e) if step == Step::Planner => {
+                    let _ = action_tx.send(Action::FetchFailed {
+                        session: id,
+                        error: e.to_string(),
+                    });
+                }
+                Err(e) => log::error!("Failed to fetch {} of {}: {e}", step.name(), profile.name),
+            }
+            let _ = action_tx.send(Action::StartupStep {
+                session: id,
+                step,
+                failed,
+            });
+        });
+    }
+}
+
+/// Shows the planner items cached by the last successful fetch while the first fetch is running
+pub fn spawn_cached(id: SessionId, profile: &Profile, action_tx: &UnboundedSender<Action>) {
+    let profile = profile.clone();
+    let action_tx = action_tx.clone();
+    tokio::spawn(async move {
+        let Ok(calendar) = load_cache(&profile) else {
+            return;
+        };
+        let _ = action_tx.send(Action::FileFetchComplete {
+            session: id,
+            calendar,
+            updated_at: cache_updated_at(&profile),
+        });
+    });
+}
+
+#[derive(Deserialize)]
+struct CanvasCourse {
+    id: u64,
+    #[serde(default)]
+    name: String,
+    /// Missing for courses the user can no longer access
+    course_code: Option<String>,
+    /// Only present with include[]=teachers, in the order Canvas lists them
+    #[serde(default)]
+    teachers: Vec<CanvasTeacher>,
+}
+
+#[derive(Deserialize)]
+struct CanvasTeacher {
+    display_name: String,
+}
+
+#[derive(Deserialize)]
+struct CanvasColors {
+    /// Colors keyed by asset string such as "course_42"
+    custom_colors: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct CanvasNickname {
+    course_id: u64,
+    nickname: String,
+}
+
+#[derive(Deserialize)]
+struct CanvasProfile {
+    name: String,
+}
+
+async fn load(profile: &Profile, step: Step, instructors: bool) -> Result<Loaded> {
+    Ok(match step {
+        Step::Courses => {
+            let mut query = vec![("per_page", "100")];
+            if instructors {
+                query.push(("include[]", "teachers"));
+            }
+            let mut courses: Vec<CanvasCourse> =
+                get_json(profile, "/api/v1/courses", &query).await?;
+            let mut excluded = HashSet::new();
+            courses.retain(|course| {
+                let names = [
+                    course.name.as_str(),
+                    course.course_code.as_deref().unwrap_or(""),
+                ];
+                let keep = !profile.exclude_courses.excludes(course.id, &names);
+                if !keep {
+                    excluded.insert(course.id);
+                }
+                keep
+            });
+            Loaded::Courses {
+                excluded,
+                instructors: courses
+                    .iter()
+                    .filter_map(|course| {
+                        Some((course.id, course.teachers.first()?.display_name.clone()))
+                    })
+                    .collect(),
+                // The name stands in for a missing code, and the planner's name for both
+                codes: courses
+                    .into_iter()
+                    .filter_map(|course| {
+                        let code = course
+                            .course_code
+                            .filter(|code| !code.is_empty())
+                            .unwrap_or(course.name);
+                        (!code.is_empty()).then_some((course.id, code))
+                    })
+                    .collect(),
+            }
+        }
+        Step::Colors => {
+            let colors: CanvasColors = get_json(profile, "/api/v1/users/self/colors", &[]).await?;
+            Loaded::Colors(
+                colors
+                    .custom_colors
+                    .into_iter()
+                    .filter_map(|(asset, color)| {
+                        let id = asset.strip_prefix("course_")?.parse().ok()?;
+                        Some((id, parse_hex(&color)?))
+                    })
+                    .collect(),
+            )
+        }
+        Step::Nicknames => {
+            let nicknames: Vec<CanvasNickname> =
+                get_json(profile, "/api/v1/users/self/course_nicknames", &[]).await?;
+            Loaded::Nicknames(
+                nicknames
+                    .into_iter()
+                    .map(|nickname| (nickname.course_id, nickname.nickname))
+                    .collect(),
+            )
+        }
+        Step::Profile => {
+            let user: CanvasProfile = get_json(profile, "/api/v1/users/self/profile", &[]).await?;
+            Loaded::Profile(user.name)
+        }
+        Step::Planner => unreachable!("the planner is fetched on its own"),
+    })
+}
+
+/// Parses a "#rrggbb" color
+fn parse_hex(color: &str) -> Option<Color> {
+    let hex = color.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+    Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?))
+}
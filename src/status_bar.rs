@@ -0,0 +1,81 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout},
+    prelude::{Buffer, Rect},
+    style::Style,
+    widgets::{Paragraph, Widget},
+};
+
+use crate::{
+    action::{Action, PromptKind},
+    component::{Component, Context},
+    confirm::Confirmation,
+    theme,
+};
+
+/// The bottom line: the open prompt or the last status message on the left, the mode on the right
+#[derive(Default)]
+pub struct StatusBar {
+    /// Action waiting for a yes or no, asked about in front of everything else
+    pub confirmation: Option<Confirmation>,
+    /// Text typed into the open-by-url or search prompt while it is shown
+    pub prompt: Option<(PromptKind, String)>,
+    pub status: Option<String>,
+    /// Mode with what Esc does and anything in progress, set by the app before each frame
+    pub mode: String,
+    /// Shown when there is neither a prompt nor a status, set by the app before each frame
+    pub summary: Option<String>,
+}
+
+impl Component for StatusBar {
+    /// Takes every key while a question or prompt is open. Any key but `y` says no
+    fn handle_event(&mut self, key: KeyEvent, bound: Action) -> Action {
+        match (&self.confirmation, &self.prompt) {
+            (Some(_), _) => Action::Confirm(key.code == KeyCode::Char('y')),
+            (None, Some(_)) => Action::PromptKey(key.code),
+            (None, None) => bound,
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, _context: &Context) {
+        let [status_area, mode_area] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(self.mode.chars().count() as u16),
+        ])
+        .areas(area);
+        Paragraph::new(self.mode.as_str())
+            .style(Style::default().fg(theme::current().heading))
+            .render(mode_area, buf);
+
+        if let Some(confirmation) = &self.confirmation {
+            Paragraph::new(format!("{} (y/n)", confirmation.question))
+                .style(Style::default().fg(theme::current().warning))
+                .render(status_area, buf);
+            return;
+        }
+        match (&self.prompt, &self.status, &self.summary) {
+            (Some((kind, input)), _, _) => Paragraph::new(format!(
+                "{}: {input}█",
+                match kind {
+                    PromptKind::Open => "Open url or id",
+                    PromptKind::Search => "Search",
+                    PromptKind::Grade => "Grade",
+                    PromptKind::Comment => "Comment",
+                    PromptKind::Submit => "Submit text or file path",
+                    PromptKind::Goto => "Go to date",
+                    PromptKind::Link => "Link or unlink file",
+                    PromptKind::SoftDeadline => "Finish early by (e.g. 2d or 36h, empty to clear)",
+                }
+            ))
+            .style(Style::default().fg(theme::current().warning))
+            .render(status_area, buf),
+            (None, Some(status), _) => Paragraph::new(status.as_str())
+                .style(Style::default().fg(theme::current().secondary))
+                .render(status_area, buf),
+            (None, None, Some(summary)) => Paragraph::new(summary.as_str())
+                .style(Style::default().fg(theme::current().muted))
+                .render(status_area, buf),
+            (None, None, None) => {}
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    path::PathBuf,
+};
+
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+const ANNOTATIONS_FILE: &str = "canvastui/annotations.json";
+
+/// Local-only data about planner items, keyed by their html_url
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Annotations {
+    /// Items marked complete outside of Canvas, e.g. through CalDAV sync
+    pub completed: BTreeSet<String>,
+    /// When each item's annotations last changed, in unix milliseconds, so concurrent edits
+    /// from other devices can be merged
+    pub changed: BTreeMap<String, i64>,
+    /// Reminders to work on items, keyed by html_url
+    pub reminders: BTreeMap<String, Reminder>,
+    /// Items snoozed in triage and when they come back, keyed by html_url
+    pub snoozed: BTreeMap<String, Snooze>,
+    /// Local files such as notes and drafts linked to items, keyed by html_url
+    pub files: BTreeMap<String, Vec<PathBuf>>,
+    /// Personal deadlines ahead of the official ones, keyed by html_url
+    pub soft_deadlines: BTreeMap<String, SoftDeadline>,
+    /// When each item's soft deadline was last set or cleared, in unix milliseconds, so a
+    /// cleared one isn't brought back by a device that still has it
+    pub soft_deadlines_changed: BTreeMap<String, i64>,
+    /// Whether each item was done when it was last synced with the CalDAV server, the base
+    /// changes on either side are told apart by. Kept to this device, keyed by html_url
+    pub caldav_synced: BTreeMap<String, bool>,
+}
+
+/// A notification to send about an item at a set time, e.g. from an accepted catch-up plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    #[serde(with = "time::serde::rfc3339")]
+    pub at: OffsetDateTime,
+    pub title: String,
+    pub course_name: String,
+}
+
+/// When an item snoozed in triage comes back
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Snooze {
+    #[serde(with = "time::serde::rfc3339")]
+    pub until: OffsetDateTime,
+}
+
+/// How long before the official deadline an item is to be finished
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SoftDeadline {
+    pub minutes_early: i64,
+}
+
+impl SoftDeadline {
+    /// The lead time in the largest whole unit, e.g. `2d` or `36h`
+    pub fn describe(&self) -> String {
+        match self.minutes_early {
+            minutes if minutes % 1440 == 0 => format!("{}d", minutes / 1440),
+            minutes if minutes % 60 == 0 => format!("{}h", minutes / 60),
+            minutes => format!("{minutes}m"),
+        }
+    }
+}
+
+/// The current time in unix milliseconds, as change times are kept
+fn now_millis() -> i64 {
+    (OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000) as i64
+}
+
+/// Directory local app data is stored under, following the XDG base directory spec
+pub fn data_dir() -> Option<PathBuf> {
+    match env::var_os("XDG_DATA_HOME") {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => Some(PathBuf::from(env::var_os("HOME")?).join(".local/share")),
+    }
+}
+
+impl Annotations {
+    pub fn path() -> Option<PathBuf> {
+        Some(data_dir()?.join(ANNOTATIONS_FILE))
+    }
+
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match std::fs::read(&path) {
+            Ok(contents) => Ok(serde_json::from_slice(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn set_completed(&mut self, html_url: &str, completed: bool) {
+        match completed {
+            true => self.completed.insert(html_url.to_string()),
+            false => self.completed.remove(html_url),
+        };
+        self.changed.insert(html_url.to_string(), now_millis());
+    }
+
+    /// Sets the item's soft deadline, or clears it if there is none
+    pub fn set_soft_deadline(&mut self, html_url: &str, soft_deadline: Option<SoftDeadline>) {
+        match soft_deadline {
+            Some(soft_deadline) => self
+                .soft_deadlines
+                .insert(html_url.to_string(), soft_deadline),
+            None => self.soft_deadlines.remove(html_url),
+        };
+        self.soft_deadlines_changed
+            .insert(html_url.to_string(), now_millis());
+    }
+
+    /// Links the file to the item, or unlinks it if it already is. Returns whether it is linked
+    pub fn toggle_file(&mut self, html_url: &str, path: PathBuf) -> bool {
+        let files = self.files.entry(html_url.to_string()).or_default();
+        let linked = match files.iter().position(|linked| *linked == path) {
+            Some(index) => {
+                files.remove(index);
+                false
+            }
+            None => {
+                files.push(path);
+                true
+            }
+        };
+        if files.is_empty() {
+            self.files.remove(html_url);
+        }
+        linked
+    }
+
+    /// Files linked to the item, in the order they were linked
+    pub fn files(&self, html_url: &str) -> &[PathBuf] {
+        self.files.get(html_url).map_or(&[], Vec::as_slice)
+    }
+
+    /// Merges annotations from another device. For each item the most recently changed side wins,
+    /// items neither side has a change time for are kept if either has them, and ties keep them done
+    pub fn merge(&mut self, other: &Annotations) {
+        for html_url in other.completed.iter().chain(other.changed.keys()) {
+            let theirs = other.changed.get(html_url);
+            let ours = self.changed.get(html_url);
+            let other_completed = other.completed.contains(html_url);
+            if theirs > ours {
+                match other_completed {
+                    true => self.completed.insert(html_url.clone()),
+                    false => self.completed.remove(html_url),
+                };
+                self.changed.insert(html_url.clone(), *theirs.unwrap());
+            } else if theirs == ours && other_completed {
+                self.completed.insert(html_url.clone());
+            }
+        }
+        // Reminders that went off are removed, so only upcoming ones are taken over or a
+        // reminder would come back from every device that still has it
+        let now = OffsetDateTime::now_utc();
+        for (html_url, reminder) in &other.reminders {
+            if reminder.at > now && !self.reminders.contains_key(html_url) {
+                self.reminders.insert(html_url.clone(), reminder.clone());
+            }
+        }
+        for (html_url, snooze) in &other.snoozed {
+            if snooze.until > now && !self.snoozed.contains_key(html_url) {
+                self.snoozed.insert(html_url.clone(), *snooze);
+            }
+        }
+        // Like completion, the latest change wins so clearing one travels as well
+        for html_url in other
+            .soft_deadlines
+            .keys()
+            .chain(other.soft_deadlines_changed.keys())
+        {
+            let theirs = other.soft_deadlines_changed.get(html_url);
+            let ours = self.soft_deadlines_changed.get(html_url);
+            let other_soft_deadline = other.soft_deadlines.get(html_url);
+            if theirs > ours {
+                match other_soft_deadline {
+                    Some(soft_deadline) => {
+                        self.soft_deadlines.insert(html_url.clone(), *soft_deadline)
+                    }
+                    None => self.soft_deadlines.remove(html_url),
+                };
+                self.soft_deadlines_changed
+                    .insert(html_url.clone(), *theirs.unwrap());
+            } else if let Some(soft_deadline) = other_soft_deadline.filter(|_| theirs == ours) {
+                self.soft_deadlines
+                    .entry(html_url.clone())
+                    .or_insert(*soft_deadline);
+            }
+        }
+        // Unlinking has no change time to compare, so links only come over for items without any
+        for (html_url, files) in &other.files {
+            if !self.files.contains_key(html_url) {
+                self.files.insert(html_url.clone(), files.clone());
+            }
+        }
+    }
+
+    /// Urls of snoozed items that are due back in triage
+    pub fn due_snoozes(&self, now: OffsetDateTime) -> Vec<String> {
+        self.snoozed
+            .iter()
+            .filter(|(_, snooze)| snooze.until <= now)
+            .map(|(html_url, _)| html_url.clone())
+            .collect()
+    }
+
+    /// Removes and returns the reminders that are due
+    pub fn take_due_reminders(&mut self, now: OffsetDateTime) -> Vec<Reminder> {
+        let due: Vec<String> = self
+            .reminders
+            .iter()
+            .filter(|(_, reminder)| reminder.at <= now)
+            .map(|(html_url, _)| html_url.clone())
+            .collect();
+        due.iter()
+            .filter_map(|html_url| self.reminders.remove(html_url))
+            .collect()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Duration;
+
+    use super::*;
+
+    /// Annotations of a device that set the items' completion at the given unix milliseconds
+    fn device(items: &[(&str, bool, i64)]) -> Annotations {
+        let mut annotations = Annotations::default();
+        for (html_url, completed, at) in items {
+            if *completed {
+                annotations.completed.insert(html_url.to_string());
+            }
+            annotations.changed.insert(html_url.to_string(), *at);
+        }
+        annotations
+    }
+
+    #[test]
+    fn the_latest_completion_change_wins() {
+        let mut laptop = device(&[("/a", true, 100), ("/b", false, 300), ("/c", true, 100)]);
+        let phone = device(&[("/a", false, 200), ("/b", true, 200), ("/d", true, 100)]);
+        laptop.merge(&phone);
+
+        // Unticked on the phone after the laptop ticked it
+        assert!(!laptop.completed.contains("/a"));
+        assert_eq!(laptop.changed["/a"], 200);
+        // The laptop's later change stands
+        assert!(!laptop.completed.contains("/b"));
+        assert_eq!(laptop.changed["/b"], 300);
+        // Items only one side knows are kept or taken over
+        assert!(laptop.completed.contains("/c"));
+        assert!(laptop.completed.contains("/d"));
+    }
+
+    #[test]
+    fn concurrent_edits_merge_the_same_on_both_devices() {
+        let laptop = device(&[("/a", true, 100), ("/tie", false, 150)]);
+        let phone = device(&[("/a", false, 200), ("/tie", true, 150)]);
+        let (mut on_laptop, mut on_phone) = (laptop.clone(), phone.clone());
+        on_laptop.merge(&phone);
+        on_phone.merge(&laptop);
+
+        assert_eq!(on_laptop.completed, on_phone.completed);
+        assert_eq!(on_laptop.changed, on_phone.changed);
+        // Ties keep the item done
+        assert!(on_laptop.completed.contains("/tie"));
+    }
+
+    #[test]
+    fn the_latest_soft_deadline_change_wins() {
+        let day = SoftDeadline {
+            minutes_early: 1440,
+        };
+        let hours = SoftDeadline { minutes_early: 360 };
+        let mut laptop = Annotations::default();
+        laptop.soft_deadlines.insert("/cleared".to_string(), day);
+        laptop
+            .soft_deadlines_changed
+            .insert("/cleared".to_string(), 100);
+        laptop.soft_deadlines.insert("/edited".to_string(), hours);
+        laptop
+            .soft_deadlines_changed
+            .insert("/edited".to_string(), 300);
+        let mut phone = laptop.clone();
+        // Cleared on the laptop after the phone last saw it, edited on the phone before that
+        laptop.soft_deadlines.remove("/cleared");
+        laptop
+            .soft_deadlines_changed
+            .insert("/cleared".to_string(), 200);
+        phone.soft_deadlines.insert("/edited".to_string(), day);
+        phone
+            .soft_deadlines_changed
+            .insert("/edited".to_string(), 250);
+        laptop.merge(&phone);
+
+        assert!(!laptop.soft_deadlines.contains_key("/cleared"));
+        assert_eq!(laptop.soft_deadlines_changed["/cleared"], 200);
+        assert_eq!(laptop.soft_deadlines.get("/edited"), Some(&hours));
+
+        // And the other way round, the phone takes over both
+        phone.merge(&laptop);
+        assert_eq!(phone.soft_deadlines, laptop.soft_deadlines);
+        assert_eq!(phone.soft_deadlines_changed, laptop.soft_deadlines_changed);
+    }
+
+    #[test]
+    fn only_upcoming_reminders_and_snoozes_come_over() {
+        let now = OffsetDateTime::now_utc();
+        let reminder = |at| Reminder {
+            at,
+            title: "Essay".to_string(),
+            course_name: "ENGL 1".to_string(),
+        };
+        let mut laptop = Annotations::default();
+        let mut phone = Annotations::default();
+        phone
+            .reminders
+            .insert("/past".to_string(), reminder(now - Duration::hours(1)));
+        phone
+            .reminders
+            .insert("/upcoming".to_string(), reminder(now + Duration::hours(1)));
+        phone.snoozed.insert(
+            "/past".to_string(),
+            Snooze {
+                until: now - Duration::hours(1),
+            },
+        );
+        phone.soft_deadlines.insert(
+            "/upcoming".to_string(),
+            SoftDeadline {
+                minutes_early: 1440,
+            },
+        );
+        laptop.merge(&phone);
+
+        assert!(laptop.reminders.contains_key("/upcoming"));
+        assert!(!laptop.reminders.contains_key("/past"));
+        assert!(laptop.snoozed.is_empty());
+        assert_eq!(
+            laptop.soft_deadlines.get("/upcoming"),
+            Some(&SoftDeadline {
+                minutes_early: 1440
+            })
+        );
+    }
+}
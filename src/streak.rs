@@ -0,0 +1,130 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+use time::{Date, Duration, PrimitiveDateTime};
+
+use crate::{fetch::Calendar, store::data_dir};
+
+const HISTORY_FILE: &str = "canvastui/history.json";
+
+/// Items due on a day and the ones seen done
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct DayRecord {
+    due: BTreeSet<String>,
+    done: BTreeSet<String>,
+}
+
+impl DayRecord {
+    fn complete(&self) -> bool {
+        self.due.is_subset(&self.done)
+    }
+}
+
+/// Which items were due on each day and whether they got done, recorded from every fetch since
+/// the planner itself only goes back to today
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct History {
+    /// Records keyed by date in YYYY-MM-DD form
+    days: BTreeMap<String, DayRecord>,
+}
+
+impl History {
+    fn path() -> Option<PathBuf> {
+        Some(data_dir()?.join(HISTORY_FILE))
+    }
+
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match std::fs::read(&path) {
+            Ok(contents) => Ok(serde_json::from_slice(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Records the items due today or earlier and which of them are done. Items moved to another
+    /// day are taken off the day they were due before. Returns whether anything changed
+    pub fn record(&mut self, calendar: &Calendar, now: PrimitiveDateTime) -> bool {
+        let before = self.days.clone();
+        for event in calendar
+            .dates
+            .iter()
+            .flat_map(|date| &date.events)
+            .filter(|event| !event.is_external() && event.due_at.date() <= now.date())
+        {
+            let day = event.due_at.date().to_string();
+            for (other_day, record) in &mut self.days {
+                if *other_day != day {
+                    record.due.remove(&event.html_url);
+                    record.done.remove(&event.html_url);
+                }
+            }
+            let record = self.days.entry(day).or_default();
+            record.due.insert(event.html_url.clone());
+            if event.is_done() {
+                record.done.insert(event.html_url.clone());
+            }
+        }
+        self.days.retain(|_, record| !record.due.is_empty());
+        self.days != before
+    }
+
+    /// Consecutive days up to today with everything due done. Days with nothing due don't break
+    /// the streak, and today only counts once it is complete
+    pub fn streak(&self, today: Date) -> u32 {
+        let today = today.to_string();
+        let mut streak = 0;
+        for (day, record) in self.days.range(..=today.clone()).rev() {
+            match (record.complete(), *day == today) {
+                (true, _) => streak += 1,
+                (false, true) => {}
+                (false, false) => break,
+            }
+        }
+        streak
+    }
+
+    /// Share of the items due over the last seven days that are done, in percent
+    pub fn weekly_completion(&self, today: Date) -> Option<f64> {
+        let start = (today - Duration::days(6)).to_string();
+        let (due, done) =
+            self.days
+                .range(start..=today.to_string())
+                .fold((0, 0), |(due, done), (_, record)| {
+                    let record_done = record.due.intersection(&record.done).count();
+                    (due + record.due.len(), done + record_done)
+                });
+        (due > 0).then(|| done as f64 / due as f64 * 100.0)
+    }
+
+    /// Streak and weekly completion for the status bar
+    pub fn summary(&self, today: Date) -> Option<String> {
+        let completion = self.weekly_completion(today)?;
+        let streak = match self.streak(today) {
+            1 => "1 day".to_string(),
+            days => format!("{days} days"),
+        };
+        Some(format!(
+            "Streak: {streak} · {completion:.0}% of this week's items done"
+        ))
+    }
+}
@@ -0,0 +1,201 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use color_eyre::eyre::{Result, eyre};
+use futures::StreamExt;
+use reqwest::{
+    Url,
+    multipart::{Form, Part},
+    redirect::Policy,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::{
+    canvas::Client,
+    config::Profile,
+    feed::escape,
+    fetch::{CalendarEvent, Requirements, post_json},
+};
+
+/// Size of the pieces a file is uploaded in, progress is reported after each
+const CHUNK_BYTES: usize = 64 * 1024;
+
+/// What is handed in for an assignment
+#[derive(Debug, Clone)]
+pub enum Submission {
+    Text(String),
+    File(PathBuf),
+}
+
+impl Submission {
+    /// Reads what was typed into the submit prompt: a path to an existing file is uploaded and
+    /// anything else is submitted as text. `requirements` rule out what the assignment doesn't
+    /// accept when they are known
+    pub fn parse(input: &str, requirements: Option<&Requirements>) -> Result<Self> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(eyre!("Nothing to submit"));
+        }
+        let accepts = |submission_type: &str| {
+            requirements.is_none_or(|requirements| {
+                requirements
+                    .submission_types
+                    .iter()
+                    .any(|accepted| accepted == submission_type)
+            })
+        };
+        let path = match (input.strip_prefix("~/"), std::env::var_os("HOME")) {
+            (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+            _ => PathBuf::from(input),
+        };
+        if path.is_file() {
+            return match accepts("online_upload") {
+                true => Ok(Submission::File(path)),
+                false => Err(eyre!("This assignment doesn't take file uploads")),
+            };
+        }
+        match accepts("online_text_entry") {
+            true => Ok(Submission::Text(input.to_string())),
+            false => Err(eyre!("No file at {}", path.display())),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Submission::Text(_) => "text entry".to_string(),
+            Submission::File(path) => path.file_name().map_or(path.display().to_string(), |name| {
+                name.to_string_lossy().to_string()
+            }),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct UploadSlot {
+    upload_url: String,
+    #[serde(default)]
+    upload_params: HashMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct UploadedFile {
+    id: u64,
+}
+
+/// Hands in a submission for an assignment. Files go through Canvas's three step upload: ask
+/// for an upload slot, send the file there, then submit it by id. `progress` is told how many
+/// bytes of the file have been sent out of how many
+pub async fn submit(
+    profile: &Profile,
+    event: &CalendarEvent,
+    submission: &Submission,
+    progress: impl Fn(u64, u64) + Send + Sync + 'static,
+) -> Result<()> {
+    let course_id = event
+        .course_id
+        .ok_or(eyre!("Item does not belong to a course"))?;
+    let endpoint = format!(
+        "/api/v1/courses/{course_id}/assignments/{}/submissions",
+        event.plannable_id
+    );
+    let body = match submission {
+        Submission::Text(text) => {
+            let paragraphs: Vec<String> = text
+                .split("\n\n")
+                .map(|paragraph| {
+                    let lines: Vec<String> = paragraph.lines().map(escape).collect();
+                    format!("<p>{}</p>", lines.join("<br>"))
+                })
+                .collect();
+            json!({
+                "submission": {
+                    "submission_type": "online_text_entry",
+                    "body": paragraphs.concat(),
+                }
+            })
+        }
+        Submission::File(path) => {
+            let file_id =
+                upload(profile, &format!("{endpoint}/self/files"), path, progress).await?;
+            json!({
+                "submission": {
+                    "submission_type": "online_upload",
+                    "file_ids": [file_id],
+                }
+            })
+        }
+    };
+    post_json::<Value>(profile, &endpoint, &body).await?;
+    Ok(())
+}
+
+/// Uploads a file for a submission and returns the id Canvas gave it
+async fn upload(
+    profile: &Profile,
+    endpoint: &str,
+    path: &PathBuf,
+    progress: impl Fn(u64, u64) + Send + Sync + 'static,
+) -> Result<u64> {
+    let contents = tokio::fs::read(path).await?;
+    let name = path
+        .file_name()
+        .ok_or(eyre!("{} is not a file", path.display()))?
+        .to_string_lossy()
+        .to_string();
+    let size = contents.len() as u64;
+    let slot: UploadSlot =
+        post_json(profile, endpoint, &json!({ "name": name, "size": size })).await?;
+
+    // Upload parameters have to come before the file
+    let mut form = Form::new();
+    for (key, value) in slot.upload_params {
+        let value = match value {
+            Value::String(value) => value,
+            Value::Null => continue,
+            other => other.to_string(),
+        };
+        form = form.text(key, value);
+    }
+    let mut sent = 0;
+    let chunks: Vec<Vec<u8>> = contents.chunks(CHUNK_BYTES).map(<[u8]>::to_vec).collect();
+    let stream = futures::stream::iter(chunks).map(move |chunk| {
+        sent += chunk.len() as u64;
+        progress(sent, size);
+        Ok::<_, std::io::Error>(chunk)
+    });
+    let part = Part::stream_with_length(reqwest::Body::wrap_stream(stream), size).file_name(name);
+    form = form.part("file", part);
+
+    // Canvas answers with the file, or with a redirect to confirm the upload at
+    let client = reqwest::Client::builder()
+        .redirect(Policy::none())
+        .build()?;
+    let response = client
+        .post(&slot.upload_url)
+        .multipart(form)
+        .send()
+        .await?
+        .error_for_status()?;
+    let body = match response.headers().get(reqwest::header::LOCATION) {
+        Some(location) if response.status().is_redirection() => {
+            let url = Url::parse(&slot.upload_url)?.join(location.to_str()?)?;
+            let canvas = Client::new(profile)?;
+            // The token only goes to the Canvas instance
+            match canvas.owns(&url) {
+                true => canvas.get_bytes(url.as_str(), &[]).await?,
+                false => {
+                    client
+                        .get(url)
+                        .send()
+                        .await?
+                        .error_for_status()?
+                        .bytes()
+                        .await?
+                }
+            }
+        }
+        _ => response.bytes().await?,
+    };
+    let file: UploadedFile = serde_json::from_slice(&body)?;
+    Ok(file.id)
+}
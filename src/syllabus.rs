@@ -0,0 +1,83 @@
+use time::{Date, Month};
+
+const MONTHS: [Month; 12] = [
+    Month::January,
+    Month::February,
+    Month::March,
+    Month::April,
+    Month::May,
+    Month::June,
+    Month::July,
+    Month::August,
+    Month::September,
+    Month::October,
+    Month::November,
+    Month::December,
+];
+
+/// Month named by a word like `Mar`, `march` or `Sept.`
+fn month(word: &str) -> Option<Month> {
+    let word = word.trim_end_matches('.').to_ascii_lowercase();
+    if word.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .into_iter()
+        .find(|month| month.to_string().to_ascii_lowercase().starts_with(&word))
+}
+
+/// Day of the month in a word like `14`, `14th` or `14,`
+fn day(word: &str) -> Option<u8> {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == ',');
+    digits.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+/// Dates written as `2025-03-14`, `3/14`, `3/14/25`, `Mar 14` or `14 March`. Dates without a
+/// year are taken to be in `year`
+pub fn dates_in(text: &str, year: i32) -> Vec<Date> {
+    let words: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || "()[];".contains(c))
+        .filter(|word| !word.is_empty())
+        .collect();
+    let mut dates = vec![];
+    for (i, word) in words.iter().enumerate() {
+        let numbers: Vec<i32> = word
+            .trim_end_matches([',', '.', ':'])
+            .split(['-', '/'])
+            .map(|part| part.parse().ok())
+            .collect::<Option<_>>()
+            .unwrap_or_default();
+        let date = match numbers[..] {
+            [year, month, day] if word.contains('-') && year > 999 => Some((year, month, day)),
+            [month, day] if word.contains('/') => Some((year, month, day)),
+            [month, day, short_year] if word.contains('/') => Some((
+                short_year + if short_year < 100 { 2000 } else { 0 },
+                month,
+                day,
+            )),
+            _ => month(word).and_then(|month| {
+                let day = words
+                    .get(i + 1)
+                    .and_then(|next| day(next))
+                    .or_else(|| day(words.get(i.checked_sub(1)?)?))?;
+                Some((year, month as i32, day as i32))
+            }),
+        };
+        if let Some((year, month, day)) = date
+            && let Ok(month) = Month::try_from(month as u8)
+            && let Ok(date) = Date::from_calendar_date(year, month, day as u8)
+        {
+            dates.push(date);
+        }
+    }
+    dates
+}
+
+/// Days between `date` and the closest date mentioned in a piece of the syllabus, None if it
+/// mentions none
+pub fn days_from(text: &str, date: Date) -> Option<i64> {
+    dates_in(text, date.year())
+        .into_iter()
+        .map(|mentioned| (mentioned - date).whole_days().abs())
+        .min()
+}
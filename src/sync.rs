@@ -0,0 +1,91 @@
+use std::{path::Path, process::Command};
+
+use color_eyre::eyre::{Result, eyre};
+
+use crate::{config::SyncConfig, store::Annotations};
+
+/// Merges the annotations every device wrote to the sync directory into the local ones, then
+/// publishes the result as this device's file. Each device only ever writes its own file, so
+/// file sync tools and git never see conflicting edits
+pub fn sync_annotations(config: &SyncConfig, annotations: &mut Annotations) -> Result<()> {
+    let dir = expand_home(&config.dir);
+    std::fs::create_dir_all(&dir)?;
+    let git = dir.join(".git").exists();
+    if git && has_remote(&dir)? {
+        git_command(&dir, &["pull", "--rebase", "--quiet"])?;
+    }
+
+    for entry in std::fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|extension| extension != "json") {
+            continue;
+        }
+        let other: Annotations = serde_json::from_slice(&std::fs::read(&path)?)
+            .map_err(|e| eyre!("Could not read {}: {e}", path.display()))?;
+        annotations.merge(&other);
+    }
+    annotations.save()?;
+
+    let file_name = format!("{}.json", device_name(config));
+    std::fs::write(
+        dir.join(&file_name),
+        serde_json::to_vec_pretty(annotations)?,
+    )?;
+
+    if git {
+        git_command(&dir, &["add", &file_name])?;
+        let unchanged = Command::new("git")
+            .current_dir(&dir)
+            .args(["diff", "--cached", "--quiet"])
+            .status()?
+            .success();
+        if !unchanged {
+            git_command(
+                &dir,
+                &["commit", "--quiet", "-m", &format!("Update {file_name}")],
+            )?;
+            if has_remote(&dir)? {
+                git_command(&dir, &["push", "--quiet"])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn device_name(config: &SyncConfig) -> String {
+    config
+        .device
+        .clone()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().replace(['/', '\\'], "_"))
+        .filter(|name| !name.is_empty())
+        .unwrap_or("device".to_string())
+}
+
+pub fn expand_home(dir: &Path) -> std::path::PathBuf {
+    match (dir.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => dir.to_path_buf(),
+    }
+}
+
+fn has_remote(dir: &Path) -> Result<bool> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .arg("remote")
+        .output()?;
+    Ok(!output.stdout.trim_ascii().is_empty())
+}
+
+fn git_command(dir: &Path, args: &[&str]) -> Result<()> {
+    let output = Command::new("git").current_dir(dir).args(args).output()?;
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(eyre!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+    }
+}
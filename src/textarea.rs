@@ -0,0 +1,167 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::Style,
+    text::Line,
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+
+use crate::{redact, theme};
+
+/// Multi-line text input with a cursor that moves through lines and characters
+#[derive(Debug, Clone)]
+pub struct TextArea {
+    lines: Vec<String>,
+    /// Line and character the cursor is on
+    row: usize,
+    column: usize,
+    /// First wrapped row shown, moved along to keep the cursor in view
+    scroll: usize,
+}
+
+impl Default for TextArea {
+    fn default() -> Self {
+        Self {
+            lines: vec![String::new()],
+            row: 0,
+            column: 0,
+            scroll: 0,
+        }
+    }
+}
+
+impl TextArea {
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.iter().all(|line| line.trim().is_empty())
+    }
+
+    /// Byte offset of the cursor in its line
+    fn offset(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(self.column)
+            .map_or(line.len(), |(offset, _)| offset)
+    }
+
+    fn line_length(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    /// Edits the text or moves the cursor for a key. Returns false for keys it doesn't use
+    pub fn input(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+        match key.code {
+            KeyCode::Char(c) => {
+                let offset = self.offset();
+                self.lines[self.row].insert(offset, c);
+                self.column += 1;
+            }
+            KeyCode::Tab => {
+                let offset = self.offset();
+                self.lines[self.row].insert_str(offset, "    ");
+                self.column += 4;
+            }
+            KeyCode::Enter => {
+                let offset = self.offset();
+                let rest = self.lines[self.row].split_off(offset);
+                self.row += 1;
+                self.lines.insert(self.row, rest);
+                self.column = 0;
+            }
+            KeyCode::Backspace if self.column > 0 => {
+                self.column -= 1;
+                let offset = self.offset();
+                self.lines[self.row].remove(offset);
+            }
+            KeyCode::Backspace if self.row > 0 => {
+                let line = self.lines.remove(self.row);
+                self.row -= 1;
+                self.column = self.line_length(self.row);
+                self.lines[self.row].push_str(&line);
+            }
+            KeyCode::Delete if self.column < self.line_length(self.row) => {
+                let offset = self.offset();
+                self.lines[self.row].remove(offset);
+            }
+            KeyCode::Delete if self.row + 1 < self.lines.len() => {
+                let line = self.lines.remove(self.row + 1);
+                self.lines[self.row].push_str(&line);
+            }
+            KeyCode::Left if self.column > 0 => self.column -= 1,
+            KeyCode::Left if self.row > 0 => {
+                self.row -= 1;
+                self.column = self.line_length(self.row);
+            }
+            KeyCode::Right if self.column < self.line_length(self.row) => self.column += 1,
+            KeyCode::Right if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.column = 0;
+            }
+            KeyCode::Up if self.row > 0 => {
+                self.row -= 1;
+                self.column = self.column.min(self.line_length(self.row));
+            }
+            KeyCode::Down if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.column = self.column.min(self.line_length(self.row));
+            }
+            KeyCode::Home => self.column = 0,
+            KeyCode::End => self.column = self.line_length(self.row),
+            KeyCode::Backspace
+            | KeyCode::Delete
+            | KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Up
+            | KeyCode::Down => {}
+            _ => return false,
+        }
+        true
+    }
+
+    /// Draws the text in a bordered box with `title`, the cursor shown as a block
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, title: &str) {
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme::current().warning));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        // Lines are wrapped at the width, so the cursor is tracked in wrapped rows
+        let width = (inner.width as usize).max(1);
+        let mut rows: Vec<String> = vec![];
+        let mut cursor_row = 0;
+        for (row, line) in self.lines.iter().enumerate() {
+            let mut chars: Vec<char> = line.chars().collect();
+            if row == self.row {
+                cursor_row = rows.len() + self.column / width;
+                chars.insert(self.column, '█');
+            }
+            match chars.is_empty() {
+                true => rows.push(String::new()),
+                false => rows.extend(chars.chunks(width).map(|chunk| chunk.iter().collect())),
+            }
+        }
+        let height = (inner.height as usize).max(1);
+        if cursor_row < self.scroll {
+            self.scroll = cursor_row;
+        } else if cursor_row >= self.scroll + height {
+            self.scroll = cursor_row + 1 - height;
+        }
+        let lines: Vec<Line> = rows
+            .iter()
+            .skip(self.scroll)
+            .take(height)
+            .map(|row| Line::raw(redact::mask(row)))
+            .collect();
+        Paragraph::new(lines)
+            .style(Style::default().fg(theme::current().text))
+            .render(inner, buf);
+    }
+}
@@ -0,0 +1,144 @@
+use std::{str::FromStr, sync::OnceLock};
+
+use color_eyre::eyre::{Result, eyre};
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::config::ThemeConfig;
+
+static CURRENT: OnceLock<Theme> = OnceLock::new();
+
+/// Built-in sets of colors a theme starts from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Palette {
+    #[default]
+    Default,
+    Gruvbox,
+    Catppuccin,
+    HighContrast,
+}
+
+/// Colors of the interface by what they are used for
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    /// Titles, table headers and popup borders
+    pub heading: Color,
+    /// Course names, dates and other labels
+    pub accent: Color,
+    pub text: Color,
+    /// Less important text, such as details under a title
+    pub secondary: Color,
+    /// Disabled, locked or out of range things
+    pub muted: Color,
+    /// Background of the selected row
+    pub selection: Color,
+    /// Text on a colored background, such as badges
+    pub inverse: Color,
+    pub done: Color,
+    pub warning: Color,
+    pub error: Color,
+    /// Items of external calendars and links
+    pub external: Color,
+}
+
+/// Sets the theme used for the rest of the program
+pub fn init(config: &ThemeConfig) -> Result<()> {
+    let theme = Theme::new(config)?;
+    let _ = CURRENT.set(theme);
+    Ok(())
+}
+
+pub fn current() -> &'static Theme {
+    CURRENT.get_or_init(|| Theme::palette(Palette::Default))
+}
+
+impl Theme {
+    fn new(config: &ThemeConfig) -> Result<Self> {
+        let mut theme = Self::palette(config.palette);
+        for (role, value) in &config.colors {
+            let color = Color::from_str(value).map_err(|_| {
+                eyre!("{value:?} is not a color, use a name like \"blue\", \"#rrggbb\" or 0-255")
+            })?;
+            *theme
+                .role(role)
+                .ok_or(eyre!("{role:?} is not a theme color"))? = color;
+        }
+        Ok(theme)
+    }
+
+    fn role(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "heading" => &mut self.heading,
+            "accent" => &mut self.accent,
+            "text" => &mut self.text,
+            "secondary" => &mut self.secondary,
+            "muted" => &mut self.muted,
+            "selection" => &mut self.selection,
+            "inverse" => &mut self.inverse,
+            "done" => &mut self.done,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "external" => &mut self.external,
+            _ => return None,
+        })
+    }
+
+    pub fn palette(palette: Palette) -> Self {
+        match palette {
+            Palette::Default => Self {
+                heading: Color::Magenta,
+                accent: Color::Blue,
+                text: Color::White,
+                secondary: Color::Gray,
+                muted: Color::DarkGray,
+                selection: Color::Black,
+                inverse: Color::Black,
+                done: Color::Green,
+                warning: Color::Yellow,
+                error: Color::Red,
+                external: Color::Cyan,
+            },
+            Palette::Gruvbox => Self {
+                heading: Color::Rgb(0xd3, 0x86, 0x9b),
+                accent: Color::Rgb(0x83, 0xa5, 0x98),
+                text: Color::Rgb(0xeb, 0xdb, 0xb2),
+                secondary: Color::Rgb(0xa8, 0x99, 0x84),
+                muted: Color::Rgb(0x66, 0x5c, 0x54),
+                selection: Color::Rgb(0x3c, 0x38, 0x36),
+                inverse: Color::Rgb(0x28, 0x28, 0x28),
+                done: Color::Rgb(0xb8, 0xbb, 0x26),
+                warning: Color::Rgb(0xfa, 0xbd, 0x2f),
+                error: Color::Rgb(0xfb, 0x49, 0x34),
+                external: Color::Rgb(0x8e, 0xc0, 0x7c),
+            },
+            // Mocha, the dark flavor
+            Palette::Catppuccin => Self {
+                heading: Color::Rgb(0xcb, 0xa6, 0xf7),
+                accent: Color::Rgb(0x89, 0xb4, 0xfa),
+                text: Color::Rgb(0xcd, 0xd6, 0xf4),
+                secondary: Color::Rgb(0xa6, 0xad, 0xc8),
+                muted: Color::Rgb(0x6c, 0x70, 0x86),
+                selection: Color::Rgb(0x31, 0x32, 0x44),
+                inverse: Color::Rgb(0x1e, 0x1e, 0x2e),
+                done: Color::Rgb(0xa6, 0xe3, 0xa1),
+                warning: Color::Rgb(0xf9, 0xe2, 0xaf),
+                error: Color::Rgb(0xf3, 0x8b, 0xa8),
+                external: Color::Rgb(0x94, 0xe2, 0xd5),
+            },
+            Palette::HighContrast => Self {
+                heading: Color::LightMagenta,
+                accent: Color::LightBlue,
+                text: Color::White,
+                secondary: Color::White,
+                muted: Color::Gray,
+                selection: Color::Blue,
+                inverse: Color::Black,
+                done: Color::LightGreen,
+                warning: Color::LightYellow,
+                error: Color::LightRed,
+                external: Color::LightCyan,
+            },
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::Style,
+    widgets::{Block, Borders, Clear, Paragraph, Widget, Wrap},
+};
+
+use crate::theme;
+
+/// How long an error stays on screen unless dismissed earlier
+const TOAST_DURATION: Duration = Duration::from_secs(10);
+const TOAST_WIDTH: u16 = 48;
+/// Most lines of the message shown before it is cut off
+const TOAST_LINES: u16 = 6;
+
+/// An error shown in the bottom right corner over whatever is on screen
+pub struct Toast {
+    message: String,
+    shown_at: Instant,
+}
+
+impl Toast {
+    pub fn new(message: String) -> Self {
+        Self {
+            message,
+            shown_at: Instant::now(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn expired(&self) -> bool {
+        self.shown_at.elapsed() >= TOAST_DURATION
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        let width = TOAST_WIDTH.min(area.width);
+        let text_width = width.saturating_sub(2).max(1) as usize;
+        // Estimated from the length, since the wrapped line count isn't exposed
+        let lines = self
+            .message
+            .lines()
+            .map(|line| line.chars().count().div_ceil(text_width).max(1))
+            .sum::<usize>()
+            .min(TOAST_LINES as usize) as u16;
+        let height = (lines + 2).min(area.height);
+        let popup = Rect {
+            x: area.right().saturating_sub(width),
+            y: area.bottom().saturating_sub(height),
+            width,
+            height,
+        };
+        Clear.render(popup, buf);
+        Paragraph::new(self.message.as_str())
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .title(" Error · Esc dismiss ")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme::current().error)),
+            )
+            .style(Style::default().fg(theme::current().text))
+            .render(popup, buf);
+    }
+}
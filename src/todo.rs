@@ -0,0 +1,183 @@
+use color_eyre::eyre::Result;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Constraint,
+    prelude::{Buffer, Rect},
+    style::{Color, Style},
+    widgets::{Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    action::{Action, Navigation, View},
+    component::{Component, Context},
+    config::Profile,
+    fetch::{get_json, to_local},
+    locale, redact, theme,
+};
+
+/// An item on the Canvas To-Do list: something to hand in, or submissions to grade
+#[derive(Debug, Clone, Deserialize)]
+pub struct TodoItem {
+    /// grading or submitting
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub context_name: Option<String>,
+    /// Assignment or quiz the item is about
+    #[serde(alias = "quiz")]
+    pub assignment: TodoAssignment,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TodoAssignment {
+    #[serde(alias = "title")]
+    pub name: String,
+    pub html_url: String,
+    #[serde(
+        default,
+        deserialize_with = "time::serde::iso8601::option::deserialize"
+    )]
+    pub due_at: Option<OffsetDateTime>,
+    /// Submissions waiting for a grade, for grading items
+    pub needs_grading_count: Option<u32>,
+}
+
+impl TodoItem {
+    fn badge(&self) -> (String, Color) {
+        match self.kind.as_str() {
+            "grading" => (
+                match self.assignment.needs_grading_count {
+                    Some(count) => format!("needs grading ({count})"),
+                    None => "needs grading".to_string(),
+                },
+                theme::current().heading,
+            ),
+            _ => ("needs submitting".to_string(), theme::current().warning),
+        }
+    }
+}
+
+/// Fetches the user's To-Do list, which Canvas builds apart from the planner
+pub async fn fetch_todo(profile: &Profile) -> Result<Vec<TodoItem>> {
+    get_json(profile, "/api/v1/users/self/todo", &[("per_page", "100")]).await
+}
+
+/// The Canvas To-Do list sorted by due date, items without one last
+pub struct TodoScreen {
+    /// None while it is being fetched
+    items: Option<Vec<TodoItem>>,
+    pub table_state: TableState,
+}
+
+impl TodoScreen {
+    pub fn loading() -> Self {
+        Self {
+            items: None,
+            table_state: TableState::default(),
+        }
+    }
+
+    fn set_items(&mut self, mut items: Vec<TodoItem>) {
+        items.sort_by_key(|item| (item.assignment.due_at.is_none(), item.assignment.due_at));
+        self.table_state.select((!items.is_empty()).then_some(0));
+        self.items = Some(items);
+    }
+
+    pub fn selected(&self) -> Option<&TodoItem> {
+        self.items.as_ref()?.get(self.table_state.selected()?)
+    }
+}
+
+impl Component for TodoScreen {
+    fn init(&mut self, profile: &Profile, action_tx: &UnboundedSender<Action>) -> Result<()> {
+        let profile = profile.clone();
+        let action_tx = action_tx.clone();
+        tokio::spawn(async move {
+            let action = match fetch_todo(&profile).await {
+                Ok(items) => Action::TodoFetched(items),
+                Err(e) => Action::Error(format!("Loading the To-Do list failed: {e}")),
+            };
+            let _ = action_tx.send(action);
+        });
+        Ok(())
+    }
+
+    fn handle_event(&mut self, _key: KeyEvent, bound: Action) -> Action {
+        match bound {
+            Action::Quit
+            | Action::OpenURL
+            | Action::Navigate(Navigation::PrevEvent | Navigation::NextEvent)
+            | Action::ToggleView(View::Todo) => bound,
+            _ => Action::None,
+        }
+    }
+
+    fn update(&mut self, action: &Action, _context: &Context) -> Option<Action> {
+        match action {
+            Action::Navigate(Navigation::PrevEvent) => self.table_state.select_previous(),
+            Action::Navigate(Navigation::NextEvent) => self.table_state.select_next(),
+            Action::TodoFetched(items) => self.set_items(items.clone()),
+            _ => {}
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, _context: &Context) {
+        let Some(items) = &self.items else {
+            Paragraph::new("Loading the To-Do list...").render(area, buf);
+            return;
+        };
+        if items.is_empty() {
+            Paragraph::new("Nothing to do").render(area, buf);
+            return;
+        }
+        let header = ["Due", "Course", "Item", ""]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(Style::default().fg(theme::current().heading));
+        let rows: Vec<Row> = items
+            .iter()
+            .map(|item| {
+                let due = match item.assignment.due_at {
+                    Some(due_at) => {
+                        let due_at = to_local(due_at);
+                        format!(
+                            "{} {:02}:{:02}",
+                            locale::current().format(due_at.date(), "%a %b %-d"),
+                            due_at.hour(),
+                            due_at.minute()
+                        )
+                    }
+                    None => "No due date".to_string(),
+                };
+                let (badge, color) = item.badge();
+                Row::new([
+                    Cell::from(due),
+                    Cell::from(redact::mask(
+                        item.context_name.as_deref().unwrap_or_default(),
+                    ))
+                    .style(Style::default().fg(theme::current().accent)),
+                    Cell::from(redact::mask(&item.assignment.name)),
+                    Cell::from(badge).style(Style::default().fg(color)),
+                ])
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(17),
+                Constraint::Percentage(25),
+                Constraint::Fill(1),
+                Constraint::Length(20),
+            ],
+        )
+        .header(header)
+        .row_highlight_style(Style::default().bg(theme::current().selection))
+        .style(Style::default().fg(theme::current().text));
+        StatefulWidget::render(table, area, buf, &mut self.table_state);
+    }
+}
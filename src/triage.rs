@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout},
+    prelude::{Buffer, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+use time::{Duration, PrimitiveDateTime};
+
+use crate::{
+    action::{Action, View},
+    component::{Component, Context},
+    fetch::{Calendar, CalendarEvent},
+    locale, redact, theme,
+};
+
+/// How long after "remind me" the reminder goes off
+pub const REMIND_AFTER: Duration = Duration::hours(3);
+/// How long a snoozed item stays out of triage
+pub const SNOOZE_FOR: Duration = Duration::days(1);
+
+/// Single-key decisions on the item being triaged
+#[derive(Debug, Clone, Copy)]
+pub enum Decision {
+    Open,
+    Remind,
+    Snooze,
+    Done,
+    Skip,
+}
+
+impl Decision {
+    pub fn from_key(c: char) -> Option<Self> {
+        Some(match c {
+            'o' => Decision::Open,
+            'r' => Decision::Remind,
+            's' => Decision::Snooze,
+            'd' => Decision::Done,
+            'n' | ' ' => Decision::Skip,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ChangeKind {
+    New,
+    Rescheduled {
+        from: PrimitiveDateTime,
+    },
+    Graded,
+    /// Back from a snooze
+    Snoozed,
+}
+
+/// An item waiting to be triaged
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub session: usize,
+    pub event: CalendarEvent,
+    pub kind: ChangeKind,
+}
+
+/// Items that appeared, moved or got graded between two fetches of a session's planner. Nothing
+/// counts as changed when there is no previous fetch to compare against
+pub fn changes(session: usize, previous: &Calendar, current: &Calendar) -> Vec<Change> {
+    let previous: HashMap<&str, &CalendarEvent> = previous
+        .dates
+        .iter()
+        .flat_map(|date| &date.events)
+        .map(|event| (event.html_url.as_str(), event))
+        .collect();
+    if previous.is_empty() {
+        return vec![];
+    }
+    current
+        .dates
+        .iter()
+        .flat_map(|date| &date.events)
+        .filter(|event| !event.is_external())
+        .filter_map(|event| {
+            let kind = match previous.get(event.html_url.as_str()) {
+                None if !event.is_done() => ChangeKind::New,
+                Some(known) if event.graded && !known.graded => ChangeKind::Graded,
+                Some(known) if known.due_at != event.due_at && !event.is_done() => {
+                    ChangeKind::Rescheduled { from: known.due_at }
+                }
+                _ => return None,
+            };
+            Some(Change {
+                session,
+                event: event.clone(),
+                kind,
+            })
+        })
+        .collect()
+}
+
+/// Adds changes to the end of the queue, replacing older changes to the same items
+pub fn enqueue(queue: &mut Vec<Change>, changes: Vec<Change>) {
+    for change in changes {
+        queue.retain(|queued| {
+            queued.session != change.session || queued.event.html_url != change.event.html_url
+        });
+        queue.push(change);
+    }
+}
+
+/// Steps through new and changed items one at a time
+#[derive(Default)]
+pub struct TriageScreen {
+    reviewed: usize,
+}
+
+impl Component for TriageScreen {
+    fn handle_event(&mut self, key: KeyEvent, bound: Action) -> Action {
+        if let KeyCode::Char(c) = key.code
+            && let Some(decision) = Decision::from_key(c)
+        {
+            return Action::Triage(decision);
+        }
+        match bound {
+            Action::Quit | Action::ToggleView(View::Triage) => bound,
+            _ => Action::None,
+        }
+    }
+
+    /// Counts the decisions, which the app carries out on the front of the queue
+    fn update(&mut self, action: &Action, context: &Context) -> Option<Action> {
+        if let Action::Triage(_) = action
+            && !context.triage_queue.is_empty()
+        {
+            self.reviewed += 1;
+        }
+        None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer, context: &Context) {
+        let queue = context.triage_queue;
+        let [help_area, item_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(area);
+        let Some(change) = queue.first() else {
+            Paragraph::new(match self.reviewed {
+                0 => "Nothing new or changed since the last fetch".to_string(),
+                reviewed => format!("All caught up, {reviewed} item(s) triaged"),
+            })
+            .render(area, buf);
+            return;
+        };
+        Paragraph::new(format!(
+            "Triage {} of {} · o open · r remind me in {}h · s snooze a day · d done · n skip",
+            self.reviewed + 1,
+            self.reviewed + queue.len(),
+            REMIND_AFTER.whole_hours()
+        ))
+        .style(Style::default().fg(theme::current().heading).bold())
+        .render(help_area, buf);
+
+        let locale = locale::current();
+        let format_due = |due_at: PrimitiveDateTime| {
+            format!(
+                "{} {:02}:{:02}",
+                locale.format(due_at.date(), "%a %b %-d"),
+                due_at.hour(),
+                due_at.minute()
+            )
+        };
+        let (label, color) = match change.kind {
+            ChangeKind::New => ("NEW".to_string(), theme::current().done),
+            ChangeKind::Rescheduled { from } => (
+                format!("MOVED from {}", format_due(from)),
+                theme::current().warning,
+            ),
+            ChangeKind::Graded => ("GRADED".to_string(), theme::current().external),
+            ChangeKind::Snoozed => ("BACK FROM SNOOZE".to_string(), theme::current().accent),
+        };
+        let event = &change.event;
+        Paragraph::new(vec![
+            Line::styled(label, Style::default().fg(color).bold()),
+            Line::raw(""),
+            Line::styled(
+                redact::mask(&event.course_name),
+                Style::default().fg(theme::current().secondary),
+            ),
+            Line::styled(redact::mask(&event.title), Style::default().bold()),
+            Line::from(vec![
+                Span::raw("Due "),
+                Span::styled(
+                    format_due(event.due_at),
+                    Style::default().fg(theme::current().accent),
+                ),
+            ]),
+        ])
+        .style(Style::default().fg(theme::current().text))
+        .render(item_area, buf);
+    }
+}
@@ -0,0 +1,69 @@
+/// Average adult silent reading speed in words per minute
+const WORDS_PER_MINUTE: usize = 230;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Estimate {
+    pub words: usize,
+    pub minutes: usize,
+}
+
+impl Estimate {
+    pub fn from_html(html: &str) -> Self {
+        let words = html_to_text(html).split_whitespace().count();
+        Self {
+            words,
+            minutes: words.div_ceil(WORDS_PER_MINUTE),
+        }
+    }
+}
+
+impl std::ops::Add for Estimate {
+    type Output = Estimate;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Estimate {
+            words: self.words + rhs.words,
+            minutes: self.minutes + rhs.minutes,
+        }
+    }
+}
+
+/// Finds an ASCII needle regardless of case, without copying the haystack
+pub fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// Strips tags, scripts and styles from HTML, leaving roughly the readable text
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let tag_end = rest.find('>').map_or(rest.len(), |end| end + 1);
+        let tag = rest[..tag_end].to_ascii_lowercase();
+        rest = &rest[tag_end..];
+
+        let skipped_element = ["script", "style"]
+            .into_iter()
+            .find(|name| tag.starts_with(&format!("<{name}")));
+        if let Some(name) = skipped_element {
+            let close = format!("</{name}");
+            let close_start = find_ignore_case(rest, &close).unwrap_or(rest.len());
+            rest = &rest[close_start..];
+            continue;
+        }
+        text.push(' ');
+    }
+    text.push_str(rest);
+
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}